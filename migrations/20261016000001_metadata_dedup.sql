@@ -0,0 +1,12 @@
+-- Deduplicación de subidas: hash del contenido y objeto compartido del proveedor.
+-- find_by_hash, count_object_references y los listados de usuario leen estas columnas.
+ALTER TABLE application.metadata
+    ADD COLUMN IF NOT EXISTS content_hash TEXT NULL,
+    ADD COLUMN IF NOT EXISTS object_id TEXT NULL;
+
+CREATE INDEX IF NOT EXISTS metadata_content_hash_idx
+    ON application.metadata (content_hash, server_id)
+    WHERE content_hash IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS metadata_object_idx
+    ON application.metadata ((COALESCE(object_id, file_id)));
@@ -0,0 +1,12 @@
+-- Papelera: trash_metadata, restore_metadata, get_expired_files y los listados de usuario
+-- leen esta columna.
+ALTER TABLE application.metadata
+    ADD COLUMN IF NOT EXISTS trashed_at TIMESTAMPTZ NULL;
+
+CREATE INDEX IF NOT EXISTS metadata_trashed_at_idx
+    ON application.metadata (trashed_at)
+    WHERE trashed_at IS NOT NULL;
+
+-- Opcional: sin esta columna trashRetention usa el valor por defecto (30 días)
+ALTER TABLE config.global
+    ADD COLUMN IF NOT EXISTS trash_retention BIGINT NULL;
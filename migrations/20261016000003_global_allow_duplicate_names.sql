@@ -0,0 +1,3 @@
+-- Política de nombres duplicados para la descarga por nombre; NULL equivale a false.
+ALTER TABLE config.global
+    ADD COLUMN IF NOT EXISTS allow_duplicate_names BOOLEAN NULL;
@@ -0,0 +1,7 @@
+-- Aplicación cliente que originó la subida; create_metadata la escribe siempre y
+-- get_client_stats agrupa por ella.
+ALTER TABLE application.metadata
+    ADD COLUMN IF NOT EXISTS client_id TEXT NULL;
+
+CREATE INDEX IF NOT EXISTS metadata_client_id_idx
+    ON application.metadata (client_id);
@@ -0,0 +1,3 @@
+-- Cuota por defecto propia de la instancia; NULL usa la global.
+ALTER TABLE config.local
+    ADD COLUMN IF NOT EXISTS default_quota BIGINT NULL;
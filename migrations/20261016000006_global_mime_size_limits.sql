@@ -0,0 +1,3 @@
+-- Límites de tamaño por patrón de mime type; NULL usa solo maxSize.
+ALTER TABLE config.global
+    ADD COLUMN IF NOT EXISTS mime_size_limits JSONB NULL;
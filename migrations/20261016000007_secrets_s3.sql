@@ -0,0 +1,3 @@
+-- Credenciales del proveedor S3 compatible.
+ALTER TABLE config.secrets
+    ADD COLUMN IF NOT EXISTS s3_secrets JSONB NULL;
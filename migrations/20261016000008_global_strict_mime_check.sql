@@ -0,0 +1,3 @@
+-- Rechazo de subidas cuyo contenido contradice el mime type declarado; NULL equivale a false.
+ALTER TABLE config.global
+    ADD COLUMN IF NOT EXISTS strict_mime_check BOOLEAN NULL;
@@ -0,0 +1,3 @@
+-- Subidas directas a Google Drive en streaming; NULL equivale a false.
+ALTER TABLE config.global
+    ADD COLUMN IF NOT EXISTS stream_uploads BOOLEAN NULL;
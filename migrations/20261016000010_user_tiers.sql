@@ -0,0 +1,6 @@
+-- Planes de usuario: definición global y plan asignado a cada usuario.
+ALTER TABLE config.global
+    ADD COLUMN IF NOT EXISTS tiers JSONB NULL;
+
+ALTER TABLE application.users
+    ADD COLUMN IF NOT EXISTS tier TEXT NULL;
@@ -0,0 +1,3 @@
+-- Credenciales de Azure Blob Storage.
+ALTER TABLE config.secrets
+    ADD COLUMN IF NOT EXISTS azure_secrets JSONB NULL;
@@ -0,0 +1,3 @@
+-- Directorio del proveedor de sistema de archivos local.
+ALTER TABLE config.secrets
+    ADD COLUMN IF NOT EXISTS localfs_secrets JSONB NULL;
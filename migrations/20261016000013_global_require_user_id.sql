@@ -0,0 +1,3 @@
+-- Rechazo de subidas anónimas; NULL equivale a false.
+ALTER TABLE config.global
+    ADD COLUMN IF NOT EXISTS require_user_id BOOLEAN NULL;
@@ -0,0 +1,526 @@
+use std::sync::{Arc, Mutex, PoisonError};
+
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    http::{header, request::Parts, HeaderMap},
+};
+use uuid::Uuid;
+
+use crate::{
+    application::{error::ApplicationError, repositories::token_repository::TokenRepository},
+    domain::{
+        config::secrets::Secrets,
+        models::{metadata::Metadata, upload_session::UploadSession},
+    },
+};
+
+/// Compara sin cortar en el primer byte distinto; solo la longitud se filtra por tiempo
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    let difference = a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+    a.len() == b.len() && difference == 0
+}
+
+/// El secreto recibido coincide con el de la instancia; uno sin configurar nunca coincide
+pub fn secret_matches(provided: &str, expected: &str) -> bool {
+    !expected.is_empty() && constant_time_eq(provided.as_bytes(), expected.as_bytes())
+}
+
+/// La cabecera X-VK-Secret coincide con el secreto de la instancia
+pub fn has_vk_secret(secrets: &Mutex<Secrets>, headers: &HeaderMap) -> bool {
+    let Some(provided_secret) = headers.get("X-VK-Secret").and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+    // Solo se lee el secreto; un pánico ajeno con el lock tomado no debe tumbar la auth
+    let secrets = secrets.lock().unwrap_or_else(PoisonError::into_inner);
+    secret_matches(provided_secret, &secrets.vk_secret)
+}
+
+/// Token de `Authorization: Bearer <token>`
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| {
+            s.strip_prefix("Bearer ")
+                .or_else(|| s.strip_prefix("bearer "))
+        })
+}
+
+/// Secreto de `X-Upload-Session-Secret` con el que se continúa una subida fragmentada
+pub fn session_secret(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get("X-Upload-Session-Secret")
+        .and_then(|v| v.to_str().ok())
+}
+
+/// Quién modifica un archivo: admin (X-VK-Secret) o el usuario acreditado por su token
+#[derive(Debug, Clone, Copy)]
+pub struct FileCaller {
+    admin: bool,
+    user_id: Option<Uuid>,
+}
+
+impl FileCaller {
+    /// Admite al admin o al dueño del archivo; los archivos anónimos solo los toca el admin
+    pub fn authorize(&self, metadata: &Metadata) -> Result<(), ApplicationError> {
+        if self.admin {
+            return Ok(());
+        }
+
+        let Some(user_id) = self.user_id else {
+            return Err(ApplicationError::Forbidden(format!(
+                "Modifying file {} requires a user token or X-VK-Secret",
+                metadata.file_id
+            )));
+        };
+        let owner = metadata
+            .user_id
+            .as_deref()
+            .and_then(|owner| Uuid::parse_str(owner).ok());
+        if owner != Some(user_id) {
+            return Err(ApplicationError::Forbidden(format!(
+                "File {} does not belong to user {}",
+                metadata.file_id, user_id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Admite al admin o al propio usuario en operaciones sobre todos sus archivos
+    pub fn authorize_user(&self, user_id: Uuid) -> Result<(), ApplicationError> {
+        if self.admin || self.user_id == Some(user_id) {
+            return Ok(());
+        }
+        Err(ApplicationError::Forbidden(format!(
+            "Acting on the files of user {} requires their user token or X-VK-Secret",
+            user_id
+        )))
+    }
+
+    /// Admite al admin, al dueño de una subida fragmentada o a quien presente el secreto que
+    /// se devolvió al abrirla; es la única credencial posible en las sesiones anónimas
+    pub fn authorize_session(
+        &self,
+        session: &UploadSession,
+        secret: Option<&str>,
+    ) -> Result<(), ApplicationError> {
+        if self.admin {
+            return Ok(());
+        }
+        if let (Some(provided), Some(expected)) = (secret, session.session_secret.as_deref()) {
+            if secret_matches(provided, expected) {
+                return Ok(());
+            }
+        }
+        let owner = session
+            .user_id
+            .as_deref()
+            .and_then(|owner| Uuid::parse_str(owner).ok());
+        match owner {
+            Some(owner) if self.user_id == Some(owner) => Ok(()),
+            _ => Err(ApplicationError::Forbidden(format!(
+                "Upload session {} requires its session secret, its owner's user token or X-VK-Secret",
+                session.session_id
+            ))),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn admin() -> Self {
+        Self {
+            admin: true,
+            user_id: None,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn user(user_id: Option<Uuid>) -> Self {
+        Self {
+            admin: false,
+            user_id,
+        }
+    }
+}
+
+impl<S> FromRequestParts<S> for FileCaller
+where
+    Arc<Mutex<Secrets>>: FromRef<S>,
+    Arc<dyn TokenRepository>: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = ApplicationError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let secrets = Arc::<Mutex<Secrets>>::from_ref(state);
+        if has_vk_secret(&secrets, &parts.headers) {
+            return Ok(Self {
+                admin: true,
+                user_id: None,
+            });
+        }
+
+        let Some(token) = bearer_token(&parts.headers) else {
+            return Ok(Self {
+                admin: false,
+                user_id: None,
+            });
+        };
+        let token_repository = Arc::<dyn TokenRepository>::from_ref(state);
+        let user_id = token_repository.verify_user_token(token).await?;
+        let user_id = Uuid::parse_str(&user_id).map_err(|_| ApplicationError::InvalidToken)?;
+        Ok(Self {
+            admin: false,
+            user_id: Some(user_id),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secrets(vk_secret: &str) -> Secrets {
+        Secrets {
+            db_password: String::new(),
+            db_username: String::new(),
+            vk_secret: vk_secret.to_string(),
+            gdrive_secrets: None,
+            supabase_secrets: None,
+            s3_secrets: None,
+            azure_secrets: None,
+            local_fs_secrets: None,
+        }
+    }
+
+    #[test]
+    fn constant_time_eq_compares_bytes_and_length() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret-longer"));
+        assert!(!constant_time_eq(b"", b"x"));
+    }
+
+    #[test]
+    fn unconfigured_secret_never_matches() {
+        assert!(secret_matches("s3cr3t", "s3cr3t"));
+        assert!(!secret_matches("wrong", "s3cr3t"));
+        assert!(!secret_matches("", ""));
+    }
+
+    #[test]
+    fn has_vk_secret_reads_the_header() {
+        let secrets = Mutex::new(secrets("s3cr3t"));
+        let mut headers = HeaderMap::new();
+        assert!(!has_vk_secret(&secrets, &headers));
+
+        headers.insert("X-VK-Secret", "wrong".parse().unwrap());
+        assert!(!has_vk_secret(&secrets, &headers));
+
+        headers.insert("X-VK-Secret", "s3cr3t".parse().unwrap());
+        assert!(has_vk_secret(&secrets, &headers));
+    }
+
+    #[test]
+    fn has_vk_secret_survives_a_poisoned_lock() {
+        let secrets = Arc::new(Mutex::new(secrets("s3cr3t")));
+        let lock = secrets.clone();
+        std::thread::spawn(move || {
+            let _guard = lock.lock().unwrap();
+            panic!("secrets refresh panicked");
+        })
+        .join()
+        .unwrap_err();
+        assert!(secrets.is_poisoned());
+
+        let mut headers = HeaderMap::new();
+        headers.insert("X-VK-Secret", "s3cr3t".parse().unwrap());
+        assert!(has_vk_secret(&secrets, &headers));
+    }
+
+    fn metadata(owner: Option<Uuid>) -> Metadata {
+        Metadata {
+            file_id: "file".to_string(),
+            mime_type: "text/plain".to_string(),
+            size: 0,
+            user_id: owner.map(|owner| owner.to_string()),
+            description: None,
+            file_name: "file.txt".to_string(),
+            server_id: "server".to_string(),
+            uploaded_at: chrono::Utc::now(),
+            download_count: 0,
+            last_access: chrono::Utc::now(),
+            delete_at: None,
+            client_id: None,
+            trashed_at: None,
+            content_hash: None,
+            object_id: None,
+        }
+    }
+
+    #[test]
+    fn authorize_admits_admin_and_owner_only() {
+        let owner = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        let admin = FileCaller {
+            admin: true,
+            user_id: None,
+        };
+        let as_owner = FileCaller {
+            admin: false,
+            user_id: Some(owner),
+        };
+        let as_other = FileCaller {
+            admin: false,
+            user_id: Some(other),
+        };
+        let anonymous = FileCaller {
+            admin: false,
+            user_id: None,
+        };
+
+        assert!(admin.authorize(&metadata(Some(owner))).is_ok());
+        assert!(as_owner.authorize(&metadata(Some(owner))).is_ok());
+        assert!(matches!(
+            as_other.authorize(&metadata(Some(owner))),
+            Err(ApplicationError::Forbidden(_))
+        ));
+        assert!(matches!(
+            anonymous.authorize(&metadata(Some(owner))),
+            Err(ApplicationError::Forbidden(_))
+        ));
+    }
+
+    #[test]
+    fn anonymous_files_are_admin_only() {
+        let as_user = FileCaller {
+            admin: false,
+            user_id: Some(Uuid::new_v4()),
+        };
+        let admin = FileCaller {
+            admin: true,
+            user_id: None,
+        };
+
+        assert!(matches!(
+            as_user.authorize(&metadata(None)),
+            Err(ApplicationError::Forbidden(_))
+        ));
+        assert!(admin.authorize(&metadata(None)).is_ok());
+    }
+
+    fn session(owner: Option<Uuid>) -> UploadSession {
+        UploadSession {
+            session_id: Uuid::new_v4().to_string(),
+            filename: "file.txt".to_string(),
+            mime_type: "text/plain".to_string(),
+            file_type: crate::domain::models::file::FileKind::Permanent,
+            user_id: owner.map(|owner| owner.to_string()),
+            description: None,
+            client_id: None,
+            location: None,
+            chunk_size: 1024,
+            max_size: 4096,
+            expires_at: chrono::Utc::now(),
+            session_secret: Some("session-secret".to_string()),
+            received_bytes: 0,
+        }
+    }
+
+    #[test]
+    fn sessions_are_authorized_for_admin_and_owner() {
+        let owner = Uuid::new_v4();
+        let owned = session(Some(owner));
+
+        assert!(FileCaller::admin().authorize_session(&owned, None).is_ok());
+        assert!(FileCaller::user(Some(owner))
+            .authorize_session(&owned, None)
+            .is_ok());
+        for caller in [
+            FileCaller::user(Some(Uuid::new_v4())),
+            FileCaller::user(None),
+        ] {
+            assert!(matches!(
+                caller.authorize_session(&owned, None),
+                Err(ApplicationError::Forbidden(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn session_secret_admits_anonymous_callers() {
+        let anonymous = session(None);
+
+        assert!(FileCaller::admin()
+            .authorize_session(&anonymous, None)
+            .is_ok());
+        assert!(FileCaller::user(None)
+            .authorize_session(&anonymous, Some("session-secret"))
+            .is_ok());
+        for secret in [None, Some("wrong"), Some("")] {
+            assert!(matches!(
+                FileCaller::user(None).authorize_session(&anonymous, secret),
+                Err(ApplicationError::Forbidden(_))
+            ));
+        }
+
+        let without_secret = UploadSession {
+            session_secret: None,
+            ..session(None)
+        };
+        assert!(matches!(
+            FileCaller::user(None).authorize_session(&without_secret, Some("")),
+            Err(ApplicationError::Forbidden(_))
+        ));
+    }
+
+    #[test]
+    fn session_secret_header_is_optional() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(session_secret(&headers), None);
+
+        headers.insert("X-Upload-Session-Secret", "abc".parse().unwrap());
+        assert_eq!(session_secret(&headers), Some("abc"));
+    }
+
+    #[test]
+    fn bearer_token_reads_authorization() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(bearer_token(&headers), None);
+
+        headers.insert(header::AUTHORIZATION, "Basic abc".parse().unwrap());
+        assert_eq!(bearer_token(&headers), None);
+
+        headers.insert(header::AUTHORIZATION, "Bearer abc".parse().unwrap());
+        assert_eq!(bearer_token(&headers), Some("abc"));
+    }
+
+    /// Tokens de usuario en memoria; solo `verify_user_token` se usa al extraer FileCaller
+    struct UserTokens(Vec<(&'static str, String)>);
+
+    #[async_trait::async_trait]
+    impl TokenRepository for UserTokens {
+        async fn generate_token(
+            &self,
+            _user_id: Option<String>,
+            _ttl_seconds: u64,
+        ) -> Result<String, ApplicationError> {
+            unimplemented!()
+        }
+
+        async fn verify_and_consume_token(
+            &self,
+            _token: &str,
+        ) -> Result<Option<String>, ApplicationError> {
+            unimplemented!()
+        }
+
+        async fn generate_user_token(
+            &self,
+            _user_id: &str,
+            _ttl_seconds: u64,
+        ) -> Result<String, ApplicationError> {
+            unimplemented!()
+        }
+
+        async fn verify_user_token(&self, token: &str) -> Result<String, ApplicationError> {
+            self.0
+                .iter()
+                .find(|(known, _)| *known == token)
+                .map(|(_, user_id)| user_id.clone())
+                .ok_or(ApplicationError::InvalidToken)
+        }
+
+        async fn ping(&self) -> Result<(), ApplicationError> {
+            Ok(())
+        }
+    }
+
+    #[derive(Clone)]
+    struct CallerState {
+        secrets: Arc<Mutex<Secrets>>,
+        tokens: Arc<dyn TokenRepository>,
+    }
+
+    impl FromRef<CallerState> for Arc<Mutex<Secrets>> {
+        fn from_ref(state: &CallerState) -> Self {
+            state.secrets.clone()
+        }
+    }
+
+    impl FromRef<CallerState> for Arc<dyn TokenRepository> {
+        fn from_ref(state: &CallerState) -> Self {
+            state.tokens.clone()
+        }
+    }
+
+    async fn caller(
+        state: &CallerState,
+        headers: &[(&str, &str)],
+    ) -> Result<FileCaller, ApplicationError> {
+        let mut request = axum::http::Request::delete("/api/v1/files/file");
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        let (mut parts, _) = request.body(()).unwrap().into_parts();
+        FileCaller::from_request_parts(&mut parts, state).await
+    }
+
+    #[tokio::test]
+    async fn delete_is_authorized_only_for_the_token_owner() {
+        let owner = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        let state = CallerState {
+            secrets: Arc::new(Mutex::new(secrets("s3cr3t"))),
+            tokens: Arc::new(UserTokens(vec![
+                ("owner-token", owner.to_string()),
+                ("other-token", other.to_string()),
+            ])),
+        };
+        let file = metadata(Some(owner));
+
+        let as_owner = caller(&state, &[("Authorization", "Bearer owner-token")])
+            .await
+            .unwrap();
+        assert!(as_owner.authorize(&file).is_ok());
+
+        let as_other = caller(&state, &[("Authorization", "Bearer other-token")])
+            .await
+            .unwrap();
+        assert!(matches!(
+            as_other.authorize(&file),
+            Err(ApplicationError::Forbidden(_))
+        ));
+
+        let admin = caller(&state, &[("X-VK-Secret", "s3cr3t")]).await.unwrap();
+        assert!(admin.authorize(&file).is_ok());
+    }
+
+    #[tokio::test]
+    async fn unverified_identity_is_rejected() {
+        let owner = Uuid::new_v4();
+        let state = CallerState {
+            secrets: Arc::new(Mutex::new(secrets("s3cr3t"))),
+            tokens: Arc::new(UserTokens(vec![])),
+        };
+        let file = metadata(Some(owner));
+
+        assert!(matches!(
+            caller(&state, &[("Authorization", "Bearer forged")]).await,
+            Err(ApplicationError::InvalidToken)
+        ));
+
+        // Un userId en la query ya no acredita al dueño
+        let mut parts = axum::http::Request::delete(format!("/api/v1/files/file?userId={}", owner))
+            .body(())
+            .unwrap()
+            .into_parts()
+            .0;
+        let spoofed = FileCaller::from_request_parts(&mut parts, &state)
+            .await
+            .unwrap();
+        assert!(matches!(
+            spoofed.authorize(&file),
+            Err(ApplicationError::Forbidden(_))
+        ));
+    }
+}
@@ -0,0 +1,304 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    str::FromStr,
+    sync::Arc,
+};
+
+use axum::{
+    extract::{ConnectInfo, FromRef, FromRequestParts},
+    http::{request::Parts, HeaderMap},
+};
+
+use crate::application::error::ApplicationError;
+
+/// Red en notación CIDR; una IP sin prefijo cubre solo esa dirección
+#[derive(Debug, Clone, Copy)]
+struct IpNet {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl IpNet {
+    fn contains(&self, ip: IpAddr) -> bool {
+        match (self.addr, canonical(ip)) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => mask_matches(
+                u32::from(net) as u128,
+                u32::from(ip) as u128,
+                self.prefix,
+                32,
+            ),
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                mask_matches(u128::from(net), u128::from(ip), self.prefix, 128)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for IpNet {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix) = match value.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (value, None),
+        };
+        let addr: IpAddr = addr
+            .parse()
+            .map_err(|_| format!("Invalid trusted proxy address '{}'", value))?;
+        let addr = canonical(addr);
+        let max_prefix = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix
+                .parse::<u8>()
+                .ok()
+                .filter(|prefix| *prefix <= max_prefix)
+                .ok_or_else(|| format!("Invalid trusted proxy prefix '{}'", value))?,
+            None => max_prefix,
+        };
+        Ok(Self { addr, prefix })
+    }
+}
+
+fn mask_matches(net: u128, ip: u128, prefix: u8, bits: u8) -> bool {
+    if prefix == 0 {
+        return true;
+    }
+    let shift = bits - prefix;
+    (net >> shift) == (ip >> shift)
+}
+
+/// Las IPv4 mapeadas en IPv6 (::ffff:a.b.c.d) se comparan como IPv4
+fn canonical(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(IpAddr::V6(v6), IpAddr::V4),
+        v4 => v4,
+    }
+}
+
+/// Proxies (VK-Gateway) cuyas cabeceras X-Forwarded-For/Forwarded se aceptan
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies {
+    networks: Vec<IpNet>,
+}
+
+impl TrustedProxies {
+    /// Lista separada por comas de IPs o redes CIDR
+    pub fn parse(list: &str) -> Result<Self, String> {
+        let networks = list
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(IpNet::from_str)
+            .collect::<Result<_, _>>()?;
+        Ok(Self { networks })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.networks.is_empty()
+    }
+
+    fn is_trusted(&self, ip: IpAddr) -> bool {
+        self.networks.iter().any(|network| network.contains(ip))
+    }
+
+    /// IP del cliente: las cabeceras de reenvío solo cuentan si el par inmediato es de confianza
+    ///
+    /// Se recorre la cadena de derecha a izquierda saltando proxies de confianza; la primera
+    /// dirección que no lo es es el cliente. Las entradas más a la izquierda las escribe el
+    /// propio cliente y no se usan salvo que toda la cadena sea de confianza.
+    pub fn resolve(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        let peer = canonical(peer);
+        if !self.is_trusted(peer) {
+            return peer;
+        }
+
+        let chain = forwarded_for_chain(headers);
+        let mut client = peer;
+        for hop in chain.into_iter().rev() {
+            let Some(hop) = hop else {
+                // Entrada ilegible u ofuscada: no se puede seguir más allá
+                break;
+            };
+            client = canonical(hop);
+            if !self.is_trusted(client) {
+                break;
+            }
+        }
+        client
+    }
+}
+
+/// Direcciones de X-Forwarded-For o, si no está, de los `for=` de Forwarded, en orden
+fn forwarded_for_chain(headers: &HeaderMap) -> Vec<Option<IpAddr>> {
+    let x_forwarded_for: Vec<&str> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .collect();
+    if !x_forwarded_for.is_empty() {
+        return x_forwarded_for.into_iter().map(parse_node).collect();
+    }
+
+    headers
+        .get_all("forwarded")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|element| {
+            element.split(';').find_map(|pair| {
+                let (key, value) = pair.trim().split_once('=')?;
+                key.eq_ignore_ascii_case("for").then_some(value)
+            })
+        })
+        .map(parse_node)
+        .collect()
+}
+
+/// Acepta `1.2.3.4`, `1.2.3.4:80`, `2001:db8::1`, `"[2001:db8::1]:80"`
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim().trim_matches('"');
+    if let Ok(ip) = node.parse::<IpAddr>() {
+        return Some(ip);
+    }
+    if let Ok(socket) = node.parse::<SocketAddr>() {
+        return Some(socket.ip());
+    }
+    node.strip_prefix('[')
+        .and_then(|rest| rest.split_once(']'))
+        .and_then(|(ip, _)| ip.parse().ok())
+}
+
+/// IP real del cliente según `TrustedProxies`; requiere servir con `ConnectInfo<SocketAddr>`
+#[derive(Debug, Clone, Copy)]
+pub struct ClientIp(pub IpAddr);
+
+impl<S> FromRequestParts<S> for ClientIp
+where
+    Arc<TrustedProxies>: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = ApplicationError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let ConnectInfo(peer) = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .copied()
+            .ok_or_else(|| {
+                ApplicationError::InternalError("Missing connection info".to_string())
+            })?;
+        let trusted_proxies = Arc::<TrustedProxies>::from_ref(state);
+        Ok(ClientIp(trusted_proxies.resolve(peer.ip(), &parts.headers)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(*name, value.parse().unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn ip_net_parses_addresses_and_cidrs() {
+        let single: IpNet = "10.0.0.1".parse().unwrap();
+        assert_eq!(single.prefix, 32);
+        assert!(single.contains(ip("10.0.0.1")));
+        assert!(!single.contains(ip("10.0.0.2")));
+
+        let network: IpNet = "10.0.0.0/8".parse().unwrap();
+        assert!(network.contains(ip("10.200.3.4")));
+        assert!(!network.contains(ip("11.0.0.1")));
+
+        let v6: IpNet = "2001:db8::/32".parse().unwrap();
+        assert_eq!(v6.prefix, 32);
+        assert!(v6.contains(ip("2001:db8::1")));
+        assert!(!v6.contains(ip("10.0.0.1")));
+
+        let any: IpNet = "0.0.0.0/0".parse().unwrap();
+        assert!(any.contains(ip("203.0.113.9")));
+    }
+
+    #[test]
+    fn ip_net_rejects_invalid_entries() {
+        for entry in [
+            "10.0.0.0/33",
+            "2001:db8::/129",
+            "10.0.0.0/x",
+            "not-an-ip",
+            "10.0.0/8",
+        ] {
+            assert!(
+                entry.parse::<IpNet>().is_err(),
+                "{:?} should be rejected",
+                entry
+            );
+        }
+        assert!(TrustedProxies::parse("10.0.0.1, bogus").is_err());
+        assert!(TrustedProxies::parse(" , ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn ipv4_mapped_addresses_match_ipv4_networks() {
+        let network: IpNet = "192.168.0.0/16".parse().unwrap();
+        assert!(network.contains(ip("::ffff:192.168.1.1")));
+    }
+
+    #[test]
+    fn resolve_ignores_forwarding_headers_from_untrusted_peers() {
+        let proxies = TrustedProxies::parse("10.0.0.0/8").unwrap();
+        let headers = headers(&[("x-forwarded-for", "198.51.100.7")]);
+        assert_eq!(
+            proxies.resolve(ip("203.0.113.9"), &headers),
+            ip("203.0.113.9")
+        );
+    }
+
+    #[test]
+    fn resolve_skips_trusted_hops_from_the_right() {
+        let proxies = TrustedProxies::parse("10.0.0.0/8").unwrap();
+        let headers = headers(&[("x-forwarded-for", "1.1.1.1, 198.51.100.7, 10.0.0.2")]);
+        // 1.1.1.1 lo escribió el cliente; el primer salto que no es de confianza es el cliente
+        assert_eq!(
+            proxies.resolve(ip("10.0.0.1"), &headers),
+            ip("198.51.100.7")
+        );
+    }
+
+    #[test]
+    fn resolve_reads_forwarded_when_x_forwarded_for_is_missing() {
+        let proxies = TrustedProxies::parse("10.0.0.1").unwrap();
+        let headers = headers(&[(
+            "forwarded",
+            "for=\"[2001:db8::7]:4711\";proto=https, for=10.0.0.1",
+        )]);
+        assert_eq!(proxies.resolve(ip("10.0.0.1"), &headers), ip("2001:db8::7"));
+    }
+
+    #[test]
+    fn resolve_stops_at_an_unreadable_hop() {
+        let proxies = TrustedProxies::parse("10.0.0.0/8").unwrap();
+        let headers = headers(&[("x-forwarded-for", "198.51.100.7, unknown, 10.0.0.2")]);
+        assert_eq!(proxies.resolve(ip("10.0.0.1"), &headers), ip("10.0.0.2"));
+    }
+
+    #[test]
+    fn resolve_without_headers_returns_the_peer() {
+        let proxies = TrustedProxies::parse("10.0.0.0/8").unwrap();
+        assert_eq!(
+            proxies.resolve(ip("10.0.0.1"), &HeaderMap::new()),
+            ip("10.0.0.1")
+        );
+    }
+}
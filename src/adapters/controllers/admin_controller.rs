@@ -0,0 +1,950 @@
+use axum::{
+    extract::{Query, State},
+    http::HeaderMap,
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures_util::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    adapters::{
+        controllers::file_controller::FileController,
+        dto::file_dto::{ClientStatsResponse, FileResponse},
+        ndjson::{ndjson_response, wants_ndjson},
+        pagination::{Pagination, PaginationParams},
+        state::AppState,
+    },
+    application::{error::ApplicationError, services::StorageService},
+    domain::config::{global::GlobalConfig, local::LocalConfig, secrets::Secrets},
+    services,
+};
+
+const DEFAULT_EXPORT_LIMIT: i64 = 500;
+const MAX_EXPORT_LIMIT: i64 = 5_000;
+/// Consultar checksums cuesta una llamada al proveedor por archivo
+const MAX_EXPORT_LIMIT_WITH_CHECKSUMS: i64 = 200;
+const DEFAULT_REINDEX_LIMIT: i64 = 50;
+/// Reindexar descarga cada archivo entero del proveedor
+const MAX_REINDEX_LIMIT: i64 = 200;
+const DEFAULT_VACUUM_BATCH_SIZE: i64 = 100;
+const MAX_VACUUM_BATCH_SIZE: i64 = 1_000;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RefreshConfigResponse {
+    #[serde(rename = "localConfigChanged")]
+    pub local_config_changed: bool,
+    #[serde(rename = "globalConfigChanged")]
+    pub global_config_changed: bool,
+    #[serde(rename = "secretsChanged")]
+    pub secrets_changed: bool,
+    #[serde(rename = "storageServiceRecreated")]
+    pub storage_service_recreated: bool,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportQuery {
+    #[serde(rename = "includeChecksums", default)]
+    pub include_checksums: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ExportItem {
+    #[serde(flatten)]
+    pub file: FileResponse,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ExportPage {
+    pub items: Vec<ExportItem>,
+    #[serde(rename = "nextCursor")]
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReindexMismatch {
+    #[serde(rename = "fileId")]
+    pub file_id: String,
+    pub stored: String,
+    pub computed: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReindexFailure {
+    #[serde(rename = "fileId")]
+    pub file_id: String,
+    pub error: String,
+}
+
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct ReindexChecksumsResponse {
+    /// Archivos de esta instancia leídos del proveedor en esta página
+    pub processed: u64,
+    /// Archivos sin hash a los que se les guardó el calculado
+    pub updated: u64,
+    /// Archivos cuyo hash guardado coincide con el contenido
+    pub verified: u64,
+    /// Filas de otras instancias; su objeto no está en este proveedor
+    pub skipped: u64,
+    pub mismatches: Vec<ReindexMismatch>,
+    pub failed: Vec<ReindexFailure>,
+    #[serde(rename = "nextCursor")]
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct VacuumQuery {
+    /// Segundos en la papelera antes de purgar; por defecto trashRetention de la configuración
+    /// global
+    #[serde(rename = "retentionSecs")]
+    pub retention_secs: Option<u64>,
+    /// Filas leídas y purgadas por lote (por defecto 100, máximo 1000)
+    #[serde(rename = "batchSize")]
+    pub batch_size: Option<i64>,
+}
+
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct VacuumResponse {
+    /// Filas de la papelera borradas, junto a su objeto si ninguna otra lo usa
+    pub purged: u64,
+    /// Filas que no se pudieron purgar; siguen en la papelera para el próximo vacuum
+    pub failed: u64,
+    pub batches: u64,
+    #[serde(rename = "retentionSecs")]
+    pub retention_secs: u64,
+    pub errors: Vec<String>,
+}
+
+/// Qué difiere entre la configuración en memoria y la recién leída de la base de datos
+#[derive(Debug, PartialEq)]
+struct ConfigChanges {
+    local: bool,
+    provider: bool,
+    global: bool,
+    secrets: bool,
+}
+
+impl ConfigChanges {
+    fn between(
+        (local, global, secrets): (&LocalConfig, &GlobalConfig, &Secrets),
+        (new_local, new_global, new_secrets): (&LocalConfig, &GlobalConfig, &Secrets),
+    ) -> Self {
+        Self {
+            local: local != new_local,
+            provider: local.provider != new_local.provider,
+            global: global != new_global,
+            secrets: secrets != new_secrets,
+        }
+    }
+
+    /// El servicio de almacenamiento solo se recrea si cambia el proveedor o sus credenciales
+    fn recreates_storage(&self) -> bool {
+        self.provider || self.secrets
+    }
+}
+
+pub struct AdminController;
+
+impl AdminController {
+    /// SHA-256 del objeto, leído en streaming desde el proveedor
+    async fn content_hash(
+        service: &dyn StorageService,
+        storage_key: &str,
+    ) -> Result<String, ApplicationError> {
+        let mut object = service.download_stream(storage_key).await?;
+        let mut hasher = Sha256::new();
+        while let Some(chunk) = object.content.next().await {
+            hasher.update(&chunk?);
+        }
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+
+    /// Variante NDJSON: sin límite por defecto, las filas se envían según se leen
+    fn export_ndjson(app_state: &AppState, pagination: Pagination, query: ExportQuery) -> Response {
+        info!("Streaming metadata export as NDJSON");
+        let limit = pagination.limit_capped(i64::MAX);
+        let rows = app_state
+            .metadata_repository
+            .stream_export(pagination.cursor, limit);
+
+        if !query.include_checksums {
+            let items = rows.map(|row| {
+                row.map(|metadata| ExportItem {
+                    file: FileResponse::from(metadata),
+                    checksum: None,
+                })
+            });
+            return ndjson_response(items);
+        }
+
+        let service = app_state.storage_service.get();
+        let items = rows
+            .map(move |row| {
+                let service = service.clone();
+                async move {
+                    let metadata = row?;
+                    let checksum = match service.get_metadata(metadata.storage_key()).await {
+                        Ok(storage_metadata) => storage_metadata.checksum,
+                        Err(e) => {
+                            warn!(
+                                "No checksum for {} during export: {:?}",
+                                metadata.file_id, e
+                            );
+                            None
+                        }
+                    };
+                    Ok::<_, ApplicationError>(ExportItem {
+                        file: FileResponse::from(metadata),
+                        checksum,
+                    })
+                }
+            })
+            .buffered(app_state.provider_request_concurrency);
+        ndjson_response(items)
+    }
+}
+
+/// Recarga la configuración en memoria desde la base de datos
+/// POST /api/v1/admin/refresh-config
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/refresh-config",
+    tag = "Admin",
+    security(("kv_secret" = [])),
+    responses((status = 200, body = RefreshConfigResponse))
+)]
+pub async fn refresh_config(
+    State(app_state): State<AppState>,
+) -> Result<Json<RefreshConfigResponse>, ApplicationError> {
+    info!(
+        "Refreshing in-memory config for server_id: {}",
+        app_state.server_id
+    );
+
+    let (local_config, global_config, secrets) = tokio::join!(
+        app_state
+            .local_config_repository
+            .get_local_config(&app_state.server_id),
+        app_state.global_config_repository.get_global_config(),
+        app_state.secrets_repository.get_secrets()
+    );
+    let (local_config, global_config, secrets) = (local_config?, global_config?, secrets?);
+
+    let changes = ConfigChanges::between(
+        (
+            &app_state.local_config.lock().unwrap(),
+            &app_state.global_config.lock().unwrap(),
+            &app_state.secrets.lock().unwrap(),
+        ),
+        (&local_config, &global_config, &secrets),
+    );
+
+    // Recrear el servicio antes de publicar la nueva configuración, para no
+    // dejar el estado a medias si las credenciales nuevas son inválidas
+    let storage_service_recreated = if changes.recreates_storage() {
+        match services::create_storage_service(&local_config.provider, &secrets).await {
+            Ok(new_service) => {
+                app_state.storage_service.replace(new_service);
+                true
+            }
+            Err(e) => {
+                warn!("Failed to recreate storage service: {:?}", e);
+                return Err(ApplicationError::InternalError(format!(
+                    "Failed to create storage service for provider {:?}: {:?}",
+                    local_config.provider, e
+                )));
+            }
+        }
+    } else {
+        false
+    };
+
+    *app_state.local_config.lock().unwrap() = local_config;
+    *app_state.global_config.lock().unwrap() = global_config;
+    *app_state.secrets.lock().unwrap() = secrets;
+
+    info!(
+        "Config refreshed: local_changed={}, global_changed={}, secrets_changed={}, storage_recreated={}",
+        changes.local, changes.global, changes.secrets, storage_service_recreated
+    );
+
+    Ok(Json(RefreshConfigResponse {
+        local_config_changed: changes.local,
+        global_config_changed: changes.global,
+        secrets_changed: changes.secrets,
+        storage_service_recreated,
+    }))
+}
+
+/// Archivos, bytes y descargas agrupados por el cliente que los subió
+/// GET /api/v1/admin/stats/clients
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/stats/clients",
+    tag = "Admin",
+    security(("kv_secret" = [])),
+    responses((status = 200, body = Vec<ClientStatsResponse>))
+)]
+pub async fn client_stats(
+    State(app_state): State<AppState>,
+) -> Result<Json<Vec<ClientStatsResponse>>, ApplicationError> {
+    let stats = app_state.metadata_repository.get_client_stats().await?;
+    Ok(Json(stats.into_iter().map(Into::into).collect()))
+}
+
+/// Exporta la metadata paginada por cursor (file_id) para respaldos
+/// GET /api/v1/admin/export?cursor=&limit=&includeChecksums=
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/export",
+    tag = "Admin",
+    security(("kv_secret" = [])),
+    params(PaginationParams, ExportQuery),
+    responses(
+        (status = 200, description = "Página de metadata, o un ExportItem por línea en NDJSON",
+            content((ExportPage = "application/json"), (ExportItem = "application/x-ndjson"))),
+        (status = 400, description = "Parámetros de paginación inválidos"),
+    )
+)]
+pub async fn export_metadata(
+    State(app_state): State<AppState>,
+    pagination: Pagination,
+    Query(query): Query<ExportQuery>,
+    headers: HeaderMap,
+) -> Result<Response, ApplicationError> {
+    if wants_ndjson(&headers) {
+        return Ok(AdminController::export_ndjson(
+            &app_state, pagination, query,
+        ));
+    }
+
+    let max_limit = if query.include_checksums {
+        MAX_EXPORT_LIMIT_WITH_CHECKSUMS
+    } else {
+        MAX_EXPORT_LIMIT
+    };
+    let limit = pagination.limit(DEFAULT_EXPORT_LIMIT, max_limit);
+
+    // Pedimos una fila extra para saber si hay otra página
+    let mut rows = app_state
+        .metadata_repository
+        .export_metadata(pagination.cursor.as_deref(), limit + 1)
+        .await?;
+    let next_cursor = if rows.len() as i64 > limit {
+        rows.truncate(limit as usize);
+        rows.last().map(|metadata| metadata.file_id.clone())
+    } else {
+        None
+    };
+
+    let items = if query.include_checksums {
+        let service = app_state.storage_service.get();
+        stream::iter(rows)
+            .map(|metadata| {
+                let service = service.clone();
+                async move {
+                    let checksum = match service.get_metadata(metadata.storage_key()).await {
+                        Ok(storage_metadata) => storage_metadata.checksum,
+                        Err(e) => {
+                            warn!(
+                                "No checksum for {} during export: {:?}",
+                                metadata.file_id, e
+                            );
+                            None
+                        }
+                    };
+                    ExportItem {
+                        file: FileResponse::from(metadata),
+                        checksum,
+                    }
+                }
+            })
+            .buffered(app_state.provider_request_concurrency)
+            .collect()
+            .await
+    } else {
+        rows.into_iter()
+            .map(|metadata| ExportItem {
+                file: FileResponse::from(metadata),
+                checksum: None,
+            })
+            .collect()
+    };
+
+    Ok(Json(ExportPage { items, next_cursor }).into_response())
+}
+
+/// Purga por lotes las filas que llevan en la papelera más que la retención, con su objeto
+/// y su cuota
+/// POST /api/v1/admin/vacuum?retentionSecs=&batchSize=
+///
+/// Se detiene en el primer lote incompleto o con fallos: las filas que fallan se volverían
+/// a leer en el siguiente. Comparte `cleanup_lock` con DELETE /api/v1/files.
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/vacuum",
+    tag = "Admin",
+    security(("kv_secret" = [])),
+    params(VacuumQuery),
+    responses(
+        (status = 200, body = VacuumResponse),
+        (status = 409, description = "Ya hay una limpieza o un vacuum en curso"),
+    )
+)]
+pub async fn vacuum(
+    State(app_state): State<AppState>,
+    Query(query): Query<VacuumQuery>,
+) -> Result<Json<VacuumResponse>, ApplicationError> {
+    let batch_size = query
+        .batch_size
+        .unwrap_or(DEFAULT_VACUUM_BATCH_SIZE)
+        .clamp(1, MAX_VACUUM_BATCH_SIZE);
+    let retention_secs = query
+        .retention_secs
+        .unwrap_or_else(|| app_state.global_config.lock().unwrap().trash_retention);
+
+    let Ok(_running) = app_state.cleanup_lock.try_lock() else {
+        return Err(ApplicationError::Conflict(
+            "Expired file cleanup is already running".to_string(),
+        ));
+    };
+
+    let mut response = VacuumResponse {
+        retention_secs,
+        ..Default::default()
+    };
+    loop {
+        let batch = app_state
+            .metadata_repository
+            .get_trashed_files(retention_secs, batch_size)
+            .await?;
+        if batch.is_empty() {
+            break;
+        }
+        let full = batch.len() as i64 == batch_size;
+
+        let purged = FileController::purge_files(&app_state, batch).await;
+        response.batches += 1;
+        response.purged += purged.deleted_count as u64;
+        response.failed += purged.results.failed as u64;
+        response.errors.extend(purged.errors);
+        if !full || purged.results.failed > 0 {
+            break;
+        }
+    }
+
+    info!(
+        "Vacuum purged {} trashed rows in {} batches ({} failed, retention {}s)",
+        response.purged, response.batches, response.failed, retention_secs
+    );
+    Ok(Json(response))
+}
+
+/// Calcula el hash del contenido de los archivos que no lo tienen y comprueba el de los
+/// que sí; procesa una página por llamada, que se reanuda con `nextCursor`
+/// POST /api/v1/admin/reindex-checksums?cursor=&limit=
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/reindex-checksums",
+    tag = "Admin",
+    security(("kv_secret" = [])),
+    params(PaginationParams),
+    responses(
+        (status = 200, description = "Resultado de la página; seguir con nextCursor si no es null",
+            body = ReindexChecksumsResponse),
+        (status = 400, description = "Parámetros de paginación inválidos"),
+    )
+)]
+pub async fn reindex_checksums(
+    State(app_state): State<AppState>,
+    pagination: Pagination,
+) -> Result<Json<ReindexChecksumsResponse>, ApplicationError> {
+    let limit = pagination.limit(DEFAULT_REINDEX_LIMIT, MAX_REINDEX_LIMIT);
+
+    let mut rows = app_state
+        .metadata_repository
+        .export_metadata(pagination.cursor.as_deref(), limit + 1)
+        .await?;
+    let next_cursor = if rows.len() as i64 > limit {
+        rows.truncate(limit as usize);
+        rows.last().map(|metadata| metadata.file_id.clone())
+    } else {
+        None
+    };
+
+    let (local, foreign): (Vec<_>, Vec<_>) = rows
+        .into_iter()
+        .partition(|metadata| metadata.server_id == app_state.server_id);
+
+    let service = app_state.storage_service.get();
+    let hashed: Vec<_> = stream::iter(local)
+        .map(|metadata| {
+            let service = service.clone();
+            async move {
+                let computed =
+                    AdminController::content_hash(service.as_ref(), metadata.storage_key()).await;
+                (metadata, computed)
+            }
+        })
+        .buffered(app_state.provider_request_concurrency)
+        .collect()
+        .await;
+
+    let mut response = ReindexChecksumsResponse {
+        processed: hashed.len() as u64,
+        skipped: foreign.len() as u64,
+        next_cursor,
+        ..Default::default()
+    };
+    for (metadata, computed) in hashed {
+        let result = match (computed, metadata.content_hash) {
+            (Err(e), _) => Err(e),
+            (Ok(computed), Some(stored)) if stored == computed => {
+                response.verified += 1;
+                Ok(())
+            }
+            (Ok(computed), Some(stored)) => {
+                warn!(
+                    "Checksum mismatch for {}: stored {}, content hashes to {}",
+                    metadata.file_id, stored, computed
+                );
+                response.mismatches.push(ReindexMismatch {
+                    file_id: metadata.file_id.clone(),
+                    stored,
+                    computed,
+                });
+                Ok(())
+            }
+            (Ok(computed), None) => app_state
+                .metadata_repository
+                .set_content_hash(&metadata.file_id, &computed)
+                .await
+                .map(|()| response.updated += 1),
+        };
+        if let Err(e) = result {
+            warn!(
+                "Failed to reindex checksum of {}: {:?}",
+                metadata.file_id, e
+            );
+            response.failed.push(ReindexFailure {
+                file_id: metadata.file_id,
+                error: format!("{:?}", e),
+            });
+        }
+    }
+
+    info!(
+        "Reindexed checksums: processed={}, updated={}, verified={}, mismatches={}, failed={}, next_cursor={:?}",
+        response.processed,
+        response.updated,
+        response.verified,
+        response.mismatches.len(),
+        response.failed.len(),
+        response.next_cursor
+    );
+    Ok(Json(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        application::dto::{
+            global_config_dto::GlobalConfigDTO, local_config_dto::LocalConfigDTO,
+            secrets_dto::SecretsDTO,
+        },
+        domain::config::local::Provider,
+    };
+
+    fn config() -> (LocalConfig, GlobalConfig, Secrets) {
+        (
+            LocalConfigDTO::default().into(),
+            GlobalConfigDTO::default().into(),
+            SecretsDTO::default().into(),
+        )
+    }
+
+    fn changes(
+        current: &(LocalConfig, GlobalConfig, Secrets),
+        fresh: &(LocalConfig, GlobalConfig, Secrets),
+    ) -> ConfigChanges {
+        ConfigChanges::between(
+            (&current.0, &current.1, &current.2),
+            (&fresh.0, &fresh.1, &fresh.2),
+        )
+    }
+
+    #[test]
+    fn identical_config_changes_nothing() {
+        let changes = changes(&config(), &config());
+        assert_eq!(
+            changes,
+            ConfigChanges {
+                local: false,
+                provider: false,
+                global: false,
+                secrets: false,
+            }
+        );
+        assert!(!changes.recreates_storage());
+    }
+
+    #[test]
+    fn local_and_global_edits_keep_the_storage_service() {
+        let mut fresh = config();
+        fresh.0.server_name = "renamed".to_string();
+        fresh.1.max_size += 1;
+
+        let changes = changes(&config(), &fresh);
+        assert!(changes.local && changes.global);
+        assert!(!changes.recreates_storage());
+    }
+
+    #[test]
+    fn provider_or_secrets_edits_recreate_the_storage_service() {
+        let mut provider = config();
+        provider.0.provider = Provider::LocalFs;
+        let changes_provider = changes(&config(), &provider);
+        assert!(changes_provider.local && changes_provider.provider);
+        assert!(changes_provider.recreates_storage());
+
+        let mut secrets = config();
+        secrets.2.vk_secret = "rotated".to_string();
+        let changes_secrets = changes(&config(), &secrets);
+        assert!(changes_secrets.secrets && !changes_secrets.local);
+        assert!(changes_secrets.recreates_storage());
+    }
+
+    mod reindex_checksums {
+        use axum::extract::FromRequestParts;
+        use uuid::Uuid;
+
+        use super::*;
+        use crate::{
+            adapters::{repositories::in_memory_metadata_repository::metadata, test_app::TestApp},
+            application::repositories::metadata_repository::MetadataRepository,
+        };
+
+        fn sha256(content: &[u8]) -> String {
+            format!("{:x}", Sha256::digest(content))
+        }
+
+        async fn reindex(app: &TestApp, query: &str) -> ReindexChecksumsResponse {
+            let request = axum::http::Request::post(format!("/reindex?{}", query))
+                .body(())
+                .unwrap();
+            let (mut parts, _) = request.into_parts();
+            let pagination = Pagination::from_request_parts(&mut parts, &())
+                .await
+                .unwrap();
+            reindex_checksums(State(app.state.clone()), pagination)
+                .await
+                .unwrap()
+                .0
+        }
+
+        #[tokio::test]
+        async fn files_without_checksum_get_it_populated() {
+            let app = TestApp::new();
+            let owner = Uuid::new_v4();
+            app.file("a", owner, b"alpha");
+            app.file("b", owner, b"bravo");
+            app.file("c", owner, b"charlie");
+            app.metadata
+                .set_content_hash("c", &sha256(b"charlie"))
+                .await
+                .unwrap();
+            app.file("d", owner, b"delta");
+            app.metadata
+                .set_content_hash("d", &sha256(b"stale"))
+                .await
+                .unwrap();
+            app.metadata.insert(metadata("e", owner, "missing.txt"));
+            let mut foreign = metadata("f", owner, "foreign.txt");
+            foreign.server_id = "other-server".to_string();
+            app.metadata.insert(foreign);
+
+            let response = reindex(&app, "").await;
+
+            assert_eq!(response.processed, 5);
+            assert_eq!(response.updated, 2);
+            assert_eq!(response.verified, 1);
+            assert_eq!(response.skipped, 1);
+            assert_eq!(response.next_cursor, None);
+            assert_eq!(app.metadata.file("a").content_hash, Some(sha256(b"alpha")));
+            assert_eq!(app.metadata.file("b").content_hash, Some(sha256(b"bravo")));
+
+            let [mismatch] = response.mismatches.as_slice() else {
+                panic!("expected one mismatch: {:?}", response.mismatches);
+            };
+            assert_eq!(mismatch.file_id, "d");
+            assert_eq!(mismatch.stored, sha256(b"stale"));
+            assert_eq!(mismatch.computed, sha256(b"delta"));
+            assert_eq!(app.metadata.file("d").content_hash, Some(sha256(b"stale")));
+
+            let [failure] = response.failed.as_slice() else {
+                panic!("expected one failure: {:?}", response.failed);
+            };
+            assert_eq!(failure.file_id, "e");
+            assert_eq!(failure.error, "NotFound");
+            assert_eq!(app.metadata.file("e").content_hash, None);
+            assert_eq!(app.metadata.file("f").content_hash, None);
+        }
+
+        #[tokio::test]
+        async fn reindex_resumes_from_the_cursor() {
+            let app = TestApp::new();
+            let owner = Uuid::new_v4();
+            for id in ["a", "b", "c"] {
+                app.file(id, owner, id.as_bytes());
+            }
+
+            let first = reindex(&app, "limit=2").await;
+            assert_eq!(first.updated, 2);
+            assert_eq!(first.next_cursor.as_deref(), Some("b"));
+            assert_eq!(app.metadata.file("c").content_hash, None);
+
+            let second = reindex(&app, "limit=2&cursor=b").await;
+            assert_eq!(second.processed, 1);
+            assert_eq!(second.updated, 1);
+            assert_eq!(second.next_cursor, None);
+            assert_eq!(app.metadata.file("c").content_hash, Some(sha256(b"c")));
+        }
+    }
+
+    mod export {
+        use axum::extract::FromRequestParts;
+        use serde_json::Value;
+        use uuid::Uuid;
+
+        use super::*;
+        use crate::adapters::test_app::TestApp;
+
+        async fn export(app: &TestApp, query: &str, headers: HeaderMap) -> Vec<u8> {
+            let request = axum::http::Request::get(format!("/export?{}", query))
+                .body(())
+                .unwrap();
+            let (mut parts, _) = request.into_parts();
+            let pagination = Pagination::from_request_parts(&mut parts, &())
+                .await
+                .unwrap();
+            let Query(query) = Query::<ExportQuery>::from_request_parts(&mut parts, &())
+                .await
+                .unwrap();
+            let response =
+                export_metadata(State(app.state.clone()), pagination, Query(query), headers)
+                    .await
+                    .unwrap();
+            axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap()
+                .to_vec()
+        }
+
+        async fn page(app: &TestApp, query: &str) -> Value {
+            serde_json::from_slice(&export(app, query, HeaderMap::new()).await).unwrap()
+        }
+
+        fn ids(items: &Value) -> Vec<&str> {
+            items
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|item| item["fileId"].as_str().unwrap())
+                .collect()
+        }
+
+        #[tokio::test]
+        async fn pages_follow_the_cursor_until_exhausted() {
+            let app = TestApp::new();
+            let owner = Uuid::new_v4();
+            for id in ["c", "a", "e", "b", "d"] {
+                app.file(id, owner, id.as_bytes());
+            }
+
+            let first = page(&app, "limit=2").await;
+            assert_eq!(ids(&first["items"]), ["a", "b"]);
+            assert_eq!(first["nextCursor"], "b");
+
+            let second = page(&app, "limit=2&cursor=b").await;
+            assert_eq!(ids(&second["items"]), ["c", "d"]);
+            assert_eq!(second["nextCursor"], "d");
+
+            let last = page(&app, "limit=2&cursor=d").await;
+            assert_eq!(ids(&last["items"]), ["e"]);
+            assert!(last["nextCursor"].is_null());
+            assert!(last["items"][0].get("checksum").is_none());
+        }
+
+        #[tokio::test]
+        async fn checksums_come_from_the_provider() {
+            let app = TestApp::new();
+            app.file("a", Uuid::new_v4(), b"alpha");
+
+            let page = page(&app, "includeChecksums=true").await;
+
+            assert_eq!(
+                page["items"][0]["checksum"],
+                format!("{:x}", Sha256::digest(b"alpha"))
+            );
+        }
+
+        #[tokio::test]
+        async fn ndjson_streams_one_row_per_line_from_the_cursor() {
+            let app = TestApp::new();
+            let owner = Uuid::new_v4();
+            for id in ["a", "b", "c", "d"] {
+                app.file(id, owner, id.as_bytes());
+            }
+
+            let body = export(
+                &app,
+                "cursor=a&limit=2",
+                TestApp::headers(&[("Accept", "application/x-ndjson")]),
+            )
+            .await;
+
+            let rows: Vec<Value> = body
+                .split(|byte| *byte == b'\n')
+                .filter(|line| !line.is_empty())
+                .map(|line| serde_json::from_slice(line).unwrap())
+                .collect();
+            assert_eq!(ids(&Value::Array(rows)), ["b", "c"]);
+        }
+    }
+
+    mod client_stats {
+        use uuid::Uuid;
+
+        use super::*;
+        use crate::adapters::{
+            repositories::in_memory_metadata_repository::metadata, test_app::TestApp,
+        };
+
+        #[tokio::test]
+        async fn files_are_grouped_by_client_with_untagged_last() {
+            let app = TestApp::new();
+            let owner = Uuid::new_v4();
+            for (file_id, client_id, size, downloads) in [
+                ("a", Some("web"), 10, 1),
+                ("b", None, 5, 0),
+                ("c", Some("mobile"), 7, 2),
+                ("d", Some("web"), 20, 3),
+            ] {
+                let mut file = metadata(file_id, owner, "file.txt");
+                file.client_id = client_id.map(str::to_string);
+                file.size = size;
+                file.download_count = downloads;
+                app.metadata.insert(file);
+            }
+
+            let Json(stats) = client_stats(State(app.state.clone())).await.unwrap();
+
+            let stats: Vec<_> = stats
+                .iter()
+                .map(|s| {
+                    (
+                        s.client_id.as_deref(),
+                        s.file_count,
+                        s.total_size,
+                        s.download_count,
+                    )
+                })
+                .collect();
+            assert_eq!(
+                stats,
+                [
+                    (Some("mobile"), 1, 7, 2),
+                    (Some("web"), 2, 30, 4),
+                    (None, 1, 5, 0),
+                ]
+            );
+        }
+    }
+
+    mod vacuum {
+        use chrono::{Duration, Utc};
+        use uuid::Uuid;
+
+        use super::*;
+        use crate::adapters::{
+            repositories::in_memory_metadata_repository::metadata, test_app::TestApp,
+        };
+
+        /// Archivo con su objeto, en la papelera desde hace `trashed_days` días
+        fn trashed(app: &TestApp, file_id: &str, owner: Uuid, trashed_days: i64) {
+            let mut file = metadata(file_id, owner, "file.txt");
+            file.size = 5;
+            file.trashed_at = Some(Utc::now() - Duration::days(trashed_days));
+            app.storage.insert(file_id, b"hello");
+            app.metadata.insert(file);
+        }
+
+        async fn run(app: &TestApp, query: &str) -> Result<VacuumResponse, ApplicationError> {
+            let uri: axum::http::Uri = format!("/vacuum?{}", query).parse().unwrap();
+            vacuum(State(app.state.clone()), Query::try_from_uri(&uri).unwrap())
+                .await
+                .map(|Json(response)| response)
+        }
+
+        #[tokio::test]
+        async fn old_trashed_rows_are_purged_in_batches_and_recent_ones_kept() {
+            let app = TestApp::new();
+            let owner = app.user(1024);
+            let mut user = app.users.user(owner);
+            user.file_count = 4;
+            user.used_space = 20;
+            app.users.insert(user);
+            for file_id in ["old-1", "old-2", "old-3"] {
+                trashed(&app, file_id, owner, 10);
+            }
+            trashed(&app, "recent", owner, 1);
+
+            let response = run(&app, "retentionSecs=432000&batchSize=2").await.unwrap();
+
+            assert_eq!(response.purged, 3);
+            assert_eq!(response.failed, 0);
+            assert_eq!(response.batches, 2);
+            assert_eq!(response.retention_secs, 432_000);
+            for file_id in ["old-1", "old-2", "old-3"] {
+                assert!(!app.metadata.contains(file_id));
+                assert!(!app.storage.contains(file_id));
+            }
+            assert!(app.metadata.contains("recent"));
+            assert!(app.storage.contains("recent"));
+            let user = app.users.user(owner);
+            assert_eq!((user.file_count, user.used_space), (1, 5));
+        }
+
+        #[tokio::test]
+        async fn retention_defaults_to_the_global_trash_retention() {
+            let app = TestApp::new();
+            trashed(&app, "old", Uuid::new_v4(), 10);
+            app.state.global_config.lock().unwrap().trash_retention = 30 * 24 * 3600;
+
+            let response = run(&app, "").await.unwrap();
+
+            assert_eq!(response.purged, 0);
+            assert_eq!(response.batches, 0);
+            assert!(app.metadata.contains("old"));
+        }
+
+        #[tokio::test]
+        async fn a_running_cleanup_is_a_conflict() {
+            let app = TestApp::new();
+            let _running = app.state.cleanup_lock.lock().await;
+
+            assert!(matches!(
+                run(&app, "").await,
+                Err(ApplicationError::Conflict(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,14 @@
+use axum::Json;
+use utoipa::OpenApi;
+
+use crate::adapters::openapi::ApiDoc;
+
+pub struct DocsController;
+
+impl DocsController {
+    /// Especificación OpenAPI derivada de los DTOs
+    /// GET /api/v1/openapi.json
+    pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+        Json(ApiDoc::openapi())
+    }
+}
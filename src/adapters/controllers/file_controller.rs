@@ -1,209 +1,420 @@
+use std::{collections::HashMap, net::IpAddr, sync::Arc};
+
 use axum::{
     body::Body,
-    extract::{Multipart, Path, State},
+    extract::{
+        multipart::{Field, MultipartError, MultipartRejection},
+        Multipart, Path, Query, State,
+    },
     http::{header, HeaderMap, StatusCode},
     response::Response,
     Json,
 };
-use chrono::{Duration, Utc};
+use bytes::{Bytes, BytesMut};
+use chrono::{DateTime, Duration, Utc};
+use futures_util::{stream, StreamExt, TryStreamExt};
+use sha2::{Digest, Sha256};
+use tokio::time::Instant;
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
 use crate::{
     adapters::{
+        authorization::{self, FileCaller},
+        client_ip::ClientIp,
         dto::{
-            file_dto::{CleanupResponse, FileResponse, UpdateFileRequest, UploadFileResponse},
+            batch_dto::BatchResult,
+            file_dto::{
+                self, BatchDeleteRequest, BatchDeleteResponse, ChunkedUploadSessionResponse,
+                CleanupResponse, DeleteFileQuery, DownloadQuery, FileLinksResponse, FileResponse,
+                FileStatsResponse, InitChunkedUploadRequest, OrphanedObject, ReconcileQuery,
+                ReconcileResponse, SignedUrlQuery, SignedUrlResponse, SignedUrlsBatchRequest,
+                SignedUrlsBatchResponse, TransferFileRequest, UpdateFileRequest,
+                UploadFileResponse,
+            },
             token_dto::{GenerateTokenRequest, TokenResponse},
         },
+        json_body::JsonBody,
         state::AppState,
+        throttled_body::throttled_body,
+        upload_buffer::BufferReservation,
     },
     application::{
         dto::{metadata_dto::MetadataDTO, user_dto::UserDTO},
         error::ApplicationError,
+        repositories::metadata_repository::MetadataRepository,
+        services::{DownloadStream, FileScanner, StorageService},
+    },
+    domain::{
+        config::global::UserTier,
+        models::{
+            audit::{AuditAction, AuditEvent},
+            file::{sniffed_mime_mismatch, FileData, FileKind, FileMetadata},
+            metadata::Metadata,
+            scan::ScanVerdict,
+            upload_session::UploadSession,
+            user::User,
+        },
     },
-    domain::models::file::FileData,
+    services::generate_file_path,
 };
 
-pub struct FileController;
+/// Límite habitual de nombre de archivo en los sistemas de archivos
+const MAX_FILENAME_OVERRIDE_LENGTH: usize = 255;
 
-impl FileController {
-    /// Genera un token de un solo uso para subir archivos
-    /// POST /api/v1/files/token
-    /// Body: {} para usuarios anónimos, {"userId": "uuid"} para usuarios específicos
-    pub async fn generate_upload_token(
-        State(app_state): State<AppState>,
-        Json(body): Json<GenerateTokenRequest>,
-    ) -> Result<(StatusCode, Json<TokenResponse>), ApplicationError> {
-        info!("Generating upload token for user_id: {:?}", body.user_id);
-
-        // Validar que el usuario existe si se proporciona user_id
-        if let Some(ref user_id_str) = body.user_id {
-            let uid = Uuid::parse_str(user_id_str).map_err(|e| {
-                warn!("Invalid UUID provided: {}, error: {}", user_id_str, e);
-                ApplicationError::BadRequest("Invalid user ID format".to_string())
-            })?;
+const MAX_CLIENT_ID_LENGTH: usize = 64;
 
-            let user_dto = UserDTO::for_query(uid);
-            app_state.user_repository.get_user(user_dto).await?;
-            info!("User validated successfully: {}", user_id_str);
-        } else {
-            info!("Generating anonymous token");
-        }
+/// Plazo máximo aceptado en X-Upload-Deadline; valores mayores se recortan
+const MAX_UPLOAD_DEADLINE_SECS: u64 = 600;
 
-        const TOKEN_TTL_SECONDS: u64 = 300; // 5 minutos
+/// Archivos por petición de URLs firmadas; una galería pide una página cada vez
+const MAX_SIGNED_URL_BATCH: usize = 100;
 
-        let token = app_state
-            .token_repository
-            .generate_token(body.user_id.clone(), TOKEN_TTL_SECONDS)
-            .await?;
+const MAX_BATCH_DELETE: usize = 200;
+const DEFAULT_SIGNED_URL_EXPIRY_SECS: u64 = 3600;
+/// Máximo que admite la firma SigV4 de S3 (7 días); SIGNED_URL_MAX_TTL_SECONDS no lo supera
+pub const MAX_SIGNED_URL_EXPIRY_SECS: u64 = 7 * 24 * 3600;
 
-        info!("Token generated successfully: {}", token);
+/// Subida con token, dueño, mime type y tamaño ya validados, lista para almacenarse
+struct ValidatedUpload {
+    /// Bytes: los reintentos de la subida al proveedor lo clonan sin copiar el contenido
+    file_bytes: Bytes,
+    filename: String,
+    mime_type: String,
+    file_type: FileKind,
+    user_id: Option<String>,
+    description: Option<String>,
+    client_id: Option<String>,
+    location: Option<String>,
+    /// Límite para la subida al proveedor pedido en X-Upload-Deadline
+    deadline: Option<Instant>,
+    /// SHA-256 en hex (minúsculas) enviado en X-Content-SHA256
+    expected_sha256: Option<String>,
+}
 
-        Ok((
-            StatusCode::CREATED,
-            Json(TokenResponse {
-                token,
-                expires_in: TOKEN_TTL_SECONDS,
-            }),
-        ))
+/// Campos de texto del formulario de subida, tal como llegan
+#[derive(Default)]
+struct UploadForm {
+    filename: Option<String>,
+    mime_type: Option<String>,
+    file_type: Option<String>,
+    user_id: Option<String>,
+    description: Option<String>,
+    location: Option<String>,
+    client_id: Option<String>,
+}
+
+impl UploadForm {
+    /// Ya llegaron los campos sin los que no se puede validar la subida
+    fn has_required_fields(&self) -> bool {
+        self.filename.is_some() && self.mime_type.is_some() && self.file_type.is_some()
     }
+}
 
-    pub async fn upload_file(
-        State(app_state): State<AppState>,
-        headers: HeaderMap,
-        mut multipart: Multipart,
-    ) -> Result<(StatusCode, Json<UploadFileResponse>), ApplicationError> {
-        // VALIDAR TOKEN ANTES DE PARSEAR MULTIPART (fail-fast)
-        let token = headers
-            .get(header::AUTHORIZATION)
-            .and_then(|v| v.to_str().ok())
-            .and_then(|s| {
-                s.strip_prefix("Bearer ")
-                    .or_else(|| s.strip_prefix("bearer "))
-            })
-            .or_else(|| headers.get("X-Upload-Token").and_then(|v| v.to_str().ok()))
-            .ok_or(ApplicationError::Unauthorized)?;
+/// Objeto ya en el proveedor (o reutilizado), pendiente de registrar en metadata
+struct StoredUpload {
+    file_id: String,
+    object_id: Option<String>,
+    content_hash: String,
+    mime_type: String,
+    size: u64,
+    filename: String,
+    file_type: FileKind,
+    user_id: Option<String>,
+    description: Option<String>,
+    client_id: Option<String>,
+}
 
-        let token_user_id = app_state
-            .token_repository
-            .verify_and_consume_token(token)
-            .await?;
+pub struct FileController;
 
-        info!("Token verified, associated user_id: {:?}", token_user_id);
-
-        let mut file_bytes: Option<Vec<u8>> = None;
-        let mut filename: Option<String> = None;
-        let mut mime_type: Option<String> = None;
-        let mut file_type: Option<String> = None;
-        let mut user_id: Option<String> = None;
-        let mut description: Option<String> = None;
-
-        while let Some(field) = multipart.next_field().await.map_err(|e| {
-            warn!("Invalid multipart data: {}", e);
-            ApplicationError::BadRequest("Invalid request format".to_string())
-        })? {
-            let name = field.name().unwrap_or("").to_string();
-
-            match name.as_str() {
-                "file" => {
-                    file_bytes = Some(
-                        field
-                            .bytes()
-                            .await
-                            .map_err(|e| {
-                                warn!("Cannot read file bytes: {}", e);
-                                ApplicationError::BadRequest("Invalid file data".to_string())
-                            })?
-                            .to_vec(),
-                    );
-                }
-                "filename" => {
-                    filename = Some(field.text().await.map_err(|e| {
-                        warn!("Invalid filename field: {}", e);
-                        ApplicationError::BadRequest("Invalid request data".to_string())
-                    })?);
-                }
-                "mime_type" => {
-                    mime_type = Some(field.text().await.map_err(|e| {
-                        warn!("Invalid mime_type field: {}", e);
-                        ApplicationError::BadRequest("Invalid request data".to_string())
-                    })?);
-                }
-                "type" => {
-                    file_type = Some(field.text().await.map_err(|e| {
-                        warn!("Invalid type field: {}", e);
-                        ApplicationError::BadRequest("Invalid request data".to_string())
-                    })?);
-                }
-                "user_id" => {
-                    user_id = Some(field.text().await.map_err(|e| {
-                        warn!("Invalid user_id field: {}", e);
-                        ApplicationError::BadRequest("Invalid request data".to_string())
-                    })?);
-                }
-                "description" => {
-                    description = Some(field.text().await.map_err(|e| {
-                        warn!("Invalid description field: {}", e);
-                        ApplicationError::BadRequest("Invalid request data".to_string())
-                    })?);
-                }
-                _ => {}
+impl FileController {
+    /// Tamaño máximo del campo `file`: el de su mime type si ya llegó, si no el mayor permitido
+    fn file_field_limit(app_state: &AppState, mime_type: Option<&str>) -> u64 {
+        let gc = app_state.global_config.lock().unwrap();
+        match mime_type {
+            Some(mime_type) => gc.max_size_for(&mime_type.trim().to_ascii_lowercase()),
+            None => gc.max_upload_size(),
+        }
+    }
+
+    /// Lee el campo `file` por bloques y corta con 413 en cuanto supera `limit`
+    ///
+    /// Cada bloque se reserva antes en el presupuesto global de memoria (503 si no cabe).
+    async fn read_file_field(
+        mut field: Field<'_>,
+        limit: u64,
+        reservation: &mut BufferReservation,
+    ) -> Result<Bytes, ApplicationError> {
+        let mut file_bytes = BytesMut::new();
+        while let Some(chunk) = field.chunk().await.map_err(Self::file_field_error)? {
+            if (file_bytes.len() + chunk.len()) as u64 > limit {
+                return Err(ApplicationError::PayloadTooLarge);
             }
+            reservation.grow(chunk.len())?;
+            file_bytes.extend_from_slice(&chunk);
         }
+        Ok(file_bytes.freeze())
+    }
 
-        let file_bytes = file_bytes.ok_or_else(|| {
-            warn!("Missing required 'file' field in upload");
-            ApplicationError::BadRequest("Missing required field".to_string())
-        })?;
-        let filename = filename.ok_or_else(|| {
+    /// Un cuerpo cortado por el límite de subida es 413; cualquier otro fallo, datos inválidos
+    fn file_field_error(e: MultipartError) -> ApplicationError {
+        if e.status() == StatusCode::PAYLOAD_TOO_LARGE {
+            return ApplicationError::PayloadTooLarge;
+        }
+        warn!("Cannot read file bytes: {}", e);
+        ApplicationError::BadRequest("Invalid file data".to_string())
+    }
+
+    /// Campos obligatorios, client id, location, mime type, tamaño y dueño de una subida directa
+    ///
+    /// Devuelve también el tamaño máximo y temp_file_life del mime type. Una subida en
+    /// streaming pasa `file_bytes` vacío: su tamaño se controla mientras llega.
+    fn validate_upload_form(
+        app_state: &AppState,
+        form: UploadForm,
+        token_user_id: Option<&String>,
+        file_bytes: Bytes,
+        deadline: Option<Instant>,
+        expected_sha256: Option<String>,
+    ) -> Result<(ValidatedUpload, u64, u64), ApplicationError> {
+        let client_id = Self::validate_client_id(app_state, form.client_id)?;
+        let location = Self::validate_location(app_state, form.location)?;
+
+        let filename = form.filename.ok_or_else(|| {
             warn!("Missing required 'filename' field in upload");
             ApplicationError::BadRequest("Missing required field".to_string())
         })?;
-        let mime_type = mime_type.ok_or_else(|| {
-            warn!("Missing required 'mime_type' field in upload");
-            ApplicationError::BadRequest("Missing required field".to_string())
-        })?;
-        let file_type = file_type.ok_or_else(|| {
-            warn!("Missing required 'type' field in upload");
-            ApplicationError::BadRequest("Missing required field".to_string())
-        })?;
+        // La lista permitida se guarda normalizada en minúsculas
+        let mime_type = form
+            .mime_type
+            .ok_or_else(|| {
+                warn!("Missing required 'mime_type' field in upload");
+                ApplicationError::BadRequest("Missing required field".to_string())
+            })?
+            .trim()
+            .to_ascii_lowercase();
+        let file_type: FileKind = form
+            .file_type
+            .ok_or_else(|| {
+                warn!("Missing required 'type' field in upload");
+                ApplicationError::BadRequest("Missing required field".to_string())
+            })?
+            .parse()
+            .map_err(ApplicationError::BadRequest)?;
+
+        let (max_size, temp_file_life) = Self::upload_limits(app_state, &mime_type)?;
+
+        if file_bytes.len() as u64 > max_size {
+            return Err(ApplicationError::PayloadTooLarge);
+        }
+
+        let require_user_id = app_state.global_config.lock().unwrap().require_user_id;
+        Self::check_upload_owner(
+            file_type,
+            form.user_id.as_ref(),
+            token_user_id,
+            require_user_id,
+        )?;
+
+        Ok((
+            ValidatedUpload {
+                file_bytes,
+                filename,
+                mime_type,
+                file_type,
+                user_id: form.user_id,
+                description: form.description,
+                client_id,
+                location,
+                deadline,
+                expected_sha256,
+            },
+            max_size,
+            temp_file_life,
+        ))
+    }
 
-        let (max_size, mime_types, temp_file_life) = {
-            let gc = app_state.global_config.lock().unwrap();
-            (gc.max_size, gc.mime_types.clone(), gc.temp_file_life)
+    /// Los ids de sesión son UUID; cualquier otro valor no puede existir
+    fn validate_session_id(session_id: &str) -> Result<(), ApplicationError> {
+        Uuid::parse_str(session_id)
+            .map(|_| ())
+            .map_err(|_| ApplicationError::NotFound)
+    }
+
+    /// X-Content-SHA256 opcional: 64 dígitos hex del SHA-256 del archivo
+    fn expected_sha256(headers: &HeaderMap) -> Result<Option<String>, ApplicationError> {
+        let Some(value) = headers.get("X-Content-SHA256") else {
+            return Ok(None);
         };
+        let digest = value
+            .to_str()
+            .ok()
+            .map(|digest| digest.trim().to_ascii_lowercase())
+            .filter(|digest| digest.len() == 64 && digest.bytes().all(|b| b.is_ascii_hexdigit()))
+            .ok_or_else(|| {
+                ApplicationError::BadRequest("Invalid X-Content-SHA256 header".to_string())
+            })?;
+        Ok(Some(digest))
+    }
 
-        if !mime_types.contains(&mime_type) {
-            return Err(ApplicationError::BadRequest(format!(
-                "MIME type '{}' not allowed",
-                mime_type
+    /// Antivirus antes de llegar al proveedor: un archivo infectado es 422; sin escáner no se
+    /// escanea
+    async fn scan_upload(
+        scanner: Option<&dyn FileScanner>,
+        filename: &str,
+        file_bytes: &[u8],
+    ) -> Result<(), ApplicationError> {
+        let Some(scanner) = scanner else {
+            return Ok(());
+        };
+        if let ScanVerdict::Infected(signature) = scanner.scan(file_bytes).await? {
+            warn!("Rejected infected upload '{}': {}", filename, signature);
+            return Err(ApplicationError::UnprocessableEntity(format!(
+                "File rejected by virus scan: {}",
+                signature
             )));
         }
+        Ok(())
+    }
 
-        let file_size = file_bytes.len() as u64;
-        if file_size > max_size {
-            return Err(ApplicationError::PayloadTooLarge);
+    /// Compara en tiempo constante el hash calculado con el que envió el cliente
+    fn check_checksum(expected: Option<&str>, computed: &str) -> Result<(), ApplicationError> {
+        let Some(expected) = expected else {
+            return Ok(());
+        };
+        if !authorization::constant_time_eq(expected.as_bytes(), computed.as_bytes()) {
+            return Err(ApplicationError::ChecksumMismatch {
+                computed: computed.to_string(),
+            });
         }
+        Ok(())
+    }
+
+    /// X-Upload-Deadline: segundos relativos (`30`) o instante RFC 3339, recortado al máximo
+    fn upload_deadline(headers: &HeaderMap) -> Result<Option<Instant>, ApplicationError> {
+        let Some(value) = headers.get("X-Upload-Deadline") else {
+            return Ok(None);
+        };
+        let invalid = || {
+            ApplicationError::BadRequest(
+                "X-Upload-Deadline must be a number of seconds or an RFC 3339 timestamp"
+                    .to_string(),
+            )
+        };
+        let value = value.to_str().map_err(|_| invalid())?.trim();
 
-        if file_type != "temporal" && file_type != "permanent" {
+        let millis = match value.parse::<u64>() {
+            Ok(seconds) => seconds.saturating_mul(1000),
+            Err(_) => {
+                let at = DateTime::parse_from_rfc3339(value).map_err(|_| invalid())?;
+                (at.with_timezone(&Utc) - Utc::now())
+                    .num_milliseconds()
+                    .max(0) as u64
+            }
+        };
+        if millis == 0 {
             return Err(ApplicationError::BadRequest(
-                "Invalid 'type' field: must be 'temporal' or 'permanent'".to_string(),
+                "X-Upload-Deadline has already passed".to_string(),
             ));
         }
 
-        if file_type == "permanent" && user_id.is_none() {
+        let millis = millis.min(MAX_UPLOAD_DEADLINE_SECS * 1000);
+        Ok(Some(
+            Instant::now() + std::time::Duration::from_millis(millis),
+        ))
+    }
+
+    /// Sube al proveedor hasta `deadline`; al vencer aborta la subida y responde 504
+    ///
+    /// Si el proveedor llegó a completarla antes de abortarse, el objeto se borra en segundo
+    /// plano para no dejar huérfanos sin metadata.
+    async fn upload_with_deadline(
+        service: Arc<dyn StorageService>,
+        file_data: FileData,
+        deadline: Instant,
+    ) -> Result<FileMetadata, ApplicationError> {
+        let mut upload = tokio::spawn({
+            let service = service.clone();
+            async move { service.upload(file_data).await }
+        });
+
+        match tokio::time::timeout_at(deadline, &mut upload).await {
+            Ok(joined) => joined.map_err(|e| {
+                ApplicationError::InternalError(format!("Upload task failed: {}", e))
+            })?,
+            Err(_) => {
+                upload.abort();
+                tokio::spawn(async move {
+                    let Ok(Ok(stored)) = upload.await else {
+                        return;
+                    };
+                    match service.delete(&stored.file_id).await {
+                        Ok(()) => info!(
+                            "Deleted {} uploaded after its deadline expired",
+                            stored.file_id
+                        ),
+                        Err(e) => error!(
+                            "Could not delete {} uploaded after its deadline expired: {:?}",
+                            stored.file_id, e
+                        ),
+                    }
+                });
+                Err(ApplicationError::UpstreamTimeout(
+                    "Upload deadline exceeded".to_string(),
+                ))
+            }
+        }
+    }
+
+    /// Token de subida del header Authorization (Bearer) o X-Upload-Token
+    fn upload_token(headers: &HeaderMap) -> Result<&str, ApplicationError> {
+        authorization::bearer_token(headers)
+            .or_else(|| headers.get("X-Upload-Token").and_then(|v| v.to_str().ok()))
+            .ok_or(ApplicationError::Unauthorized)
+    }
+
+    /// Comprueba que el mime type esté permitido; devuelve su tamaño máximo y temp_file_life
+    fn upload_limits(
+        app_state: &AppState,
+        mime_type: &str,
+    ) -> Result<(u64, u64), ApplicationError> {
+        let gc = app_state.global_config.lock().unwrap();
+
+        if !gc.mime_types.iter().any(|allowed| allowed == mime_type) {
+            return Err(ApplicationError::BadRequest(format!(
+                "MIME type '{}' not allowed",
+                mime_type
+            )));
+        }
+        Ok((gc.max_size_for(mime_type), gc.temp_file_life))
+    }
+
+    /// El user_id de la subida debe coincidir con el del token; los permanentes lo exigen,
+    /// y con `require_user_id` también los temporales
+    fn check_upload_owner(
+        file_type: FileKind,
+        user_id: Option<&String>,
+        token_user_id: Option<&String>,
+        require_user_id: bool,
+    ) -> Result<(), ApplicationError> {
+        if file_type == FileKind::Permanent && user_id.is_none() {
             return Err(ApplicationError::BadRequest(
                 "Missing 'user_id' for permanent file".to_string(),
             ));
         }
+        if require_user_id && user_id.is_none() {
+            return Err(ApplicationError::BadRequest(
+                "Missing 'user_id': anonymous uploads are disabled".to_string(),
+            ));
+        }
 
-        // VALIDAR CONSISTENCIA: user_id del token vs user_id del multipart
-        if let Some(ref multipart_user_id) = user_id {
-            match &token_user_id {
-                Some(token_uid) if token_uid != multipart_user_id => {
+        // VALIDAR CONSISTENCIA: user_id del token vs user_id de la subida
+        if let Some(upload_user_id) = user_id {
+            match token_user_id {
+                Some(token_uid) if token_uid != upload_user_id => {
                     error!(
-                        "Token user_id '{}' does not match multipart user_id '{}'",
-                        token_uid, multipart_user_id
+                        "Token user_id '{}' does not match upload user_id '{}'",
+                        token_uid, upload_user_id
                     );
                     return Err(ApplicationError::Unauthorized);
                 }
@@ -211,51 +422,395 @@ impl FileController {
                     // Token anónimo pero upload de usuario
                     error!(
                         "Anonymous token used for user-specific upload with user_id '{}'",
-                        multipart_user_id
+                        upload_user_id
                     );
                     return Err(ApplicationError::Unauthorized);
                 }
-                _ => {} // Token y multipart coinciden
+                _ => {} // Token y subida coinciden
             }
         } else if token_user_id.is_some() {
             // Token de usuario pero upload anónimo
             return Err(ApplicationError::Unauthorized);
         }
+        Ok(())
+    }
 
-        let user = if file_type == "permanent" {
-            let uid_str = user_id.as_ref().unwrap();
-            let uid = Uuid::parse_str(uid_str)
-                .map_err(|_| ApplicationError::BadRequest(format!("Invalid UUID: {}", uid_str)))?;
+    /// 503 sin leer el cuerpo mientras siga vigente el último fallo del proveedor
+    fn check_storage_health(app_state: &AppState) -> Result<(), ApplicationError> {
+        if app_state.storage_down_reject_secs == 0 {
+            return Ok(());
+        }
+        let window = std::time::Duration::from_secs(app_state.storage_down_reject_secs);
+        match app_state.storage_service.health().degraded_for(window) {
+            Some(remaining) => Err(ApplicationError::StorageDegraded {
+                retry_after: remaining.as_secs().max(1),
+            }),
+            None => Ok(()),
+        }
+    }
 
-            let user_dto = UserDTO::for_query(uid);
-            let user = app_state.user_repository.get_user(user_dto).await?;
+    /// El usuario del token debe seguir existiendo al subir; 404 si se borró tras emitirlo
+    async fn check_token_user(
+        app_state: &AppState,
+        token_user_id: Option<&String>,
+    ) -> Result<(), ApplicationError> {
+        let Some(token_uid) = token_user_id else {
+            return Ok(());
+        };
+        let uid = Uuid::parse_str(token_uid).map_err(|_| ApplicationError::Unauthorized)?;
 
-            if user.used_space + file_size > user.total_space {
-                return Err(ApplicationError::InsufficientStorage);
+        match app_state
+            .user_repository
+            .get_user(UserDTO::for_query(uid))
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(ApplicationError::NotFound) => {
+                warn!("Upload token user '{}' no longer exists", token_uid);
+                Err(ApplicationError::NotFound)
             }
+            Err(e) => Err(e),
+        }
+    }
 
-            Some(user)
-        } else {
-            None
-        };
+    /// Contenido, cuota, antivirus, proveedor y metadata: común a la subida directa y la
+    /// fragmentada
+    async fn store_upload(
+        app_state: &AppState,
+        upload: ValidatedUpload,
+        temp_file_life: u64,
+        client_ip: IpAddr,
+    ) -> Result<Metadata, ApplicationError> {
+        let ValidatedUpload {
+            file_bytes,
+            filename,
+            mime_type,
+            file_type,
+            user_id,
+            description,
+            client_id,
+            location,
+            deadline,
+            expected_sha256,
+        } = upload;
+        let file_size = file_bytes.len() as u64;
 
-        let file_data = FileData::new(file_bytes, filename.clone(), mime_type.clone());
+        Self::check_content_type(app_state, &filename, &mime_type, &file_bytes)?;
+
+        let content_hash = format!("{:x}", Sha256::digest(&file_bytes));
+        Self::check_checksum(expected_sha256.as_deref(), &content_hash)?;
+
+        let user =
+            Self::upload_owner(app_state, file_type, &user_id, &mime_type, file_size).await?;
+
+        Self::scan_upload(app_state.file_scanner.as_deref(), &filename, &file_bytes).await?;
+
+        let duplicate = Self::find_duplicate(
+            app_state,
+            &content_hash,
+            file_type,
+            user_id.as_deref(),
+            location.is_some(),
+        )
+        .await?
+        .filter(|existing| existing.size == file_size);
+
+        if let Some(existing) = duplicate {
+            info!(
+                "Upload '{}' has the same content as {}, reusing its object",
+                filename, existing.file_id
+            );
+            let stored_mime_type = app_state
+                .mime_type_policy
+                .resolve(mime_type.clone(), existing.mime_type.clone());
+            let reused = StoredUpload {
+                file_id: generate_file_path(&filename),
+                object_id: Some(existing.storage_key().to_string()),
+                content_hash: content_hash.clone(),
+                mime_type: stored_mime_type,
+                size: file_size,
+                filename: filename.clone(),
+                file_type,
+                user_id: user_id.clone(),
+                description: description.clone(),
+                client_id: client_id.clone(),
+            };
+            match Self::record_upload(app_state, reused, user.clone(), temp_file_life, client_ip)
+                .await
+            {
+                // El último archivo con ese contenido se borró entre la búsqueda y el alta
+                Err(ApplicationError::Conflict(reason)) => {
+                    info!("{}, uploading '{}' instead", reason, filename)
+                }
+                result => return result,
+            }
+        }
+
+        let file_data =
+            FileData::new(file_bytes, filename.clone(), mime_type.clone()).with_location(location);
         let storage_metadata = {
             let service = app_state.storage_service.get();
-            service.upload(file_data).await?
+            match deadline {
+                Some(deadline) => Self::upload_with_deadline(service, file_data, deadline).await?,
+                None => service.upload(file_data).await?,
+            }
+        };
+        let stored_mime_type =
+            Self::stored_mime_type(app_state, &filename, mime_type, storage_metadata.mime_type);
+
+        Self::record_upload(
+            app_state,
+            StoredUpload {
+                file_id: storage_metadata.file_id,
+                object_id: None,
+                content_hash,
+                mime_type: stored_mime_type,
+                size: file_size,
+                filename,
+                file_type,
+                user_id,
+                description,
+                client_id,
+            },
+            user,
+            temp_file_life,
+            client_ip,
+        )
+        .await
+    }
+
+    /// Envía el campo `file` al proveedor según llega, con el hash calculado por el camino
+    ///
+    /// Sin el contenido completo no hay antivirus ni deduplicación; el hash se guarda igual
+    /// para que las subidas siguientes puedan reutilizar el objeto. La cuota que le queda a
+    /// `user` limita el tamaño igual que max_size.
+    async fn stream_upload(
+        app_state: &AppState,
+        field: Field<'_>,
+        upload: &ValidatedUpload,
+        max_size: u64,
+        user: Option<&User>,
+    ) -> Result<StoredUpload, ApplicationError> {
+        let quota_left = user.map(|user| user.total_space.saturating_sub(user.used_space));
+        let limit = quota_left.map_or(max_size, |left| left.min(max_size));
+
+        let mut hasher = Sha256::new();
+        let mut first_chunk = true;
+        let content = field
+            .map(|chunk| {
+                let chunk = chunk.map_err(Self::file_field_error)?;
+                // Las firmas de formato están al principio: basta con el primer bloque
+                if std::mem::take(&mut first_chunk) {
+                    Self::check_content_type(
+                        app_state,
+                        &upload.filename,
+                        &upload.mime_type,
+                        &chunk,
+                    )?;
+                }
+                hasher.update(&chunk);
+                Ok(chunk)
+            })
+            .boxed();
+
+        let file_data = FileData::new(
+            Vec::new(),
+            upload.filename.clone(),
+            upload.mime_type.clone(),
+        )
+        .with_location(upload.location.clone());
+        let service = app_state.storage_service.get();
+        let storing = service.upload_stream(file_data, content, limit);
+        // Al vencer se abandona la sesión a medias; el proveedor no llega a crear el objeto
+        let stored = match upload.deadline {
+            Some(deadline) => tokio::time::timeout_at(deadline, storing)
+                .await
+                .map_err(|_| {
+                    ApplicationError::UpstreamTimeout("Upload deadline exceeded".to_string())
+                })?,
+            None => storing.await,
+        }
+        .map_err(|e| match e {
+            ApplicationError::PayloadTooLarge if limit < max_size => {
+                ApplicationError::InsufficientStorage
+            }
+            e => e,
+        })?;
+
+        info!(
+            "Streamed upload '{}' to {} ({} bytes)",
+            upload.filename, stored.file_id, stored.size
+        );
+
+        // El hash solo se conoce con el objeto ya subido: si no cuadra se borra
+        let content_hash = format!("{:x}", hasher.finalize());
+        if let Err(e) = Self::check_checksum(upload.expected_sha256.as_deref(), &content_hash) {
+            if let Err(delete_error) = service.delete(&stored.file_id).await {
+                error!(
+                    "Could not delete streamed {}: {:?}",
+                    stored.file_id, delete_error
+                );
+            }
+            return Err(e);
+        }
+
+        Ok(StoredUpload {
+            file_id: stored.file_id,
+            object_id: None,
+            content_hash,
+            mime_type: Self::stored_mime_type(
+                app_state,
+                &upload.filename,
+                upload.mime_type.clone(),
+                stored.mime_type,
+            ),
+            size: stored.size,
+            filename: upload.filename.clone(),
+            file_type: upload.file_type,
+            user_id: upload.user_id.clone(),
+            description: upload.description.clone(),
+            client_id: upload.client_id.clone(),
+        })
+    }
+
+    /// Avisa (o rechaza con strictMimeCheck) si los bytes iniciales contradicen el mime type
+    fn check_content_type(
+        app_state: &AppState,
+        filename: &str,
+        mime_type: &str,
+        content: &[u8],
+    ) -> Result<(), ApplicationError> {
+        let Some(sniffed) = sniffed_mime_mismatch(mime_type, content) else {
+            return Ok(());
         };
+        let strict = app_state.global_config.lock().unwrap().strict_mime_check;
+        warn!(
+            "Upload '{}' declared as '{}' looks like '{}' (strict: {})",
+            filename, mime_type, sniffed, strict
+        );
+        if strict {
+            return Err(ApplicationError::BadRequest(format!(
+                "File content looks like '{}', not the declared '{}'",
+                sniffed, mime_type
+            )));
+        }
+        Ok(())
+    }
+
+    /// Dueño de una subida permanente, comprobando su cuota y los límites de su plan
+    async fn upload_owner(
+        app_state: &AppState,
+        file_type: FileKind,
+        user_id: &Option<String>,
+        mime_type: &str,
+        file_size: u64,
+    ) -> Result<Option<User>, ApplicationError> {
+        if file_type != FileKind::Permanent {
+            return Ok(None);
+        }
+        let uid_str = user_id.as_ref().unwrap();
+        let uid = Uuid::parse_str(uid_str)
+            .map_err(|_| ApplicationError::BadRequest(format!("Invalid UUID: {}", uid_str)))?;
+
+        let user_dto = UserDTO::for_query(uid);
+        let user = app_state.user_repository.get_user(user_dto).await?;
+
+        Self::check_owner_limits(
+            &user,
+            &app_state.global_config.lock().unwrap().tiers,
+            mime_type,
+            file_size,
+        )?;
+
+        Ok(Some(user))
+    }
+
+    /// Cuota y límites del plan de `user` para un archivo permanente más
+    fn check_owner_limits(
+        user: &User,
+        tiers: &HashMap<String, UserTier>,
+        mime_type: &str,
+        file_size: u64,
+    ) -> Result<(), ApplicationError> {
+        if user.used_space + file_size > user.total_space {
+            return Err(ApplicationError::InsufficientStorage);
+        }
+
+        // Un plan borrado de la configuración deja de imponer límites
+        let tier = user
+            .tier
+            .as_ref()
+            .and_then(|name| tiers.get(name).map(|tier| (name, tier)));
+        if let Some((name, tier)) = tier {
+            if let Some(max) = tier.max_files.filter(|&max| user.file_count >= max) {
+                return Err(ApplicationError::Forbidden(format!(
+                    "Tier '{}' allows at most {} files",
+                    name, max
+                )));
+            }
+            let allowed = tier
+                .allowed_mime_types
+                .as_ref()
+                .is_none_or(|allowed| allowed.iter().any(|m| m == mime_type));
+            if !allowed {
+                return Err(ApplicationError::BadRequest(format!(
+                    "MIME type '{}' not allowed for tier '{}'",
+                    mime_type, name
+                )));
+            }
+        }
+
+        Ok(())
+    }
 
-        let delete_at = if file_type == "temporal" {
+    /// Mime type a persistir cuando el proveedor informa uno distinto del declarado
+    fn stored_mime_type(
+        app_state: &AppState,
+        filename: &str,
+        declared: String,
+        reported: String,
+    ) -> String {
+        if reported != declared {
+            warn!(
+                "Provider reported mime type '{}' for '{}' declared as '{}', persisting per {:?}",
+                reported, filename, declared, app_state.mime_type_policy
+            );
+        }
+        app_state.mime_type_policy.resolve(declared, reported)
+    }
+
+    /// Metadata, cuota del dueño y evento de auditoría de una subida ya almacenada
+    async fn record_upload(
+        app_state: &AppState,
+        stored: StoredUpload,
+        user: Option<User>,
+        temp_file_life: u64,
+        client_ip: IpAddr,
+    ) -> Result<Metadata, ApplicationError> {
+        let StoredUpload {
+            file_id,
+            object_id,
+            content_hash,
+            mime_type: stored_mime_type,
+            size: file_size,
+            filename,
+            file_type,
+            user_id,
+            description,
+            client_id,
+        } = stored;
+
+        let delete_at = if file_type == FileKind::Temporal {
             Some(Utc::now() + Duration::seconds(temp_file_life as i64))
         } else {
             None
         };
 
         let metadata_dto = MetadataDTO {
-            file_id: storage_metadata.file_id.clone(),
-            mime_type: Some(storage_metadata.mime_type),
-            size: Some(storage_metadata.size),
-            user_id: if file_type == "permanent" {
+            file_id,
+            mime_type: Some(stored_mime_type),
+            size: Some(file_size),
+            user_id: if file_type == FileKind::Permanent {
                 user_id.clone()
             } else {
                 None
@@ -267,13 +822,17 @@ impl FileController {
             download_count: Some(0),
             last_access: Some(Utc::now()),
             delete_at,
+            client_id,
+            trashed_at: None,
+            content_hash: Some(content_hash),
+            object_id,
         };
         let metadata = app_state
             .metadata_repository
             .create_metadata(metadata_dto)
             .await?;
 
-        if file_type == "permanent" {
+        if file_type == FileKind::Permanent {
             if let Some(user) = user {
                 let uid_str = user_id.as_ref().unwrap();
                 let uid = Uuid::parse_str(uid_str).unwrap();
@@ -285,190 +844,3469 @@ impl FileController {
             }
         }
 
-        Ok((
-            StatusCode::CREATED,
-            Json(UploadFileResponse::from(metadata)),
-        ))
+        app_state.event_queue.publish(
+            AuditEvent::new(
+                AuditAction::Upload,
+                metadata.file_id.clone(),
+                metadata.user_id.clone(),
+                app_state.server_id.clone(),
+            )
+            .with_client_ip(client_ip),
+        );
+
+        Ok(metadata)
     }
 
-    pub async fn cleanup_expired_files(
-        State(app_state): State<AppState>,
-        headers: HeaderMap,
-    ) -> Result<Json<CleanupResponse>, ApplicationError> {
-        let provided_secret = headers
-            .get("X-VK-Secret")
-            .and_then(|v| v.to_str().ok())
-            .ok_or(ApplicationError::Unauthorized)?;
+    /// Borra la metadata de `files`, que comparten objeto, y el objeto del proveedor salvo
+    /// que otras filas lo sigan usando por deduplicación
+    async fn release_object(
+        app_state: &AppState,
+        service: &Arc<dyn StorageService>,
+        storage_key: &str,
+        files: &[Metadata],
+    ) -> Result<(), ApplicationError> {
+        let file_ids: Vec<String> = files.iter().map(|file| file.file_id.clone()).collect();
+        let references = app_state
+            .metadata_repository
+            .release_object(storage_key, &file_ids, service.delete(storage_key))
+            .await?;
+        if references > 0 {
+            info!(
+                "Keeping object {} shared with {} other files",
+                storage_key, references
+            );
+        }
+        Ok(())
+    }
 
-        let vk_secret = app_state.secrets.lock().unwrap().vk_secret.clone();
-        if provided_secret != vk_secret {
-            return Err(ApplicationError::Unauthorized);
+    /// Archivo ya almacenado con el mismo contenido cuyo objeto puede reutilizar la subida
+    ///
+    /// Los permanentes solo se comparan con los del mismo usuario y los temporales con toda
+    /// la instancia. Una subida con `location` explícito no se deduplica: el objeto existente
+    /// podría estar en otro bucket.
+    async fn find_duplicate(
+        app_state: &AppState,
+        content_hash: &str,
+        file_type: FileKind,
+        user_id: Option<&str>,
+        has_location: bool,
+    ) -> Result<Option<Metadata>, ApplicationError> {
+        if has_location {
+            return Ok(None);
         }
+        let owner = match file_type {
+            FileKind::Permanent => user_id,
+            FileKind::Temporal => None,
+        };
+        app_state
+            .metadata_repository
+            .find_by_hash(content_hash, &app_state.server_id, owner)
+            .await
+    }
+
+    /// Borra los archivos expirados o con la papelera vencida del proveedor y su metadata,
+    /// y descuenta la cuota
+    ///
+    /// La comparten DELETE /api/v1/files y la limpieza periódica; si ya hay una ejecución
+    /// en curso devuelve Conflict en lugar de solaparse. Los fallos por archivo se
+    /// acumulan en el informe sin interrumpir el resto.
+    pub async fn run_cleanup(app_state: &AppState) -> Result<CleanupResponse, ApplicationError> {
+        let Ok(_running) = app_state.cleanup_lock.try_lock() else {
+            return Err(ApplicationError::Conflict(
+                "Expired file cleanup is already running".to_string(),
+            ));
+        };
+
+        let trash_retention = app_state.global_config.lock().unwrap().trash_retention;
+        let expired_files = app_state
+            .metadata_repository
+            .get_expired_files(trash_retention)
+            .await?;
 
-        let expired_files = app_state.metadata_repository.get_expired_files().await?;
+        Ok(Self::purge_files(app_state, expired_files).await)
+    }
 
+    /// Borra del proveedor y de la metadata los archivos dados y descuenta la cuota; quien
+    /// llama debe tener `cleanup_lock`
+    pub async fn purge_files(app_state: &AppState, files: Vec<Metadata>) -> CleanupResponse {
         let mut deleted_count = 0;
         let mut errors = Vec::new();
+        let mut results = BatchResult::default();
+        // Deltas de cuota por usuario (archivos, bytes), aplicados en una sola actualización
+        let mut quota_deltas: HashMap<Uuid, (u64, u64)> = HashMap::new();
 
-        for file_metadata in expired_files {
-            let delete_result = {
-                let service = app_state.storage_service.get();
-                service.delete(&file_metadata.file_id).await
-            };
+        // Agrupadas por objeto: las copias deduplicadas que expiran juntas lo liberan una vez
+        let mut by_object: HashMap<String, Vec<Metadata>> = HashMap::new();
+        for file_metadata in files {
+            by_object
+                .entry(file_metadata.storage_key().to_string())
+                .or_default()
+                .push(file_metadata);
+        }
 
-            match delete_result {
-                Ok(_) => {
-                    match app_state
-                        .metadata_repository
-                        .delete_metadata(&file_metadata.file_id)
-                        .await
-                    {
-                        Ok(_) => {
-                            if let Some(user_id_str) = file_metadata.user_id.clone() {
-                                if let Ok(uid) = Uuid::parse_str(&user_id_str) {
-                                    let get_user_dto = UserDTO::for_query(uid);
-
-                                    if let Ok(user) =
-                                        app_state.user_repository.get_user(get_user_dto).await
-                                    {
-                                        let mut update_dto = UserDTO::for_update(uid);
-                                        update_dto.file_count =
-                                            Some(user.file_count.saturating_sub(1));
-                                        update_dto.used_space = Some(
-                                            user.used_space.saturating_sub(file_metadata.size),
-                                        );
-
-                                        if let Err(e) =
-                                            app_state.user_repository.update_user(update_dto).await
-                                        {
-                                            errors.push(format!(
-                                                "Error updating user quota for file {}: {:?}",
-                                                file_metadata.file_id, e
-                                            ));
-                                        }
-                                    }
-                                }
-                            }
-
-                            deleted_count += 1;
-                        }
-                        Err(e) => {
-                            errors.push(format!(
-                                "Error deleting metadata for file {}: {:?}",
-                                file_metadata.file_id, e
-                            ));
-                        }
-                    }
-                }
-                Err(e) => {
-                    errors.push(format!(
-                        "Error deleting file {} from storage: {:?}",
-                        file_metadata.file_id, e
-                    ));
+        // Borrados en el proveedor acotados para no acaparar sus conexiones
+        let service = app_state.storage_service.get();
+        let deletions: Vec<_> = stream::iter(by_object)
+            .map(|(storage_key, files)| {
+                let service = service.clone();
+                async move {
+                    let result =
+                        Self::release_object(app_state, &service, &storage_key, &files).await;
+                    (files, result)
                 }
+            })
+            .buffered(app_state.provider_request_concurrency)
+            .collect()
+            .await;
+
+        let deletions = deletions.into_iter().flat_map(|(files, result)| {
+            files
+                .into_iter()
+                .map(move |file_metadata| (file_metadata, result.clone()))
+        });
+        for (file_metadata, delete_result) in deletions {
+            if let Err(e) = delete_result {
+                errors.push(format!(
+                    "Error deleting file {}: {:?}",
+                    file_metadata.file_id, e
+                ));
+                results.push_err(file_metadata.file_id, e);
+                continue;
+            }
+
+            Self::add_quota_delta(&mut quota_deltas, &file_metadata);
+            deleted_count += 1;
+            results.push_ok(file_metadata.file_id, StatusCode::NO_CONTENT, None);
+        }
+
+        for (uid, (file_delta, size_delta)) in quota_deltas {
+            if let Err(e) = Self::release_quota(app_state, uid, file_delta, size_delta).await {
+                errors.push(format!("Error updating quota for user {}: {:?}", uid, e));
             }
         }
 
-        Ok(Json(CleanupResponse {
+        CleanupResponse {
             deleted_count,
             errors,
-        }))
+            results,
+        }
     }
 
-    pub async fn download_file(
-        State(app_state): State<AppState>,
-        Path(file_id): Path<String>,
-    ) -> Result<Response, ApplicationError> {
-        let metadata = app_state
-            .metadata_repository
-            .increment_download_count(&file_id)
+    /// Archivo del usuario con ese nombre: el más reciente, o Conflict si hay varios y se
+    /// admiten nombres duplicados
+    async fn resolve_by_name(
+        metadata_repository: &dyn MetadataRepository,
+        user_id: Uuid,
+        filename: &str,
+        allow_duplicates: bool,
+    ) -> Result<Metadata, ApplicationError> {
+        let mut matches = metadata_repository
+            .find_user_files_by_name(&user_id.to_string(), filename)
             .await?;
 
-        let file_bytes = {
-            let service = app_state.storage_service.get();
-            service.download(&file_id).await?
-        };
+        if allow_duplicates && matches.len() > 1 {
+            warn!(
+                "{} files named '{}' found for user {}",
+                matches.len(),
+                filename,
+                user_id
+            );
+            return Err(ApplicationError::Conflict(format!(
+                "{} files are named '{}'; download one by its fileId",
+                matches.len(),
+                filename
+            )));
+        }
+        if matches.is_empty() {
+            warn!("No file named '{}' found for user {}", filename, user_id);
+            return Err(ApplicationError::NotFound);
+        }
+        Ok(matches.swap_remove(0))
+    }
 
-        let response = Response::builder()
-            .status(StatusCode::OK)
-            .header(header::CONTENT_TYPE, metadata.mime_type)
-            .header(header::CONTENT_LENGTH, file_bytes.len())
-            .header(
-                header::CONTENT_DISPOSITION,
-                format!("attachment; filename=\"{}\"", metadata.file_name),
-            )
-            .body(Body::from(file_bytes))
-            .unwrap();
+    /// Si la petición trae el X-VK-Secret de la instancia (uso administrativo)
+    fn has_vk_secret(app_state: &AppState, headers: &HeaderMap) -> bool {
+        authorization::has_vk_secret(&app_state.secrets, headers)
+    }
+
+    /// Rechaza file_ids que podrían alterar la ruta del objeto en el proveedor
+    fn validate_file_id(file_id: &str) -> Result<(), ApplicationError> {
+        let suspicious = file_id.is_empty()
+            || file_id.contains("..")
+            || file_id.starts_with('/')
+            || file_id.contains('\\')
+            || file_id.chars().any(char::is_control);
 
-        Ok(response)
+        if suspicious {
+            warn!("Rejected suspicious file_id: {:?}", file_id);
+            return Err(ApplicationError::BadRequest("Invalid file ID".to_string()));
+        }
+        Ok(())
     }
 
-    pub async fn get_file_metadata(
-        State(app_state): State<AppState>,
-        Path(file_id): Path<String>,
-    ) -> Result<Json<FileResponse>, ApplicationError> {
-        let metadata = app_state.metadata_repository.get_metadata(&file_id).await?;
-        Ok(Json(FileResponse::from(metadata)))
+    /// Bucket/carpeta adicional pedido en la subida; debe estar configurado en el proveedor
+    fn validate_location(
+        app_state: &AppState,
+        location: Option<String>,
+    ) -> Result<Option<String>, ApplicationError> {
+        let Some(location) = location.map(|name| name.trim().to_string()) else {
+            return Ok(None);
+        };
+        if location.is_empty() {
+            return Ok(None);
+        }
+
+        let locations = app_state.storage_service.get().locations();
+        if !locations.contains(&location) {
+            return Err(ApplicationError::BadRequest(format!(
+                "Unknown storage location '{}'; expected one of: {}",
+                location,
+                locations.join(", ")
+            )));
+        }
+        Ok(Some(location))
     }
 
-    pub async fn update_file_metadata(
-        State(app_state): State<AppState>,
-        Path(file_id): Path<String>,
-        Json(body): Json<UpdateFileRequest>,
-    ) -> Result<Json<FileResponse>, ApplicationError> {
-        let current_metadata = app_state.metadata_repository.get_metadata(&file_id).await?;
+    /// Identificador del cliente que origina la subida; con ALLOWED_CLIENT_IDS debe estar listado
+    fn validate_client_id(
+        app_state: &AppState,
+        client_id: Option<String>,
+    ) -> Result<Option<String>, ApplicationError> {
+        let Some(client_id) = client_id.map(|id| id.trim().to_string()) else {
+            return Ok(None);
+        };
 
-        if current_metadata.user_id.is_none() {
-            return Err(ApplicationError::BadRequest(
-                "Cannot update metadata of temporary files".to_string(),
-            ));
+        let valid = !client_id.is_empty()
+            && client_id.len() <= MAX_CLIENT_ID_LENGTH
+            && client_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+        if !valid {
+            warn!("Rejected malformed client id: {:?}", client_id);
+            return Err(ApplicationError::BadRequest(format!(
+                "client id must be 1-{} characters of [A-Za-z0-9._-]",
+                MAX_CLIENT_ID_LENGTH
+            )));
+        }
+
+        if !app_state.allowed_client_ids.is_empty()
+            && !app_state.allowed_client_ids.contains(&client_id)
+        {
+            warn!("Rejected upload from unknown client id '{}'", client_id);
+            return Err(ApplicationError::BadRequest(format!(
+                "Unknown client id '{}'",
+                client_id
+            )));
         }
+        Ok(Some(client_id))
+    }
 
-        let update_dto = MetadataDTO {
-            file_id: file_id.clone(),
-            description: body.description,
-            file_name: body.file_name,
-            delete_at: body.delete_at,
-            ..Default::default()
+    /// Nombre alternativo para la descarga; se valida igual que un nombre de archivo
+    fn validate_filename_override(
+        filename: Option<&str>,
+    ) -> Result<Option<&str>, ApplicationError> {
+        let Some(filename) = filename.map(str::trim) else {
+            return Ok(None);
         };
 
-        let updated_metadata = app_state
-            .metadata_repository
-            .update_metadata(update_dto)
-            .await?;
+        let invalid = filename.is_empty()
+            || filename.chars().count() > MAX_FILENAME_OVERRIDE_LENGTH
+            || filename.contains(['/', '\\'])
+            || filename.chars().any(char::is_control);
+
+        if invalid {
+            warn!("Rejected filename override: {:?}", filename);
+            return Err(ApplicationError::BadRequest(format!(
+                "filename must be 1-{} characters without path separators or control characters",
+                MAX_FILENAME_OVERRIDE_LENGTH
+            )));
+        }
+        Ok(Some(filename))
+    }
+
+    /// `filename` con un respaldo ASCII y `filename*` (RFC 5987) con el nombre en UTF-8
+    fn content_disposition(filename: &str) -> String {
+        let fallback: String = filename
+            .chars()
+            .map(|c| match c {
+                ' '..='~' if c != '"' && c != '\\' => c,
+                _ => '_',
+            })
+            .collect();
+
+        let mut encoded = String::with_capacity(filename.len());
+        for byte in filename.bytes() {
+            match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' => encoded.push(byte as char),
+                b'!' | b'#' | b'$' | b'&' | b'+' | b'-' | b'.' | b'^' | b'_' | b'`' | b'|'
+                | b'~' => encoded.push(byte as char),
+                _ => encoded.push_str(&format!("%{:02X}", byte)),
+            }
+        }
 
-        Ok(Json(FileResponse::from(updated_metadata)))
+        format!(
+            "attachment; filename=\"{}\"; filename*=UTF-8''{}",
+            fallback, encoded
+        )
     }
 
-    pub async fn delete_file(
-        State(app_state): State<AppState>,
-        Path(file_id): Path<String>,
-    ) -> Result<StatusCode, ApplicationError> {
-        let metadata = app_state.metadata_repository.get_metadata(&file_id).await?;
+    async fn serve_file(
+        app_state: &AppState,
+        file_id: &str,
+        filename: Option<&str>,
+        client_ip: IpAddr,
+    ) -> Result<Response, ApplicationError> {
+        Self::validate_file_id(file_id)?;
 
-        {
-            let service = app_state.storage_service.get();
-            service.delete(&file_id).await?;
+        let metadata = app_state.metadata_repository.get_metadata(file_id).await?;
+        if metadata.is_trashed() {
+            return Err(ApplicationError::NotFound);
         }
 
+        let download = {
+            let service = app_state.storage_service.get();
+            service.download_stream(metadata.storage_key()).await?
+        };
+        let content_length = Self::content_length(file_id, download.length, metadata.size);
+        let content =
+            Self::warn_on_size_mismatch(download.content, file_id.to_string(), metadata.size);
+
+        // Solo se cuentan las descargas que el proveedor empezó a servir
         app_state
             .metadata_repository
-            .delete_metadata(&file_id)
+            .record_download(file_id)
             .await?;
 
-        if let Some(user_id_str) = metadata.user_id {
-            if let Ok(uid) = Uuid::parse_str(&user_id_str) {
-                let get_user_dto = UserDTO::for_query(uid);
+        app_state.event_queue.publish(
+            AuditEvent::new(
+                AuditAction::Download,
+                file_id,
+                metadata.user_id.clone(),
+                app_state.server_id.clone(),
+            )
+            .with_client_ip(client_ip),
+        );
 
-                if let Ok(user) = app_state.user_repository.get_user(get_user_dto).await {
-                    let mut update_dto = UserDTO::for_update(uid);
-                    update_dto.file_count = Some(user.file_count.saturating_sub(1));
-                    update_dto.used_space = Some(user.used_space.saturating_sub(metadata.size));
-                    app_state.user_repository.update_user(update_dto).await?;
-                }
+        let mut response = Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, metadata.mime_type)
+            .header(
+                header::CONTENT_DISPOSITION,
+                Self::content_disposition(filename.unwrap_or(&metadata.file_name)),
+            );
+        // Sin longitud fiable el cuerpo va con chunked en lugar de anunciar un tamaño falso
+        if let Some(content_length) = content_length {
+            response = response.header(header::CONTENT_LENGTH, content_length);
+        }
+
+        Ok(response
+            .body(throttled_body(content, app_state.download_bytes_per_second))
+            .unwrap())
+    }
+
+    /// Content-Length de una descarga: el del proveedor, solo si coincide con la metadata
+    fn content_length(
+        file_id: &str,
+        provider_length: Option<u64>,
+        metadata_size: u64,
+    ) -> Option<u64> {
+        match provider_length {
+            Some(length) if length == metadata_size => Some(length),
+            Some(length) => {
+                warn!(
+                    "Size mismatch for file {}: metadata says {} bytes, provider announced {}",
+                    file_id, metadata_size, length
+                );
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Avisa al terminar la transferencia si el proveedor envió otro tamaño que la metadata
+    fn warn_on_size_mismatch(
+        content: DownloadStream,
+        file_id: String,
+        expected: u64,
+    ) -> DownloadStream {
+        stream::unfold((content, 0u64), move |(mut content, received)| {
+            let file_id = file_id.clone();
+            async move {
+                match content.next().await {
+                    Some(Ok(bytes)) => {
+                        let received = received + bytes.len() as u64;
+                        Some((Ok(bytes), (content, received)))
+                    }
+                    Some(Err(e)) => Some((Err(e), (content, received))),
+                    None => {
+                        if received != expected {
+                            warn!(
+                                "Size mismatch for file {}: metadata says {} bytes, provider returned {}",
+                                file_id, expected, received
+                            );
+                        }
+                        None
+                    }
+                }
+            }
+        })
+        .boxed()
+    }
+
+    /// Metadata de un archivo fuera de la papelera; los ids mal formados no llegan a la base
+    async fn live_metadata(
+        repo: &dyn MetadataRepository,
+        file_id: &str,
+    ) -> Result<Metadata, ApplicationError> {
+        Self::validate_file_id(file_id)?;
+        let metadata = repo.get_metadata(file_id).await?;
+        if metadata.is_trashed() {
+            return Err(ApplicationError::NotFound);
+        }
+        Ok(metadata)
+    }
+
+    /// If-None-Match con comparación débil: `*` o cualquier etiqueta de la lista
+    fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+        let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+        headers
+            .get_all(header::IF_NONE_MATCH)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .any(|tag| tag.trim() == "*" || opaque(tag) == opaque(etag))
+    }
+
+    /// URL de descarga a través del servicio; relativa si no hay server_url configurada
+    fn proxied_url(server_url: &str, file_id: &str) -> String {
+        file_dto::content_url(server_url, file_id)
+    }
+
+    /// Libera el objeto si nadie más lo referencia, borra los metadatos y audita el borrado
+    async fn delete_permanently(
+        app_state: &AppState,
+        metadata: &Metadata,
+        client_ip: IpAddr,
+    ) -> Result<(), ApplicationError> {
+        {
+            let service = app_state.storage_service.get();
+            Self::release_object(
+                app_state,
+                &service,
+                metadata.storage_key(),
+                std::slice::from_ref(metadata),
+            )
+            .await?;
+        }
+
+        app_state.event_queue.publish(
+            AuditEvent::new(
+                AuditAction::Delete,
+                metadata.file_id.clone(),
+                metadata.user_id.clone(),
+                app_state.server_id.clone(),
+            )
+            .with_client_ip(client_ip),
+        );
+        Ok(())
+    }
+
+    fn owner_id(metadata: &Metadata) -> Option<Uuid> {
+        metadata
+            .user_id
+            .as_deref()
+            .and_then(|id| Uuid::parse_str(id).ok())
+    }
+
+    /// Suma un archivo borrado a los deltas (archivos, bytes) de su dueño; sin dueño no cuenta
+    fn add_quota_delta(quota_deltas: &mut HashMap<Uuid, (u64, u64)>, metadata: &Metadata) {
+        if let Some(uid) = Self::owner_id(metadata) {
+            let delta = quota_deltas.entry(uid).or_insert((0, 0));
+            delta.0 += 1;
+            delta.1 += metadata.size;
+        }
+    }
+
+    /// Descuenta archivos y bytes de la cuota del usuario; sin efecto si el usuario ya no existe
+    async fn release_quota(
+        app_state: &AppState,
+        uid: Uuid,
+        file_delta: u64,
+        size_delta: u64,
+    ) -> Result<(), ApplicationError> {
+        let get_user_dto = UserDTO::for_query(uid);
+        let Ok(user) = app_state.user_repository.get_user(get_user_dto).await else {
+            return Ok(());
+        };
+
+        let mut update_dto = UserDTO::for_update(uid);
+        update_dto.file_count = Some(user.file_count.saturating_sub(file_delta));
+        update_dto.used_space = Some(user.used_space.saturating_sub(size_delta));
+        app_state.user_repository.update_user(update_dto).await?;
+        Ok(())
+    }
+
+    /// Archivo a traspasar: con dueño y fuera de la papelera
+    async fn transfer_source(
+        repo: &dyn MetadataRepository,
+        file_id: &str,
+    ) -> Result<Metadata, ApplicationError> {
+        let metadata = Self::live_metadata(repo, file_id).await?;
+        if metadata.user_id.is_none() {
+            return Err(ApplicationError::BadRequest(
+                "Temporal files without an owner cannot be transferred".to_string(),
+            ));
+        }
+        Ok(metadata)
+    }
+}
+
+/// Genera un token de un solo uso para subir archivos
+/// POST /api/v1/files/token
+/// Body: {} para usuarios anónimos, {"userId": "uuid"} para usuarios específicos
+#[utoipa::path(
+    post,
+    path = "/api/v1/files/token",
+    tag = "Tokens",
+    request_body = GenerateTokenRequest,
+    responses(
+        (status = 201, description = "Token de subida de un solo uso", body = TokenResponse),
+        (status = 404, description = "Usuario no encontrado"),
+    )
+)]
+pub async fn generate_upload_token(
+    State(app_state): State<AppState>,
+    JsonBody(body): JsonBody<GenerateTokenRequest>,
+) -> Result<(StatusCode, Json<TokenResponse>), ApplicationError> {
+    info!("Generating upload token for user_id: {:?}", body.user_id);
+
+    let require_user_id = app_state.global_config.lock().unwrap().require_user_id;
+    if require_user_id && body.user_id.is_none() {
+        warn!("Rejected anonymous token request: requireUserId is enabled");
+        return Err(ApplicationError::BadRequest(
+            "Missing 'user_id': anonymous uploads are disabled".to_string(),
+        ));
+    }
+
+    // Validar que el usuario existe si se proporciona user_id
+    if let Some(ref user_id_str) = body.user_id {
+        let uid = Uuid::parse_str(user_id_str).map_err(|e| {
+            warn!("Invalid UUID provided: {}, error: {}", user_id_str, e);
+            ApplicationError::BadRequest("Invalid user ID format".to_string())
+        })?;
+
+        let user_dto = UserDTO::for_query(uid);
+        app_state.user_repository.get_user(user_dto).await?;
+        info!("User validated successfully: {}", user_id_str);
+    } else {
+        info!("Generating anonymous token");
+    }
+
+    const TOKEN_TTL_SECONDS: u64 = 300; // 5 minutos
+
+    let token = app_state
+        .token_repository
+        .generate_token(body.user_id.clone(), TOKEN_TTL_SECONDS)
+        .await?;
+
+    info!("Token generated successfully: {}", token);
+
+    let (max_size, mime_types) = {
+        let gc = app_state.global_config.lock().unwrap();
+        (gc.max_size, gc.mime_types.clone())
+    };
+
+    // Los tokens anónimos solo pueden subir archivos temporales
+    let allowed_type = if body.user_id.is_none() {
+        Some("temporal".to_string())
+    } else {
+        None
+    };
+
+    Ok((
+        StatusCode::CREATED,
+        Json(TokenResponse {
+            token,
+            expires_in: TOKEN_TTL_SECONDS,
+            allowed_mime_types: Some(mime_types),
+            max_size: Some(max_size),
+            allowed_type,
+            user_id: body.user_id,
+        }),
+    ))
+}
+
+/// Sube un archivo como multipart/form-data (campos file, type, userId, description)
+#[utoipa::path(
+    post,
+    path = "/api/v1/files",
+    tag = "Files",
+    params(
+        ("Authorization" = String, Header, description = "Bearer <token de subida>"),
+        ("X-Client-Id" = Option<String>, Header,
+            description = "Aplicación que origina la subida; alternativa al campo client_id"),
+        ("X-Upload-Deadline" = Option<String>, Header,
+            description = "Segundos o instante RFC 3339 límite para la subida al proveedor"),
+        ("X-Content-SHA256" = Option<String>, Header,
+            description = "SHA-256 en hex del archivo; si no coincide se rechaza con 400"),
+    ),
+    responses(
+        (status = 201, body = UploadFileResponse),
+        (status = 400, description = "Campos, client id o plazo inválidos, o checksum distinto"),
+        (status = 401, description = "Token ausente, inválido o ya usado"),
+        (status = 404, description = "El usuario del token ya no existe"),
+        (status = 413, description = "El archivo excede el tamaño máximo"),
+        (status = 415, description = "Content-Type distinto de multipart/form-data"),
+        (status = 503, description = "Memoria de subidas agotada o sonda del proveedor fallida"),
+        (status = 504, description = "Venció X-Upload-Deadline antes de completar la subida"),
+    )
+)]
+pub async fn upload_file(
+    State(app_state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    headers: HeaderMap,
+    multipart: Result<Multipart, MultipartRejection>,
+) -> Result<(StatusCode, Json<UploadFileResponse>), ApplicationError> {
+    // VALIDAR TOKEN ANTES DE PARSEAR MULTIPART (fail-fast)
+    let token = FileController::upload_token(&headers)?;
+    let deadline = FileController::upload_deadline(&headers)?;
+    let expected_sha256 = FileController::expected_sha256(&headers)?;
+
+    // Rechazar un Content-Type inválido antes de consumir el token
+    let mut multipart = multipart?;
+    FileController::check_storage_health(&app_state)?;
+
+    let token_user_id = app_state
+        .token_repository
+        .verify_and_consume_token(token)
+        .await?;
+
+    info!("Token verified, associated user_id: {:?}", token_user_id);
+    // Antes de leer el archivo: el usuario pudo borrarse después de emitir el token
+    FileController::check_token_user(&app_state, token_user_id.as_ref()).await?;
+
+    let mut file_bytes: Option<Bytes> = None;
+    // Se libera al terminar la petición, cuando el buffer ya no está en memoria
+    let mut buffer_reservation = app_state.upload_buffer_budget.reservation();
+    let mut form = UploadForm {
+        client_id: headers
+            .get("X-Client-Id")
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string),
+        ..Default::default()
+    };
+    // El antivirus necesita el archivo entero; sin él puede ir al proveedor según llega
+    let stream_uploads = app_state.global_config.lock().unwrap().stream_uploads
+        && app_state.file_scanner.is_none()
+        && app_state.storage_service.get().streams_uploads();
+
+    while let Some(field) = multipart.next_field().await.map_err(|e| {
+        if e.status() == StatusCode::PAYLOAD_TOO_LARGE {
+            return ApplicationError::PayloadTooLarge;
+        }
+        warn!("Invalid multipart data: {}", e);
+        ApplicationError::BadRequest("Invalid request format".to_string())
+    })? {
+        let name = field.name().unwrap_or("").to_string();
+
+        match name.as_str() {
+            "file" if stream_uploads && form.has_required_fields() => {
+                let (upload, max_size, temp_file_life) = FileController::validate_upload_form(
+                    &app_state,
+                    std::mem::take(&mut form),
+                    token_user_id.as_ref(),
+                    Bytes::new(),
+                    deadline,
+                    expected_sha256.clone(),
+                )?;
+                let user = FileController::upload_owner(
+                    &app_state,
+                    upload.file_type,
+                    &upload.user_id,
+                    &upload.mime_type,
+                    0,
+                )
+                .await?;
+                let stored = FileController::stream_upload(
+                    &app_state,
+                    field,
+                    &upload,
+                    max_size,
+                    user.as_ref(),
+                )
+                .await?;
+
+                // El objeto ya está subido: un campo posterior no puede cambiar la subida
+                if let Ok(Some(extra)) = multipart.next_field().await {
+                    warn!(
+                        "Field '{}' sent after a streamed file, discarding {}",
+                        extra.name().unwrap_or(""),
+                        stored.file_id
+                    );
+                    let service = app_state.storage_service.get();
+                    if let Err(e) = service.delete(&stored.file_id).await {
+                        error!("Could not delete streamed {}: {:?}", stored.file_id, e);
+                    }
+                    return Err(ApplicationError::BadRequest(
+                        "The 'file' field must be the last form field".to_string(),
+                    ));
+                }
+
+                let metadata = FileController::record_upload(
+                    &app_state,
+                    stored,
+                    user,
+                    temp_file_life,
+                    client_ip,
+                )
+                .await?;
+                return Ok((
+                    StatusCode::CREATED,
+                    Json(UploadFileResponse::from(metadata)),
+                ));
+            }
+            "file" => {
+                let limit = FileController::file_field_limit(&app_state, form.mime_type.as_deref());
+                file_bytes = Some(
+                    FileController::read_file_field(field, limit, &mut buffer_reservation).await?,
+                );
+            }
+            "filename" => {
+                form.filename = Some(field.text().await.map_err(|e| {
+                    warn!("Invalid filename field: {}", e);
+                    ApplicationError::BadRequest("Invalid request data".to_string())
+                })?);
+            }
+            "mime_type" => {
+                form.mime_type = Some(field.text().await.map_err(|e| {
+                    warn!("Invalid mime_type field: {}", e);
+                    ApplicationError::BadRequest("Invalid request data".to_string())
+                })?);
+            }
+            "type" => {
+                form.file_type = Some(field.text().await.map_err(|e| {
+                    warn!("Invalid type field: {}", e);
+                    ApplicationError::BadRequest("Invalid request data".to_string())
+                })?);
+            }
+            "user_id" => {
+                form.user_id = Some(field.text().await.map_err(|e| {
+                    warn!("Invalid user_id field: {}", e);
+                    ApplicationError::BadRequest("Invalid request data".to_string())
+                })?);
+            }
+            "description" => {
+                form.description = Some(field.text().await.map_err(|e| {
+                    warn!("Invalid description field: {}", e);
+                    ApplicationError::BadRequest("Invalid request data".to_string())
+                })?);
+            }
+            "location" => {
+                form.location = Some(field.text().await.map_err(|e| {
+                    warn!("Invalid location field: {}", e);
+                    ApplicationError::BadRequest("Invalid request data".to_string())
+                })?);
+            }
+            "client_id" => {
+                // El header X-Client-Id tiene prioridad sobre el campo del formulario
+                let field_client_id = field.text().await.map_err(|e| {
+                    warn!("Invalid client_id field: {}", e);
+                    ApplicationError::BadRequest("Invalid request data".to_string())
+                })?;
+                form.client_id.get_or_insert(field_client_id);
+            }
+            _ => {}
+        }
+    }
+
+    let file_bytes = file_bytes.ok_or_else(|| {
+        warn!("Missing required 'file' field in upload");
+        ApplicationError::BadRequest("Missing required field".to_string())
+    })?;
+    let (upload, _, temp_file_life) = FileController::validate_upload_form(
+        &app_state,
+        form,
+        token_user_id.as_ref(),
+        file_bytes,
+        deadline,
+        expected_sha256,
+    )?;
+
+    let metadata =
+        FileController::store_upload(&app_state, upload, temp_file_life, client_ip).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(UploadFileResponse::from(metadata)),
+    ))
+}
+
+/// Abre una subida fragmentada; consume el token de subida igual que upload_file
+/// POST /api/v1/files/chunked/init
+#[utoipa::path(
+    post,
+    path = "/api/v1/files/chunked/init",
+    tag = "Files",
+    params(
+        ("Authorization" = String, Header, description = "Bearer <token de subida>"),
+        ("X-Client-Id" = Option<String>, Header, description = "Aplicación que origina la subida"),
+    ),
+    request_body = InitChunkedUploadRequest,
+    responses(
+        (status = 201, body = ChunkedUploadSessionResponse),
+        (status = 400, description = "Campos inválidos o subidas fragmentadas deshabilitadas"),
+        (status = 401, description = "Token ausente, inválido o ya usado"),
+        (status = 404, description = "El usuario del token ya no existe"),
+        (status = 413, description = "size excede el tamaño máximo"),
+    )
+)]
+pub async fn init_chunked_upload(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    JsonBody(body): JsonBody<InitChunkedUploadRequest>,
+) -> Result<(StatusCode, Json<ChunkedUploadSessionResponse>), ApplicationError> {
+    let token = FileController::upload_token(&headers)?;
+
+    let chunk_size = app_state.global_config.lock().unwrap().chunk_size;
+    if chunk_size == 0 {
+        return Err(ApplicationError::BadRequest(
+            "Chunked uploads are disabled: chunkSize is 0".to_string(),
+        ));
+    }
+
+    let filename = body.filename.trim().to_string();
+    if filename.is_empty() {
+        return Err(ApplicationError::BadRequest(
+            "Missing required field".to_string(),
+        ));
+    }
+    // La lista permitida se guarda normalizada en minúsculas
+    let mime_type = body.mime_type.trim().to_ascii_lowercase();
+    let file_type: FileKind = body
+        .file_type
+        .parse()
+        .map_err(ApplicationError::BadRequest)?;
+
+    let (max_size, temp_file_life) = FileController::upload_limits(&app_state, &mime_type)?;
+    if body.size.is_some_and(|size| size > max_size) {
+        return Err(ApplicationError::PayloadTooLarge);
+    }
+
+    let client_id = headers
+        .get("X-Client-Id")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let client_id = FileController::validate_client_id(&app_state, client_id)?;
+    let location = FileController::validate_location(&app_state, body.location)?;
+
+    let token_user_id = app_state
+        .token_repository
+        .verify_and_consume_token(token)
+        .await?;
+    let require_user_id = app_state.global_config.lock().unwrap().require_user_id;
+    FileController::check_upload_owner(
+        file_type,
+        body.user_id.as_ref(),
+        token_user_id.as_ref(),
+        require_user_id,
+    )?;
+    FileController::check_token_user(&app_state, token_user_id.as_ref()).await?;
+
+    let session = UploadSession {
+        session_id: Uuid::new_v4().to_string(),
+        filename,
+        mime_type,
+        file_type,
+        user_id: body.user_id,
+        description: body.description,
+        client_id,
+        location,
+        chunk_size,
+        max_size,
+        expires_at: Utc::now() + Duration::seconds(temp_file_life as i64),
+        session_secret: Some(Uuid::new_v4().simple().to_string()),
+        received_bytes: 0,
+    };
+    app_state
+        .upload_session_repository
+        .create_session(&session, temp_file_life)
+        .await?;
+
+    // El secreto solo viaja en esta respuesta; sin él una sesión anónima no se continúa
+    let session_secret = session.session_secret.clone();
+    Ok((
+        StatusCode::CREATED,
+        Json(ChunkedUploadSessionResponse {
+            session_secret,
+            ..session.into()
+        }),
+    ))
+}
+
+/// Añade el siguiente bloque (cuerpo binario) a una subida fragmentada
+/// PATCH /api/v1/files/chunked/{session_id}
+#[utoipa::path(
+    patch,
+    path = "/api/v1/files/chunked/{session_id}",
+    tag = "Files",
+    params(
+        ("session_id" = String, Path),
+        ("Upload-Offset" = Option<u64>, Header, description = "Bytes que el cliente cree enviados"),
+        ("X-Upload-Session-Secret" = Option<String>, Header, description = "sessionSecret devuelto al abrir la sesión"),
+        ("Authorization" = Option<String>, Header, description = "Bearer <token de usuario>"),
+        ("X-VK-Secret" = Option<String>, Header, description = "Permite operar sobre cualquier sesión"),
+    ),
+    request_body(content = Vec<u8>, content_type = "application/octet-stream"),
+    responses(
+        (status = 200, body = ChunkedUploadSessionResponse),
+        (status = 400, description = "Bloque vacío, posterior al último u offset distinto"),
+        (status = 401, description = "Token de usuario inválido o expirado"),
+        (status = 403, description = "Sin secreto de sesión válido, token del dueño ni X-VK-Secret"),
+        (status = 404, description = "Sesión inexistente o expirada"),
+        (status = 413, description = "Bloque mayor que chunkSize o archivo mayor que maxSize"),
+    )
+)]
+pub async fn append_chunk(
+    State(app_state): State<AppState>,
+    caller: FileCaller,
+    Path(session_id): Path<String>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<Json<ChunkedUploadSessionResponse>, ApplicationError> {
+    FileController::validate_session_id(&session_id)?;
+    let mut session = app_state
+        .upload_session_repository
+        .get_session(&session_id)
+        .await?;
+    caller.authorize_session(&session, authorization::session_secret(&headers))?;
+
+    let chunk = axum::body::to_bytes(body, session.chunk_size as usize)
+        .await
+        .map_err(|e| {
+            warn!("Rejected chunk for upload session {}: {}", session_id, e);
+            ApplicationError::PayloadTooLarge
+        })?;
+    if chunk.is_empty() {
+        return Err(ApplicationError::BadRequest("Empty chunk".to_string()));
+    }
+
+    // Upload-Offset hace idempotente el reintento de un bloque
+    let expected_offset = headers
+        .get("Upload-Offset")
+        .map(|v| {
+            v.to_str()
+                .ok()
+                .and_then(|offset| offset.parse::<u64>().ok())
+                .ok_or_else(|| {
+                    ApplicationError::BadRequest("Invalid Upload-Offset header".to_string())
+                })
+        })
+        .transpose()?;
+
+    session.received_bytes = app_state
+        .upload_session_repository
+        .append_chunk(&session_id, &chunk, expected_offset)
+        .await?;
+
+    Ok(Json(ChunkedUploadSessionResponse::from(session)))
+}
+
+/// Cancela una subida fragmentada en curso y descarta los bloques recibidos
+/// DELETE /api/v1/files/chunked/{session_id}
+#[utoipa::path(
+    delete,
+    path = "/api/v1/files/chunked/{session_id}",
+    tag = "Files",
+    params(
+        ("session_id" = String, Path),
+        ("X-Upload-Session-Secret" = Option<String>, Header, description = "sessionSecret devuelto al abrir la sesión"),
+        ("Authorization" = Option<String>, Header, description = "Bearer <token de usuario>"),
+        ("X-VK-Secret" = Option<String>, Header, description = "Permite operar sobre cualquier sesión"),
+    ),
+    responses(
+        (status = 204, description = "Sesión cancelada"),
+        (status = 401, description = "Token de usuario inválido o expirado"),
+        (status = 403, description = "Sin secreto de sesión válido, token del dueño ni X-VK-Secret"),
+        (status = 404, description = "Sesión inexistente, expirada o ya completada"),
+    )
+)]
+pub async fn cancel_chunked_upload(
+    State(app_state): State<AppState>,
+    caller: FileCaller,
+    Path(session_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<StatusCode, ApplicationError> {
+    FileController::validate_session_id(&session_id)?;
+    let session = app_state
+        .upload_session_repository
+        .get_session(&session_id)
+        .await?;
+    caller.authorize_session(&session, authorization::session_secret(&headers))?;
+    // Los bloques solo llegan al proveedor en complete, no hay objeto parcial que borrar
+    app_state
+        .upload_session_repository
+        .delete_session(&session_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Ensambla los bloques recibidos y almacena el archivo igual que upload_file
+/// POST /api/v1/files/chunked/{session_id}/complete
+#[utoipa::path(
+    post,
+    path = "/api/v1/files/chunked/{session_id}/complete",
+    tag = "Files",
+    params(
+        ("session_id" = String, Path),
+        ("X-Upload-Deadline" = Option<String>, Header,
+            description = "Segundos o instante RFC 3339 límite para la subida al proveedor"),
+        ("X-Content-SHA256" = Option<String>, Header,
+            description = "SHA-256 en hex del archivo; si no coincide se rechaza con 400"),
+        ("X-Upload-Session-Secret" = Option<String>, Header, description = "sessionSecret devuelto al abrir la sesión"),
+        ("Authorization" = Option<String>, Header, description = "Bearer <token de usuario>"),
+        ("X-VK-Secret" = Option<String>, Header, description = "Permite operar sobre cualquier sesión"),
+    ),
+    responses(
+        (status = 201, body = UploadFileResponse),
+        (status = 400, description = "Sin bloques, plazo inválido o checksum distinto"),
+        (status = 401, description = "Token de usuario inválido o expirado"),
+        (status = 403, description = "Sin secreto de sesión válido, token del dueño ni X-VK-Secret"),
+        (status = 404, description = "Sesión inexistente, expirada o ya completada"),
+        (status = 413, description = "El archivo excede el tamaño máximo"),
+        (status = 503, description = "Presupuesto de buffer agotado; la sesión sigue abierta"),
+        (status = 504, description = "Venció X-Upload-Deadline antes de completar la subida"),
+        (status = 507, description = "Cuota del usuario excedida"),
+    )
+)]
+pub async fn complete_chunked_upload(
+    State(app_state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    caller: FileCaller,
+    Path(session_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<(StatusCode, Json<UploadFileResponse>), ApplicationError> {
+    FileController::validate_session_id(&session_id)?;
+    let deadline = FileController::upload_deadline(&headers)?;
+    let expected_sha256 = FileController::expected_sha256(&headers)?;
+    // Se comprueba antes de tomar la sesión: un intento ajeno no debe cerrarla
+    let session = app_state
+        .upload_session_repository
+        .get_session(&session_id)
+        .await?;
+    caller.authorize_session(&session, authorization::session_secret(&headers))?;
+    // El archivo ensamblado se retiene entero como en upload_file; se reserva antes de
+    // cerrar la sesión para que un 503 se pueda reintentar
+    let reserved = session.received_bytes as usize;
+    let mut buffer_reservation = app_state.upload_buffer_budget.reservation();
+    buffer_reservation.grow(reserved)?;
+    let (session, file_bytes) = app_state
+        .upload_session_repository
+        .take_session(&session_id)
+        .await?;
+    // Un bloque pudo llegar entre la consulta y el cierre de la sesión
+    if file_bytes.len() > reserved {
+        buffer_reservation.grow(file_bytes.len() - reserved)?;
+    }
+
+    if file_bytes.is_empty() {
+        return Err(ApplicationError::BadRequest(
+            "Upload session received no chunks".to_string(),
+        ));
+    }
+
+    // La configuración pudo cambiar mientras llegaban los bloques
+    let (max_size, temp_file_life) = FileController::upload_limits(&app_state, &session.mime_type)?;
+    if file_bytes.len() as u64 > max_size {
+        return Err(ApplicationError::PayloadTooLarge);
+    }
+
+    info!(
+        "Completing upload session {}: {} bytes",
+        session_id,
+        file_bytes.len()
+    );
+
+    let metadata = FileController::store_upload(
+        &app_state,
+        ValidatedUpload {
+            file_bytes,
+            filename: session.filename,
+            mime_type: session.mime_type,
+            file_type: session.file_type,
+            user_id: session.user_id,
+            description: session.description,
+            client_id: session.client_id,
+            location: session.location,
+            deadline,
+            expected_sha256,
+        },
+        temp_file_life,
+        client_ip,
+    )
+    .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(UploadFileResponse::from(metadata)),
+    ))
+}
+
+/// Cruza el listado del proveedor con application.metadata; solo informa, no borra
+/// GET /api/v1/files/reconcile?prefix=
+#[utoipa::path(
+    get,
+    path = "/api/v1/files/reconcile",
+    tag = "Files",
+    params(
+        ReconcileQuery,
+        ("X-VK-Secret" = String, Header, description = "Secreto de la instancia"),
+    ),
+    responses(
+        (status = 200, body = ReconcileResponse),
+        (status = 401, description = "X-VK-Secret ausente o inválido"),
+    )
+)]
+pub async fn reconcile_files(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ReconcileQuery>,
+) -> Result<Json<ReconcileResponse>, ApplicationError> {
+    if !FileController::has_vk_secret(&app_state, &headers) {
+        return Err(ApplicationError::Unauthorized);
+    }
+    let prefix = query
+        .prefix
+        .as_deref()
+        .map(str::trim)
+        .filter(|prefix| !prefix.is_empty());
+
+    let stored = {
+        let service = app_state.storage_service.get();
+        service.list_files(prefix).await?
+    };
+
+    // Todas las filas: un objeto que referencia otra instancia del bucket no es huérfano
+    let rows: Vec<Metadata> = app_state
+        .metadata_repository
+        .stream_export(None, None)
+        .try_collect()
+        .await?;
+    // Por objeto del proveedor: las copias deduplicadas comparten uno
+    let mut rows_by_object: HashMap<String, Vec<Metadata>> = HashMap::new();
+    for row in rows {
+        if prefix.is_none_or(|prefix| row.storage_key().starts_with(prefix)) {
+            rows_by_object
+                .entry(row.storage_key().to_string())
+                .or_default()
+                .push(row);
+        }
+    }
+
+    let orphaned_objects: Vec<OrphanedObject> = stored
+        .into_iter()
+        .filter(|object| rows_by_object.remove(&object.file_id).is_none())
+        .map(OrphanedObject::from)
+        .collect();
+
+    // Solo las filas de esta instancia apuntan al proveedor que acabamos de listar
+    let mut missing_objects: Vec<FileResponse> = rows_by_object
+        .into_values()
+        .flatten()
+        .filter(|row| row.server_id == app_state.server_id)
+        .map(FileResponse::from)
+        .collect();
+    missing_objects.sort_by(|a, b| a.file_id.cmp(&b.file_id));
+
+    info!(
+        "Reconcile found {} orphaned objects and {} rows with missing objects",
+        orphaned_objects.len(),
+        missing_objects.len()
+    );
+
+    Ok(Json(ReconcileResponse {
+        orphaned_objects,
+        missing_objects,
+    }))
+}
+
+/// Elimina los archivos temporales expirados
+#[utoipa::path(
+    delete,
+    path = "/api/v1/files",
+    tag = "Files",
+    responses(
+        (status = 200, description = "Todos los archivos expirados fueron eliminados"),
+        (status = 207, description = "Algunos archivos no pudieron eliminarse"),
+        (status = 409, description = "Ya hay una limpieza en curso"),
+    )
+)]
+pub async fn cleanup_expired_files(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<(StatusCode, Json<CleanupResponse>), ApplicationError> {
+    if !FileController::has_vk_secret(&app_state, &headers) {
+        return Err(ApplicationError::Unauthorized);
+    }
+
+    let report = FileController::run_cleanup(&app_state).await?;
+    let status = if report.errors.is_empty() {
+        report.results.status_code()
+    } else {
+        StatusCode::MULTI_STATUS
+    };
+    Ok((status, Json(report)))
+}
+
+/// GET /api/v1/files/{file_id}/content?filename=
+#[utoipa::path(
+    get,
+    path = "/api/v1/files/{file_id}/content",
+    tag = "Files",
+    params(("file_id" = String, Path), DownloadQuery),
+    responses(
+        (status = 200, description = "Contenido del archivo"),
+        (status = 400, description = "filename inválido"),
+        (status = 404, description = "Archivo no encontrado"),
+    )
+)]
+pub async fn download_file(
+    State(app_state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Path(file_id): Path<String>,
+    Query(query): Query<DownloadQuery>,
+) -> Result<Response, ApplicationError> {
+    let filename = FileController::validate_filename_override(query.filename.as_deref())?;
+    FileController::serve_file(&app_state, &file_id, filename, client_ip).await
+}
+
+/// Cabeceras de la descarga sin transferir el contenido ni contar la descarga
+/// HEAD /api/v1/files/{file_id}/content
+#[utoipa::path(
+    head,
+    path = "/api/v1/files/{file_id}/content",
+    tag = "Files",
+    params(("file_id" = String, Path), DownloadQuery),
+    responses(
+        (status = 200, description = "Cabeceras de la descarga sin contenido"),
+        (status = 400, description = "filename inválido"),
+        (status = 404, description = "Archivo no encontrado"),
+    )
+)]
+pub async fn head_file(
+    State(app_state): State<AppState>,
+    Path(file_id): Path<String>,
+    Query(query): Query<DownloadQuery>,
+) -> Result<Response, ApplicationError> {
+    let filename = FileController::validate_filename_override(query.filename.as_deref())?;
+    FileController::validate_file_id(&file_id)?;
+    let metadata = app_state.metadata_repository.get_metadata(&file_id).await?;
+    if metadata.is_trashed() {
+        return Err(ApplicationError::NotFound);
+    }
+
+    let exists = {
+        let service = app_state.storage_service.get();
+        service.exists(metadata.storage_key()).await?
+    };
+    if !exists {
+        warn!("File {} has metadata but is missing in storage", file_id);
+        return Err(ApplicationError::NotFound);
+    }
+
+    let response = Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, metadata.mime_type)
+        .header(header::CONTENT_LENGTH, metadata.size)
+        .header(
+            header::CONTENT_DISPOSITION,
+            FileController::content_disposition(filename.unwrap_or(&metadata.file_name)),
+        )
+        .body(Body::empty())
+        .unwrap();
+
+    Ok(response)
+}
+
+/// Descarga el archivo permanente más reciente de un usuario con ese nombre
+/// GET /api/v1/users/{user_id}/files/by-name/{filename}/content
+///
+/// Con allowDuplicateNames varios archivos con el nombre son ambiguos (409).
+#[utoipa::path(
+    get,
+    path = "/api/v1/users/{user_id}/files/by-name/{filename}/content",
+    tag = "Files",
+    params(("user_id" = uuid::Uuid, Path), ("filename" = String, Path)),
+    responses(
+        (status = 200, description = "Contenido del archivo más reciente con ese nombre"),
+        (status = 404, description = "Archivo no encontrado"),
+        (status = 409, description = "Varios archivos con ese nombre y allowDuplicateNames activo"),
+    )
+)]
+pub async fn download_file_by_name(
+    State(app_state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Path((user_id, filename)): Path<(Uuid, String)>,
+) -> Result<Response, ApplicationError> {
+    let filename = filename.trim();
+    if filename.is_empty() {
+        return Err(ApplicationError::BadRequest(
+            "Filename must not be empty".to_string(),
+        ));
+    }
+
+    let allow_duplicates = app_state
+        .global_config
+        .lock()
+        .unwrap()
+        .allow_duplicate_names;
+    let metadata = FileController::resolve_by_name(
+        app_state.metadata_repository.as_ref(),
+        user_id,
+        filename,
+        allow_duplicates,
+    )
+    .await?;
+
+    FileController::serve_file(&app_state, &metadata.file_id, None, client_ip).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/files/{file_id}",
+    tag = "Files",
+    params(
+        ("file_id" = String, Path),
+        ("If-None-Match" = Option<String>, Header, description = "ETag de una respuesta anterior"),
+    ),
+    responses(
+        (status = 200, body = FileResponse,
+            headers(("ETag" = String, description = "ETag débil sobre la metadata"))),
+        (status = 304, description = "La metadata no cambió desde el ETag enviado"),
+        (status = 400, description = "file_id mal formado"),
+        (status = 404, description = "Archivo no encontrado o en la papelera"),
+    )
+)]
+pub async fn get_file_metadata(
+    State(app_state): State<AppState>,
+    Path(file_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, ApplicationError> {
+    let metadata =
+        FileController::live_metadata(app_state.metadata_repository.as_ref(), &file_id).await?;
+    let body = serde_json::to_vec(&FileResponse::from(metadata)).map_err(|e| {
+        ApplicationError::InternalError(format!("Cannot serialize metadata: {}", e))
+    })?;
+
+    // ETag débil sobre el JSON: cambia con cualquier campo, incluidos los contadores
+    let etag = format!("W/\"{:x}\"", Sha256::digest(&body));
+    let (status, body) = if FileController::etag_matches(&headers, &etag) {
+        (StatusCode::NOT_MODIFIED, Body::empty())
+    } else {
+        (StatusCode::OK, Body::from(body))
+    };
+
+    let response = Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::ETAG, etag)
+        .body(body)
+        .unwrap();
+
+    Ok(response)
+}
+
+/// Devuelve solo los contadores del archivo; no incrementa downloadCount
+/// GET /api/v1/files/{file_id}/stats
+#[utoipa::path(
+    get,
+    path = "/api/v1/files/{file_id}/stats",
+    tag = "Files",
+    params(("file_id" = String, Path)),
+    responses(
+        (status = 200, body = FileStatsResponse),
+        (status = 400, description = "file_id mal formado"),
+        (status = 404, description = "Archivo no encontrado o en la papelera"),
+    )
+)]
+pub async fn get_file_stats(
+    State(app_state): State<AppState>,
+    Path(file_id): Path<String>,
+) -> Result<Json<FileStatsResponse>, ApplicationError> {
+    FileController::validate_file_id(&file_id)?;
+    let stats = app_state
+        .metadata_repository
+        .get_file_stats(&file_id)
+        .await?;
+    Ok(Json(FileStatsResponse::from(stats)))
+}
+
+/// Devuelve los enlaces nativos de Google Drive (webViewLink/webContentLink)
+/// GET /api/v1/files/{file_id}/links
+#[utoipa::path(
+    get,
+    path = "/api/v1/files/{file_id}/links",
+    tag = "Files",
+    params(("file_id" = String, Path)),
+    responses(
+        (status = 200, body = FileLinksResponse),
+        (status = 404, description = "Archivo no encontrado o no almacenado en Google Drive"),
+    )
+)]
+pub async fn get_file_links(
+    State(app_state): State<AppState>,
+    Path(file_id): Path<String>,
+) -> Result<Json<FileLinksResponse>, ApplicationError> {
+    FileController::validate_file_id(&file_id)?;
+    let metadata = app_state.metadata_repository.get_metadata(&file_id).await?;
+    if metadata.is_trashed() {
+        return Err(ApplicationError::NotFound);
+    }
+
+    let storage_metadata = {
+        let service = app_state.storage_service.get();
+        service.get_metadata(metadata.storage_key()).await?
+    };
+
+    if storage_metadata.provider != "gdrive" {
+        warn!(
+            "Native links requested for file {} stored in provider {}",
+            file_id, storage_metadata.provider
+        );
+        return Err(ApplicationError::NotFound);
+    }
+
+    Ok(Json(FileLinksResponse {
+        file_id,
+        web_view_link: storage_metadata.web_view_link,
+        web_content_link: storage_metadata.web_content_link,
+    }))
+}
+
+/// URLs firmadas del proveedor para varios archivos a la vez (galerías)
+/// POST /api/v1/files/signed-urls:batch
+///
+/// Si el proveedor no emite URLs firmadas (Google Drive) se devuelve la URL de descarga
+/// del propio servicio. Los archivos inexistentes o ajenos fallan por separado, con
+/// `207 Multi-Status`.
+#[utoipa::path(
+    post,
+    path = "/api/v1/files/signed-urls:batch",
+    tag = "Files",
+    params(
+        ("Authorization" = Option<String>, Header, description = "Bearer <token de usuario>"),
+        ("X-VK-Secret" = Option<String>, Header, description = "Permite firmar cualquier archivo"),
+    ),
+    request_body = SignedUrlsBatchRequest,
+    responses(
+        (status = 200, body = SignedUrlsBatchResponse),
+        (status = 207, description = "Algunos archivos no existen o son ajenos; ver el `status` de cada uno",
+            body = SignedUrlsBatchResponse),
+        (status = 400, description = "Más de 100 fileIds o expiresIn fuera de rango"),
+        (status = 401, description = "Token de usuario inválido o expirado"),
+    )
+)]
+pub async fn signed_urls_batch(
+    State(app_state): State<AppState>,
+    caller: FileCaller,
+    JsonBody(body): JsonBody<SignedUrlsBatchRequest>,
+) -> Result<(StatusCode, Json<SignedUrlsBatchResponse>), ApplicationError> {
+    if body.file_ids.len() > MAX_SIGNED_URL_BATCH {
+        return Err(ApplicationError::BadRequest(format!(
+            "At most {} fileIds per request",
+            MAX_SIGNED_URL_BATCH
+        )));
+    }
+    let max_expiry = app_state.signed_url_max_ttl_secs;
+    let expires_in = body
+        .expires_in
+        .unwrap_or(DEFAULT_SIGNED_URL_EXPIRY_SECS.min(max_expiry));
+    if !(1..=max_expiry).contains(&expires_in) {
+        return Err(ApplicationError::BadRequest(format!(
+            "expiresIn must be between 1 and {} seconds",
+            max_expiry
+        )));
+    }
+
+    let mut file_ids = body.file_ids;
+    file_ids.sort();
+    file_ids.dedup();
+
+    let server_url = app_state.local_config.lock().unwrap().server_url.clone();
+    let service = app_state.storage_service.get();
+    let metadata_repository = app_state.metadata_repository.clone();
+    let results: Vec<_> = stream::iter(file_ids)
+        .map(|file_id| {
+            let service = service.clone();
+            let metadata_repository = metadata_repository.clone();
+            let server_url = &server_url;
+            async move {
+                let result = async {
+                    FileController::validate_file_id(&file_id)?;
+                    let metadata = metadata_repository.get_metadata(&file_id).await?;
+                    if metadata.is_trashed() {
+                        return Err(ApplicationError::NotFound);
+                    }
+                    caller.authorize(&metadata)?;
+                    let signed = service
+                        .generate_presigned_url(
+                            metadata.storage_key(),
+                            std::time::Duration::from_secs(expires_in),
+                        )
+                        .await?;
+                    Ok::<_, ApplicationError>(
+                        signed.unwrap_or_else(|| FileController::proxied_url(server_url, &file_id)),
+                    )
+                }
+                .await;
+                (file_id, result)
+            }
+        })
+        .buffered(app_state.provider_request_concurrency)
+        .collect()
+        .await;
+
+    let mut batch = BatchResult::default();
+    for (file_id, result) in results {
+        match result {
+            Ok(url) => batch.push_ok(file_id, StatusCode::OK, Some(url)),
+            Err(e) => {
+                warn!("No signed URL for file {}: {:?}", file_id, e);
+                batch.push_err(file_id, e);
+            }
+        }
+    }
+
+    Ok((
+        batch.status_code(),
+        Json(SignedUrlsBatchResponse {
+            results: batch,
+            expires_in,
+        }),
+    ))
+}
+
+/// URL firmada del proveedor para descargar el archivo sin pasar por el servicio
+/// GET /api/v1/files/{file_id}/url?ttl=
+///
+/// `ttl` se recorta al máximo configurado. Si el proveedor no firma URLs (Google Drive)
+/// se devuelve la URL de descarga del propio servicio con `direct: false`.
+#[utoipa::path(
+    get,
+    path = "/api/v1/files/{file_id}/url",
+    tag = "Files",
+    params(
+        ("file_id" = String, Path),
+        SignedUrlQuery,
+        ("Authorization" = Option<String>, Header, description = "Bearer <token de usuario>"),
+        ("X-VK-Secret" = Option<String>, Header, description = "Permite firmar cualquier archivo"),
+    ),
+    responses(
+        (status = 200, body = SignedUrlResponse),
+        (status = 400, description = "ttl es 0"),
+        (status = 401, description = "Token de usuario inválido o expirado"),
+        (status = 403, description = "No pertenece al usuario del token, o falta token sin X-VK-Secret"),
+        (status = 404, description = "Archivo no encontrado o en la papelera"),
+    )
+)]
+pub async fn get_signed_url(
+    State(app_state): State<AppState>,
+    caller: FileCaller,
+    Path(file_id): Path<String>,
+    Query(query): Query<SignedUrlQuery>,
+) -> Result<Json<SignedUrlResponse>, ApplicationError> {
+    FileController::validate_file_id(&file_id)?;
+    let ttl = query.ttl.unwrap_or(DEFAULT_SIGNED_URL_EXPIRY_SECS);
+    if ttl == 0 {
+        return Err(ApplicationError::BadRequest(
+            "ttl must be at least 1 second".to_string(),
+        ));
+    }
+    let ttl = ttl.min(app_state.signed_url_max_ttl_secs);
+
+    let metadata = app_state.metadata_repository.get_metadata(&file_id).await?;
+    if metadata.is_trashed() {
+        return Err(ApplicationError::NotFound);
+    }
+    caller.authorize(&metadata)?;
+
+    let signed = {
+        let service = app_state.storage_service.get();
+        service
+            .generate_presigned_url(metadata.storage_key(), std::time::Duration::from_secs(ttl))
+            .await?
+    };
+
+    let response = match signed {
+        Some(url) => SignedUrlResponse {
+            file_id,
+            url,
+            direct: true,
+            expires_in: Some(ttl),
+        },
+        None => {
+            let server_url = app_state.local_config.lock().unwrap().server_url.clone();
+            SignedUrlResponse {
+                url: FileController::proxied_url(&server_url, &file_id),
+                file_id,
+                direct: false,
+                expires_in: None,
+            }
+        }
+    };
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/api/v1/files/{file_id}",
+    tag = "Files",
+    params(
+        ("file_id" = String, Path),
+        ("Authorization" = Option<String>, Header, description = "Bearer <token de usuario>"),
+        ("X-VK-Secret" = Option<String>, Header, description = "Permite modificar cualquier archivo"),
+    ),
+    request_body = UpdateFileRequest,
+    responses(
+        (status = 200, body = FileResponse),
+        (status = 400, description = "Los archivos temporales no se pueden modificar"),
+        (status = 401, description = "Token de usuario inválido o expirado"),
+        (status = 403, description = "No pertenece al usuario del token, o falta token sin X-VK-Secret"),
+        (status = 404, description = "Archivo no encontrado"),
+    )
+)]
+pub async fn update_file_metadata(
+    State(app_state): State<AppState>,
+    caller: FileCaller,
+    Path(file_id): Path<String>,
+    JsonBody(body): JsonBody<UpdateFileRequest>,
+) -> Result<Json<FileResponse>, ApplicationError> {
+    // Un archivo en la papelera solo se restaura o se purga
+    let current_metadata =
+        FileController::live_metadata(app_state.metadata_repository.as_ref(), &file_id).await?;
+    caller.authorize(&current_metadata)?;
+
+    if current_metadata.user_id.is_none() {
+        return Err(ApplicationError::BadRequest(
+            "Cannot update metadata of temporary files".to_string(),
+        ));
+    }
+
+    let update_dto = MetadataDTO {
+        file_id: file_id.clone(),
+        description: body.description,
+        file_name: body.file_name,
+        delete_at: body.delete_at,
+        ..Default::default()
+    };
+
+    let updated_metadata = app_state
+        .metadata_repository
+        .update_metadata(update_dto)
+        .await?;
+
+    Ok(Json(FileResponse::from(updated_metadata)))
+}
+
+/// Mueve el archivo a la papelera; con `permanent=true` lo borra definitivamente
+/// DELETE /api/v1/files/{file_id}
+///
+/// El objeto sigue en el proveedor y contando en la cuota hasta que la limpieza lo
+/// purga al vencer trash_retention. Solo lo borra el dueño acreditado por su token de
+/// usuario, o el admin con X-VK-Secret.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/files/{file_id}",
+    tag = "Files",
+    params(
+        ("file_id" = String, Path),
+        ("Authorization" = Option<String>, Header, description = "Bearer <token de usuario>"),
+        DeleteFileQuery,
+        ("X-VK-Secret" = Option<String>, Header, description = "Permite borrar cualquier archivo"),
+    ),
+    responses(
+        (status = 204, description = "Archivo en la papelera, o eliminado con permanent=true"),
+        (status = 401, description = "Token de usuario inválido o expirado"),
+        (status = 403, description = "No pertenece al usuario del token, o falta token sin X-VK-Secret"),
+        (status = 404, description = "Archivo no encontrado"),
+    )
+)]
+pub async fn delete_file(
+    State(app_state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    caller: FileCaller,
+    Path(file_id): Path<String>,
+    Query(query): Query<DeleteFileQuery>,
+) -> Result<StatusCode, ApplicationError> {
+    FileController::validate_file_id(&file_id)?;
+    let metadata = app_state.metadata_repository.get_metadata(&file_id).await?;
+    caller.authorize(&metadata)?;
+
+    if !query.permanent {
+        app_state
+            .metadata_repository
+            .trash_metadata(&file_id)
+            .await?;
+
+        app_state.event_queue.publish(
+            AuditEvent::new(
+                AuditAction::Trash,
+                file_id,
+                metadata.user_id,
+                app_state.server_id.clone(),
+            )
+            .with_client_ip(client_ip),
+        );
+        return Ok(StatusCode::NO_CONTENT);
+    }
+
+    FileController::delete_permanently(&app_state, &metadata, client_ip).await?;
+
+    if let Some(uid) = FileController::owner_id(&metadata) {
+        FileController::release_quota(&app_state, uid, 1, metadata.size).await?;
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Borra varios archivos de forma permanente; un fallo no detiene el resto del lote
+/// POST /api/v1/files/batch-delete
+#[utoipa::path(
+    post,
+    path = "/api/v1/files/batch-delete",
+    tag = "Files",
+    params(("X-VK-Secret" = String, Header, description = "Secreto de la instancia")),
+    request_body = BatchDeleteRequest,
+    responses(
+        (status = 200, body = BatchDeleteResponse),
+        (status = 207, description = "Algunos archivos no se borraron; ver `failed`",
+            body = BatchDeleteResponse),
+        (status = 400, description = "Ningún fileId o más de 200"),
+        (status = 401, description = "X-VK-Secret ausente o inválido"),
+    )
+)]
+pub async fn batch_delete_files(
+    State(app_state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    headers: HeaderMap,
+    JsonBody(body): JsonBody<BatchDeleteRequest>,
+) -> Result<(StatusCode, Json<BatchDeleteResponse>), ApplicationError> {
+    if !FileController::has_vk_secret(&app_state, &headers) {
+        return Err(ApplicationError::Unauthorized);
+    }
+    if body.file_ids.is_empty() || body.file_ids.len() > MAX_BATCH_DELETE {
+        return Err(ApplicationError::BadRequest(format!(
+            "Between 1 and {} fileIds per request",
+            MAX_BATCH_DELETE
+        )));
+    }
+
+    let mut file_ids = body.file_ids;
+    file_ids.sort();
+    file_ids.dedup();
+
+    let mut results = BatchResult::default();
+    // Deltas de cuota por usuario (archivos, bytes), aplicados en una sola actualización
+    let mut quota_deltas: HashMap<Uuid, (u64, u64)> = HashMap::new();
+
+    // En serie: las copias deduplicadas del lote liberan el objeto al borrar la última
+    for file_id in file_ids {
+        let result = async {
+            FileController::validate_file_id(&file_id)?;
+            let metadata = app_state.metadata_repository.get_metadata(&file_id).await?;
+            FileController::delete_permanently(&app_state, &metadata, client_ip).await?;
+            Ok::<_, ApplicationError>(metadata)
+        }
+        .await;
+
+        match result {
+            Ok(metadata) => {
+                FileController::add_quota_delta(&mut quota_deltas, &metadata);
+                results.push_ok(file_id, StatusCode::NO_CONTENT, None);
+            }
+            Err(e) => {
+                warn!("Batch delete failed for file {}: {:?}", file_id, e);
+                results.push_err(file_id, e);
+            }
+        }
+    }
+
+    for (uid, (file_delta, size_delta)) in quota_deltas {
+        if let Err(e) = FileController::release_quota(&app_state, uid, file_delta, size_delta).await
+        {
+            error!("Error updating quota for user {}: {:?}", uid, e);
+        }
+    }
+
+    Ok((results.status_code(), Json(results.into())))
+}
+
+/// Saca un archivo de la papelera antes de que la limpieza lo purgue
+/// POST /api/v1/files/{file_id}/restore
+#[utoipa::path(
+    post,
+    path = "/api/v1/files/{file_id}/restore",
+    tag = "Files",
+    params(
+        ("file_id" = String, Path),
+        ("Authorization" = Option<String>, Header, description = "Bearer <token de usuario>"),
+        ("X-VK-Secret" = Option<String>, Header, description = "Permite restaurar cualquier archivo"),
+    ),
+    responses(
+        (status = 200, body = FileResponse),
+        (status = 401, description = "Token de usuario inválido o expirado"),
+        (status = 403, description = "No pertenece al usuario del token, o falta token sin X-VK-Secret"),
+        (status = 404, description = "Archivo no encontrado"),
+        (status = 409, description = "El archivo no está en la papelera"),
+    )
+)]
+pub async fn restore_file(
+    State(app_state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    caller: FileCaller,
+    Path(file_id): Path<String>,
+) -> Result<Json<FileResponse>, ApplicationError> {
+    FileController::validate_file_id(&file_id)?;
+    let metadata = app_state.metadata_repository.get_metadata(&file_id).await?;
+    caller.authorize(&metadata)?;
+
+    if !metadata.is_trashed() {
+        return Err(ApplicationError::Conflict(format!(
+            "File {} is not in the trash",
+            file_id
+        )));
+    }
+
+    let restored = app_state
+        .metadata_repository
+        .restore_metadata(&file_id)
+        .await?;
+
+    app_state.event_queue.publish(
+        AuditEvent::new(
+            AuditAction::Restore,
+            file_id,
+            restored.user_id.clone(),
+            app_state.server_id.clone(),
+        )
+        .with_client_ip(client_ip),
+    );
+
+    Ok(Json(FileResponse::from(restored)))
+}
+
+/// Pasa un archivo permanente a otro usuario, moviendo su tamaño entre ambas cuotas
+/// POST /api/v1/files/{file_id}/transfer
+#[utoipa::path(
+    post,
+    path = "/api/v1/files/{file_id}/transfer",
+    tag = "Files",
+    params(
+        ("file_id" = String, Path),
+        ("X-VK-Secret" = String, Header, description = "Secreto de la instancia"),
+    ),
+    request_body = TransferFileRequest,
+    responses(
+        (status = 200, body = FileResponse),
+        (status = 400, description = "Id inválido, archivo temporal sin dueño, ya pertenece a toUserId, o mimeType no permitido por el plan del destino"),
+        (status = 401, description = "X-VK-Secret ausente o inválido"),
+        (status = 403, description = "El destino alcanzó el máximo de archivos de su plan"),
+        (status = 404, description = "Archivo no encontrado o en la papelera, o usuario destino no encontrado"),
+        (status = 507, description = "El archivo no cabe en la cuota del usuario destino"),
+    )
+)]
+pub async fn transfer_file(
+    State(app_state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    headers: HeaderMap,
+    Path(file_id): Path<String>,
+    JsonBody(body): JsonBody<TransferFileRequest>,
+) -> Result<Json<FileResponse>, ApplicationError> {
+    if !FileController::has_vk_secret(&app_state, &headers) {
+        return Err(ApplicationError::Unauthorized);
+    }
+    let metadata =
+        FileController::transfer_source(app_state.metadata_repository.as_ref(), &file_id).await?;
+
+    // El destinatario recibe el archivo como si lo subiera: cuota y límites de su plan
+    let user_dto = UserDTO::for_query(body.to_user_id);
+    let target = app_state.user_repository.get_user(user_dto).await?;
+    FileController::check_owner_limits(
+        &target,
+        &app_state.global_config.lock().unwrap().tiers,
+        &metadata.mime_type,
+        metadata.size,
+    )?;
+
+    let transferred = app_state
+        .metadata_repository
+        .transfer_metadata(&file_id, body.to_user_id)
+        .await?;
+    info!(
+        "File {} transferred from user {:?} to user {}",
+        file_id, metadata.user_id, body.to_user_id
+    );
+
+    app_state.event_queue.publish(
+        AuditEvent::new(
+            AuditAction::Transfer,
+            file_id,
+            transferred.user_id.clone(),
+            app_state.server_id.clone(),
+        )
+        .with_client_ip(client_ip),
+    );
+
+    Ok(Json(FileResponse::from(transferred)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_file_id_rejects_path_traversal() {
+        for file_id in [
+            "",
+            "..",
+            "../etc/passwd",
+            "a/../b",
+            "/etc/passwd",
+            "a\\b",
+            "a\u{0}b",
+            "a\nb",
+        ] {
+            assert!(
+                matches!(
+                    FileController::validate_file_id(file_id),
+                    Err(ApplicationError::BadRequest(_))
+                ),
+                "{:?} should be rejected",
+                file_id
+            );
+        }
+    }
+
+    #[test]
+    fn validate_file_id_accepts_provider_ids() {
+        for file_id in [
+            "1a2b3c4d5e6f7890",
+            "1AbC-dEf_gHi",
+            "2025/12/report.pdf",
+            "file.name.pdf",
+        ] {
+            assert!(
+                FileController::validate_file_id(file_id).is_ok(),
+                "{:?} should be accepted",
+                file_id
+            );
+        }
+    }
+
+    const EMPTY_SHA256: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    #[test]
+    fn check_checksum_accepts_matching_or_missing_digest() {
+        assert!(FileController::check_checksum(Some(EMPTY_SHA256), EMPTY_SHA256).is_ok());
+        assert!(FileController::check_checksum(None, EMPTY_SHA256).is_ok());
+    }
+
+    #[test]
+    fn check_checksum_rejects_a_different_digest() {
+        let other = "0".repeat(64);
+        assert!(matches!(
+            FileController::check_checksum(Some(&other), EMPTY_SHA256),
+            Err(ApplicationError::ChecksumMismatch { computed }) if computed == EMPTY_SHA256
+        ));
+    }
+
+    #[test]
+    fn expected_sha256_normalizes_and_validates_the_header() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(FileController::expected_sha256(&headers).unwrap(), None);
+
+        headers.insert(
+            "X-Content-SHA256",
+            format!(" {} ", EMPTY_SHA256.to_ascii_uppercase())
+                .parse()
+                .unwrap(),
+        );
+        assert_eq!(
+            FileController::expected_sha256(&headers)
+                .unwrap()
+                .as_deref(),
+            Some(EMPTY_SHA256)
+        );
+
+        headers.insert("X-Content-SHA256", "not-a-digest".parse().unwrap());
+        assert!(FileController::expected_sha256(&headers).is_err());
+    }
+
+    struct StubScanner(ScanVerdict);
+
+    #[async_trait::async_trait]
+    impl FileScanner for StubScanner {
+        async fn scan(&self, _bytes: &[u8]) -> Result<ScanVerdict, ApplicationError> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn infected_uploads_are_rejected_with_422() {
+        let scanner = StubScanner(ScanVerdict::Infected("Eicar-Test-Signature".to_string()));
+        let result = FileController::scan_upload(Some(&scanner), "eicar.txt", b"X5O!P%@AP").await;
+
+        match result {
+            Err(ApplicationError::UnprocessableEntity(message)) => {
+                assert!(message.contains("Eicar-Test-Signature"));
+            }
+            other => panic!("expected UnprocessableEntity, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn clean_or_unscanned_uploads_pass() {
+        let scanner = StubScanner(ScanVerdict::Clean);
+        assert!(
+            FileController::scan_upload(Some(&scanner), "a.txt", b"hello")
+                .await
+                .is_ok()
+        );
+        assert!(FileController::scan_upload(None, "a.txt", b"hello")
+            .await
+            .is_ok());
+    }
+
+    #[test]
+    fn content_length_uses_the_provider_length_when_it_matches() {
+        assert_eq!(FileController::content_length("file", Some(5), 5), Some(5));
+    }
+
+    #[test]
+    fn content_length_is_omitted_when_unknown_or_different() {
+        assert_eq!(FileController::content_length("file", None, 5), None);
+        assert_eq!(FileController::content_length("file", Some(4), 5), None);
+    }
+
+    mod live_metadata {
+        use super::*;
+        use crate::adapters::repositories::in_memory_metadata_repository::{
+            metadata, InMemoryMetadataRepository,
+        };
+
+        fn repository() -> InMemoryMetadataRepository {
+            let mut trashed = metadata("trashed", Uuid::new_v4(), "old.txt");
+            trashed.trashed_at = Some(Utc::now());
+            InMemoryMetadataRepository::new(vec![
+                metadata("live", Uuid::new_v4(), "notes.txt"),
+                trashed,
+            ])
+        }
+
+        #[tokio::test]
+        async fn returns_files_outside_the_trash() {
+            let file = FileController::live_metadata(&repository(), "live")
+                .await
+                .unwrap();
+            assert_eq!(file.file_id, "live");
+        }
+
+        #[tokio::test]
+        async fn trashed_and_missing_files_are_not_found() {
+            for file_id in ["trashed", "missing"] {
+                assert!(matches!(
+                    FileController::live_metadata(&repository(), file_id).await,
+                    Err(ApplicationError::NotFound)
+                ));
+            }
+        }
+
+        #[tokio::test]
+        async fn malformed_ids_are_rejected_before_the_lookup() {
+            assert!(matches!(
+                FileController::live_metadata(&repository(), "../live").await,
+                Err(ApplicationError::BadRequest(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn require_user_id_rejects_anonymous_temporal_uploads() {
+        let anonymous = FileController::check_upload_owner(FileKind::Temporal, None, None, true);
+        assert!(
+            matches!(anonymous, Err(ApplicationError::BadRequest(message)) if message.contains("anonymous uploads are disabled"))
+        );
+
+        assert!(FileController::check_upload_owner(FileKind::Temporal, None, None, false).is_ok());
+    }
+
+    #[test]
+    fn require_user_id_accepts_uploads_matching_the_token() {
+        let user_id = Uuid::new_v4().to_string();
+        for file_type in [FileKind::Temporal, FileKind::Permanent] {
+            assert!(FileController::check_upload_owner(
+                file_type,
+                Some(&user_id),
+                Some(&user_id),
+                true
+            )
+            .is_ok());
+        }
+        assert!(matches!(
+            FileController::check_upload_owner(FileKind::Temporal, Some(&user_id), None, true),
+            Err(ApplicationError::Unauthorized)
+        ));
+    }
+
+    mod quota_deltas {
+        use super::*;
+        use crate::adapters::repositories::in_memory_metadata_repository::metadata;
+
+        fn file(owner: Uuid, size: u64) -> Metadata {
+            let mut file = metadata("file", owner, "file.txt");
+            file.size = size;
+            file
+        }
+
+        #[test]
+        fn deltas_are_summed_per_owner() {
+            let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+            let mut deltas = HashMap::new();
+            for file in [file(alice, 10), file(bob, 5), file(alice, 32)] {
+                FileController::add_quota_delta(&mut deltas, &file);
+            }
+
+            assert_eq!(deltas.len(), 2);
+            assert_eq!(deltas[&alice], (2, 42));
+            assert_eq!(deltas[&bob], (1, 5));
+        }
+
+        #[test]
+        fn files_without_a_valid_owner_are_skipped() {
+            let mut temporal = file(Uuid::new_v4(), 10);
+            temporal.user_id = None;
+            let mut malformed = file(Uuid::new_v4(), 10);
+            malformed.user_id = Some("not-a-uuid".to_string());
+
+            let mut deltas = HashMap::new();
+            FileController::add_quota_delta(&mut deltas, &temporal);
+            FileController::add_quota_delta(&mut deltas, &malformed);
+            assert!(deltas.is_empty());
+        }
+    }
+
+    mod transfer {
+        use super::*;
+        use crate::adapters::repositories::in_memory_metadata_repository::{
+            metadata, InMemoryMetadataRepository,
+        };
+
+        fn target(used_space: u64, file_count: u64, tier: Option<&str>) -> User {
+            User {
+                uid: Uuid::new_v4(),
+                file_count,
+                total_space: 100,
+                used_space,
+                tier: tier.map(str::to_string),
+            }
+        }
+
+        fn tiers() -> HashMap<String, UserTier> {
+            HashMap::from([(
+                "basic".to_string(),
+                UserTier {
+                    quota: 100,
+                    max_files: Some(2),
+                    allowed_mime_types: Some(vec!["text/plain".to_string()]),
+                },
+            )])
+        }
+
+        #[tokio::test]
+        async fn trashed_sources_are_not_found() {
+            let mut trashed = metadata("trashed", Uuid::new_v4(), "old.txt");
+            trashed.trashed_at = Some(Utc::now());
+            let repo = InMemoryMetadataRepository::new(vec![
+                metadata("live", Uuid::new_v4(), "notes.txt"),
+                trashed,
+            ]);
+
+            assert!(matches!(
+                FileController::transfer_source(&repo, "trashed").await,
+                Err(ApplicationError::NotFound)
+            ));
+            let source = FileController::transfer_source(&repo, "live")
+                .await
+                .unwrap();
+            assert_eq!(source.file_id, "live");
+        }
+
+        #[tokio::test]
+        async fn files_without_an_owner_cannot_be_transferred() {
+            let mut temporal = metadata("temporal", Uuid::new_v4(), "tmp.txt");
+            temporal.user_id = None;
+            let repo = InMemoryMetadataRepository::new(vec![temporal]);
+
+            assert!(matches!(
+                FileController::transfer_source(&repo, "temporal").await,
+                Err(ApplicationError::BadRequest(_))
+            ));
+        }
+
+        #[test]
+        fn target_quota_is_enforced() {
+            let tiers = tiers();
+            assert!(
+                FileController::check_owner_limits(&target(60, 0, None), &tiers, "a/b", 40).is_ok()
+            );
+            assert!(matches!(
+                FileController::check_owner_limits(&target(61, 0, None), &tiers, "a/b", 40),
+                Err(ApplicationError::InsufficientStorage)
+            ));
+        }
+
+        #[test]
+        fn target_tier_limits_are_enforced() {
+            let tiers = tiers();
+            let basic = target(0, 1, Some("basic"));
+            assert!(FileController::check_owner_limits(&basic, &tiers, "text/plain", 1).is_ok());
+            assert!(matches!(
+                FileController::check_owner_limits(&basic, &tiers, "image/png", 1),
+                Err(ApplicationError::BadRequest(_))
+            ));
+            assert!(matches!(
+                FileController::check_owner_limits(
+                    &target(0, 2, Some("basic")),
+                    &tiers,
+                    "text/plain",
+                    1
+                ),
+                Err(ApplicationError::Forbidden(_))
+            ));
+            // Un plan que ya no existe en la configuración no limita
+            let removed = target(0, 5, Some("gone"));
+            assert!(FileController::check_owner_limits(&removed, &tiers, "image/png", 1).is_ok());
+        }
+    }
+
+    mod by_name {
+        use super::*;
+        use crate::adapters::repositories::in_memory_metadata_repository::{
+            metadata, InMemoryMetadataRepository,
+        };
+
+        fn repository(owner: Uuid) -> InMemoryMetadataRepository {
+            let mut older = metadata("older", owner, "report.pdf");
+            older.uploaded_at -= Duration::days(1);
+            InMemoryMetadataRepository::new(vec![
+                older,
+                metadata("newer", owner, "report.pdf"),
+                metadata("notes", owner, "notes.txt"),
+                metadata("other", Uuid::new_v4(), "photo.png"),
+            ])
+        }
+
+        #[tokio::test]
+        async fn resolves_the_single_match() {
+            let owner = Uuid::new_v4();
+            let repo = repository(owner);
+
+            for allow_duplicates in [false, true] {
+                let file =
+                    FileController::resolve_by_name(&repo, owner, "notes.txt", allow_duplicates)
+                        .await
+                        .unwrap();
+                assert_eq!(file.file_id, "notes");
+            }
+        }
+
+        #[tokio::test]
+        async fn missing_or_foreign_names_are_not_found() {
+            let owner = Uuid::new_v4();
+            let repo = repository(owner);
+
+            for filename in ["missing.txt", "photo.png"] {
+                assert!(matches!(
+                    FileController::resolve_by_name(&repo, owner, filename, false).await,
+                    Err(ApplicationError::NotFound)
+                ));
+            }
+        }
+
+        #[tokio::test]
+        async fn duplicates_resolve_to_the_newest_unless_allowed() {
+            let owner = Uuid::new_v4();
+            let repo = repository(owner);
+
+            let file = FileController::resolve_by_name(&repo, owner, "report.pdf", false)
+                .await
+                .unwrap();
+            assert_eq!(file.file_id, "newer");
+
+            assert!(matches!(
+                FileController::resolve_by_name(&repo, owner, "report.pdf", true).await,
+                Err(ApplicationError::Conflict(_))
+            ));
+        }
+    }
+
+    mod chunked {
+        use axum::response::IntoResponse;
+
+        use super::*;
+        use crate::adapters::{test_app::TestApp, upload_buffer::UploadBufferBudget};
+
+        pub(super) async fn init(app: &TestApp) -> ChunkedUploadSessionResponse {
+            let token = app.upload_token(None).await;
+            let (status, Json(session)) = init_chunked_upload(
+                State(app.state.clone()),
+                TestApp::headers(&[("Authorization", &format!("Bearer {}", token))]),
+                JsonBody(InitChunkedUploadRequest {
+                    filename: "notes.txt".to_string(),
+                    mime_type: "text/plain".to_string(),
+                    file_type: "temporal".to_string(),
+                    user_id: None,
+                    description: None,
+                    size: Some(6),
+                    location: None,
+                }),
+            )
+            .await
+            .unwrap();
+            assert_eq!(status, StatusCode::CREATED);
+            session
+        }
+
+        async fn append(
+            app: &TestApp,
+            session_id: &str,
+            secret: &str,
+            chunk: &[u8],
+        ) -> Result<u64, ApplicationError> {
+            let Json(session) = append_chunk(
+                State(app.state.clone()),
+                FileCaller::user(None),
+                Path(session_id.to_string()),
+                TestApp::headers(&[("X-Upload-Session-Secret", secret)]),
+                Body::from(chunk.to_vec()),
+            )
+            .await?;
+            assert!(session.session_secret.is_none());
+            Ok(session.received_bytes)
+        }
+
+        /// Sube `content` como temporal anónimo a través de una sesión fragmentada
+        pub(super) async fn upload(
+            app: &TestApp,
+            content: &[u8],
+        ) -> Result<UploadFileResponse, ApplicationError> {
+            let session = init(app).await;
+            let secret = session.session_secret.unwrap();
+            for chunk in content.chunks(session.chunk_size as usize) {
+                append(app, &session.session_id, &secret, chunk).await?;
+            }
+            let (_, Json(file)) = complete_chunked_upload(
+                State(app.state.clone()),
+                TestApp::client_ip(),
+                FileCaller::user(None),
+                Path(session.session_id),
+                TestApp::headers(&[("X-Upload-Session-Secret", &secret)]),
+            )
+            .await?;
+            Ok(file)
+        }
+
+        #[tokio::test]
+        async fn anonymous_sessions_complete_with_their_secret() {
+            let app = TestApp::new();
+            let session = init(&app).await;
+            let secret = session.session_secret.expect("init returns the secret");
+
+            assert_eq!(
+                append(&app, &session.session_id, &secret, b"abcd")
+                    .await
+                    .unwrap(),
+                4
+            );
+            assert_eq!(
+                append(&app, &session.session_id, &secret, b"ef")
+                    .await
+                    .unwrap(),
+                6
+            );
+
+            let (status, Json(file)) = complete_chunked_upload(
+                State(app.state.clone()),
+                TestApp::client_ip(),
+                FileCaller::user(None),
+                Path(session.session_id.clone()),
+                TestApp::headers(&[("X-Upload-Session-Secret", &secret)]),
+            )
+            .await
+            .unwrap();
+
+            assert_eq!(status, StatusCode::CREATED);
+            assert_eq!(file.size, 6);
+            assert!(file.delete_at.is_some());
+            assert!(app.metadata.contains(&file.file_id));
+            assert!(!app.sessions.contains(&session.session_id));
+            assert_eq!(app.storage.object_count(), 1);
+        }
+
+        #[tokio::test]
+        async fn completion_is_rejected_while_the_upload_buffer_budget_is_exhausted() {
+            let mut app = TestApp::new();
+            app.state.upload_buffer_budget = UploadBufferBudget::new(8);
+            let session = init(&app).await;
+            let secret = session.session_secret.unwrap();
+            append(&app, &session.session_id, &secret, b"abcd")
+                .await
+                .unwrap();
+            append(&app, &session.session_id, &secret, b"ef")
+                .await
+                .unwrap();
+            let complete = || {
+                complete_chunked_upload(
+                    State(app.state.clone()),
+                    TestApp::client_ip(),
+                    FileCaller::user(None),
+                    Path(session.session_id.clone()),
+                    TestApp::headers(&[("X-Upload-Session-Secret", &secret)]),
+                )
+            };
+
+            let mut other_upload = app.state.upload_buffer_budget.reservation();
+            other_upload.grow(4).unwrap();
+            let error = complete().await.unwrap_err();
+            assert!(matches!(error, ApplicationError::ServiceUnavailable(_)));
+            assert_eq!(
+                error.into_response().status(),
+                StatusCode::SERVICE_UNAVAILABLE
+            );
+            // La sesión sigue abierta para reintentar
+            assert!(app.sessions.contains(&session.session_id));
+
+            drop(other_upload);
+            let (status, _) = complete().await.unwrap();
+            assert_eq!(status, StatusCode::CREATED);
+        }
+
+        #[tokio::test]
+        async fn checksum_mismatches_are_reported_to_the_client() {
+            let app = TestApp::new();
+            let session = init(&app).await;
+            let secret = session.session_secret.unwrap();
+            append(&app, &session.session_id, &secret, b"abcd")
+                .await
+                .unwrap();
+
+            let error = complete_chunked_upload(
+                State(app.state.clone()),
+                TestApp::client_ip(),
+                FileCaller::user(None),
+                Path(session.session_id.clone()),
+                TestApp::headers(&[
+                    ("X-Upload-Session-Secret", &secret),
+                    ("X-Content-SHA256", &"0".repeat(64)),
+                ]),
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(app.storage.object_count(), 0);
+
+            let response = error.into_response();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body["code"], "CHECKSUM_MISMATCH");
+            assert_eq!(
+                body["error"],
+                format!(
+                    "Checksum mismatch: received content hashes to {:x}",
+                    Sha256::digest(b"abcd")
+                )
+            );
+        }
+
+        #[tokio::test]
+        async fn anonymous_sessions_reject_a_missing_or_wrong_secret() {
+            let app = TestApp::new();
+            let session = init(&app).await;
+
+            assert!(matches!(
+                append(&app, &session.session_id, "wrong", b"abcd").await,
+                Err(ApplicationError::Forbidden(_))
+            ));
+            assert!(matches!(
+                complete_chunked_upload(
+                    State(app.state.clone()),
+                    TestApp::client_ip(),
+                    FileCaller::user(None),
+                    Path(session.session_id.clone()),
+                    HeaderMap::new(),
+                )
+                .await,
+                Err(ApplicationError::Forbidden(_))
+            ));
+            // Un intento rechazado no cierra la sesión
+            assert!(app.sessions.contains(&session.session_id));
+        }
+
+        #[tokio::test]
+        async fn anonymous_sessions_are_canceled_with_their_secret() {
+            let app = TestApp::new();
+            let session = init(&app).await;
+            let secret = session.session_secret.unwrap();
+            let cancel = |secret: &str| {
+                cancel_chunked_upload(
+                    State(app.state.clone()),
+                    FileCaller::user(None),
+                    Path(session.session_id.clone()),
+                    TestApp::headers(&[("X-Upload-Session-Secret", secret)]),
+                )
+            };
+
+            assert!(matches!(
+                cancel("wrong").await,
+                Err(ApplicationError::Forbidden(_))
+            ));
+            assert!(app.sessions.contains(&session.session_id));
+
+            assert_eq!(cancel(&secret).await.unwrap(), StatusCode::NO_CONTENT);
+            assert!(!app.sessions.contains(&session.session_id));
+        }
+    }
+
+    mod signed_urls_batch {
+        use super::*;
+        use crate::{adapters::test_app::TestApp, services::InMemoryStorageService};
+
+        #[tokio::test]
+        async fn missing_files_fail_on_their_own() {
+            let app = TestApp::with_storage(InMemoryStorageService::new("s3", true));
+            let owner = Uuid::new_v4();
+            app.file("present", owner, b"hello");
+
+            let (status, Json(response)) = signed_urls_batch(
+                State(app.state.clone()),
+                FileCaller::user(Some(owner)),
+                JsonBody(SignedUrlsBatchRequest {
+                    file_ids: vec!["present".to_string(), "missing".to_string()],
+                    expires_in: Some(60),
+                }),
+            )
+            .await
+            .unwrap();
+
+            assert_eq!(status, StatusCode::MULTI_STATUS);
+            assert_eq!(
+                (response.results.succeeded, response.results.failed),
+                (1, 1)
+            );
+            let missing = &response.results.items[0];
+            assert_eq!((missing.id.as_str(), missing.status), ("missing", 404));
+            let present = &response.results.items[1];
+            assert_eq!((present.id.as_str(), present.status), ("present", 200));
+            assert_eq!(
+                present.data.as_deref(),
+                Some("https://signed.example/present?expires=60")
+            );
+        }
+
+        #[tokio::test]
+        async fn provider_calls_stay_within_the_concurrency_limit() {
+            let storage = InMemoryStorageService::new("s3", true)
+                .with_latency(std::time::Duration::from_millis(20));
+            let app = TestApp::with_storage(storage);
+            let owner = Uuid::new_v4();
+            let file_ids: Vec<String> = (0..20).map(|i| format!("file-{:02}", i)).collect();
+            for file_id in &file_ids {
+                app.file(file_id, owner, b"hello");
+            }
+
+            let (status, Json(response)) = signed_urls_batch(
+                State(app.state.clone()),
+                FileCaller::user(Some(owner)),
+                JsonBody(SignedUrlsBatchRequest {
+                    file_ids,
+                    expires_in: Some(60),
+                }),
+            )
+            .await
+            .unwrap();
+
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(response.results.succeeded, 20);
+            assert_eq!(
+                app.storage.peak_in_flight(),
+                app.state.provider_request_concurrency
+            );
+        }
+    }
+
+    mod batch_delete {
+        use super::*;
+        use crate::adapters::test_app::TestApp;
+
+        #[tokio::test]
+        async fn missing_files_are_listed_as_failed() {
+            let app = TestApp::new();
+            app.file("present", Uuid::new_v4(), b"hello");
+
+            let (status, Json(response)) = batch_delete_files(
+                State(app.state.clone()),
+                TestApp::client_ip(),
+                TestApp::admin_headers(),
+                JsonBody(BatchDeleteRequest {
+                    file_ids: vec!["present".to_string(), "missing".to_string()],
+                }),
+            )
+            .await
+            .unwrap();
+
+            assert_eq!(status, StatusCode::MULTI_STATUS);
+            assert_eq!(response.deleted, vec!["present".to_string()]);
+            assert_eq!(response.failed.len(), 1);
+            assert_eq!(response.failed[0].file_id, "missing");
+            assert_eq!(response.failed[0].error, "NotFound");
+            assert!(!app.metadata.contains("present"));
+            assert!(!app.storage.contains("present"));
+
+            let body = serde_json::to_value(&response).unwrap();
+            assert_eq!(
+                body,
+                serde_json::json!({
+                    "deleted": ["present"],
+                    "failed": [{ "fileId": "missing", "error": "NotFound" }],
+                })
+            );
+        }
+    }
+
+    mod get_file_metadata {
+        use axum::response::IntoResponse;
+
+        use super::*;
+        use crate::adapters::test_app::TestApp;
+
+        async fn get(app: &TestApp, file_id: &str, headers: HeaderMap) -> Response {
+            get_file_metadata(State(app.state.clone()), Path(file_id.to_string()), headers)
+                .await
+                .unwrap()
+        }
+
+        #[tokio::test]
+        async fn matching_if_none_match_is_not_modified() {
+            let app = TestApp::new();
+            app.file("present", Uuid::new_v4(), b"hello");
+
+            let first = get(&app, "present", HeaderMap::new()).await;
+            assert_eq!(first.status(), StatusCode::OK);
+            let etag = first.headers()[header::ETAG].to_str().unwrap().to_string();
+
+            let cached = get(
+                &app,
+                "present",
+                TestApp::headers(&[("If-None-Match", &etag)]),
+            )
+            .await;
+            assert_eq!(cached.status(), StatusCode::NOT_MODIFIED);
+            assert_eq!(cached.headers()[header::ETAG], etag.as_str());
+            let body = axum::body::to_bytes(cached.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            assert!(body.is_empty());
+
+            let stale = get(
+                &app,
+                "present",
+                TestApp::headers(&[("If-None-Match", "W/\"stale\"")]),
+            )
+            .await;
+            assert_eq!(stale.status(), StatusCode::OK);
+        }
+
+        #[tokio::test]
+        async fn missing_files_are_not_found() {
+            let app = TestApp::new();
+
+            let result = get_file_metadata(
+                State(app.state.clone()),
+                Path("missing".to_string()),
+                HeaderMap::new(),
+            )
+            .await;
+            assert!(matches!(result, Err(ApplicationError::NotFound)));
+            assert_eq!(
+                result.unwrap_err().into_response().status(),
+                StatusCode::NOT_FOUND
+            );
+        }
+    }
+
+    mod deduplication {
+        use super::*;
+        use crate::adapters::{
+            repositories::in_memory_metadata_repository::InMemoryMetadataRepository,
+            test_app::TestApp,
+        };
+
+        async fn delete(app: &TestApp, file_id: &str) {
+            let status = delete_file(
+                State(app.state.clone()),
+                TestApp::client_ip(),
+                FileCaller::admin(),
+                Path(file_id.to_string()),
+                Query(DeleteFileQuery { permanent: true }),
+            )
+            .await
+            .unwrap();
+            assert_eq!(status, StatusCode::NO_CONTENT);
+        }
+
+        #[tokio::test]
+        async fn the_object_outlives_every_copy_but_the_last() {
+            let app = TestApp::new();
+
+            let first = super::chunked::upload(&app, b"same bytes").await.unwrap();
+            let second = super::chunked::upload(&app, b"same bytes").await.unwrap();
+            assert!(!first.deduplicated);
+            assert!(second.deduplicated);
+            assert_eq!(first.checksum, second.checksum);
+            assert_eq!(app.storage.object_count(), 1);
+            assert!(app.storage.contains(&first.file_id));
+
+            delete(&app, &first.file_id).await;
+            assert!(!app.metadata.contains(&first.file_id));
+            assert!(app.storage.contains(&first.file_id));
+
+            delete(&app, &second.file_id).await;
+            assert!(!app.metadata.contains(&second.file_id));
+            assert_eq!(app.storage.object_count(), 0);
+        }
+
+        #[tokio::test]
+        async fn different_content_is_not_deduplicated() {
+            let app = TestApp::new();
+
+            let first = super::chunked::upload(&app, b"first").await.unwrap();
+            let second = super::chunked::upload(&app, b"second").await.unwrap();
+            assert!(!second.deduplicated);
+            assert_ne!(first.checksum, second.checksum);
+            assert_eq!(app.storage.object_count(), 2);
+        }
+
+        #[tokio::test]
+        async fn a_trashed_copy_is_not_reused() {
+            let app = TestApp::new();
+
+            let first = super::chunked::upload(&app, b"same bytes").await.unwrap();
+            let status = delete_file(
+                State(app.state.clone()),
+                TestApp::client_ip(),
+                FileCaller::admin(),
+                Path(first.file_id.clone()),
+                Query(DeleteFileQuery { permanent: false }),
+            )
+            .await
+            .unwrap();
+            assert_eq!(status, StatusCode::NO_CONTENT);
+
+            let second = super::chunked::upload(&app, b"same bytes").await.unwrap();
+            assert!(!second.deduplicated);
+            assert_ne!(second.file_id, first.file_id);
+            assert_eq!(app.storage.object_count(), 2);
+        }
+
+        #[tokio::test]
+        async fn a_released_object_is_not_reused() {
+            let repo = InMemoryMetadataRepository::new(Vec::new());
+            let reused = MetadataDTO {
+                file_id: "copy".to_string(),
+                object_id: Some("released".to_string()),
+                ..Default::default()
+            };
+
+            assert!(matches!(
+                repo.create_metadata(reused).await,
+                Err(ApplicationError::Conflict(_))
+            ));
+        }
+    }
+
+    mod trash {
+        use axum::response::IntoResponse;
+
+        use super::*;
+        use crate::adapters::test_app::TestApp;
+
+        async fn trash(app: &TestApp, file_id: &str) {
+            let status = delete_file(
+                State(app.state.clone()),
+                TestApp::client_ip(),
+                FileCaller::admin(),
+                Path(file_id.to_string()),
+                Query(DeleteFileQuery { permanent: false }),
+            )
+            .await
+            .unwrap();
+            assert_eq!(status, StatusCode::NO_CONTENT);
+        }
+
+        async fn rename(app: &TestApp, file_id: &str) -> Result<FileResponse, ApplicationError> {
+            let Json(file) = update_file_metadata(
+                State(app.state.clone()),
+                FileCaller::admin(),
+                Path(file_id.to_string()),
+                JsonBody(UpdateFileRequest {
+                    description: None,
+                    file_name: Some("renamed.txt".to_string()),
+                    delete_at: None,
+                }),
+            )
+            .await?;
+            Ok(file)
+        }
+
+        async fn lookup(app: &TestApp, file_id: &str) -> StatusCode {
+            match get_file_metadata(
+                State(app.state.clone()),
+                Path(file_id.to_string()),
+                HeaderMap::new(),
+            )
+            .await
+            {
+                Ok(response) => response.status(),
+                Err(e) => e.into_response().status(),
+            }
+        }
+
+        #[tokio::test]
+        async fn trashed_files_cannot_be_updated() {
+            let app = TestApp::new();
+            app.file("notes", Uuid::new_v4(), b"hello");
+            trash(&app, "notes").await;
+
+            assert!(matches!(
+                rename(&app, "notes").await,
+                Err(ApplicationError::NotFound)
+            ));
+            assert!(matches!(
+                rename(&app, "../notes").await,
+                Err(ApplicationError::BadRequest(_))
+            ));
+            assert_eq!(app.metadata.file("notes").file_name, "notes.txt");
+        }
+
+        #[tokio::test]
+        async fn restored_files_are_live_again() {
+            let app = TestApp::new();
+            app.file("notes", Uuid::new_v4(), b"hello");
+            trash(&app, "notes").await;
+            assert_eq!(lookup(&app, "notes").await, StatusCode::NOT_FOUND);
+
+            let Json(restored) = restore_file(
+                State(app.state.clone()),
+                TestApp::client_ip(),
+                FileCaller::admin(),
+                Path("notes".to_string()),
+            )
+            .await
+            .unwrap();
+            assert!(restored.trashed_at.is_none());
+
+            assert_eq!(lookup(&app, "notes").await, StatusCode::OK);
+            assert_eq!(
+                rename(&app, "notes").await.unwrap().file_name,
+                "renamed.txt"
+            );
+            assert!(app.storage.contains("notes"));
+        }
+
+        #[tokio::test]
+        async fn trashed_files_are_purged_after_the_retention() {
+            let app = TestApp::new();
+            app.file("notes", Uuid::new_v4(), b"hello");
+            trash(&app, "notes").await;
+
+            app.state.global_config.lock().unwrap().trash_retention = 3600;
+            let report = FileController::run_cleanup(&app.state).await.unwrap();
+            assert_eq!(report.deleted_count, 0);
+            assert!(app.metadata.contains("notes"));
+            assert!(app.storage.contains("notes"));
+
+            app.state.global_config.lock().unwrap().trash_retention = 0;
+            let report = FileController::run_cleanup(&app.state).await.unwrap();
+            assert_eq!(report.deleted_count, 1);
+            assert!(!app.metadata.contains("notes"));
+            assert!(!app.storage.contains("notes"));
+        }
+    }
+
+    mod file_links {
+        use super::*;
+        use crate::{adapters::test_app::TestApp, services::InMemoryStorageService};
+
+        async fn links(
+            app: &TestApp,
+            file_id: &str,
+        ) -> Result<FileLinksResponse, ApplicationError> {
+            get_file_links(State(app.state.clone()), Path(file_id.to_string()))
+                .await
+                .map(|Json(links)| links)
+        }
+
+        #[tokio::test]
+        async fn drive_files_expose_their_native_links() {
+            let app = TestApp::with_storage(InMemoryStorageService::new("gdrive", false));
+            app.file("drive-file", Uuid::new_v4(), b"hello");
+
+            let response = links(&app, "drive-file").await.unwrap();
+
+            assert_eq!(response.file_id, "drive-file");
+            assert_eq!(
+                response.web_view_link.as_deref(),
+                Some("https://drive.example/drive-file/view")
+            );
+            assert_eq!(
+                response.web_content_link.as_deref(),
+                Some("https://drive.example/drive-file/download")
+            );
+        }
+
+        #[tokio::test]
+        async fn other_providers_and_trashed_files_are_not_found() {
+            let app = TestApp::new();
+            app.file("bucket-file", Uuid::new_v4(), b"hello");
+            assert!(matches!(
+                links(&app, "bucket-file").await,
+                Err(ApplicationError::NotFound)
+            ));
+
+            let drive = TestApp::with_storage(InMemoryStorageService::new("gdrive", false));
+            drive.file("trashed", Uuid::new_v4(), b"hello");
+            drive.metadata.trash_metadata("trashed").await.unwrap();
+            assert!(matches!(
+                links(&drive, "trashed").await,
+                Err(ApplicationError::NotFound)
+            ));
+        }
+    }
+
+    mod upload_token {
+        use super::*;
+        use crate::{
+            adapters::test_app::TestApp,
+            application::repositories::token_repository::TokenRepository,
+        };
+
+        async fn token_body(app: &TestApp, user_id: Option<String>) -> serde_json::Value {
+            let (status, Json(response)) = generate_upload_token(
+                State(app.state.clone()),
+                JsonBody(GenerateTokenRequest { user_id }),
+            )
+            .await
+            .unwrap();
+            assert_eq!(status, StatusCode::CREATED);
+            serde_json::to_value(response).unwrap()
+        }
+
+        #[tokio::test]
+        async fn anonymous_tokens_echo_the_temporal_only_constraints() {
+            let app = TestApp::new();
+
+            let body = token_body(&app, None).await;
+
+            assert_eq!(body["expiresIn"], 300);
+            assert_eq!(
+                body["allowedMimeTypes"],
+                serde_json::json!(["text/plain", "image/png"])
+            );
+            assert_eq!(body["maxSize"], 1024);
+            assert_eq!(body["allowedType"], "temporal");
+            assert!(body.get("userId").is_none());
+        }
+
+        #[tokio::test]
+        async fn user_tokens_echo_the_user_and_allow_any_type() {
+            let app = TestApp::new();
+            let uid = app.user(1024).to_string();
+
+            let body = token_body(&app, Some(uid.clone())).await;
+
+            assert_eq!(body["userId"], uid);
+            assert!(body.get("allowedType").is_none());
+            assert_eq!(body["maxSize"], 1024);
+            assert_eq!(
+                app.tokens
+                    .verify_and_consume_token(body["token"].as_str().unwrap())
+                    .await
+                    .unwrap()
+                    .as_deref(),
+                Some(uid.as_str())
+            );
+        }
+    }
+
+    mod head_file {
+        use super::*;
+        use crate::adapters::{
+            repositories::in_memory_metadata_repository::metadata, test_app::TestApp,
+        };
+
+        async fn head(app: &TestApp, file_id: &str) -> Result<Response, ApplicationError> {
+            head_file(
+                State(app.state.clone()),
+                Path(file_id.to_string()),
+                Query(DownloadQuery { filename: None }),
+            )
+            .await
+        }
+
+        #[tokio::test]
+        async fn present_objects_report_their_headers() {
+            let app = TestApp::new();
+            app.file("present", Uuid::new_v4(), b"hello");
+
+            let response = head(&app, "present").await.unwrap();
+
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers()[header::CONTENT_LENGTH], "5");
+            assert_eq!(response.headers()[header::CONTENT_TYPE], "text/plain");
+        }
+
+        #[tokio::test]
+        async fn objects_missing_from_the_provider_are_not_found() {
+            let app = TestApp::new();
+            app.metadata
+                .insert(metadata("orphan", Uuid::new_v4(), "orphan.txt"));
+
+            assert!(matches!(
+                head(&app, "orphan").await,
+                Err(ApplicationError::NotFound)
+            ));
+            assert!(matches!(
+                head(&app, "missing").await,
+                Err(ApplicationError::NotFound)
+            ));
+        }
+    }
+
+    mod file_stats {
+        use super::*;
+        use crate::adapters::test_app::TestApp;
+
+        async fn stats(
+            app: &TestApp,
+            file_id: &str,
+        ) -> Result<serde_json::Value, ApplicationError> {
+            let Json(stats) =
+                get_file_stats(State(app.state.clone()), Path(file_id.to_string())).await?;
+            Ok(serde_json::to_value(stats).unwrap())
+        }
+
+        #[tokio::test]
+        async fn stats_are_lean_and_do_not_count_as_downloads() {
+            let app = TestApp::new();
+            app.file("present", Uuid::new_v4(), b"hello");
+
+            let first = stats(&app, "present").await.unwrap();
+            let second = stats(&app, "present").await.unwrap();
+
+            let keys: Vec<&str> = first
+                .as_object()
+                .unwrap()
+                .keys()
+                .map(String::as_str)
+                .collect();
+            assert_eq!(keys, vec!["downloadCount", "lastAccess", "size"]);
+            assert_eq!(first["size"], 5);
+            assert_eq!(first["downloadCount"], 0);
+            assert_eq!(second["downloadCount"], 0);
+            assert_eq!(app.metadata.file("present").download_count, 0);
+        }
+
+        #[tokio::test]
+        async fn trashed_or_missing_files_have_no_stats() {
+            let app = TestApp::new();
+            app.file("trashed", Uuid::new_v4(), b"hello");
+            app.metadata.trash_metadata("trashed").await.unwrap();
+
+            assert!(matches!(
+                stats(&app, "trashed").await,
+                Err(ApplicationError::NotFound)
+            ));
+            assert!(matches!(
+                stats(&app, "missing").await,
+                Err(ApplicationError::NotFound)
+            ));
+        }
+    }
+
+    mod download {
+        use super::*;
+        use crate::adapters::{
+            repositories::in_memory_metadata_repository::metadata, test_app::TestApp,
+        };
+
+        async fn download(app: &TestApp, file_id: &str) -> Result<Response, ApplicationError> {
+            download_as(app, file_id, None).await
+        }
+
+        async fn download_as(
+            app: &TestApp,
+            file_id: &str,
+            filename: Option<&str>,
+        ) -> Result<Response, ApplicationError> {
+            download_file(
+                State(app.state.clone()),
+                TestApp::client_ip(),
+                Path(file_id.to_string()),
+                Query(DownloadQuery {
+                    filename: filename.map(str::to_string),
+                }),
+            )
+            .await
+        }
+
+        #[tokio::test]
+        async fn each_download_increments_the_count() {
+            let app = TestApp::new();
+            let uploaded = app.file("present", Uuid::new_v4(), b"hello");
+
+            for _ in 0..3 {
+                let response = download(&app, "present").await.unwrap();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                assert_eq!(body.as_ref(), b"hello");
+            }
+
+            let stored = app.metadata.file("present");
+            assert_eq!(stored.download_count, 3);
+            assert!(stored.last_access >= uploaded.last_access);
+        }
+
+        #[tokio::test]
+        async fn failed_downloads_are_not_counted() {
+            let app = TestApp::new();
+            app.metadata
+                .insert(metadata("orphan", Uuid::new_v4(), "orphan.txt"));
+
+            assert!(matches!(
+                download(&app, "orphan").await,
+                Err(ApplicationError::NotFound)
+            ));
+            assert_eq!(app.metadata.file("orphan").download_count, 0);
+        }
+
+        #[tokio::test]
+        async fn filename_override_only_changes_the_content_disposition() {
+            let app = TestApp::new();
+            app.file("report", Uuid::new_v4(), b"hello");
+
+            let response = download_as(&app, "report", Some("Informe de año.txt"))
+                .await
+                .unwrap();
+
+            assert_eq!(
+                response.headers()[header::CONTENT_DISPOSITION],
+                "attachment; filename=\"Informe de a_o.txt\"; \
+                 filename*=UTF-8''Informe%20de%20a%C3%B1o.txt"
+            );
+            assert_eq!(app.metadata.file("report").file_name, "report.txt");
+        }
+
+        #[tokio::test]
+        async fn invalid_filename_overrides_are_rejected() {
+            let app = TestApp::new();
+            app.file("report", Uuid::new_v4(), b"hello");
+            let too_long = "a".repeat(MAX_FILENAME_OVERRIDE_LENGTH + 1);
+
+            for filename in [
+                "  ",
+                "../secret.txt",
+                "a\\b.txt",
+                "line\nbreak.txt",
+                &too_long,
+            ] {
+                assert!(
+                    matches!(
+                        download_as(&app, "report", Some(filename)).await,
+                        Err(ApplicationError::BadRequest(_))
+                    ),
+                    "{:?} should be rejected",
+                    filename
+                );
+            }
+            assert_eq!(app.metadata.file("report").download_count, 0);
+        }
+    }
+
+    mod upload {
+        use axum::{extract::FromRequest, response::IntoResponse};
+
+        use super::*;
+        use crate::{
+            adapters::test_app::TestApp,
+            application::repositories::{
+                token_repository::TokenRepository, user_repository::UserRepository,
+            },
+        };
+
+        #[tokio::test]
+        async fn non_multipart_body_gets_a_json_error_and_keeps_the_token() {
+            let app = TestApp::new();
+            let token = app.upload_token(None).await;
+            let request = axum::http::Request::post("/api/v1/files")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(r#"{"file": "hello"}"#))
+                .unwrap();
+            let multipart = Multipart::from_request(request, &()).await;
+            assert!(multipart.is_err());
+
+            let result = upload_file(
+                State(app.state.clone()),
+                TestApp::client_ip(),
+                TestApp::headers(&[("Authorization", &format!("Bearer {}", token))]),
+                multipart,
+            )
+            .await;
+
+            let response = result.unwrap_err().into_response();
+            assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+            assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body["code"], "UNSUPPORTED_MEDIA_TYPE");
+            assert!(body["error"]
+                .as_str()
+                .unwrap()
+                .contains("multipart/form-data"));
+            assert!(app.tokens.verify_and_consume_token(&token).await.is_ok());
+        }
+
+        async fn upload(
+            app: &TestApp,
+            headers: &[(&'static str, &str)],
+            fields: &[(&str, &str)],
+        ) -> Result<UploadFileResponse, ApplicationError> {
+            let token = app.upload_token(None).await;
+            upload_with_token(app, &token, headers, fields).await
+        }
+
+        async fn upload_with_token(
+            app: &TestApp,
+            token: &str,
+            headers: &[(&'static str, &str)],
+            fields: &[(&str, &str)],
+        ) -> Result<UploadFileResponse, ApplicationError> {
+            let authorization = format!("Bearer {}", token);
+            let mut headers = headers.to_vec();
+            headers.push(("Authorization", &authorization));
+
+            let mut body = String::new();
+            for (name, value) in fields {
+                body.push_str(&format!(
+                    "--BOUNDARY\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                    name, value
+                ));
+            }
+            body.push_str("--BOUNDARY--\r\n");
+            let request = axum::http::Request::post("/api/v1/files")
+                .header(
+                    header::CONTENT_TYPE,
+                    "multipart/form-data; boundary=BOUNDARY",
+                )
+                .body(Body::from(body))
+                .unwrap();
+            let multipart = Multipart::from_request(request, &()).await;
+
+            let (_, Json(response)) = upload_file(
+                State(app.state.clone()),
+                TestApp::client_ip(),
+                TestApp::headers(&headers),
+                multipart,
+            )
+            .await?;
+            Ok(response)
+        }
+
+        const TEXT_FILE: [(&str, &str); 4] = [
+            ("filename", "notes.txt"),
+            ("mime_type", "text/plain"),
+            ("type", "temporal"),
+            ("file", "hello"),
+        ];
+
+        #[tokio::test]
+        async fn client_id_is_persisted_and_exposed() {
+            let app = TestApp::new();
+
+            let uploaded = upload(&app, &[("X-Client-Id", "mobile-app")], &TEXT_FILE)
+                .await
+                .unwrap();
+
+            assert_eq!(
+                app.metadata.file(&uploaded.file_id).client_id.as_deref(),
+                Some("mobile-app")
+            );
+            let response = get_file_metadata(
+                State(app.state.clone()),
+                Path(uploaded.file_id),
+                HeaderMap::new(),
+            )
+            .await
+            .unwrap();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body["clientId"], "mobile-app");
+        }
+
+        #[tokio::test]
+        async fn client_id_form_field_is_used_without_the_header() {
+            let app = TestApp::new();
+            let mut fields = vec![("client_id", "web")];
+            fields.extend(TEXT_FILE);
+
+            let uploaded = upload(&app, &[], &fields).await.unwrap();
+
+            assert_eq!(
+                app.metadata.file(&uploaded.file_id).client_id.as_deref(),
+                Some("web")
+            );
+        }
+
+        #[tokio::test]
+        async fn client_ids_outside_the_allowlist_are_rejected() {
+            let mut app = TestApp::new();
+            app.state.allowed_client_ids = Arc::new(vec!["web".to_string()]);
+
+            for client_id in ["mobile-app", "bad id"] {
+                assert!(matches!(
+                    upload(&app, &[("X-Client-Id", client_id)], &TEXT_FILE).await,
+                    Err(ApplicationError::BadRequest(_))
+                ));
             }
+            assert_eq!(app.storage.object_count(), 0);
+        }
+
+        #[tokio::test]
+        async fn user_deleted_after_the_token_was_minted_is_not_found() {
+            let app = TestApp::new();
+            let uid = app.user(1024);
+            let token = app.upload_token(Some(uid)).await;
+            app.users
+                .delete_user(UserDTO::for_query(uid))
+                .await
+                .unwrap();
+            let uid = uid.to_string();
+            let mut fields = vec![("user_id", uid.as_str())];
+            fields.extend(TEXT_FILE);
+            fields[3] = ("type", "permanent");
+
+            let result = upload_with_token(&app, &token, &[], &fields).await;
+
+            assert!(matches!(result, Err(ApplicationError::NotFound)));
+            assert_eq!(app.storage.object_count(), 0);
+        }
+
+        #[tokio::test]
+        async fn uploads_are_rejected_up_front_while_storage_is_degraded() {
+            let mut app = TestApp::new();
+            app.state.storage_down_reject_secs = 30;
+            app.state.storage_service.health().record_probe(false);
+            let token = app.upload_token(None).await;
+
+            let result = upload_with_token(&app, &token, &[], &TEXT_FILE).await;
+
+            let response = result.unwrap_err().into_response();
+            assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+            let retry_after: u64 = response.headers()[header::RETRY_AFTER]
+                .to_str()
+                .unwrap()
+                .parse()
+                .unwrap();
+            assert!((1..=30).contains(&retry_after));
+            assert_eq!(app.storage.object_count(), 0);
+            assert!(app.tokens.verify_and_consume_token(&token).await.is_ok());
+        }
+
+        #[tokio::test]
+        async fn a_failed_provider_upload_rejects_the_next_one_without_a_health_check() {
+            let mut app = TestApp::new();
+            app.state.storage_down_reject_secs = 30;
+            app.storage.set_unavailable(true);
+            let first = app.upload_token(None).await;
+            let second = app.upload_token(None).await;
+
+            let result = upload_with_token(&app, &first, &[], &TEXT_FILE).await;
+            assert!(matches!(
+                result,
+                Err(ApplicationError::StorageUnavailable(_))
+            ));
+
+            let response = upload_with_token(&app, &second, &[], &TEXT_FILE)
+                .await
+                .unwrap_err()
+                .into_response();
+            assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+            let retry_after: u64 = response.headers()[header::RETRY_AFTER]
+                .to_str()
+                .unwrap()
+                .parse()
+                .unwrap();
+            assert!((1..=30).contains(&retry_after));
+            assert!(app.tokens.verify_and_consume_token(&second).await.is_ok());
+        }
+
+        #[tokio::test]
+        async fn degraded_storage_is_ignored_when_rejection_is_disabled() {
+            let mut app = TestApp::new();
+            app.state.storage_down_reject_secs = 0;
+            app.state.storage_service.health().record_probe(false);
+
+            assert!(upload(&app, &[], &TEXT_FILE).await.is_ok());
+            assert_eq!(app.storage.object_count(), 1);
+        }
+    }
+
+    mod mime_type_policy {
+        use crate::{
+            adapters::test_app::TestApp, domain::models::file::MimeTypePolicy,
+            services::InMemoryStorageService,
+        };
+
+        /// Sube text/plain a un proveedor que informa application/octet-stream
+        async fn stored_mime_type(policy: MimeTypePolicy) -> (String, String) {
+            let mut app = TestApp::with_storage(
+                InMemoryStorageService::new("gdrive", false)
+                    .reporting_mime_type("application/octet-stream"),
+            );
+            app.state.mime_type_policy = policy;
+
+            let file = super::chunked::upload(&app, b"hello!").await.unwrap();
+            (file.mime_type, app.metadata.file(&file.file_id).mime_type)
+        }
+
+        #[tokio::test]
+        async fn trust_client_keeps_the_declared_type() {
+            let (response, stored) = stored_mime_type(MimeTypePolicy::TrustClient).await;
+            assert_eq!(response, "text/plain");
+            assert_eq!(stored, "text/plain");
         }
 
-        Ok(StatusCode::NO_CONTENT)
+        #[tokio::test]
+        async fn trust_provider_keeps_the_reported_type() {
+            let (response, stored) = stored_mime_type(MimeTypePolicy::TrustProvider).await;
+            assert_eq!(response, "application/octet-stream");
+            assert_eq!(stored, "application/octet-stream");
+        }
     }
 }
@@ -1,11 +1,26 @@
-use axum::{extract::State, Json};
+use std::{future::Future, time::Duration};
+
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::Utc;
 use serde::Serialize;
 use sysinfo::System;
-use tracing::info;
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+use crate::{
+    adapters::state::AppState, application::error::ApplicationError,
+    services::ProviderLatencySummary,
+};
 
-use crate::adapters::state::AppState;
+/// Tiempo máximo de cada sonda de dependencia; las tres corren en paralelo
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct HealthResponse {
     pub status: String,
     #[serde(rename = "serverId")]
@@ -17,9 +32,52 @@ pub struct HealthResponse {
     pub provider: String,
     pub config: HealthConfigInfo,
     pub metrics: SystemMetrics,
+    #[serde(rename = "providerLatency")]
+    pub provider_latency: Vec<ProviderLatencySummary>,
+    pub events: EventQueueMetrics,
+    /// NOW() de la base de datos menos la hora local; null si no se pudo medir
+    #[serde(rename = "clockSkewMs")]
+    pub clock_skew_ms: Option<i64>,
+    pub dependencies: DependencyStatus,
+    /// true solo si base de datos, Redis y almacenamiento responden
+    pub ready: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ProbeStatus {
+    Up,
+    Down,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DependencyStatus {
+    pub database: ProbeStatus,
+    pub redis: ProbeStatus,
+    pub storage: ProbeStatus,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReadinessResponse {
+    pub ready: bool,
+    pub dependencies: DependencyStatus,
+}
+
+impl DependencyStatus {
+    fn all_up(&self) -> bool {
+        [self.database, self.redis, self.storage]
+            .iter()
+            .all(|status| *status == ProbeStatus::Up)
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EventQueueMetrics {
+    pub queued: usize,
+    pub dropped: u64,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct SystemMetrics {
     #[serde(rename = "cpuUsagePercent")]
     pub cpu_usage_percent: f32,
@@ -31,7 +89,7 @@ pub struct SystemMetrics {
     pub memory_usage_percent: f32,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct HealthConfigInfo {
     #[serde(rename = "maxSize")]
     pub max_size: u64,
@@ -46,59 +104,256 @@ pub struct HealthConfigInfo {
 pub struct HealthController;
 
 impl HealthController {
-    /// Health check endpoint - exclusive for VK-Gateway
-    /// GET /api/v1/health
-    pub async fn health_check(State(app_state): State<AppState>) -> Json<HealthResponse> {
-        info!("Health check requested");
-
-        let (server_name, server_url, provider) = {
-            let local_config = app_state.local_config.lock().unwrap();
-            (
-                local_config.server_name.clone(),
-                local_config.server_url.clone(),
-                format!("{:?}", local_config.provider),
-            )
-        };
+    /// Base de datos, Redis y almacenamiento sondeados en paralelo
+    async fn probe_dependencies(app_state: &AppState) -> DependencyStatus {
+        let storage_service = app_state.storage_service.get();
+        let (database, redis, storage) = tokio::join!(
+            Self::probe("database", app_state.metadata_repository.ping()),
+            Self::probe("redis", app_state.token_repository.ping()),
+            Self::probe("storage", storage_service.ping()),
+        );
+        app_state
+            .storage_service
+            .health()
+            .record_probe(storage == ProbeStatus::Up);
+        DependencyStatus {
+            database,
+            redis,
+            storage,
+        }
+    }
+
+    /// Down si la sonda falla o no responde dentro de PROBE_TIMEOUT
+    async fn probe(
+        dependency: &str,
+        check: impl Future<Output = Result<(), ApplicationError>>,
+    ) -> ProbeStatus {
+        match tokio::time::timeout(PROBE_TIMEOUT, check).await {
+            Ok(Ok(())) => ProbeStatus::Up,
+            Ok(Err(e)) => {
+                warn!("Health probe for {} failed: {:?}", dependency, e);
+                ProbeStatus::Down
+            }
+            Err(_) => {
+                warn!(
+                    "Health probe for {} timed out after {:?}",
+                    dependency, PROBE_TIMEOUT
+                );
+                ProbeStatus::Down
+            }
+        }
+    }
 
-        let config_info = {
-            let global_config = app_state.global_config.lock().unwrap();
-            HealthConfigInfo {
-                max_size: global_config.max_size,
-                default_quota: global_config.default_quota,
-                temp_file_life: global_config.temp_file_life,
-                allowed_mime_types: global_config.mime_types.clone(),
+    /// Compara NOW() de la base de datos con el punto medio de la consulta
+    async fn clock_skew_ms(app_state: &AppState) -> Option<i64> {
+        let before = Utc::now();
+        let database_now = match app_state.metadata_repository.database_now().await {
+            Ok(now) => now,
+            Err(e) => {
+                warn!("Could not read database time for clock skew check: {:?}", e);
+                return None;
             }
         };
+        let after = Utc::now();
+
+        let local_now = before + (after - before) / 2;
+        Some((database_now - local_now).num_milliseconds())
+    }
+}
+
+/// Health check endpoint - exclusive for VK-Gateway
+/// GET /api/v1/health
+#[utoipa::path(
+    get,
+    path = "/api/v1/health",
+    tag = "Health",
+    security(("kv_secret" = [])),
+    responses(
+        (status = 200, description = "Estado y métricas de la instancia", body = HealthResponse),
+        (status = 401, description = "X-KV-SECRET ausente o inválido"),
+    )
+)]
+pub async fn health_check(State(app_state): State<AppState>) -> Json<HealthResponse> {
+    info!("Health check requested");
+
+    let (server_name, server_url, provider, quota_override) = {
+        let local_config = app_state.local_config.lock().unwrap();
+        (
+            local_config.server_name.clone(),
+            local_config.server_url.clone(),
+            format!("{:?}", local_config.provider),
+            local_config.default_quota,
+        )
+    };
 
-        // Collect system metrics (optimized - only refresh what's needed)
-        let mut sys = System::new();
-        sys.refresh_cpu_usage();
-        sys.refresh_memory();
+    let config_info = {
+        let global_config = app_state.global_config.lock().unwrap();
+        HealthConfigInfo {
+            max_size: global_config.max_size,
+            // Cuota efectiva para usuarios nuevos en esta instancia
+            default_quota: quota_override.unwrap_or(global_config.default_quota),
+            temp_file_life: global_config.temp_file_life,
+            allowed_mime_types: global_config.mime_types.clone(),
+        }
+    };
 
-        let cpu_usage = sys.global_cpu_usage();
-        let memory_used = sys.used_memory();
-        let memory_total = sys.total_memory();
-        let memory_usage_percent = if memory_total > 0 {
-            (memory_used as f32 / memory_total as f32) * 100.0
+    // Collect system metrics (optimized - only refresh what's needed)
+    let mut sys = System::new();
+    sys.refresh_cpu_usage();
+    sys.refresh_memory();
+
+    let cpu_usage = sys.global_cpu_usage();
+    let memory_used = sys.used_memory();
+    let memory_total = sys.total_memory();
+    let memory_usage_percent = if memory_total > 0 {
+        (memory_used as f32 / memory_total as f32) * 100.0
+    } else {
+        0.0
+    };
+
+    let metrics = SystemMetrics {
+        cpu_usage_percent: cpu_usage,
+        memory_used_bytes: memory_used,
+        memory_total_bytes: memory_total,
+        memory_usage_percent,
+    };
+
+    let (clock_skew_ms, dependencies) = tokio::join!(
+        HealthController::clock_skew_ms(&app_state),
+        HealthController::probe_dependencies(&app_state),
+    );
+    let ready = dependencies.all_up();
+
+    let clock_skewed =
+        clock_skew_ms.is_some_and(|skew| skew.unsigned_abs() > app_state.clock_skew_threshold_ms);
+    if clock_skewed {
+        warn!(
+            "Clock skew against the database is {:?} ms (threshold {} ms)",
+            clock_skew_ms, app_state.clock_skew_threshold_ms
+        );
+    }
+
+    Json(HealthResponse {
+        status: if clock_skewed || !ready {
+            "degraded"
         } else {
-            0.0
-        };
+            "healthy"
+        }
+        .to_string(),
+        server_id: app_state.server_id.clone(),
+        server_name,
+        server_url,
+        provider,
+        config: config_info,
+        metrics,
+        provider_latency: app_state.storage_service.latency().summary(),
+        events: EventQueueMetrics {
+            queued: app_state.event_queue.queued_events(),
+            dropped: app_state.event_queue.dropped_events(),
+        },
+        clock_skew_ms,
+        dependencies,
+        ready,
+    })
+}
 
-        let metrics = SystemMetrics {
-            cpu_usage_percent: cpu_usage,
-            memory_used_bytes: memory_used,
-            memory_total_bytes: memory_total,
-            memory_usage_percent,
-        };
+/// Liveness para Kubernetes: el proceso responde; sin llamadas externas
+/// GET /api/v1/health/live
+#[utoipa::path(
+    get,
+    path = "/api/v1/health/live",
+    tag = "Health",
+    security(("kv_secret" = [])),
+    responses(
+        (status = 200, description = "El proceso responde"),
+        (status = 401, description = "X-KV-SECRET ausente o inválido"),
+    )
+)]
+pub async fn liveness() -> StatusCode {
+    StatusCode::OK
+}
+
+/// Readiness para Kubernetes: 503 si alguna dependencia está caída
+/// GET /api/v1/health/ready
+#[utoipa::path(
+    get,
+    path = "/api/v1/health/ready",
+    tag = "Health",
+    security(("kv_secret" = [])),
+    responses(
+        (status = 200, description = "Todas las dependencias responden", body = ReadinessResponse),
+        (status = 401, description = "X-KV-SECRET ausente o inválido"),
+        (status = 503, description = "Alguna dependencia está caída", body = ReadinessResponse),
+    )
+)]
+pub async fn readiness(State(app_state): State<AppState>) -> (StatusCode, Json<ReadinessResponse>) {
+    let dependencies = HealthController::probe_dependencies(&app_state).await;
+    let ready = dependencies.all_up();
+    if !ready {
+        warn!("Readiness check failed: {:?}", dependencies);
+    }
+
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (
+        status,
+        Json(ReadinessResponse {
+            ready,
+            dependencies,
+        }),
+    )
+}
+
+/// Contadores de rechazos por endpoint en formato Prometheus
+/// GET /metrics
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "Health",
+    security(("kv_secret" = [])),
+    responses(
+        (status = 200, description = "Contadores por endpoint y resultado", content_type = "text/plain"),
+        (status = 401, description = "X-KV-SECRET ausente o inválido"),
+    )
+)]
+pub async fn metrics(State(app_state): State<AppState>) -> Response {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        app_state.error_metrics.render(),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::adapters::test_app::TestApp;
+
+    #[tokio::test]
+    async fn synchronized_clocks_are_healthy() {
+        let app = TestApp::new();
+
+        let Json(health) = health_check(State(app.state.clone())).await;
+
+        assert_eq!(health.status, "healthy");
+        assert!(health.clock_skew_ms.unwrap().abs() < 1_000);
+    }
+
+    #[tokio::test]
+    async fn skew_beyond_the_threshold_degrades_health() {
+        let app = TestApp::new();
+        app.metadata.set_clock_offset(Duration::seconds(-5));
+
+        let Json(health) = health_check(State(app.state.clone())).await;
 
-        Json(HealthResponse {
-            status: "healthy".to_string(),
-            server_id: app_state.server_id.clone(),
-            server_name,
-            server_url,
-            provider,
-            config: config_info,
-            metrics,
-        })
+        assert_eq!(health.status, "degraded");
+        assert!(health.ready);
+        let skew = health.clock_skew_ms.unwrap();
+        assert!((-5_100..=-4_900).contains(&skew), "skew was {} ms", skew);
     }
 }
@@ -1,3 +1,5 @@
+pub mod admin_controller;
+pub mod docs_controller;
 pub mod file_controller;
 pub mod health_controller;
 pub mod instance_controller;
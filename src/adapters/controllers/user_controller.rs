@@ -1,82 +1,837 @@
 use std::sync::{Arc, Mutex};
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, HeaderName, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
-use serde::Deserialize;
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
 use tracing::info;
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
 use crate::{
+    adapters::{
+        authorization::FileCaller,
+        dto::file_dto::{FileResponse, ScheduleExpiryRequest, ScheduleExpiryResponse},
+        json_body::JsonBody,
+        ndjson::{ndjson_response, wants_ndjson},
+        pagination::{Pagination, PaginationParams},
+    },
     application::{
         dto::user_dto::UserDTO,
         error::ApplicationError,
-        repositories::{metadata_repository::MetadataRepository, user_repository::UserRepository},
+        repositories::{
+            metadata_repository::MetadataRepository, token_repository::TokenRepository,
+            user_repository::UserRepository,
+        },
+    },
+    domain::{
+        config::{global::GlobalConfig, local::LocalConfig},
+        models::{
+            metadata::{UserFilesFilter, UserFilesSortBy},
+            user::User,
+        },
     },
-    domain::{config::global::GlobalConfig, models::user::User},
 };
 
-pub struct UserController;
+const DEFAULT_USER_FILES_LIMIT: i64 = 50;
+const MAX_USER_FILES_LIMIT: i64 = 500;
+const USER_TOKEN_TTL_SECS: u64 = 3600;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct CreateUser {
     uid: Uuid,
+    /// Plan de la configuración global; su cuota sustituye a defaultQuota
+    tier: Option<String>,
+}
+
+/// Resumen de cuota para el panel del usuario
+#[derive(Serialize, ToSchema)]
+pub struct UserUsageResponse {
+    #[serde(rename = "totalSpace")]
+    pub total_space: u64,
+    #[serde(rename = "usedSpace")]
+    pub used_space: u64,
+    #[serde(rename = "fileCount")]
+    pub file_count: u64,
+    /// 0 si el usuario no tiene archivos
+    #[serde(rename = "largestFileSize")]
+    pub largest_file_size: u64,
+    /// null si el usuario no tiene archivos
+    #[serde(rename = "oldestFileUploadedAt")]
+    pub oldest_file_uploaded_at: Option<DateTime<Utc>>,
 }
 
-impl UserController {
-    pub async fn create_user(
-        State(global_config): State<Arc<Mutex<GlobalConfig>>>,
-        State(user_repo): State<Arc<dyn UserRepository>>,
-        Json(body): Json<CreateUser>,
-    ) -> Result<Json<User>, ApplicationError> {
-        let mut user = User::default();
-        user.uid = body.uid;
-        let user_dto = UserDTO::from(user);
-        let default_quota = {
-            let gc = global_config.lock().unwrap();
-            gc.default_quota
+/// Token que acredita al usuario como dueño de sus archivos
+#[derive(Serialize, ToSchema)]
+pub struct UserTokenResponse {
+    pub token: String,
+    #[serde(rename = "userId")]
+    pub user_id: Uuid,
+    /// Segundos de validez del token
+    #[serde(rename = "expiresIn")]
+    pub expires_in: u64,
+}
+
+/// Página de archivos del usuario con el total para construir la paginación
+#[derive(Serialize, ToSchema)]
+pub struct UserFilesPage {
+    pub items: Vec<FileResponse>,
+    pub total: u64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UserFilesQuery {
+    /// En NDJSON, emite metadata completa con downloadUrl en lugar de solo ids
+    #[serde(default)]
+    full: bool,
+    /// Solo archivos con este tipo MIME exacto
+    #[serde(rename = "mimeType")]
+    mime_type: Option<String>,
+    /// uploadedAt (por defecto), size o downloadCount
+    #[serde(rename = "sortBy")]
+    sort_by: Option<String>,
+    /// asc o desc (por defecto)
+    order: Option<String>,
+}
+
+impl UserFilesQuery {
+    fn filter(&self) -> Result<UserFilesFilter, ApplicationError> {
+        let sort_by = match self.sort_by.as_deref() {
+            None | Some("uploadedAt") => UserFilesSortBy::UploadedAt,
+            Some("size") => UserFilesSortBy::Size,
+            Some("downloadCount") => UserFilesSortBy::DownloadCount,
+            Some(other) => {
+                return Err(ApplicationError::BadRequest(format!(
+                    "Unsupported sortBy '{}'; expected one of: uploadedAt, size, downloadCount",
+                    other
+                )))
+            }
         };
-        let user = user_repo.create_user(user_dto, default_quota).await?;
-        Ok(Json(user))
-    }
-
-    pub async fn get_user(
-        State(user_repo): State<Arc<dyn UserRepository>>,
-        Path(user_id): Path<Uuid>,
-    ) -> Result<Json<User>, ApplicationError> {
-        let user_dto = UserDTO::for_query(user_id);
-        let user = user_repo.get_user(user_dto).await?;
-        Ok(Json(user))
-    }
-
-    pub async fn update_user(
-        State(user_repo): State<Arc<dyn UserRepository>>,
-        Path(user_id): Path<Uuid>,
-        Json(body): Json<UserDTO>,
-    ) -> Result<Json<User>, ApplicationError> {
-        let mut user_dto = body;
-        user_dto.uid = user_id;
-        let user = user_repo.update_user(user_dto).await?;
-        Ok(Json(user))
-    }
-
-    pub async fn delete_user(
-        State(user_repo): State<Arc<dyn UserRepository>>,
-        Path(user_id): Path<Uuid>,
-    ) -> Result<Json<User>, ApplicationError> {
-        let user_dto = UserDTO::for_query(user_id);
-        let user = user_repo.delete_user(user_dto).await?;
-        Ok(Json(user))
-    }
-
-    pub async fn get_user_files(
-        State(metadata_repo): State<Arc<dyn MetadataRepository>>,
-        Path(user_id): Path<Uuid>,
-    ) -> Result<Json<Vec<String>>, ApplicationError> {
-        info!("Getting file IDs for user: {}", user_id);
-        let user_id_str = user_id.to_string();
-        let file_ids = metadata_repo.get_file_ids_by_user(&user_id_str).await?;
-        Ok(Json(file_ids))
+        let descending = match self.order.as_deref() {
+            None | Some("desc") => true,
+            Some("asc") => false,
+            Some(other) => {
+                return Err(ApplicationError::BadRequest(format!(
+                    "Unsupported order '{}'; expected asc or desc",
+                    other
+                )))
+            }
+        };
+        let mime_type = match self.mime_type.as_deref().map(str::trim) {
+            None | Some("") => None,
+            Some(mime_type) => Some(mime_type.to_string()),
+        };
+
+        Ok(UserFilesFilter {
+            mime_type,
+            sort_by,
+            descending,
+        })
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/users",
+    tag = "Users",
+    request_body = CreateUser,
+    responses(
+        (status = 201, description = "Usuario creado", body = User,
+            headers(("Location" = String, description = "URL del usuario creado"))),
+        (status = 400, description = "Plan desconocido"),
+        (status = 409, description = "El usuario ya existe"),
+    )
+)]
+pub async fn create_user(
+    State(global_config): State<Arc<Mutex<GlobalConfig>>>,
+    State(local_config): State<Arc<Mutex<LocalConfig>>>,
+    State(user_repo): State<Arc<dyn UserRepository>>,
+    JsonBody(body): JsonBody<CreateUser>,
+) -> Result<(StatusCode, [(HeaderName, String); 1], Json<User>), ApplicationError> {
+    let mut user = User {
+        uid: body.uid,
+        ..Default::default()
+    };
+    let quota = match body.tier.as_deref().map(str::trim) {
+        None | Some("") => {
+            let global_default = global_config.lock().unwrap().default_quota;
+            local_config
+                .lock()
+                .unwrap()
+                .effective_default_quota(global_default)
+        }
+        Some(tier) => {
+            let tier = tier.to_ascii_lowercase();
+            let quota = global_config
+                .lock()
+                .unwrap()
+                .tiers
+                .get(&tier)
+                .map(|t| t.quota)
+                .ok_or_else(|| ApplicationError::BadRequest(format!("Unknown tier '{}'", tier)))?;
+            user.tier = Some(tier);
+            quota
+        }
+    };
+    let user_dto = UserDTO::from(user);
+    let user = user_repo.create_user(user_dto, quota).await?;
+    let location = format!("/api/v1/users/{}", user.uid);
+    Ok((
+        StatusCode::CREATED,
+        [(header::LOCATION, location)],
+        Json(user),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/users/{user_id}",
+    tag = "Users",
+    params(("user_id" = uuid::Uuid, Path)),
+    responses(
+        (status = 200, body = User),
+        (status = 404, description = "Usuario no encontrado"),
+    )
+)]
+pub async fn get_user(
+    State(user_repo): State<Arc<dyn UserRepository>>,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<User>, ApplicationError> {
+    let user_dto = UserDTO::for_query(user_id);
+    let user = user_repo.get_user(user_dto).await?;
+    Ok(Json(user))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/api/v1/users/{user_id}",
+    tag = "Users",
+    params(("user_id" = uuid::Uuid, Path)),
+    request_body = UserDTO,
+    responses(
+        (status = 200, body = User),
+        (status = 404, description = "Usuario no encontrado"),
+    )
+)]
+pub async fn update_user(
+    State(user_repo): State<Arc<dyn UserRepository>>,
+    Path(user_id): Path<Uuid>,
+    JsonBody(body): JsonBody<UserDTO>,
+) -> Result<Json<User>, ApplicationError> {
+    let mut user_dto = body;
+    user_dto.uid = user_id;
+    let user = user_repo.update_user(user_dto).await?;
+    Ok(Json(user))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/users/{user_id}",
+    tag = "Users",
+    params(("user_id" = uuid::Uuid, Path)),
+    responses(
+        (status = 200, description = "Usuario eliminado", body = User),
+        (status = 404, description = "Usuario no encontrado"),
+    )
+)]
+pub async fn delete_user(
+    State(user_repo): State<Arc<dyn UserRepository>>,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<User>, ApplicationError> {
+    let user_dto = UserDTO::for_query(user_id);
+    let user = user_repo.delete_user(user_dto).await?;
+    Ok(Json(user))
+}
+
+/// Emite un token de usuario para `Authorization: Bearer`; solo lo pide el backend que
+/// ya autenticó al usuario (ruta protegida por X-KV-SECRET)
+/// POST /api/v1/users/{user_id}/token
+#[utoipa::path(
+    post,
+    path = "/api/v1/users/{user_id}/token",
+    tag = "Users",
+    security(("kv_secret" = [])),
+    params(("user_id" = uuid::Uuid, Path)),
+    responses(
+        (status = 201, body = UserTokenResponse),
+        (status = 401, description = "X-KV-SECRET ausente o inválido"),
+        (status = 404, description = "Usuario no encontrado"),
+    )
+)]
+pub async fn issue_user_token(
+    State(user_repo): State<Arc<dyn UserRepository>>,
+    State(token_repo): State<Arc<dyn TokenRepository>>,
+    Path(user_id): Path<Uuid>,
+) -> Result<(StatusCode, Json<UserTokenResponse>), ApplicationError> {
+    user_repo.get_user(UserDTO::for_query(user_id)).await?;
+    let token = token_repo
+        .generate_user_token(&user_id.to_string(), USER_TOKEN_TTL_SECS)
+        .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(UserTokenResponse {
+            token,
+            user_id,
+            expires_in: USER_TOKEN_TTL_SECS,
+        }),
+    ))
+}
+
+/// GET /api/v1/users/{user_id}/files?limit=&offset=&mimeType=&sortBy=&order=
+/// Con `Accept: application/x-ndjson` se emite un objeto por línea en streaming
+#[utoipa::path(
+    get,
+    path = "/api/v1/users/{user_id}/files",
+    tag = "Users",
+    params(("user_id" = uuid::Uuid, Path), PaginationParams, UserFilesQuery),
+    responses(
+        (status = 200, description = "Página de archivos; por defecto, más recientes primero",
+            body = UserFilesPage),
+        (status = 400, description = "sortBy u order no soportados"),
+    )
+)]
+pub async fn get_user_files(
+    State(metadata_repo): State<Arc<dyn MetadataRepository>>,
+    State(local_config): State<Arc<Mutex<LocalConfig>>>,
+    Path(user_id): Path<Uuid>,
+    Query(query): Query<UserFilesQuery>,
+    pagination: Pagination,
+    headers: HeaderMap,
+) -> Result<Response, ApplicationError> {
+    let user_id_str = user_id.to_string();
+    let filter = query.filter()?;
+
+    if wants_ndjson(&headers) {
+        info!(
+            "Streaming files for user {} as NDJSON ({:?})",
+            user_id, filter
+        );
+        let rows = metadata_repo.stream_files_by_user(user_id_str, filter);
+        if !query.full {
+            let ids = rows.map(|row| row.map(|metadata| json!({ "fileId": metadata.file_id })));
+            return Ok(ndjson_response(ids));
+        }
+        let server_url = local_config.lock().unwrap().server_url.clone();
+        let files = rows.map(move |row| {
+            row.map(|metadata| FileResponse::from(metadata).with_download_url(&server_url))
+        });
+        return Ok(ndjson_response(files));
+    }
+
+    let limit = pagination.limit(DEFAULT_USER_FILES_LIMIT, MAX_USER_FILES_LIMIT);
+    info!(
+        "Getting files for user {} (limit {}, offset {}, {:?})",
+        user_id, limit, pagination.offset, filter
+    );
+    let (rows, total) = metadata_repo
+        .get_files_by_user_paginated(&user_id_str, &filter, limit, pagination.offset)
+        .await?;
+
+    let server_url = local_config.lock().unwrap().server_url.clone();
+    let items = rows
+        .into_iter()
+        .map(|metadata| FileResponse::from(metadata).with_download_url(&server_url))
+        .collect();
+    Ok(Json(UserFilesPage {
+        items,
+        total,
+        limit,
+        offset: pagination.offset,
+    })
+    .into_response())
+}
+
+/// Cuota y uso del usuario sin descargar la metadata de cada archivo
+/// GET /api/v1/users/{user_id}/usage
+#[utoipa::path(
+    get,
+    path = "/api/v1/users/{user_id}/usage",
+    tag = "Users",
+    params(("user_id" = uuid::Uuid, Path)),
+    responses(
+        (status = 200, body = UserUsageResponse),
+        (status = 404, description = "Usuario no encontrado"),
+    )
+)]
+pub async fn get_user_usage(
+    State(user_repo): State<Arc<dyn UserRepository>>,
+    State(metadata_repo): State<Arc<dyn MetadataRepository>>,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<UserUsageResponse>, ApplicationError> {
+    let user = user_repo.get_user(UserDTO::for_query(user_id)).await?;
+    let stats = metadata_repo
+        .get_user_usage_stats(&user_id.to_string())
+        .await?;
+
+    Ok(Json(UserUsageResponse {
+        total_space: user.total_space,
+        used_space: user.used_space,
+        file_count: user.file_count,
+        largest_file_size: stats.largest_file_size.unwrap_or(0),
+        oldest_file_uploaded_at: stats.oldest_file_uploaded_at,
+    }))
+}
+
+/// Programa la eliminación de archivos permanentes del usuario (p. ej. al bajar de plan)
+/// POST /api/v1/users/{user_id}/schedule-expiry
+///
+/// Solo el propio usuario (token de usuario) o el admin (X-VK-Secret).
+#[utoipa::path(
+    post,
+    path = "/api/v1/users/{user_id}/schedule-expiry",
+    tag = "Users",
+    params(
+        ("user_id" = uuid::Uuid, Path),
+        ("Authorization" = Option<String>, Header, description = "Bearer <token de usuario>"),
+        ("X-VK-Secret" = Option<String>, Header, description = "Permite programar los archivos de cualquier usuario"),
+    ),
+    request_body = ScheduleExpiryRequest,
+    responses(
+        (status = 200, body = ScheduleExpiryResponse),
+        (status = 400, description = "deleteAt no está en el futuro"),
+        (status = 401, description = "Token de usuario inválido o expirado"),
+        (status = 403, description = "El token es de otro usuario, o falta token sin X-VK-Secret"),
+    )
+)]
+pub async fn schedule_expiry(
+    State(metadata_repo): State<Arc<dyn MetadataRepository>>,
+    caller: FileCaller,
+    Path(user_id): Path<Uuid>,
+    JsonBody(body): JsonBody<ScheduleExpiryRequest>,
+) -> Result<Json<ScheduleExpiryResponse>, ApplicationError> {
+    caller.authorize_user(user_id)?;
+    if body.delete_at <= Utc::now() {
+        return Err(ApplicationError::BadRequest(
+            "deleteAt must be in the future".to_string(),
+        ));
+    }
+
+    let scheduled_count = metadata_repo
+        .schedule_user_files_expiry(
+            &user_id.to_string(),
+            body.file_ids.as_deref(),
+            body.older_than,
+            body.delete_at,
+        )
+        .await?;
+
+    info!(
+        "Scheduled {} files of user {} for deletion at {}",
+        scheduled_count, user_id, body.delete_at
+    );
+    Ok(Json(ScheduleExpiryResponse {
+        scheduled_count,
+        delete_at: body.delete_at,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::extract::FromRequestParts;
+    use chrono::Duration;
+
+    use super::*;
+    use crate::{
+        adapters::repositories::{
+            in_memory_metadata_repository::{metadata, InMemoryMetadataRepository},
+            in_memory_user_repository::InMemoryUserRepository,
+        },
+        application::dto::{global_config_dto::GlobalConfigDTO, local_config_dto::LocalConfigDTO},
+    };
+
+    fn schedule(file_ids: Option<Vec<&str>>) -> JsonBody<ScheduleExpiryRequest> {
+        JsonBody(ScheduleExpiryRequest {
+            file_ids: file_ids.map(|ids| ids.into_iter().map(str::to_string).collect()),
+            older_than: None,
+            delete_at: Utc::now() + Duration::days(7),
+        })
+    }
+
+    #[tokio::test]
+    async fn schedule_expiry_only_touches_targeted_files() {
+        let owner = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        let repo = Arc::new(InMemoryMetadataRepository::new(vec![
+            metadata("a", owner, "a.txt"),
+            metadata("b", owner, "b.txt"),
+            metadata("c", other, "c.txt"),
+        ]));
+
+        let Json(response) = schedule_expiry(
+            State(repo.clone() as Arc<dyn MetadataRepository>),
+            FileCaller::user(Some(owner)),
+            Path(owner),
+            schedule(Some(vec!["a", "c"])),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.scheduled_count, 1);
+        assert_eq!(repo.file("a").delete_at, Some(response.delete_at));
+        assert_eq!(repo.file("b").delete_at, None);
+        assert_eq!(repo.file("c").delete_at, None);
+    }
+
+    #[tokio::test]
+    async fn schedule_expiry_requires_the_owner_or_admin() {
+        let owner = Uuid::new_v4();
+        let repo: Arc<dyn MetadataRepository> =
+            Arc::new(InMemoryMetadataRepository::new(vec![metadata(
+                "a", owner, "a.txt",
+            )]));
+
+        for caller in [
+            FileCaller::user(None),
+            FileCaller::user(Some(Uuid::new_v4())),
+        ] {
+            let result =
+                schedule_expiry(State(repo.clone()), caller, Path(owner), schedule(None)).await;
+            assert!(matches!(result, Err(ApplicationError::Forbidden(_))));
+        }
+
+        let Json(response) = schedule_expiry(
+            State(repo.clone()),
+            FileCaller::admin(),
+            Path(owner),
+            schedule(None),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.scheduled_count, 1);
+    }
+
+    async fn ndjson_files(
+        repo: Arc<dyn MetadataRepository>,
+        user_id: Uuid,
+        query: &str,
+    ) -> Result<Vec<serde_json::Value>, ApplicationError> {
+        let uri: axum::http::Uri = format!("/files?{}", query).parse().unwrap();
+        let request = axum::http::Request::get(&uri).body(()).unwrap();
+        let (mut parts, _) = request.into_parts();
+        let pagination = Pagination::from_request_parts(&mut parts, &()).await?;
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, "application/x-ndjson".parse().unwrap());
+
+        let response = get_user_files(
+            State(repo),
+            State(Arc::new(Mutex::new(LocalConfigDTO::default().into()))),
+            Path(user_id),
+            Query::try_from_uri(&uri).unwrap(),
+            pagination,
+            headers,
+        )
+        .await?;
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        Ok(body
+            .split(|byte| *byte == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).unwrap())
+            .collect())
+    }
+
+    #[tokio::test]
+    async fn ndjson_listing_applies_the_filter() {
+        let owner = Uuid::new_v4();
+        let mut small = metadata("small", owner, "small.png");
+        small.mime_type = "image/png".to_string();
+        small.size = 1;
+        let mut large = metadata("large", owner, "large.png");
+        large.mime_type = "image/png".to_string();
+        large.size = 10;
+        let repo: Arc<dyn MetadataRepository> = Arc::new(InMemoryMetadataRepository::new(vec![
+            large,
+            metadata("text", owner, "text.txt"),
+            small,
+        ]));
+
+        let lines = ndjson_files(repo, owner, "mimeType=image/png&sortBy=size&order=asc")
+            .await
+            .unwrap();
+        assert_eq!(
+            lines,
+            vec![json!({ "fileId": "small" }), json!({ "fileId": "large" })]
+        );
+    }
+
+    #[tokio::test]
+    async fn ndjson_listing_rejects_an_invalid_filter() {
+        let owner = Uuid::new_v4();
+        let repo: Arc<dyn MetadataRepository> = Arc::new(InMemoryMetadataRepository::new(vec![]));
+
+        for query in ["sortBy=name", "order=up"] {
+            let result = ndjson_files(repo.clone(), owner, query).await;
+            assert!(matches!(result, Err(ApplicationError::BadRequest(_))));
+        }
+    }
+
+    mod trash {
+        use super::*;
+
+        /// Un archivo vigente y otro más grande y antiguo en la papelera
+        fn repo(owner: Uuid) -> Arc<InMemoryMetadataRepository> {
+            let mut kept = metadata("kept", owner, "kept.txt");
+            kept.size = 5;
+            let mut trashed = metadata("trashed", owner, "trashed.txt");
+            trashed.size = 500;
+            trashed.uploaded_at = Utc::now() - Duration::days(30);
+            trashed.trashed_at = Some(Utc::now());
+            Arc::new(InMemoryMetadataRepository::new(vec![kept, trashed]))
+        }
+
+        #[tokio::test]
+        async fn listing_excludes_trashed_files() {
+            let owner = Uuid::new_v4();
+            let uri: axum::http::Uri = "/files".parse().unwrap();
+            let (mut parts, _) = axum::http::Request::get(&uri)
+                .body(())
+                .unwrap()
+                .into_parts();
+
+            let response = get_user_files(
+                State(repo(owner) as Arc<dyn MetadataRepository>),
+                State(Arc::new(Mutex::new(LocalConfigDTO::default().into()))),
+                Path(owner),
+                Query::try_from_uri(&uri).unwrap(),
+                Pagination::from_request_parts(&mut parts, &())
+                    .await
+                    .unwrap(),
+                HeaderMap::new(),
+            )
+            .await
+            .unwrap();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let page: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+            assert_eq!(page["total"], 1);
+            assert_eq!(page["items"].as_array().unwrap().len(), 1);
+            assert_eq!(page["items"][0]["fileId"], "kept");
+        }
+
+        #[tokio::test]
+        async fn usage_ignores_trashed_files() {
+            let owner = Uuid::new_v4();
+            let repo = repo(owner);
+            let users = Arc::new(InMemoryUserRepository::default());
+            users.insert(User {
+                uid: owner,
+                ..Default::default()
+            });
+
+            let Json(usage) = get_user_usage(
+                State(users as Arc<dyn UserRepository>),
+                State(repo.clone() as Arc<dyn MetadataRepository>),
+                Path(owner),
+            )
+            .await
+            .unwrap();
+
+            assert_eq!(usage.largest_file_size, 5);
+            assert_eq!(
+                usage.oldest_file_uploaded_at,
+                Some(repo.file("kept").uploaded_at)
+            );
+        }
+
+        #[tokio::test]
+        async fn schedule_expiry_skips_trashed_files() {
+            let owner = Uuid::new_v4();
+            let repo = repo(owner);
+
+            let Json(response) = schedule_expiry(
+                State(repo.clone() as Arc<dyn MetadataRepository>),
+                FileCaller::admin(),
+                Path(owner),
+                schedule(None),
+            )
+            .await
+            .unwrap();
+
+            assert_eq!(response.scheduled_count, 1);
+            assert_eq!(repo.file("kept").delete_at, Some(response.delete_at));
+            assert_eq!(repo.file("trashed").delete_at, None);
+        }
+    }
+
+    /// Repositorio que devuelve el usuario tal como se crearía, con la cuota recibida
+    struct CreatedUsers;
+
+    #[async_trait::async_trait]
+    impl UserRepository for CreatedUsers {
+        async fn create_user(
+            &self,
+            user: UserDTO,
+            new_space: u64,
+        ) -> Result<User, ApplicationError> {
+            Ok(User {
+                uid: user.uid,
+                total_space: new_space,
+                tier: user.tier,
+                ..Default::default()
+            })
+        }
+
+        async fn get_user(&self, _user: UserDTO) -> Result<User, ApplicationError> {
+            unimplemented!()
+        }
+
+        async fn update_user(&self, _user: UserDTO) -> Result<User, ApplicationError> {
+            unimplemented!()
+        }
+
+        async fn delete_user(&self, _user: UserDTO) -> Result<User, ApplicationError> {
+            unimplemented!()
+        }
+    }
+
+    #[tokio::test]
+    async fn create_user_returns_201_with_location() {
+        let uid = Uuid::new_v4();
+        let mut global_config: GlobalConfig = GlobalConfigDTO::default().into();
+        global_config.default_quota = 1024;
+
+        let (status, [(name, location)], Json(user)) = create_user(
+            State(Arc::new(Mutex::new(global_config))),
+            State(Arc::new(Mutex::new(LocalConfigDTO::default().into()))),
+            State(Arc::new(CreatedUsers) as Arc<dyn UserRepository>),
+            JsonBody(CreateUser { uid, tier: None }),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(name, header::LOCATION);
+        assert_eq!(location, format!("/api/v1/users/{}", uid));
+        assert_eq!((user.uid, user.total_space), (uid, 1024));
+    }
+
+    async fn created_quota(local_default_quota: Option<u64>) -> u64 {
+        let mut global_config: GlobalConfig = GlobalConfigDTO::default().into();
+        global_config.default_quota = 1024;
+        let mut local_config: LocalConfig = LocalConfigDTO::default().into();
+        local_config.default_quota = local_default_quota;
+
+        let (_, _, Json(user)) = create_user(
+            State(Arc::new(Mutex::new(global_config))),
+            State(Arc::new(Mutex::new(local_config))),
+            State(Arc::new(CreatedUsers) as Arc<dyn UserRepository>),
+            JsonBody(CreateUser {
+                uid: Uuid::new_v4(),
+                tier: None,
+            }),
+        )
+        .await
+        .unwrap();
+        user.total_space
+    }
+
+    #[tokio::test]
+    async fn instance_default_quota_overrides_the_global_one() {
+        assert_eq!(created_quota(Some(256)).await, 256);
+        assert_eq!(created_quota(Some(0)).await, 0);
+        assert_eq!(created_quota(None).await, 1024);
+    }
+
+    #[tokio::test]
+    async fn creating_an_existing_user_is_a_conflict() {
+        let users = Arc::new(InMemoryUserRepository::default());
+        let uid = Uuid::new_v4();
+        let create = || {
+            create_user(
+                State(Arc::new(Mutex::new(GlobalConfigDTO::default().into()))),
+                State(Arc::new(Mutex::new(LocalConfigDTO::default().into()))),
+                State(users.clone() as Arc<dyn UserRepository>),
+                JsonBody(CreateUser { uid, tier: None }),
+            )
+        };
+
+        assert!(create().await.is_ok());
+        assert!(matches!(
+            create().await,
+            Err(ApplicationError::Conflict(message)) if message.contains(&uid.to_string())
+        ));
+    }
+
+    mod tiers {
+        use std::collections::HashMap;
+
+        use super::*;
+        use crate::{
+            adapters::repositories::in_memory_user_repository::InMemoryUserRepository,
+            domain::config::global::UserTier,
+        };
+
+        fn with_quota(quota: u64) -> UserTier {
+            UserTier {
+                quota,
+                max_files: None,
+                allowed_mime_types: None,
+            }
+        }
+
+        async fn create(
+            users: &Arc<InMemoryUserRepository>,
+            tier: Option<&str>,
+        ) -> Result<User, ApplicationError> {
+            let mut global_config: GlobalConfig = GlobalConfigDTO::default().into();
+            global_config.default_quota = 1024;
+            global_config.tiers = HashMap::from([
+                ("free".to_string(), with_quota(100)),
+                ("pro".to_string(), with_quota(5_000)),
+            ]);
+
+            let (_, _, Json(user)) = create_user(
+                State(Arc::new(Mutex::new(global_config))),
+                State(Arc::new(Mutex::new(LocalConfigDTO::default().into()))),
+                State(users.clone() as Arc<dyn UserRepository>),
+                JsonBody(CreateUser {
+                    uid: Uuid::new_v4(),
+                    tier: tier.map(str::to_string),
+                }),
+            )
+            .await?;
+            Ok(user)
+        }
+
+        #[tokio::test]
+        async fn each_tier_applies_its_quota_and_is_stored() {
+            let users = Arc::new(InMemoryUserRepository::default());
+
+            let free = create(&users, Some("free")).await.unwrap();
+            let pro = create(&users, Some(" PRO ")).await.unwrap();
+            let untiered = create(&users, None).await.unwrap();
+
+            assert_eq!(
+                (free.total_space, free.tier.as_deref()),
+                (100, Some("free"))
+            );
+            assert_eq!((pro.total_space, pro.tier.as_deref()), (5_000, Some("pro")));
+            assert_eq!((untiered.total_space, untiered.tier), (1024, None));
+            let stored = users.get_user(UserDTO::for_query(pro.uid)).await.unwrap();
+            assert_eq!(stored.tier.as_deref(), Some("pro"));
+        }
+
+        #[tokio::test]
+        async fn unknown_tiers_are_rejected() {
+            let users = Arc::new(InMemoryUserRepository::default());
+
+            assert!(matches!(
+                create(&users, Some("enterprise")).await,
+                Err(ApplicationError::BadRequest(message)) if message.contains("enterprise")
+            ));
+        }
     }
 }
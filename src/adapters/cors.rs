@@ -0,0 +1,269 @@
+use axum::http::{HeaderName, HeaderValue, Method};
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, Any, CorsLayer};
+use tracing::{info, warn};
+
+use crate::adapters::middleware::REQUEST_ID_HEADER;
+
+/// Métodos permitidos cuando CORS_ALLOWED_METHODS no se fija
+const DEFAULT_METHODS: [Method; 6] = [
+    Method::GET,
+    Method::HEAD,
+    Method::POST,
+    Method::PUT,
+    Method::PATCH,
+    Method::DELETE,
+];
+
+/// Lista separada por comas; `*` (o ausente, según el caso) = cualquiera
+#[derive(Debug, PartialEq)]
+enum Rule<T> {
+    Any,
+    List(Vec<T>),
+}
+
+/// CORS_ALLOWED_ORIGINS, CORS_ALLOWED_METHODS, CORS_ALLOWED_HEADERS, CORS_ALLOW_CREDENTIALS
+/// y CORS_DEV_MODE ya validados
+#[derive(Debug)]
+struct CorsSettings {
+    dev_mode: bool,
+    /// None = sin orígenes: el navegador solo permite peticiones del mismo origen
+    origins: Option<Rule<HeaderValue>>,
+    methods: Rule<Method>,
+    headers: Rule<HeaderName>,
+    credentials: bool,
+}
+
+impl CorsSettings {
+    fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+        Self::parse(
+            var("CORS_DEV_MODE").as_deref(),
+            var("CORS_ALLOWED_ORIGINS").as_deref(),
+            var("CORS_ALLOWED_METHODS").as_deref(),
+            var("CORS_ALLOWED_HEADERS").as_deref(),
+            var("CORS_ALLOW_CREDENTIALS").as_deref(),
+        )
+    }
+
+    /// Entra en pánico ante valores inválidos o un origen comodín con credenciales
+    fn parse(
+        dev_mode: Option<&str>,
+        origins: Option<&str>,
+        methods: Option<&str>,
+        headers: Option<&str>,
+        credentials: Option<&str>,
+    ) -> Self {
+        let parse_bool = |value: Option<&str>, name: &str| {
+            value.is_some_and(|v| {
+                v.trim()
+                    .parse::<bool>()
+                    .unwrap_or_else(|_| panic!("{} must be true or false", name))
+            })
+        };
+        let dev_mode = parse_bool(dev_mode, "CORS_DEV_MODE");
+        let credentials = parse_bool(credentials, "CORS_ALLOW_CREDENTIALS");
+
+        let origins = origins.map(|v| {
+            parse_rule(v, |origin| {
+                HeaderValue::from_str(origin)
+                    .unwrap_or_else(|_| panic!("Invalid CORS origin: {}", origin))
+            })
+        });
+        let methods = methods.map_or_else(
+            || Rule::List(DEFAULT_METHODS.to_vec()),
+            |v| {
+                parse_rule(v, |method| {
+                    Method::from_bytes(method.to_ascii_uppercase().as_bytes())
+                        .unwrap_or_else(|_| panic!("Invalid CORS method: {}", method))
+                })
+            },
+        );
+        let headers = headers.map_or(Rule::Any, |v| {
+            parse_rule(v, |header| {
+                HeaderName::from_bytes(header.as_bytes())
+                    .unwrap_or_else(|_| panic!("Invalid CORS header: {}", header))
+            })
+        });
+
+        // El navegador descarta las credenciales con `*`; mejor fallar al arrancar
+        if credentials && (dev_mode || origins == Some(Rule::Any)) {
+            panic!(
+                "CORS_ALLOW_CREDENTIALS=true cannot be combined with a wildcard origin \
+                 (CORS_ALLOWED_ORIGINS=* or CORS_DEV_MODE=true); list the allowed origins"
+            );
+        }
+
+        Self {
+            dev_mode,
+            origins,
+            methods,
+            headers,
+            credentials,
+        }
+    }
+
+    fn into_layer(self) -> CorsLayer {
+        if self.dev_mode {
+            warn!("CORS_DEV_MODE enabled: allowing every origin, method and header");
+            return CorsLayer::permissive();
+        }
+
+        let Some(origins) = self.origins else {
+            info!("CORS_ALLOWED_ORIGINS not set: cross-origin requests are not allowed");
+            return CorsLayer::new();
+        };
+
+        // Con credenciales `*` no es válido en métodos ni cabeceras: se reflejan los pedidos
+        let methods = match self.methods {
+            Rule::Any if self.credentials => AllowMethods::mirror_request(),
+            Rule::Any => AllowMethods::from(Any),
+            Rule::List(methods) => AllowMethods::list(methods),
+        };
+        let headers = match self.headers {
+            Rule::Any if self.credentials => AllowHeaders::mirror_request(),
+            Rule::Any => AllowHeaders::from(Any),
+            Rule::List(headers) => AllowHeaders::list(headers),
+        };
+        let origins = match origins {
+            Rule::Any => AllowOrigin::from(Any),
+            Rule::List(origins) => AllowOrigin::list(origins),
+        };
+
+        CorsLayer::new()
+            .allow_origin(origins)
+            .allow_methods(methods)
+            .allow_headers(headers)
+            .allow_credentials(self.credentials)
+            .expose_headers([HeaderName::from_static(REQUEST_ID_HEADER)])
+    }
+}
+
+fn parse_rule<T>(value: &str, parse: impl Fn(&str) -> T) -> Rule<T> {
+    let items: Vec<&str> = value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect();
+    if items.contains(&"*") {
+        Rule::Any
+    } else {
+        Rule::List(items.into_iter().map(parse).collect())
+    }
+}
+
+/// Capa CORS a partir del entorno; el modo permisivo solo con CORS_DEV_MODE=true
+pub fn build_cors_layer() -> CorsLayer {
+    CorsSettings::from_env().into_layer()
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{http::header, routing::get, Router};
+
+    use super::*;
+
+    fn with_origins(origins: Option<&str>, credentials: Option<&str>) -> CorsSettings {
+        CorsSettings::parse(None, origins, None, None, credentials)
+    }
+
+    /// Preflight desde `origin` contra un router con la capa; devuelve las cabeceras de respuesta
+    async fn preflight(settings: CorsSettings, origin: &str) -> reqwest::header::HeaderMap {
+        let app = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .layer(settings.into_layer());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        reqwest::Client::new()
+            .request(Method::OPTIONS, url)
+            .header(header::ORIGIN, origin)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "DELETE")
+            .header(header::ACCESS_CONTROL_REQUEST_HEADERS, "x-vk-secret")
+            .send()
+            .await
+            .unwrap()
+            .headers()
+            .clone()
+    }
+
+    #[test]
+    fn defaults_allow_no_origin_and_the_standard_methods() {
+        let settings = CorsSettings::parse(None, None, None, None, None);
+        assert_eq!(settings.origins, None);
+        assert_eq!(settings.methods, Rule::List(DEFAULT_METHODS.to_vec()));
+        assert_eq!(settings.headers, Rule::Any);
+        assert!(!settings.credentials && !settings.dev_mode);
+    }
+
+    #[test]
+    fn parses_lists_and_wildcards() {
+        let settings = CorsSettings::parse(
+            Some("false"),
+            Some(" https://a.example , https://b.example,"),
+            Some("get, post"),
+            Some("*"),
+            Some("true"),
+        );
+        assert_eq!(
+            settings.origins,
+            Some(Rule::List(vec![
+                HeaderValue::from_static("https://a.example"),
+                HeaderValue::from_static("https://b.example"),
+            ]))
+        );
+        assert_eq!(
+            settings.methods,
+            Rule::List(vec![Method::GET, Method::POST])
+        );
+        assert_eq!(settings.headers, Rule::Any);
+        assert!(settings.credentials);
+        assert_eq!(with_origins(Some("*"), None).origins, Some(Rule::Any));
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot be combined with a wildcard origin")]
+    fn credentials_with_a_wildcard_origin_fail_at_startup() {
+        with_origins(Some("*"), Some("true"));
+    }
+
+    #[test]
+    #[should_panic(expected = "CORS_ALLOW_CREDENTIALS must be true or false")]
+    fn invalid_booleans_fail_at_startup() {
+        with_origins(Some("https://a.example"), Some("yes"));
+    }
+
+    #[tokio::test]
+    async fn credentials_mirror_the_requested_headers() {
+        let headers = preflight(
+            CorsSettings::parse(
+                None,
+                Some("https://a.example"),
+                Some("*"),
+                None,
+                Some("true"),
+            ),
+            "https://a.example",
+        )
+        .await;
+
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://a.example"
+        );
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_METHODS], "DELETE");
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_HEADERS], "x-vk-secret");
+    }
+
+    #[tokio::test]
+    async fn unlisted_origins_get_no_cors_headers() {
+        let headers = preflight(
+            with_origins(Some("https://a.example"), None),
+            "https://evil.example",
+        )
+        .await;
+
+        assert!(headers.get(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+    }
+}
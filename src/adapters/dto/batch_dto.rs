@@ -0,0 +1,124 @@
+use axum::{http::StatusCode, response::IntoResponse};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::application::error::ApplicationError;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BatchItemResult<T> {
+    pub id: String,
+    /// Código HTTP que habría devuelto la operación sobre este elemento
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Resultado por elemento de una operación por lotes
+///
+/// Se responde `200 OK` si todos los elementos tuvieron éxito y `207 Multi-Status`
+/// si alguno falló; cada elemento lleva su propio código.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BatchResult<T> {
+    pub items: Vec<BatchItemResult<T>>,
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+impl<T> Default for BatchResult<T> {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            succeeded: 0,
+            failed: 0,
+        }
+    }
+}
+
+impl<T> BatchResult<T> {
+    pub fn push_ok(&mut self, id: impl Into<String>, status: StatusCode, data: Option<T>) {
+        self.succeeded += 1;
+        self.items.push(BatchItemResult {
+            id: id.into(),
+            status: status.as_u16(),
+            data,
+            error: None,
+        });
+    }
+
+    pub fn push_err(&mut self, id: impl Into<String>, error: ApplicationError) {
+        let message = format!("{:?}", error);
+        // Reutiliza el mapeo de ApplicationError a código HTTP
+        let status = error.into_response().status();
+        self.failed += 1;
+        self.items.push(BatchItemResult {
+            id: id.into(),
+            status: status.as_u16(),
+            data: None,
+            error: Some(message),
+        });
+    }
+
+    pub fn status_code(&self) -> StatusCode {
+        if self.failed == 0 {
+            StatusCode::OK
+        } else {
+            StatusCode::MULTI_STATUS
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_succeeded_is_ok() {
+        let mut result = BatchResult::default();
+        result.push_ok("a", StatusCode::NO_CONTENT, None::<()>);
+        result.push_ok("b", StatusCode::NO_CONTENT, None);
+
+        assert_eq!(result.status_code(), StatusCode::OK);
+        assert_eq!((result.succeeded, result.failed), (2, 0));
+    }
+
+    #[test]
+    fn partial_failure_is_multi_status() {
+        let mut result = BatchResult::default();
+        result.push_ok("a", StatusCode::OK, Some("https://signed/a".to_string()));
+        result.push_err("b", ApplicationError::NotFound);
+
+        assert_eq!(result.status_code(), StatusCode::MULTI_STATUS);
+        assert_eq!((result.succeeded, result.failed), (1, 1));
+        let statuses: Vec<_> = result.items.iter().map(|item| item.status).collect();
+        assert_eq!(statuses, [200, 404]);
+    }
+
+    #[test]
+    fn all_failed_is_multi_status() {
+        let mut result = BatchResult::<()>::default();
+        result.push_err("a", ApplicationError::NotFound);
+        result.push_err("b", ApplicationError::BadRequest("bad id".to_string()));
+
+        assert_eq!(result.status_code(), StatusCode::MULTI_STATUS);
+        assert_eq!((result.succeeded, result.failed), (0, 2));
+        assert_eq!(result.items[1].status, 400);
+    }
+
+    #[test]
+    fn items_omit_absent_data_and_error() {
+        let mut result = BatchResult::default();
+        result.push_ok("a", StatusCode::OK, Some(1));
+        result.push_err("b", ApplicationError::NotFound);
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(
+            json["items"][0],
+            serde_json::json!({"id": "a", "status": 200, "data": 1})
+        );
+        assert_eq!(json["items"][1]["status"], 404);
+        assert!(json["items"][1].get("data").is_none());
+        assert!(json["items"][1]["error"].is_string());
+    }
+}
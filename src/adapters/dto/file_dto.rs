@@ -1,9 +1,19 @@
 use chrono::{DateTime, Utc};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
 
-use crate::domain::models::metadata::Metadata;
+use crate::{
+    adapters::dto::batch_dto::BatchResult,
+    domain::models::{
+        file::FileMetadata,
+        metadata::{ClientStats, FileStats, Metadata},
+        upload_session::UploadSession,
+    },
+};
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct UploadFileResponse {
     #[serde(rename = "fileId")]
     pub file_id: String,
@@ -15,6 +25,11 @@ pub struct UploadFileResponse {
     pub uploaded_at: DateTime<Utc>,
     #[serde(rename = "deleteAt")]
     pub delete_at: Option<DateTime<Utc>>,
+    /// El contenido ya estaba almacenado y se reutilizó su objeto en lugar de subirlo
+    pub deduplicated: bool,
+    /// SHA-256 en hex de los bytes recibidos
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
 }
 
 impl From<Metadata> for UploadFileResponse {
@@ -26,11 +41,60 @@ impl From<Metadata> for UploadFileResponse {
             filename: metadata.file_name,
             uploaded_at: metadata.uploaded_at,
             delete_at: metadata.delete_at,
+            deduplicated: metadata.object_id.is_some(),
+            checksum: metadata.content_hash,
         }
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct InitChunkedUploadRequest {
+    pub filename: String,
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+    /// `temporal` o `permanent`, como el campo type de la subida directa
+    #[serde(rename = "type")]
+    pub file_type: String,
+    #[serde(rename = "userId")]
+    pub user_id: Option<String>,
+    pub description: Option<String>,
+    /// Tamaño total previsto; permite rechazar el archivo antes de recibir bloques
+    pub size: Option<u64>,
+    /// Bucket (Supabase) o carpeta (Google Drive) adicional; sin él se usa el principal
+    pub location: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChunkedUploadSessionResponse {
+    #[serde(rename = "sessionId")]
+    pub session_id: String,
+    #[serde(rename = "chunkSize")]
+    pub chunk_size: u64,
+    #[serde(rename = "maxSize")]
+    pub max_size: u64,
+    #[serde(rename = "receivedBytes")]
+    pub received_bytes: u64,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: DateTime<Utc>,
+    /// Solo al abrir la sesión: se envía en X-Upload-Session-Secret para continuarla
+    #[serde(rename = "sessionSecret", skip_serializing_if = "Option::is_none")]
+    pub session_secret: Option<String>,
+}
+
+impl From<UploadSession> for ChunkedUploadSessionResponse {
+    fn from(session: UploadSession) -> Self {
+        Self {
+            session_id: session.session_id,
+            chunk_size: session.chunk_size,
+            max_size: session.max_size,
+            received_bytes: session.received_bytes,
+            expires_at: session.expires_at,
+            session_secret: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateFileRequest {
     pub description: Option<String>,
     #[serde(rename = "fileName")]
@@ -39,7 +103,41 @@ pub struct UpdateFileRequest {
     pub delete_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TransferFileRequest {
+    /// Usuario que pasa a ser dueño del archivo
+    #[serde(rename = "toUserId")]
+    pub to_user_id: Uuid,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DownloadQuery {
+    /// Nombre para Content-Disposition; la metadata almacenada no cambia
+    pub filename: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ScheduleExpiryRequest {
+    /// Limita el cambio a estos archivos; todos los permanentes del usuario si se omite
+    #[serde(rename = "fileIds")]
+    pub file_ids: Option<Vec<String>>,
+    /// Solo archivos subidos antes de esta fecha
+    #[serde(rename = "olderThan")]
+    pub older_than: Option<DateTime<Utc>>,
+    #[serde(rename = "deleteAt")]
+    pub delete_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ScheduleExpiryResponse {
+    #[serde(rename = "scheduledCount")]
+    pub scheduled_count: u64,
+    #[serde(rename = "deleteAt")]
+    pub delete_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct FileResponse {
     #[serde(rename = "fileId")]
     pub file_id: String,
@@ -61,6 +159,39 @@ pub struct FileResponse {
     pub last_access: DateTime<Utc>,
     #[serde(rename = "deleteAt")]
     pub delete_at: Option<DateTime<Utc>>,
+    #[serde(rename = "clientId", skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(rename = "trashedAt", skip_serializing_if = "Option::is_none")]
+    pub trashed_at: Option<DateTime<Utc>>,
+    #[serde(rename = "downloadUrl", skip_serializing_if = "Option::is_none")]
+    pub download_url: Option<String>,
+}
+
+impl FileResponse {
+    /// Enlace de descarga absoluto a partir del server_url de la instancia; vacío lo omite
+    pub fn with_download_url(mut self, server_url: &str) -> Self {
+        let base = server_url.trim().trim_end_matches('/');
+        if !base.is_empty() {
+            self.download_url = Some(content_url(base, &self.file_id));
+        }
+        self
+    }
+}
+
+/// Caracteres no reservados de RFC 3986; el resto se escapa dentro de un segmento
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// URL de descarga a través del servicio con el file_id escapado como un único segmento
+pub fn content_url(base: &str, file_id: &str) -> String {
+    format!(
+        "{}/api/v1/files/{}/content",
+        base.trim().trim_end_matches('/'),
+        utf8_percent_encode(file_id, PATH_SEGMENT)
+    )
 }
 
 impl From<Metadata> for FileResponse {
@@ -77,13 +208,229 @@ impl From<Metadata> for FileResponse {
             download_count: metadata.download_count,
             last_access: metadata.last_access,
             delete_at: metadata.delete_at,
+            client_id: metadata.client_id,
+            trashed_at: metadata.trashed_at,
+            download_url: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DeleteFileQuery {
+    /// Borra el archivo definitivamente en lugar de moverlo a la papelera
+    #[serde(default)]
+    pub permanent: bool,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ReconcileQuery {
+    /// Limita la comparación a los file_id con este prefijo
+    pub prefix: Option<String>,
+}
+
+/// Objeto del proveedor sin fila en application.metadata
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OrphanedObject {
+    #[serde(rename = "fileId")]
+    pub file_id: String,
+    pub size: u64,
+    #[serde(rename = "fileName", skip_serializing_if = "Option::is_none")]
+    pub file_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+}
+
+impl From<FileMetadata> for OrphanedObject {
+    fn from(object: FileMetadata) -> Self {
+        Self {
+            file_id: object.file_id,
+            size: object.size,
+            file_name: object.filename,
+            checksum: object.checksum,
         }
     }
 }
 
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReconcileResponse {
+    /// Objetos almacenados que ninguna fila de metadata referencia
+    #[serde(rename = "orphanedObjects")]
+    pub orphaned_objects: Vec<OrphanedObject>,
+    /// Filas de esta instancia cuyo objeto ya no está en el proveedor
+    #[serde(rename = "missingObjects")]
+    pub missing_objects: Vec<FileResponse>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct CleanupResponse {
     #[serde(rename = "deletedCount")]
     pub deleted_count: usize,
     pub errors: Vec<String>,
+    pub results: BatchResult<()>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SignedUrlsBatchRequest {
+    #[serde(rename = "fileIds")]
+    pub file_ids: Vec<String>,
+    /// Segundos de validez de las URLs firmadas; 3600 si se omite
+    #[serde(rename = "expiresIn")]
+    pub expires_in: Option<u64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SignedUrlsBatchResponse {
+    /// Por archivo, la URL firmada del proveedor o la URL de descarga del servicio si no las emite
+    #[serde(flatten)]
+    pub results: BatchResult<String>,
+    #[serde(rename = "expiresIn")]
+    pub expires_in: u64,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BatchDeleteRequest {
+    #[serde(rename = "fileIds")]
+    pub file_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BatchDeleteFailure {
+    #[serde(rename = "fileId")]
+    pub file_id: String,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BatchDeleteResponse {
+    /// Archivos borrados del almacenamiento y de los metadatos
+    pub deleted: Vec<String>,
+    /// Archivos que no se pudieron borrar (inexistentes, ids inválidos, fallos del proveedor)
+    pub failed: Vec<BatchDeleteFailure>,
+}
+
+impl From<BatchResult<()>> for BatchDeleteResponse {
+    fn from(results: BatchResult<()>) -> Self {
+        let mut response = Self {
+            deleted: Vec::with_capacity(results.succeeded),
+            failed: Vec::with_capacity(results.failed),
+        };
+        for item in results.items {
+            match item.error {
+                Some(error) => response.failed.push(BatchDeleteFailure {
+                    file_id: item.id,
+                    error,
+                }),
+                None => response.deleted.push(item.id),
+            }
+        }
+        response
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SignedUrlQuery {
+    /// Segundos de validez; 3600 si se omite, recortado a SIGNED_URL_MAX_TTL_SECONDS
+    pub ttl: Option<u64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SignedUrlResponse {
+    #[serde(rename = "fileId")]
+    pub file_id: String,
+    pub url: String,
+    /// false: el proveedor no firma URLs y `url` es la descarga a través del servicio
+    pub direct: bool,
+    /// Validez aplicada a la URL firmada; ausente cuando `direct` es false
+    #[serde(rename = "expiresIn", skip_serializing_if = "Option::is_none")]
+    pub expires_in: Option<u64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FileLinksResponse {
+    #[serde(rename = "fileId")]
+    pub file_id: String,
+    #[serde(rename = "webViewLink")]
+    pub web_view_link: Option<String>,
+    #[serde(rename = "webContentLink")]
+    pub web_content_link: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FileStatsResponse {
+    #[serde(rename = "downloadCount")]
+    pub download_count: u64,
+    #[serde(rename = "lastAccess")]
+    pub last_access: DateTime<Utc>,
+    pub size: u64,
+}
+
+impl From<FileStats> for FileStatsResponse {
+    fn from(stats: FileStats) -> Self {
+        Self {
+            download_count: stats.download_count,
+            last_access: stats.last_access,
+            size: stats.size,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ClientStatsResponse {
+    /// null agrupa los archivos subidos sin X-Client-Id
+    #[serde(rename = "clientId")]
+    pub client_id: Option<String>,
+    #[serde(rename = "fileCount")]
+    pub file_count: u64,
+    #[serde(rename = "totalSize")]
+    pub total_size: u64,
+    #[serde(rename = "downloadCount")]
+    pub download_count: u64,
+}
+
+impl From<ClientStats> for ClientStatsResponse {
+    fn from(stats: ClientStats) -> Self {
+        Self {
+            client_id: stats.client_id,
+            file_count: stats.file_count,
+            total_size: stats.total_size,
+            download_count: stats.download_count,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_url_keeps_plain_ids_untouched() {
+        let id = "0b6f1e9c-54a1-4c8e-9d0e-2f0c1a7b3d4e";
+        assert_eq!(
+            content_url("https://vk.example.com/", id),
+            format!("https://vk.example.com/api/v1/files/{id}/content")
+        );
+    }
+
+    #[test]
+    fn content_url_escapes_the_id_as_one_segment() {
+        assert_eq!(
+            content_url("https://vk.example.com", "2025/12/report final.pdf"),
+            "https://vk.example.com/api/v1/files/2025%2F12%2Freport%20final.pdf/content"
+        );
+        assert_eq!(
+            content_url("", "../a?b#c"),
+            "/api/v1/files/..%2Fa%3Fb%23c/content"
+        );
+    }
+
+    #[test]
+    fn content_url_encodes_unicode_as_utf8() {
+        assert_eq!(
+            content_url("http://localhost:8080", "año"),
+            "http://localhost:8080/api/v1/files/a%C3%B1o/content"
+        );
+    }
 }
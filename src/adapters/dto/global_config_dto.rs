@@ -1,14 +1,65 @@
-use sqlx::{postgres::PgRow, FromRow, Row};
+use std::collections::HashMap;
 
-use crate::application::dto::global_config_dto::GlobalConfigDTO;
+use sqlx::{postgres::PgRow, types::Json, FromRow, Row};
+
+use crate::{
+    application::dto::global_config_dto::GlobalConfigDTO, domain::config::global::UserTier,
+};
 
 impl FromRow<'_, PgRow> for GlobalConfigDTO {
     fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
-        let mime_types: Vec<String> = row.try_get("mime_types")?;
+        // Un text[] NULL se trata como lista vacía
+        let mime_types: Vec<String> = row
+            .try_get::<Option<Vec<String>>, _>("mime_types")?
+            .unwrap_or_default();
         let max_size: i64 = row.try_get("max_size")?;
         let chunk_size: i64 = row.try_get("chunk_size")?;
         let temp_file_life: i64 = row.try_get("temp_file_life")?;
         let default_quota: i64 = row.try_get("default_quota")?;
+        // Columna opcional: instalaciones sin migrar no tienen límites por mime type
+        let mime_size_limits =
+            match row.try_get::<Option<Json<HashMap<String, u64>>>, _>("mime_size_limits") {
+                Ok(limits) => limits.map(|Json(limits)| limits).unwrap_or_default(),
+                Err(sqlx::Error::ColumnNotFound(_)) => HashMap::new(),
+                Err(e) => return Err(e),
+            };
+        // Columna opcional: sin ella (o a NULL) se usa la retención por defecto
+        let trash_retention = match row.try_get::<Option<i64>, _>("trash_retention") {
+            Ok(trash_retention) => trash_retention.map(|secs| secs as u64),
+            Err(sqlx::Error::ColumnNotFound(_)) => None,
+            Err(e) => return Err(e),
+        };
+        // Columna opcional: sin ella el contenido no se contrasta con el mime type
+        let strict_mime_check = match row.try_get::<Option<bool>, _>("strict_mime_check") {
+            Ok(strict_mime_check) => strict_mime_check,
+            Err(sqlx::Error::ColumnNotFound(_)) => None,
+            Err(e) => return Err(e),
+        };
+        // Columna opcional: sin ella las subidas siempre se acumulan antes de enviarse
+        let stream_uploads = match row.try_get::<Option<bool>, _>("stream_uploads") {
+            Ok(stream_uploads) => stream_uploads,
+            Err(sqlx::Error::ColumnNotFound(_)) => None,
+            Err(e) => return Err(e),
+        };
+        // Columna opcional: sin ella se admiten subidas anónimas
+        let require_user_id = match row.try_get::<Option<bool>, _>("require_user_id") {
+            Ok(require_user_id) => require_user_id,
+            Err(sqlx::Error::ColumnNotFound(_)) => None,
+            Err(e) => return Err(e),
+        };
+        // Columna opcional: sin ella la descarga por nombre sirve el archivo más reciente
+        let allow_duplicate_names = match row.try_get::<Option<bool>, _>("allow_duplicate_names") {
+            Ok(allow_duplicate_names) => allow_duplicate_names,
+            Err(sqlx::Error::ColumnNotFound(_)) => None,
+            Err(e) => return Err(e),
+        };
+
+        // Columna opcional: sin ella no hay planes y todos los usuarios usan default_quota
+        let tiers = match row.try_get::<Option<Json<HashMap<String, UserTier>>>, _>("tiers") {
+            Ok(tiers) => tiers.map(|Json(tiers)| tiers).unwrap_or_default(),
+            Err(sqlx::Error::ColumnNotFound(_)) => HashMap::new(),
+            Err(e) => return Err(e),
+        };
 
         Ok(GlobalConfigDTO {
             mime_types: Some(mime_types),
@@ -16,6 +67,13 @@ impl FromRow<'_, PgRow> for GlobalConfigDTO {
             chunk_size: Some(chunk_size as u64),
             temp_file_life: Some(temp_file_life as u64),
             default_quota: Some(default_quota as u64),
+            mime_size_limits: Some(mime_size_limits),
+            trash_retention,
+            strict_mime_check,
+            stream_uploads,
+            require_user_id,
+            allow_duplicate_names,
+            tiers: Some(tiers),
         })
     }
 }
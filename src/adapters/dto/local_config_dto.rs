@@ -1,6 +1,13 @@
 use sqlx::{postgres::PgRow, FromRow, Row};
+use url::Url;
 
-use crate::{application::dto::local_config_dto::LocalConfigDTO, domain::config::local::Provider};
+use crate::{
+    application::{dto::local_config_dto::LocalConfigDTO, error::ApplicationError},
+    domain::config::local::Provider,
+};
+
+const MAX_SERVER_NAME_LENGTH: usize = 128;
+const MAX_SERVER_URL_LENGTH: usize = 2048;
 
 impl FromRow<'_, PgRow> for LocalConfigDTO {
     fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
@@ -9,6 +16,9 @@ impl FromRow<'_, PgRow> for LocalConfigDTO {
         let provider = match provider_str.as_str() {
             "gdrive" => Provider::GDrive,
             "supabase" => Provider::Supabase,
+            "s3" => Provider::S3,
+            "azure" => Provider::Azure,
+            "localfs" => Provider::LocalFs,
             _ => {
                 return Err(sqlx::Error::Decode(
                     format!("Unknown provider: {}", provider_str).into(),
@@ -16,14 +26,74 @@ impl FromRow<'_, PgRow> for LocalConfigDTO {
             }
         };
 
+        // Columna opcional: instalaciones sin migrar no tienen default_quota
+        let default_quota = match row.try_get::<Option<i64>, _>("default_quota") {
+            Ok(quota) => quota.map(|q| q as u64),
+            Err(sqlx::Error::ColumnNotFound(_)) => None,
+            Err(e) => return Err(e),
+        };
+
         Ok(LocalConfigDTO {
             provider: Some(provider),
             server_name: Some(row.try_get("server_name")?),
             server_url: Some(row.try_get("server_url")?),
+            default_quota,
         })
     }
 }
 
 impl LocalConfigDTO {
-    pub fn sanitize(&mut self) {}
+    pub fn sanitize(&mut self) {
+        if let Some(default_quota) = self.default_quota {
+            self.default_quota = Some(std::cmp::min(default_quota, i64::MAX as u64));
+        }
+        if let Some(ref mut server_name) = self.server_name {
+            *server_name = server_name.trim().to_string();
+        }
+        if let Some(ref mut server_url) = self.server_url {
+            *server_url = server_url.trim().to_string();
+        }
+    }
+
+    /// serverName sin caracteres de control; serverUrl vacía o http(s) absoluta. Tras sanitize
+    pub fn validate(&self) -> Result<(), ApplicationError> {
+        if let Some(ref server_name) = self.server_name {
+            if server_name.chars().count() > MAX_SERVER_NAME_LENGTH
+                || server_name.chars().any(char::is_control)
+            {
+                return Err(ApplicationError::BadRequest(format!(
+                    "serverName must be at most {} characters without control characters",
+                    MAX_SERVER_NAME_LENGTH
+                )));
+            }
+        }
+        if let Some(ref server_url) = self.server_url {
+            // Vacía: las descargas se enlazan con rutas relativas
+            if !server_url.is_empty() && !is_valid_server_url(server_url) {
+                return Err(ApplicationError::BadRequest(format!(
+                    "serverUrl must be an absolute http or https URL of at most {} characters, \
+                     without query or fragment",
+                    MAX_SERVER_URL_LENGTH
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Base a la que se añade /api/v1/files/...: esquema http(s), host y nada tras la ruta
+fn is_valid_server_url(server_url: &str) -> bool {
+    // Url::parse descarta tabuladores y saltos de línea en silencio
+    if server_url.len() > MAX_SERVER_URL_LENGTH || server_url.chars().any(char::is_control) {
+        return false;
+    }
+    match Url::parse(server_url) {
+        Ok(url) => {
+            matches!(url.scheme(), "http" | "https")
+                && url.host_str().is_some()
+                && url.query().is_none()
+                && url.fragment().is_none()
+        }
+        Err(_) => false,
+    }
 }
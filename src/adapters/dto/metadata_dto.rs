@@ -6,6 +6,21 @@ impl FromRow<'_, PgRow> for MetadataDTO {
     fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
         let size: i64 = row.try_get("size")?;
         let download_count: i64 = row.try_get("download_count")?;
+        let trashed_at = match row.try_get("trashed_at") {
+            Ok(trashed_at) => trashed_at,
+            Err(sqlx::Error::ColumnNotFound(_)) => None,
+            Err(e) => return Err(e),
+        };
+        let content_hash = match row.try_get("content_hash") {
+            Ok(content_hash) => content_hash,
+            Err(sqlx::Error::ColumnNotFound(_)) => None,
+            Err(e) => return Err(e),
+        };
+        let object_id = match row.try_get("object_id") {
+            Ok(object_id) => object_id,
+            Err(sqlx::Error::ColumnNotFound(_)) => None,
+            Err(e) => return Err(e),
+        };
 
         Ok(MetadataDTO {
             file_id: row.try_get("file_id")?,
@@ -19,6 +34,10 @@ impl FromRow<'_, PgRow> for MetadataDTO {
             download_count: Some(download_count as u64),
             last_access: Some(row.try_get("last_access")?),
             delete_at: row.try_get("delete_at")?,
+            client_id: row.try_get("client_id")?,
+            trashed_at,
+            content_hash,
+            object_id,
         })
     }
 }
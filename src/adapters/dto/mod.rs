@@ -1,3 +1,4 @@
+pub mod batch_dto;
 pub mod file_dto;
 pub mod global_config_dto;
 pub mod local_config_dto;
@@ -2,7 +2,9 @@ use sqlx::{postgres::PgRow, FromRow, Row};
 
 use crate::{
     application::dto::secrets_dto::SecretsDTO,
-    domain::config::secrets::{GDriveSecrets, SupabaseSecrets},
+    domain::config::secrets::{
+        AzureSecrets, GDriveSecrets, LocalFsSecrets, S3Secrets, SupabaseSecrets,
+    },
 };
 
 impl FromRow<'_, PgRow> for SecretsDTO {
@@ -24,12 +26,45 @@ impl FromRow<'_, PgRow> for SecretsDTO {
                 None => None,
             };
 
+        // Columna opcional: instalaciones sin migrar no tienen s3_secrets
+        let s3_secrets: Option<S3Secrets> =
+            match row.try_get::<Option<sqlx::types::JsonValue>, _>("s3_secrets") {
+                Ok(Some(json)) => Some(
+                    serde_json::from_value(json).map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+                ),
+                Ok(None) | Err(sqlx::Error::ColumnNotFound(_)) => None,
+                Err(e) => return Err(e),
+            };
+
+        // Columna opcional: instalaciones sin migrar no tienen azure_secrets
+        let azure_secrets: Option<AzureSecrets> =
+            match row.try_get::<Option<sqlx::types::JsonValue>, _>("azure_secrets") {
+                Ok(Some(json)) => Some(
+                    serde_json::from_value(json).map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+                ),
+                Ok(None) | Err(sqlx::Error::ColumnNotFound(_)) => None,
+                Err(e) => return Err(e),
+            };
+
+        // Columna opcional: instalaciones sin migrar no tienen localfs_secrets
+        let local_fs_secrets: Option<LocalFsSecrets> =
+            match row.try_get::<Option<sqlx::types::JsonValue>, _>("localfs_secrets") {
+                Ok(Some(json)) => Some(
+                    serde_json::from_value(json).map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+                ),
+                Ok(None) | Err(sqlx::Error::ColumnNotFound(_)) => None,
+                Err(e) => return Err(e),
+            };
+
         Ok(SecretsDTO {
             db_password: Some(row.try_get("db_password")?),
             db_username: Some(row.try_get("db_username")?),
             vk_secret: Some(row.try_get("vk_secret")?),
             gdrive_secrets,
             supabase_secrets,
+            s3_secrets,
+            azure_secrets,
+            local_fs_secrets,
         })
     }
 }
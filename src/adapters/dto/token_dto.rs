@@ -1,13 +1,22 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TokenResponse {
     pub token: String,
     #[serde(rename = "expiresIn")]
     pub expires_in: u64,
+    #[serde(rename = "allowedMimeTypes", skip_serializing_if = "Option::is_none")]
+    pub allowed_mime_types: Option<Vec<String>>,
+    #[serde(rename = "maxSize", skip_serializing_if = "Option::is_none")]
+    pub max_size: Option<u64>,
+    #[serde(rename = "allowedType", skip_serializing_if = "Option::is_none")]
+    pub allowed_type: Option<String>,
+    #[serde(rename = "userId", skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Deserialize, Default, ToSchema)]
 pub struct GenerateTokenRequest {
     #[serde(rename = "userId")]
     pub user_id: Option<String>,
@@ -7,11 +7,18 @@ impl FromRow<'_, PgRow> for UserDTO {
         let file_count: i64 = row.try_get("file_count")?;
         let total_space: i64 = row.try_get("total_space")?;
         let used_space: i64 = row.try_get("used_space")?;
+        // Columna opcional: instalaciones sin migrar no guardan el plan
+        let tier = match row.try_get::<Option<String>, _>("tier") {
+            Ok(tier) => tier,
+            Err(sqlx::Error::ColumnNotFound(_)) => None,
+            Err(e) => return Err(e),
+        };
         Ok(UserDTO {
             uid: row.try_get("uid")?,
             file_count: Some(file_count as u64),
             total_space: Some(total_space as u64),
             used_space: Some(used_space as u64),
+            tier,
         })
     }
 }
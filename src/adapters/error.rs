@@ -1,47 +1,196 @@
 use axum::{
-    http::StatusCode,
+    extract::{multipart::MultipartRejection, rejection::JsonRejection},
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
 use serde_json::json;
 use tracing::{error, warn};
 
-use crate::application::error::ApplicationError;
+use crate::{adapters::error_metrics::ErrorOutcome, application::error::ApplicationError};
 
 impl IntoResponse for ApplicationError {
     fn into_response(self) -> Response {
-        let (status, error_message) = match self {
+        if let ApplicationError::RetriesExhausted { attempts, error } = self {
+            warn!("Storage operation failed after {} attempts", attempts);
+            let mut response = error.into_response();
+            response
+                .headers_mut()
+                .insert("X-Retry-Attempts", HeaderValue::from(attempts));
+            return response;
+        }
+
+        let outcome = match self {
+            ApplicationError::InsufficientStorage => Some("insufficient_storage"),
+            ApplicationError::PayloadTooLarge => Some("payload_too_large"),
+            // Los 400 se cuentan por motivo para distinguirlos en /metrics
+            ApplicationError::BadRequest(_) => Some("bad_request_validation"),
+            ApplicationError::InvalidJson(_) => Some("bad_request_invalid_json"),
+            ApplicationError::ChecksumMismatch { .. } => Some("bad_request_checksum_mismatch"),
+            ApplicationError::InvalidToken => Some("invalid_token"),
+            _ => None,
+        };
+
+        let retry_after = match self {
+            ApplicationError::TooManyRequests { retry_after } => retry_after,
+            ApplicationError::StorageDegraded { retry_after } => Some(retry_after),
+            _ => None,
+        };
+
+        let (status, code, error_message) = match self {
             ApplicationError::NotFound => {
                 warn!("Resource not found");
-                (StatusCode::NOT_FOUND, "Resource not found".to_string())
+                (
+                    StatusCode::NOT_FOUND,
+                    "NOT_FOUND",
+                    "Resource not found".to_string(),
+                )
             }
             ApplicationError::BadRequest(ref msg) => {
                 warn!("Bad request: {}", msg);
-                (StatusCode::BAD_REQUEST, "Bad request".to_string())
+                (
+                    StatusCode::BAD_REQUEST,
+                    "BAD_REQUEST",
+                    "Bad request".to_string(),
+                )
+            }
+            ApplicationError::InvalidJson(ref msg) => {
+                warn!("Invalid JSON body: {}", msg);
+                // El detalle (campo, línea y columna) es del propio cuerpo del cliente
+                (StatusCode::BAD_REQUEST, "INVALID_JSON", msg.clone())
+            }
+            ApplicationError::ChecksumMismatch { ref computed } => {
+                warn!(
+                    "Upload checksum mismatch: received bytes hash to {}",
+                    computed
+                );
+                // El cliente necesita saber que el fallo es de integridad para reenviar
+                (
+                    StatusCode::BAD_REQUEST,
+                    "CHECKSUM_MISMATCH",
+                    format!("Checksum mismatch: received content hashes to {}", computed),
+                )
             }
             ApplicationError::Unauthorized => {
                 warn!("Unauthorized access attempt");
-                (StatusCode::UNAUTHORIZED, "Unauthorized".to_string())
+                (
+                    StatusCode::UNAUTHORIZED,
+                    "UNAUTHORIZED",
+                    "Unauthorized".to_string(),
+                )
+            }
+            ApplicationError::Forbidden(ref msg) => {
+                warn!("Forbidden: {}", msg);
+                (StatusCode::FORBIDDEN, "FORBIDDEN", "Forbidden".to_string())
+            }
+            ApplicationError::Conflict(ref msg) => {
+                warn!("Conflict: {}", msg);
+                (StatusCode::CONFLICT, "CONFLICT", msg.clone())
             }
             ApplicationError::InvalidToken => {
                 warn!("Invalid or expired upload token");
-                (StatusCode::UNAUTHORIZED, "Unauthorized".to_string())
+                (
+                    StatusCode::UNAUTHORIZED,
+                    "INVALID_TOKEN",
+                    "Unauthorized".to_string(),
+                )
             }
             ApplicationError::PayloadTooLarge => {
                 warn!("File too large");
-                (StatusCode::PAYLOAD_TOO_LARGE, "File too large".to_string())
+                (
+                    StatusCode::PAYLOAD_TOO_LARGE,
+                    "PAYLOAD_TOO_LARGE",
+                    "File too large".to_string(),
+                )
+            }
+            ApplicationError::UnsupportedMediaType { expected } => {
+                warn!("Unsupported media type, expected {}", expected);
+                (
+                    StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                    "UNSUPPORTED_MEDIA_TYPE",
+                    format!("Unsupported media type, expected {}", expected),
+                )
             }
             ApplicationError::InsufficientStorage => {
                 warn!("Insufficient storage quota");
                 (
                     StatusCode::INSUFFICIENT_STORAGE,
+                    "INSUFFICIENT_STORAGE",
                     "Insufficient storage quota".to_string(),
                 )
             }
+            ApplicationError::UnprocessableEntity(ref msg) => {
+                warn!("Unprocessable entity: {}", msg);
+                (
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "UNPROCESSABLE_ENTITY",
+                    "Unprocessable entity".to_string(),
+                )
+            }
+            ApplicationError::TooManyRequests { retry_after } => {
+                warn!(
+                    "Storage provider rate limited, retry after {:?}s",
+                    retry_after
+                );
+                (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    "TOO_MANY_REQUESTS",
+                    "Too many requests".to_string(),
+                )
+            }
+            ApplicationError::StorageDegraded { retry_after } => {
+                warn!(
+                    "Rejected request while storage is degraded, retry after {}s",
+                    retry_after
+                );
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "STORAGE_UNAVAILABLE",
+                    "Storage provider unavailable".to_string(),
+                )
+            }
+            ApplicationError::RetriesExhausted { .. } => unreachable!("handled above"),
+            ApplicationError::StorageUnavailable(ref msg) => {
+                error!("Storage provider unreachable: {}", msg);
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "STORAGE_UNAVAILABLE",
+                    "Storage provider unavailable".to_string(),
+                )
+            }
+            ApplicationError::UpstreamTimeout(ref msg) => {
+                error!("Storage provider timed out: {}", msg);
+                (
+                    StatusCode::GATEWAY_TIMEOUT,
+                    "UPSTREAM_TIMEOUT",
+                    "Storage provider timed out".to_string(),
+                )
+            }
+            ApplicationError::ServiceUnavailable(ref msg) => {
+                error!("Service dependency unavailable: {}", msg);
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "SERVICE_UNAVAILABLE",
+                    "Service unavailable".to_string(),
+                )
+            }
             ApplicationError::InternalError(ref msg) => {
                 error!("Internal server error: {}", msg);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
+                    "INTERNAL_ERROR",
+                    "Internal server error".to_string(),
+                )
+            }
+            ApplicationError::ProviderUnauthorized(ref msg) => {
+                // Configuración del proveedor, no un fallo transitorio
+                error!(
+                    "Storage provider misconfigured, credentials rejected: {}",
+                    msg
+                );
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "STORAGE_MISCONFIGURED",
                     "Internal server error".to_string(),
                 )
             }
@@ -49,6 +198,7 @@ impl IntoResponse for ApplicationError {
                 error!("Database error: {}", msg);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
+                    "DATABASE_ERROR",
                     "Internal server error".to_string(),
                 )
             }
@@ -56,8 +206,80 @@ impl IntoResponse for ApplicationError {
 
         let body = Json(json!({
             "error": error_message,
+            "code": code,
         }));
 
-        (status, body).into_response()
+        let mut response = match retry_after {
+            Some(seconds) => {
+                (status, [(header::RETRY_AFTER, seconds.to_string())], body).into_response()
+            }
+            None => (status, body).into_response(),
+        };
+        if let Some(outcome) = outcome {
+            response.extensions_mut().insert(ErrorOutcome(outcome));
+        }
+        response
+    }
+}
+
+impl From<MultipartRejection> for ApplicationError {
+    fn from(rejection: MultipartRejection) -> Self {
+        // Falta Content-Type multipart/form-data o su boundary
+        warn!("Multipart request rejected: {}", rejection.body_text());
+        ApplicationError::UnsupportedMediaType {
+            expected: "multipart/form-data with a boundary",
+        }
+    }
+}
+
+impl From<JsonRejection> for ApplicationError {
+    fn from(rejection: JsonRejection) -> Self {
+        match rejection {
+            JsonRejection::JsonDataError(e) => ApplicationError::InvalidJson(e.body_text()),
+            JsonRejection::JsonSyntaxError(e) => ApplicationError::InvalidJson(e.body_text()),
+            JsonRejection::MissingJsonContentType(_) => ApplicationError::UnsupportedMediaType {
+                expected: "application/json",
+            },
+            // Cuerpo por encima de DefaultBodyLimit u otro fallo al leerlo
+            other if other.status() == StatusCode::PAYLOAD_TOO_LARGE => {
+                ApplicationError::PayloadTooLarge
+            }
+            other => ApplicationError::BadRequest(other.body_text()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn render(error: ApplicationError) -> (StatusCode, serde_json::Value) {
+        let response = error.into_response();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn conflict_is_a_409_with_the_detail_message() {
+        let (status, body) = render(ApplicationError::Conflict(
+            "User 42 already exists".to_string(),
+        ))
+        .await;
+
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["code"], "CONFLICT");
+        assert_eq!(body["error"], "User 42 already exists");
+    }
+
+    #[tokio::test]
+    async fn bad_request_keeps_its_generic_message() {
+        let (status, body) = render(ApplicationError::BadRequest("detail".to_string())).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "BAD_REQUEST");
+        assert_eq!(body["error"], "Bad request");
     }
 }
@@ -0,0 +1,37 @@
+use std::{collections::BTreeMap, fmt::Write, sync::Mutex};
+
+/// Resultado de error que se contabiliza, añadido como extensión a la respuesta
+#[derive(Debug, Clone, Copy)]
+pub struct ErrorOutcome(pub &'static str);
+
+/// Rechazos por cuota, tamaño, validación o token, agrupados por endpoint
+#[derive(Default)]
+pub struct ErrorMetrics {
+    counts: Mutex<BTreeMap<(String, &'static str), u64>>,
+}
+
+impl ErrorMetrics {
+    pub fn record(&self, endpoint: &str, outcome: ErrorOutcome) {
+        let mut counts = self.counts.lock().unwrap();
+        *counts.entry((endpoint.to_string(), outcome.0)).or_default() += 1;
+    }
+
+    /// Formato de exposición de texto de Prometheus
+    pub fn render(&self) -> String {
+        let counts = self.counts.lock().unwrap();
+        let mut output = String::from(
+            "# HELP vk_request_errors_total Requests rejected by quota, size, validation or token checks\n\
+             # TYPE vk_request_errors_total counter\n",
+        );
+        for ((endpoint, outcome), count) in counts.iter() {
+            let _ = writeln!(
+                output,
+                "vk_request_errors_total{{endpoint=\"{}\",outcome=\"{}\"}} {}",
+                endpoint.replace('\\', "\\\\").replace('"', "\\\""),
+                outcome,
+                count
+            );
+        }
+        output
+    }
+}
@@ -0,0 +1,87 @@
+use axum::{extract::FromRequest, Json};
+
+use crate::application::error::ApplicationError;
+
+/// Como `Json`, pero un cuerpo mal formado responde con el envoltorio de error estándar
+/// (`INVALID_JSON`) en lugar del texto plano de axum
+#[derive(Debug, FromRequest)]
+#[from_request(via(Json), rejection(ApplicationError))]
+pub struct JsonBody<T>(pub T);
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+        response::IntoResponse,
+    };
+    use serde::Deserialize;
+    use serde_json::Value;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    struct GenerateToken {
+        ttl: u64,
+    }
+
+    async fn extract(
+        content_type: Option<&str>,
+        body: &str,
+    ) -> Result<JsonBody<GenerateToken>, ApplicationError> {
+        let mut request = Request::post("/api/v1/files/token");
+        if let Some(content_type) = content_type {
+            request = request.header(header::CONTENT_TYPE, content_type);
+        }
+        let request = request.body(Body::from(body.to_string())).unwrap();
+        JsonBody::from_request(request, &()).await
+    }
+
+    async fn reject(content_type: Option<&str>, body: &str) -> (StatusCode, Value) {
+        let response = extract(content_type, body)
+            .await
+            .unwrap_err()
+            .into_response();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn valid_json_is_deserialized() {
+        let JsonBody(body) = extract(Some("application/json"), "{\"ttl\": 60}")
+            .await
+            .unwrap();
+        assert_eq!(body.ttl, 60);
+    }
+
+    #[tokio::test]
+    async fn malformed_json_points_at_the_parse_location() {
+        let (status, body) = reject(Some("application/json"), "{\"ttl\": 60,").await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "INVALID_JSON");
+        let message = body["error"].as_str().unwrap();
+        assert!(message.contains("line 1 column"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn wrong_field_type_names_the_field() {
+        let (status, body) = reject(Some("application/json"), "{\"ttl\": \"soon\"}").await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "INVALID_JSON");
+        let message = body["error"].as_str().unwrap();
+        assert!(message.contains("ttl"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn missing_content_type_is_unsupported_media_type() {
+        let (status, body) = reject(None, "{\"ttl\": 60}").await;
+
+        assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(body["code"], "UNSUPPORTED_MEDIA_TYPE");
+    }
+}
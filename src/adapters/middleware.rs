@@ -1,14 +1,28 @@
 use axum::{
     body::Body,
-    extract::{Request, State},
-    http::{HeaderMap, StatusCode},
-    middleware::Next,
+    extract::{DefaultBodyLimit, MatchedPath, Request, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::{from_fn_with_state, Next},
     response::{IntoResponse, Response},
+    routing::MethodRouter,
 };
-use std::sync::{Arc, Mutex};
-use tracing::warn;
+use http_body_util::Limited;
+use std::{
+    convert::Infallible,
+    sync::{Arc, Mutex},
+};
+use tower_http::decompression::RequestDecompressionLayer;
+use tracing::{info_span, warn, Instrument};
+use uuid::Uuid;
 
-use crate::domain::config::secrets::Secrets;
+use crate::{
+    adapters::{
+        authorization::secret_matches,
+        error_metrics::{ErrorMetrics, ErrorOutcome},
+    },
+    application::error::ApplicationError,
+    domain::config::{global::GlobalConfig, secrets::Secrets},
+};
 
 /// Middleware to validate the X-KV-SECRET header
 pub async fn validate_kv_secret(
@@ -26,7 +40,7 @@ pub async fn validate_kv_secret(
         Some(header_value) => {
             match header_value.to_str() {
                 Ok(provided_secret) => {
-                    if provided_secret == expected_secret {
+                    if secret_matches(provided_secret, &expected_secret) {
                         // Secret is valid, continue to the handler
                         next.run(request).await
                     } else {
@@ -49,3 +63,223 @@ pub async fn validate_kv_secret(
         }
     }
 }
+
+/// Cuenta las respuestas de error marcadas con `ErrorOutcome`, etiquetadas por ruta
+///
+/// Debe aplicarse con `route_layer` para que `MatchedPath` esté disponible
+pub async fn record_error_outcomes(
+    State(metrics): State<Arc<ErrorMetrics>>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let endpoint = format!(
+        "{} {}",
+        request.method(),
+        request
+            .extensions()
+            .get::<MatchedPath>()
+            .map_or("unmatched", |path| path.as_str())
+    );
+
+    let response = next.run(request).await;
+    if let Some(outcome) = response.extensions().get::<ErrorOutcome>() {
+        metrics.record(&endpoint, *outcome);
+    }
+    response
+}
+
+/// Correlaciona las líneas de log de una misma petición; se devuelve en la respuesta
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Ids del cliente más largos se sustituyen por uno generado, para no inflar los logs
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+/// Envuelve la petición en un span `request` con su request id y lo devuelve en la respuesta
+///
+/// Reutiliza el `X-Request-Id` del cliente si es ASCII imprimible; si no, genera un UUID v4.
+/// Los logs de tareas lanzadas con `tokio::spawn` no heredan el span.
+pub async fn request_id(request: Request<Body>, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|id| {
+            !id.is_empty()
+                && id.len() <= MAX_REQUEST_ID_LENGTH
+                && id.chars().all(|c| c.is_ascii_graphic())
+        })
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let span = info_span!(
+        "request",
+        request_id = %request_id,
+        method = %request.method(),
+        path = %request.uri().path(),
+    );
+    let mut response = next.run(request).instrument(span).await;
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+/// Límite del cuerpo JSON, medido tras descomprimir (protege contra bombas de compresión)
+const JSON_BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+/// Acepta cuerpos JSON con `Content-Encoding: gzip`/`deflate` en las rutas indicadas
+pub fn json_body<S>(route: MethodRouter<S>) -> MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    route
+        .layer::<_, Infallible>(RequestDecompressionLayer::new())
+        .layer(DefaultBodyLimit::max(JSON_BODY_LIMIT))
+}
+
+/// Margen sobre el tamaño máximo de archivo para el resto de campos y los delimitadores
+const MULTIPART_OVERHEAD: u64 = 64 * 1024;
+
+/// Limita el cuerpo de una subida al mayor tamaño que permite la configuración actual
+///
+/// Rechaza por Content-Length antes de leer nada y corta el cuerpo en cuanto lo supera si
+/// llega sin él (chunked). Se lee en cada petición para seguir a /admin/refresh-config.
+pub async fn upload_body_limit(
+    State(global_config): State<Arc<Mutex<GlobalConfig>>>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let limit = global_config
+        .lock()
+        .unwrap()
+        .max_upload_size()
+        .saturating_add(MULTIPART_OVERHEAD);
+
+    let declared = request
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    if let Some(declared) = declared.filter(|&declared| declared > limit) {
+        warn!(
+            "Rejected upload of {} bytes, limit is {} bytes",
+            declared, limit
+        );
+        return ApplicationError::PayloadTooLarge.into_response();
+    }
+
+    let limit = usize::try_from(limit).unwrap_or(usize::MAX);
+    next.run(request.map(|body| Body::new(Limited::new(body, limit))))
+        .await
+}
+
+/// Sustituye el límite fijo de axum (2 MiB) por `upload_body_limit` en las rutas indicadas
+pub fn upload_body<S>(
+    route: MethodRouter<S>,
+    global_config: Arc<Mutex<GlobalConfig>>,
+) -> MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    route
+        .layer::<_, Infallible>(from_fn_with_state(global_config, upload_body_limit))
+        .layer(DefaultBodyLimit::disable())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use axum::{routing::post, Json, Router};
+    use flate2::{write::GzEncoder, Compression};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::adapters::json_body::JsonBody;
+
+    fn gzip(content: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(content).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    async fn echo(JsonBody(body): JsonBody<serde_json::Value>) -> Json<serde_json::Value> {
+        Json(body)
+    }
+
+    async fn send_gzip(content: &[u8]) -> Response {
+        let app = Router::new().route("/echo", json_body(post(echo)));
+        let request = Request::post("/echo")
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::CONTENT_ENCODING, "gzip")
+            .body(Body::from(gzip(content)))
+            .unwrap();
+        app.oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn gzip_json_bodies_are_decompressed() {
+        let response = send_gzip(br#"{"fileIds": ["a", "b"]}"#).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body, serde_json::json!({ "fileIds": ["a", "b"] }));
+    }
+
+    #[tokio::test]
+    async fn limit_applies_to_the_decompressed_size() {
+        let padding = " ".repeat(JSON_BODY_LIMIT);
+        let bomb = format!("{{{}}}", padding);
+        let compressed = gzip(bomb.as_bytes());
+        assert!(compressed.len() < JSON_BODY_LIMIT / 100);
+
+        let response = send_gzip(bomb.as_bytes()).await;
+
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn bad_requests_are_counted_per_reason() {
+        async fn reject(body: String) -> Result<(), ApplicationError> {
+            Err(match body.as_str() {
+                "json" => ApplicationError::InvalidJson("expected value".to_string()),
+                "checksum" => ApplicationError::ChecksumMismatch {
+                    computed: "abc".to_string(),
+                },
+                _ => ApplicationError::BadRequest("missing field".to_string()),
+            })
+        }
+
+        let metrics = Arc::new(ErrorMetrics::default());
+        let app = Router::new().route("/upload", post(reject)).route_layer(
+            axum::middleware::from_fn_with_state(metrics.clone(), record_error_outcomes),
+        );
+        for reason in ["json", "checksum", "checksum", "form"] {
+            let response = app
+                .clone()
+                .oneshot(Request::post("/upload").body(Body::from(reason)).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+
+        let rendered = metrics.render();
+        for series in [
+            r#"{endpoint="POST /upload",outcome="bad_request_checksum_mismatch"} 2"#,
+            r#"{endpoint="POST /upload",outcome="bad_request_invalid_json"} 1"#,
+            r#"{endpoint="POST /upload",outcome="bad_request_validation"} 1"#,
+        ] {
+            assert!(
+                rendered.contains(series),
+                "{} missing from\n{}",
+                series,
+                rendered
+            );
+        }
+    }
+}
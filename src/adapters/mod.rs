@@ -1,7 +1,20 @@
+pub mod authorization;
+pub mod client_ip;
 pub mod controllers;
+pub mod cors;
 mod dto;
 pub mod error;
+pub mod error_metrics;
+pub mod json_body;
 pub mod middleware;
+mod ndjson;
+mod openapi;
+mod pagination;
 pub mod repositories;
+pub mod startup;
 pub mod state;
 pub mod storage_service_wrapper;
+#[cfg(test)]
+pub mod test_app;
+mod throttled_body;
+pub mod upload_buffer;
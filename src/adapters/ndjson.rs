@@ -0,0 +1,54 @@
+use axum::{
+    body::Body,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::Response,
+};
+use futures_util::{Stream, StreamExt};
+use serde::Serialize;
+use tracing::error;
+
+use crate::application::error::ApplicationError;
+
+pub const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+
+/// El cliente pidió NDJSON mediante `Accept: application/x-ndjson`
+pub fn wants_ndjson(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .map(|accept| {
+            accept
+                .split(',')
+                .any(|media| media.trim().starts_with(NDJSON_CONTENT_TYPE))
+        })
+        .unwrap_or(false)
+}
+
+/// Serializa cada elemento en su propia línea a medida que el stream lo produce
+///
+/// El status ya se envió cuando llega un error a mitad de stream, así que se corta
+/// la respuesta; el cliente ve una última línea incompleta o la conexión cerrada
+pub fn ndjson_response<T, S>(items: S) -> Response
+where
+    T: Serialize,
+    S: Stream<Item = Result<T, ApplicationError>> + Send + 'static,
+{
+    let lines = items.map(|item| {
+        let item = item.map_err(|e| {
+            error!("NDJSON stream aborted: {:?}", e);
+            std::io::Error::other("stream aborted")
+        })?;
+        let mut line = serde_json::to_vec(&item).map_err(std::io::Error::other)?;
+        line.push(b'\n');
+        Ok::<_, std::io::Error>(line)
+    });
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static(NDJSON_CONTENT_TYPE),
+        )
+        .body(Body::from_stream(lines))
+        .unwrap()
+}
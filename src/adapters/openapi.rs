@@ -0,0 +1,230 @@
+//! Especificación OpenAPI generada a partir de los DTOs y de las anotaciones
+//! `#[utoipa::path]` de los handlers
+
+use utoipa::{
+    openapi::security::{ApiKey, ApiKeyValue, SecurityScheme},
+    Modify, OpenApi,
+};
+
+use crate::{
+    adapters::{
+        controllers::{
+            admin_controller::{
+                self, ExportItem, ExportPage, RefreshConfigResponse, ReindexChecksumsResponse,
+                ReindexFailure, ReindexMismatch, VacuumResponse,
+            },
+            file_controller,
+            health_controller::{
+                self, DependencyStatus, EventQueueMetrics, HealthConfigInfo, HealthResponse,
+                ProbeStatus, ReadinessResponse, SystemMetrics,
+            },
+            instance_controller,
+            user_controller::{
+                self, CreateUser, UserFilesPage, UserTokenResponse, UserUsageResponse,
+            },
+        },
+        dto::{
+            file_dto::{
+                BatchDeleteFailure, BatchDeleteRequest, BatchDeleteResponse,
+                ChunkedUploadSessionResponse, ClientStatsResponse, FileLinksResponse, FileResponse,
+                FileStatsResponse, InitChunkedUploadRequest, OrphanedObject, ReconcileResponse,
+                ScheduleExpiryRequest, ScheduleExpiryResponse, SignedUrlResponse,
+                SignedUrlsBatchRequest, SignedUrlsBatchResponse, TransferFileRequest,
+                UpdateFileRequest, UploadFileResponse,
+            },
+            token_dto::{GenerateTokenRequest, TokenResponse},
+        },
+    },
+    application::dto::{local_config_dto::LocalConfigDTO, user_dto::UserDTO},
+    domain::{
+        config::local::{LocalConfig, Provider},
+        models::user::User,
+    },
+    services::ProviderLatencySummary,
+};
+
+const KV_SECRET_SCHEME: &str = "kv_secret";
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "VK-Service API", description = "Servicio de almacenamiento de archivos"),
+    paths(
+        health_controller::health_check,
+        health_controller::liveness,
+        health_controller::readiness,
+        health_controller::metrics,
+        instance_controller::get_all_instances,
+        instance_controller::get_instance,
+        instance_controller::update_instance,
+        admin_controller::refresh_config,
+        admin_controller::export_metadata,
+        admin_controller::reindex_checksums,
+        admin_controller::client_stats,
+        admin_controller::vacuum,
+        user_controller::create_user,
+        user_controller::get_user,
+        user_controller::update_user,
+        user_controller::delete_user,
+        user_controller::issue_user_token,
+        user_controller::get_user_files,
+        user_controller::get_user_usage,
+        user_controller::schedule_expiry,
+        file_controller::download_file_by_name,
+        file_controller::generate_upload_token,
+        file_controller::upload_file,
+        file_controller::init_chunked_upload,
+        file_controller::append_chunk,
+        file_controller::cancel_chunked_upload,
+        file_controller::complete_chunked_upload,
+        file_controller::cleanup_expired_files,
+        file_controller::reconcile_files,
+        file_controller::download_file,
+        file_controller::head_file,
+        file_controller::get_file_metadata,
+        file_controller::update_file_metadata,
+        file_controller::delete_file,
+        file_controller::restore_file,
+        file_controller::transfer_file,
+        file_controller::batch_delete_files,
+        file_controller::get_file_links,
+        file_controller::signed_urls_batch,
+        file_controller::get_signed_url,
+        file_controller::get_file_stats,
+    ),
+    components(schemas(
+        HealthResponse,
+        HealthConfigInfo,
+        SystemMetrics,
+        EventQueueMetrics,
+        DependencyStatus,
+        ProbeStatus,
+        ReadinessResponse,
+        ProviderLatencySummary,
+        Provider,
+        LocalConfig,
+        LocalConfigDTO,
+        RefreshConfigResponse,
+        ExportItem,
+        ExportPage,
+        ReindexChecksumsResponse,
+        ReindexMismatch,
+        ReindexFailure,
+        VacuumResponse,
+        ClientStatsResponse,
+        CreateUser,
+        User,
+        UserDTO,
+        UserUsageResponse,
+        UserTokenResponse,
+        UserFilesPage,
+        GenerateTokenRequest,
+        TokenResponse,
+        UploadFileResponse,
+        InitChunkedUploadRequest,
+        ChunkedUploadSessionResponse,
+        FileResponse,
+        UpdateFileRequest,
+        TransferFileRequest,
+        FileLinksResponse,
+        BatchDeleteRequest,
+        BatchDeleteResponse,
+        BatchDeleteFailure,
+        SignedUrlsBatchRequest,
+        SignedUrlsBatchResponse,
+        SignedUrlResponse,
+        FileStatsResponse,
+        OrphanedObject,
+        ReconcileResponse,
+        ScheduleExpiryRequest,
+        ScheduleExpiryResponse,
+    )),
+    modifiers(&KvSecretAuth),
+    tags(
+        (name = "Health"),
+        (name = "Instances"),
+        (name = "Admin"),
+        (name = "Users"),
+        (name = "Tokens"),
+        (name = "Files"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Registra el header X-KV-SECRET que exigen las rutas protegidas
+struct KvSecretAuth;
+
+impl Modify for KvSecretAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            KV_SECRET_SCHEME,
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-KV-SECRET"))),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+
+    fn spec() -> Value {
+        serde_json::to_value(ApiDoc::openapi()).unwrap()
+    }
+
+    /// Todos los `$ref` del documento, en cualquier nivel
+    fn refs<'a>(value: &'a Value, found: &mut Vec<&'a str>) {
+        match value {
+            Value::Object(map) => {
+                if let Some(Value::String(target)) = map.get("$ref") {
+                    found.push(target);
+                }
+                map.values().for_each(|v| refs(v, found));
+            }
+            Value::Array(items) => items.iter().for_each(|v| refs(v, found)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn every_schema_reference_is_registered() {
+        let spec = spec();
+        let mut found = Vec::new();
+        refs(&spec, &mut found);
+        assert!(!found.is_empty());
+
+        for target in found {
+            let name = target
+                .strip_prefix("#/components/schemas/")
+                .unwrap_or_else(|| panic!("unexpected $ref {}", target));
+            assert!(
+                spec["components"]["schemas"].get(name).is_some(),
+                "{} is referenced but not listed in components(schemas(...))",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn documents_the_main_routes_and_the_kv_secret_scheme() {
+        let spec = spec();
+        for (path, method) in [
+            ("/api/v1/health", "get"),
+            ("/api/v1/users", "post"),
+            ("/api/v1/files/{file_id}/content", "get"),
+            ("/api/v1/admin/refresh-config", "post"),
+        ] {
+            assert!(
+                spec["paths"][path].get(method).is_some(),
+                "{} {} is missing",
+                method,
+                path
+            );
+        }
+        assert_eq!(
+            spec["components"]["securitySchemes"][KV_SECRET_SCHEME]["name"],
+            "X-KV-SECRET"
+        );
+    }
+}
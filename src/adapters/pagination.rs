@@ -0,0 +1,206 @@
+use axum::{
+    extract::{FromRequestParts, Query},
+    http::request::Parts,
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::application::error::ApplicationError;
+
+/// Los cursores son file_ids o server_ids; algo más largo no es un cursor válido
+const MAX_CURSOR_LENGTH: usize = 256;
+
+/// Query params de paginación compartidos por los endpoints de listado
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PaginationParams {
+    /// Tamaño de página; se ajusta al máximo del endpoint
+    limit: Option<i64>,
+    /// Elementos a saltar
+    offset: Option<i64>,
+    /// Último id de la página anterior
+    cursor: Option<String>,
+    /// Campo de orden; con prefijo `-` es descendente
+    sort: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Sort {
+    pub field: String,
+    pub descending: bool,
+}
+
+/// Paginación ya validada: offset no negativo, cursor saneado y sort bien formado
+#[derive(Debug, Clone)]
+pub struct Pagination {
+    limit: Option<i64>,
+    pub offset: i64,
+    pub cursor: Option<String>,
+    sort: Option<Sort>,
+}
+
+impl Pagination {
+    /// Límite pedido ajustado a [1, max], o `default` si no se indicó
+    pub fn limit(&self, default: i64, max: i64) -> i64 {
+        self.limit.unwrap_or(default).clamp(1, max)
+    }
+
+    /// Como `limit`, pero sin valor por defecto para endpoints que devuelven todo
+    pub fn limit_capped(&self, max: i64) -> Option<i64> {
+        self.limit.map(|limit| limit.clamp(1, max))
+    }
+
+    /// El orden pedido, rechazando campos que el endpoint no soporta
+    pub fn sort(&self, allowed: &[&str]) -> Result<Option<&Sort>, ApplicationError> {
+        match &self.sort {
+            Some(sort) if !allowed.contains(&sort.field.as_str()) => {
+                Err(ApplicationError::BadRequest(format!(
+                    "Unsupported sort field '{}'; expected one of: {}",
+                    sort.field,
+                    allowed.join(", ")
+                )))
+            }
+            sort => Ok(sort.as_ref()),
+        }
+    }
+
+    fn from_params(params: PaginationParams) -> Result<Self, ApplicationError> {
+        let offset = params.offset.unwrap_or(0);
+        if offset < 0 {
+            return Err(ApplicationError::BadRequest(
+                "offset must not be negative".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            limit: params.limit,
+            offset,
+            cursor: params
+                .cursor
+                .map(Self::decode_cursor)
+                .transpose()?
+                .flatten(),
+            sort: params.sort.map(Self::parse_sort).transpose()?.flatten(),
+        })
+    }
+
+    /// Un cursor vacío equivale a empezar desde el principio
+    fn decode_cursor(cursor: String) -> Result<Option<String>, ApplicationError> {
+        let cursor = cursor.trim();
+        if cursor.is_empty() {
+            return Ok(None);
+        }
+        if cursor.len() > MAX_CURSOR_LENGTH || cursor.chars().any(char::is_control) {
+            return Err(ApplicationError::BadRequest("Invalid cursor".to_string()));
+        }
+        Ok(Some(cursor.to_string()))
+    }
+
+    fn parse_sort(sort: String) -> Result<Option<Sort>, ApplicationError> {
+        let sort = sort.trim();
+        if sort.is_empty() {
+            return Ok(None);
+        }
+
+        let (field, descending) = match sort.strip_prefix('-') {
+            Some(field) => (field, true),
+            None => (sort, false),
+        };
+        if field.is_empty() || !field.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(ApplicationError::BadRequest(format!(
+                "Invalid sort '{}'",
+                sort
+            )));
+        }
+
+        Ok(Some(Sort {
+            field: field.to_string(),
+            descending,
+        }))
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Pagination {
+    type Rejection = ApplicationError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Query(params) = Query::<PaginationParams>::try_from_uri(&parts.uri)
+            .map_err(|e| ApplicationError::BadRequest(e.body_text()))?;
+        Self::from_params(params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn pagination(query: &str) -> Result<Pagination, ApplicationError> {
+        let request = axum::http::Request::get(format!("/items?{}", query))
+            .body(())
+            .unwrap();
+        let (mut parts, _) = request.into_parts();
+        Pagination::from_request_parts(&mut parts, &()).await
+    }
+
+    #[tokio::test]
+    async fn limit_is_clamped_to_the_endpoint_range() {
+        assert_eq!(pagination("").await.unwrap().limit(50, 500), 50);
+        assert_eq!(pagination("limit=20").await.unwrap().limit(50, 500), 20);
+        assert_eq!(pagination("limit=10000").await.unwrap().limit(50, 500), 500);
+        assert_eq!(pagination("limit=0").await.unwrap().limit(50, 500), 1);
+        assert_eq!(pagination("limit=-3").await.unwrap().limit(50, 500), 1);
+    }
+
+    #[tokio::test]
+    async fn limit_capped_has_no_default() {
+        assert_eq!(pagination("").await.unwrap().limit_capped(100), None);
+        assert_eq!(
+            pagination("limit=1000").await.unwrap().limit_capped(100),
+            Some(100)
+        );
+        assert_eq!(
+            pagination("limit=0").await.unwrap().limit_capped(100),
+            Some(1)
+        );
+    }
+
+    #[tokio::test]
+    async fn offset_must_not_be_negative() {
+        assert_eq!(pagination("offset=30").await.unwrap().offset, 30);
+        assert!(matches!(
+            pagination("offset=-1").await,
+            Err(ApplicationError::BadRequest(_))
+        ));
+        assert!(matches!(
+            pagination("limit=abc").await,
+            Err(ApplicationError::BadRequest(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn cursor_is_trimmed_and_bounded() {
+        assert_eq!(pagination("cursor=%20%20").await.unwrap().cursor, None);
+        assert_eq!(
+            pagination("cursor=%20abc%20")
+                .await
+                .unwrap()
+                .cursor
+                .as_deref(),
+            Some("abc")
+        );
+        let long = "a".repeat(MAX_CURSOR_LENGTH + 1);
+        assert!(pagination(&format!("cursor={}", long)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn sort_is_parsed_and_checked_against_the_endpoint() {
+        let pagination_desc = pagination("sort=-uploaded_at").await.unwrap();
+        let sort = pagination_desc.sort(&["uploaded_at"]).unwrap().unwrap();
+        assert_eq!(sort.field, "uploaded_at");
+        assert!(sort.descending);
+
+        assert!(pagination_desc.sort(&["size"]).is_err());
+        assert!(pagination("sort=size;drop").await.is_err());
+        assert!(pagination("sort=-").await.is_err());
+    }
+}
@@ -0,0 +1,92 @@
+//! Repositorios de configuración y secretos en memoria para los tests de los controladores;
+//! un upsert sustituye la fila entera.
+
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+
+use crate::{
+    application::{
+        dto::{
+            global_config_dto::GlobalConfigDTO, local_config_dto::LocalConfigDTO,
+            secrets_dto::SecretsDTO,
+        },
+        error::ApplicationError,
+        repositories::{
+            global_config_repository::GlobalConfigRepository,
+            local_config_repository::LocalConfigRepository, secrets_repository::SecretsRepository,
+        },
+    },
+    domain::config::{global::GlobalConfig, local::LocalConfig, secrets::Secrets},
+};
+
+pub struct InMemoryConfigRepository {
+    secrets: Mutex<Secrets>,
+    global: Mutex<GlobalConfig>,
+    local: Mutex<LocalConfig>,
+}
+
+impl InMemoryConfigRepository {
+    pub fn new(secrets: Secrets, global: GlobalConfig, local: LocalConfig) -> Self {
+        Self {
+            secrets: Mutex::new(secrets),
+            global: Mutex::new(global),
+            local: Mutex::new(local),
+        }
+    }
+}
+
+#[async_trait]
+impl SecretsRepository for InMemoryConfigRepository {
+    async fn get_secrets(&self) -> Result<Secrets, ApplicationError> {
+        Ok(self.secrets.lock().unwrap().clone())
+    }
+
+    async fn upsert_secrets(&self, secrets: SecretsDTO) -> Result<Secrets, ApplicationError> {
+        let secrets: Secrets = secrets.into();
+        *self.secrets.lock().unwrap() = secrets.clone();
+        Ok(secrets)
+    }
+}
+
+#[async_trait]
+impl GlobalConfigRepository for InMemoryConfigRepository {
+    async fn get_global_config(&self) -> Result<GlobalConfig, ApplicationError> {
+        Ok(self.global.lock().unwrap().clone())
+    }
+
+    async fn upsert_global_config(
+        &self,
+        config: GlobalConfigDTO,
+    ) -> Result<GlobalConfig, ApplicationError> {
+        let config: GlobalConfig = config.into();
+        *self.global.lock().unwrap() = config.clone();
+        Ok(config)
+    }
+}
+
+#[async_trait]
+impl LocalConfigRepository for InMemoryConfigRepository {
+    async fn get_local_config(&self, server_id: &str) -> Result<LocalConfig, ApplicationError> {
+        let local = self.local.lock().unwrap();
+        if local.server_id != server_id {
+            return Err(ApplicationError::NotFound);
+        }
+        Ok(local.clone())
+    }
+
+    async fn upsert_local_config(
+        &self,
+        server_id: &str,
+        config: LocalConfigDTO,
+    ) -> Result<LocalConfig, ApplicationError> {
+        let mut config: LocalConfig = config.into();
+        config.server_id = server_id.to_string();
+        *self.local.lock().unwrap() = config.clone();
+        Ok(config)
+    }
+
+    async fn get_all_instance_ids(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(vec![self.local.lock().unwrap().server_id.clone()])
+    }
+}
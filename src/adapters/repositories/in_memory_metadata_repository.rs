@@ -0,0 +1,476 @@
+//! MetadataRepository en memoria para los tests de los controladores; solo implementa las
+//! consultas que esos tests ejercitan, con la misma semántica que las de Postgres.
+
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use futures_util::{future::BoxFuture, stream};
+use uuid::Uuid;
+
+use crate::{
+    application::{
+        dto::metadata_dto::MetadataDTO,
+        error::ApplicationError,
+        repositories::metadata_repository::{MetadataRepository, MetadataStream},
+    },
+    domain::models::metadata::{
+        ClientStats, FileStats, Metadata, UserFilesFilter, UserFilesSortBy, UserUsageStats,
+    },
+};
+
+pub struct InMemoryMetadataRepository {
+    files: Mutex<Vec<Metadata>>,
+    /// Hace de pg_advisory_xact_lock entre create_metadata y release_object
+    object_lock: tokio::sync::Mutex<()>,
+    /// Desfase de NOW() respecto a la hora local, para simular relojes desincronizados
+    clock_offset: Mutex<Duration>,
+}
+
+impl InMemoryMetadataRepository {
+    pub fn new(files: Vec<Metadata>) -> Self {
+        Self {
+            files: Mutex::new(files),
+            object_lock: tokio::sync::Mutex::new(()),
+            clock_offset: Mutex::new(Duration::zero()),
+        }
+    }
+
+    pub fn set_clock_offset(&self, offset: Duration) {
+        *self.clock_offset.lock().unwrap() = offset;
+    }
+
+    fn references(&self, object_id: &str, excluding: &[String]) -> u64 {
+        self.files
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|file| file.storage_key() == object_id && !excluding.contains(&file.file_id))
+            .count() as u64
+    }
+
+    /// Archivos del usuario fuera de la papelera, filtrados y ordenados como en Postgres
+    fn user_files(&self, user_id: &str, filter: &UserFilesFilter) -> Vec<Metadata> {
+        let mut files: Vec<Metadata> = self
+            .files
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|file| {
+                file.user_id.as_deref() == Some(user_id)
+                    && file.trashed_at.is_none()
+                    && filter
+                        .mime_type
+                        .as_ref()
+                        .is_none_or(|mime_type| &file.mime_type == mime_type)
+            })
+            .cloned()
+            .collect();
+        files.sort_by(|a, b| {
+            let order = match filter.sort_by {
+                UserFilesSortBy::UploadedAt => a.uploaded_at.cmp(&b.uploaded_at),
+                UserFilesSortBy::Size => a.size.cmp(&b.size),
+                UserFilesSortBy::DownloadCount => a.download_count.cmp(&b.download_count),
+            };
+            let order = if filter.descending {
+                order.reverse()
+            } else {
+                order
+            };
+            order.then_with(|| a.file_id.cmp(&b.file_id))
+        });
+        files
+    }
+
+    pub fn file(&self, file_id: &str) -> Metadata {
+        self.files
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|file| file.file_id == file_id)
+            .cloned()
+            .expect("unknown file")
+    }
+
+    pub fn contains(&self, file_id: &str) -> bool {
+        self.files
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|file| file.file_id == file_id)
+    }
+
+    pub fn insert(&self, metadata: Metadata) {
+        self.files.lock().unwrap().push(metadata);
+    }
+
+    /// Filas ordenadas por file_id a partir de `cursor`, como la exportación de Postgres
+    fn exported(&self, cursor: Option<&str>, limit: Option<i64>) -> Vec<Metadata> {
+        let mut files: Vec<Metadata> = self
+            .files
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|file| cursor.is_none_or(|cursor| file.file_id.as_str() > cursor))
+            .cloned()
+            .collect();
+        files.sort_by(|a, b| a.file_id.cmp(&b.file_id));
+        if let Some(limit) = limit {
+            files.truncate(limit as usize);
+        }
+        files
+    }
+
+    /// Aplica `change` a la fila; NotFound si no existe
+    fn modify(
+        &self,
+        file_id: &str,
+        change: impl FnOnce(&mut Metadata),
+    ) -> Result<Metadata, ApplicationError> {
+        let mut files = self.files.lock().unwrap();
+        let file = files
+            .iter_mut()
+            .find(|file| file.file_id == file_id)
+            .ok_or(ApplicationError::NotFound)?;
+        change(file);
+        Ok(file.clone())
+    }
+}
+
+/// Archivo permanente de `user_id` subido ahora
+pub fn metadata(file_id: &str, user_id: Uuid, file_name: &str) -> Metadata {
+    let now = Utc::now();
+    Metadata {
+        file_id: file_id.to_string(),
+        mime_type: "text/plain".to_string(),
+        size: 0,
+        user_id: Some(user_id.to_string()),
+        description: None,
+        file_name: file_name.to_string(),
+        server_id: "server".to_string(),
+        uploaded_at: now,
+        download_count: 0,
+        last_access: now,
+        delete_at: None,
+        client_id: None,
+        trashed_at: None,
+        content_hash: None,
+        object_id: None,
+    }
+}
+
+#[async_trait]
+impl MetadataRepository for InMemoryMetadataRepository {
+    async fn create_metadata(&self, metadata: MetadataDTO) -> Result<Metadata, ApplicationError> {
+        let metadata: Metadata = metadata.into();
+        let _locked = self.object_lock.lock().await;
+        if let Some(object_id) = &metadata.object_id {
+            if self.references(object_id, &[]) == 0 {
+                return Err(ApplicationError::Conflict(format!(
+                    "Object {} was released before it could be reused",
+                    object_id
+                )));
+            }
+        }
+        self.insert(metadata.clone());
+        Ok(metadata)
+    }
+
+    async fn get_metadata(&self, file_id: &str) -> Result<Metadata, ApplicationError> {
+        self.files
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|file| file.file_id == file_id)
+            .cloned()
+            .ok_or(ApplicationError::NotFound)
+    }
+
+    async fn get_file_stats(&self, file_id: &str) -> Result<FileStats, ApplicationError> {
+        let file = self.get_metadata(file_id).await?;
+        if file.is_trashed() {
+            return Err(ApplicationError::NotFound);
+        }
+        Ok(FileStats {
+            download_count: file.download_count,
+            last_access: file.last_access,
+            size: file.size,
+        })
+    }
+
+    async fn update_metadata(&self, metadata: MetadataDTO) -> Result<Metadata, ApplicationError> {
+        self.modify(&metadata.file_id, |file| {
+            if let Some(description) = metadata.description {
+                file.description = Some(description);
+            }
+            if let Some(file_name) = metadata.file_name {
+                file.file_name = file_name;
+            }
+            if metadata.delete_at.is_some() {
+                file.delete_at = metadata.delete_at;
+            }
+            if metadata.trashed_at.is_some() {
+                file.trashed_at = metadata.trashed_at;
+            }
+        })
+    }
+
+    async fn delete_metadata(&self, file_id: &str) -> Result<Metadata, ApplicationError> {
+        let mut files = self.files.lock().unwrap();
+        let position = files
+            .iter()
+            .position(|file| file.file_id == file_id)
+            .ok_or(ApplicationError::NotFound)?;
+        Ok(files.remove(position))
+    }
+
+    async fn trash_metadata(&self, file_id: &str) -> Result<Metadata, ApplicationError> {
+        self.modify(file_id, |file| {
+            file.trashed_at.get_or_insert_with(Utc::now);
+        })
+    }
+
+    async fn restore_metadata(&self, file_id: &str) -> Result<Metadata, ApplicationError> {
+        self.modify(file_id, |file| file.trashed_at = None)
+    }
+
+    async fn transfer_metadata(
+        &self,
+        _file_id: &str,
+        _to_user_id: Uuid,
+    ) -> Result<Metadata, ApplicationError> {
+        unimplemented!()
+    }
+
+    async fn find_by_hash(
+        &self,
+        content_hash: &str,
+        server_id: &str,
+        user_id: Option<&str>,
+    ) -> Result<Option<Metadata>, ApplicationError> {
+        Ok(self
+            .files
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|file| {
+                file.content_hash.as_deref() == Some(content_hash)
+                    && file.server_id == server_id
+                    && file.user_id.as_deref() == user_id
+                    && file.trashed_at.is_none()
+            })
+            .min_by_key(|file| file.uploaded_at)
+            .cloned())
+    }
+
+    async fn release_object(
+        &self,
+        object_id: &str,
+        file_ids: &[String],
+        delete_object: BoxFuture<'_, Result<(), ApplicationError>>,
+    ) -> Result<u64, ApplicationError> {
+        let _locked = self.object_lock.lock().await;
+        let references = self.references(object_id, file_ids);
+        if references == 0 {
+            delete_object.await?;
+        }
+        self.files
+            .lock()
+            .unwrap()
+            .retain(|file| !file_ids.contains(&file.file_id));
+        Ok(references)
+    }
+
+    async fn set_content_hash(
+        &self,
+        file_id: &str,
+        content_hash: &str,
+    ) -> Result<(), ApplicationError> {
+        self.modify(file_id, |file| {
+            file.content_hash = Some(content_hash.to_string())
+        })
+        .map(|_| ())
+    }
+
+    async fn record_download(&self, file_id: &str) -> Result<(), ApplicationError> {
+        self.modify(file_id, |file| {
+            file.download_count += 1;
+            file.last_access = Utc::now();
+        })
+        .map(|_| ())
+    }
+
+    async fn export_metadata(
+        &self,
+        cursor: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<Metadata>, ApplicationError> {
+        Ok(self.exported(cursor, Some(limit)))
+    }
+
+    fn stream_export(&self, cursor: Option<String>, limit: Option<i64>) -> MetadataStream {
+        let files = self.exported(cursor.as_deref(), limit);
+        Box::pin(stream::iter(files.into_iter().map(Ok)))
+    }
+
+    fn stream_files_by_user(&self, user_id: String, filter: UserFilesFilter) -> MetadataStream {
+        let files = self.user_files(&user_id, &filter);
+        Box::pin(stream::iter(files.into_iter().map(Ok)))
+    }
+
+    async fn get_expired_files(
+        &self,
+        trash_retention: u64,
+    ) -> Result<Vec<Metadata>, ApplicationError> {
+        let now = Utc::now();
+        let purge_before = now - chrono::Duration::seconds(trash_retention as i64);
+        Ok(self
+            .files
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|file| {
+                file.delete_at.is_some_and(|delete_at| delete_at <= now)
+                    || file
+                        .trashed_at
+                        .is_some_and(|trashed_at| trashed_at <= purge_before)
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn get_trashed_files(
+        &self,
+        trash_retention: u64,
+        limit: i64,
+    ) -> Result<Vec<Metadata>, ApplicationError> {
+        let purge_before = Utc::now() - chrono::Duration::seconds(trash_retention as i64);
+        let mut files: Vec<Metadata> = self
+            .files
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|file| {
+                file.trashed_at
+                    .is_some_and(|trashed_at| trashed_at <= purge_before)
+            })
+            .cloned()
+            .collect();
+        files.sort_by(|a, b| (a.trashed_at, &a.file_id).cmp(&(b.trashed_at, &b.file_id)));
+        files.truncate(limit as usize);
+        Ok(files)
+    }
+
+    async fn get_file_ids_by_user(&self, _user_id: &str) -> Result<Vec<String>, ApplicationError> {
+        unimplemented!()
+    }
+
+    async fn get_files_by_user(&self, user_id: &str) -> Result<Vec<Metadata>, ApplicationError> {
+        Ok(self.user_files(user_id, &UserFilesFilter::default()))
+    }
+
+    async fn get_files_by_user_paginated(
+        &self,
+        user_id: &str,
+        filter: &UserFilesFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<Metadata>, u64), ApplicationError> {
+        let files = self.user_files(user_id, filter);
+        let total = files.len() as u64;
+        let page = files
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .collect();
+        Ok((page, total))
+    }
+
+    async fn get_user_usage_stats(
+        &self,
+        user_id: &str,
+    ) -> Result<UserUsageStats, ApplicationError> {
+        let files = self.user_files(user_id, &UserFilesFilter::default());
+        Ok(UserUsageStats {
+            largest_file_size: files.iter().map(|file| file.size).max(),
+            oldest_file_uploaded_at: files.iter().map(|file| file.uploaded_at).min(),
+        })
+    }
+
+    async fn schedule_user_files_expiry(
+        &self,
+        user_id: &str,
+        file_ids: Option<&[String]>,
+        older_than: Option<DateTime<Utc>>,
+        delete_at: DateTime<Utc>,
+    ) -> Result<u64, ApplicationError> {
+        let mut scheduled = 0;
+        for file in self.files.lock().unwrap().iter_mut() {
+            let targeted = file.user_id.as_deref() == Some(user_id)
+                && file.trashed_at.is_none()
+                && file_ids.is_none_or(|ids| ids.contains(&file.file_id))
+                && older_than.is_none_or(|older_than| file.uploaded_at < older_than);
+            if targeted {
+                file.delete_at = Some(delete_at);
+                scheduled += 1;
+            }
+        }
+        Ok(scheduled)
+    }
+
+    async fn find_user_files_by_name(
+        &self,
+        user_id: &str,
+        file_name: &str,
+    ) -> Result<Vec<Metadata>, ApplicationError> {
+        let mut files: Vec<Metadata> = self
+            .files
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|file| {
+                file.user_id.as_deref() == Some(user_id)
+                    && file.file_name == file_name
+                    && file.trashed_at.is_none()
+            })
+            .cloned()
+            .collect();
+        files.sort_by_key(|file| std::cmp::Reverse(file.uploaded_at));
+        Ok(files)
+    }
+
+    async fn get_client_stats(&self) -> Result<Vec<ClientStats>, ApplicationError> {
+        let mut stats: Vec<ClientStats> = Vec::new();
+        for file in self.files.lock().unwrap().iter() {
+            let index = match stats.iter().position(|s| s.client_id == file.client_id) {
+                Some(index) => index,
+                None => {
+                    stats.push(ClientStats {
+                        client_id: file.client_id.clone(),
+                        file_count: 0,
+                        total_size: 0,
+                        download_count: 0,
+                    });
+                    stats.len() - 1
+                }
+            };
+            let client = &mut stats[index];
+            client.file_count += 1;
+            client.total_size += file.size;
+            client.download_count += file.download_count;
+        }
+        // ORDER BY client_id NULLS LAST
+        stats.sort_by(|a, b| {
+            (a.client_id.is_none(), &a.client_id).cmp(&(b.client_id.is_none(), &b.client_id))
+        });
+        Ok(stats)
+    }
+
+    async fn database_now(&self) -> Result<DateTime<Utc>, ApplicationError> {
+        Ok(Utc::now() + *self.clock_offset.lock().unwrap())
+    }
+
+    async fn ping(&self) -> Result<(), ApplicationError> {
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+//! TokenRepository en memoria para los tests de los controladores: tokens de subida de un
+//! solo uso y tokens de usuario reutilizables, sin expiración.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::application::{
+    error::ApplicationError, repositories::token_repository::TokenRepository,
+};
+
+#[derive(Default)]
+pub struct InMemoryTokenRepository {
+    upload_tokens: Mutex<HashMap<String, Option<String>>>,
+    user_tokens: Mutex<HashMap<String, String>>,
+}
+
+#[async_trait]
+impl TokenRepository for InMemoryTokenRepository {
+    async fn generate_token(
+        &self,
+        user_id: Option<String>,
+        _ttl_seconds: u64,
+    ) -> Result<String, ApplicationError> {
+        let token = Uuid::new_v4().to_string();
+        self.upload_tokens
+            .lock()
+            .unwrap()
+            .insert(token.clone(), user_id);
+        Ok(token)
+    }
+
+    async fn verify_and_consume_token(
+        &self,
+        token: &str,
+    ) -> Result<Option<String>, ApplicationError> {
+        self.upload_tokens
+            .lock()
+            .unwrap()
+            .remove(token)
+            .ok_or(ApplicationError::InvalidToken)
+    }
+
+    async fn generate_user_token(
+        &self,
+        user_id: &str,
+        _ttl_seconds: u64,
+    ) -> Result<String, ApplicationError> {
+        let token = Uuid::new_v4().to_string();
+        self.user_tokens
+            .lock()
+            .unwrap()
+            .insert(token.clone(), user_id.to_string());
+        Ok(token)
+    }
+
+    async fn verify_user_token(&self, token: &str) -> Result<String, ApplicationError> {
+        self.user_tokens
+            .lock()
+            .unwrap()
+            .get(token)
+            .cloned()
+            .ok_or(ApplicationError::InvalidToken)
+    }
+
+    async fn ping(&self) -> Result<(), ApplicationError> {
+        Ok(())
+    }
+}
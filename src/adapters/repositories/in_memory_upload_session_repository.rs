@@ -0,0 +1,120 @@
+//! UploadSessionRepository en memoria para los tests de los controladores, con las mismas
+//! validaciones que el script de Redis al añadir bloques.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{
+    application::{
+        error::ApplicationError, repositories::upload_session_repository::UploadSessionRepository,
+    },
+    domain::models::upload_session::UploadSession,
+};
+
+struct StoredSession {
+    session: UploadSession,
+    chunks: Vec<u8>,
+    /// Ya llegó un bloque menor que chunk_size: era el último
+    sealed: bool,
+}
+
+#[derive(Default)]
+pub struct InMemoryUploadSessionRepository {
+    sessions: Mutex<HashMap<String, StoredSession>>,
+}
+
+impl InMemoryUploadSessionRepository {
+    pub fn contains(&self, session_id: &str) -> bool {
+        self.sessions.lock().unwrap().contains_key(session_id)
+    }
+}
+
+#[async_trait]
+impl UploadSessionRepository for InMemoryUploadSessionRepository {
+    async fn create_session(
+        &self,
+        session: &UploadSession,
+        _ttl_seconds: u64,
+    ) -> Result<(), ApplicationError> {
+        self.sessions.lock().unwrap().insert(
+            session.session_id.clone(),
+            StoredSession {
+                session: session.clone(),
+                chunks: Vec::new(),
+                sealed: false,
+            },
+        );
+        Ok(())
+    }
+
+    async fn get_session(&self, session_id: &str) -> Result<UploadSession, ApplicationError> {
+        let sessions = self.sessions.lock().unwrap();
+        let stored = sessions.get(session_id).ok_or(ApplicationError::NotFound)?;
+        let mut session = stored.session.clone();
+        session.received_bytes = stored.chunks.len() as u64;
+        Ok(session)
+    }
+
+    async fn append_chunk(
+        &self,
+        session_id: &str,
+        chunk: &[u8],
+        expected_offset: Option<u64>,
+    ) -> Result<u64, ApplicationError> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let stored = sessions
+            .get_mut(session_id)
+            .ok_or(ApplicationError::NotFound)?;
+        let received = stored.chunks.len() as u64;
+        let length = chunk.len() as u64;
+
+        if stored.sealed {
+            return Err(ApplicationError::BadRequest(
+                "Upload session already received its final chunk".to_string(),
+            ));
+        }
+        if length > stored.session.chunk_size {
+            return Err(ApplicationError::BadRequest(
+                "Chunk exceeds the session chunk size".to_string(),
+            ));
+        }
+        if received + length > stored.session.max_size {
+            return Err(ApplicationError::PayloadTooLarge);
+        }
+        if expected_offset.is_some_and(|offset| offset != received) {
+            return Err(ApplicationError::BadRequest(
+                "Upload-Offset does not match the bytes received".to_string(),
+            ));
+        }
+
+        stored.chunks.extend_from_slice(chunk);
+        stored.sealed = length < stored.session.chunk_size;
+        Ok(stored.chunks.len() as u64)
+    }
+
+    async fn take_session(
+        &self,
+        session_id: &str,
+    ) -> Result<(UploadSession, Bytes), ApplicationError> {
+        let stored = self
+            .sessions
+            .lock()
+            .unwrap()
+            .remove(session_id)
+            .ok_or(ApplicationError::NotFound)?;
+        let mut session = stored.session;
+        session.received_bytes = stored.chunks.len() as u64;
+        Ok((session, stored.chunks.into()))
+    }
+
+    async fn delete_session(&self, session_id: &str) -> Result<(), ApplicationError> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .remove(session_id)
+            .map(|_| ())
+            .ok_or(ApplicationError::NotFound)
+    }
+}
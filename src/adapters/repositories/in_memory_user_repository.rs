@@ -0,0 +1,95 @@
+//! UserRepository en memoria para los tests de los controladores; las actualizaciones solo
+//! cambian los campos presentes, como en Postgres.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::{
+    application::{
+        dto::user_dto::UserDTO, error::ApplicationError,
+        repositories::user_repository::UserRepository,
+    },
+    domain::models::user::User,
+};
+
+#[derive(Default)]
+pub struct InMemoryUserRepository {
+    users: Mutex<HashMap<Uuid, User>>,
+}
+
+impl InMemoryUserRepository {
+    pub fn insert(&self, user: User) {
+        self.users.lock().unwrap().insert(user.uid, user);
+    }
+
+    pub fn user(&self, uid: Uuid) -> User {
+        self.users
+            .lock()
+            .unwrap()
+            .get(&uid)
+            .cloned()
+            .expect("unknown user")
+    }
+}
+
+#[async_trait]
+impl UserRepository for InMemoryUserRepository {
+    async fn create_user(&self, user: UserDTO, new_space: u64) -> Result<User, ApplicationError> {
+        let mut users = self.users.lock().unwrap();
+        if users.contains_key(&user.uid) {
+            return Err(ApplicationError::Conflict(format!(
+                "User {} already exists",
+                user.uid
+            )));
+        }
+        users.insert(
+            user.uid,
+            User {
+                uid: user.uid,
+                total_space: new_space,
+                tier: user.tier.clone(),
+                ..Default::default()
+            },
+        );
+        Ok(User {
+            uid: user.uid,
+            total_space: new_space,
+            tier: user.tier,
+            ..Default::default()
+        })
+    }
+
+    async fn get_user(&self, user: UserDTO) -> Result<User, ApplicationError> {
+        if !self.users.lock().unwrap().contains_key(&user.uid) {
+            return Err(ApplicationError::NotFound);
+        }
+        Ok(self.user(user.uid))
+    }
+
+    async fn update_user(&self, user: UserDTO) -> Result<User, ApplicationError> {
+        {
+            let mut users = self.users.lock().unwrap();
+            let stored = users.get_mut(&user.uid).ok_or(ApplicationError::NotFound)?;
+            if let Some(file_count) = user.file_count {
+                stored.file_count = file_count;
+            }
+            if let Some(total_space) = user.total_space {
+                stored.total_space = total_space;
+            }
+            if let Some(used_space) = user.used_space {
+                stored.used_space = used_space;
+            }
+        }
+        Ok(self.user(user.uid))
+    }
+
+    async fn delete_user(&self, user: UserDTO) -> Result<User, ApplicationError> {
+        self.users
+            .lock()
+            .unwrap()
+            .remove(&user.uid)
+            .ok_or(ApplicationError::NotFound)
+    }
+}
@@ -1,9 +1,22 @@
+#[cfg(test)]
+pub mod in_memory_config_repository;
+#[cfg(test)]
+pub mod in_memory_metadata_repository;
+#[cfg(test)]
+pub mod in_memory_token_repository;
+#[cfg(test)]
+pub mod in_memory_upload_session_repository;
+#[cfg(test)]
+pub mod in_memory_user_repository;
 mod pg_global_config_repository;
 mod pg_local_config_repository;
 mod pg_metadata_repository;
 mod pg_secrets_repository;
 mod pg_user_repository;
+mod redis_error;
 mod redis_token_repository;
+mod redis_upload_session_repository;
+mod single_row;
 
 pub use pg_global_config_repository::PgGlobalConfigRepository;
 pub use pg_local_config_repository::PgLocalConfigRepository;
@@ -11,3 +24,4 @@ pub use pg_metadata_repository::PgMetadataRepository;
 pub use pg_secrets_repository::PgSecretsRepository;
 pub use pg_user_repository::PgUserRepository;
 pub use redis_token_repository::RedisTokenRepository;
+pub use redis_upload_session_repository::RedisUploadSessionRepository;
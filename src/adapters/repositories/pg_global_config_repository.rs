@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use sqlx::{query_as, QueryBuilder};
+use sqlx::{query_as, types::Json, QueryBuilder};
 use tracing::{debug, info};
 
 use crate::{
@@ -10,25 +10,67 @@ use crate::{
     domain::config::global::GlobalConfig,
 };
 
+use super::single_row::single_row;
+
 pub struct PgGlobalConfigRepository {
     pool: sqlx::PgPool,
 }
 
+/// Valores iniciales cuando config.global está vacía (base de datos nueva)
+const DEFAULT_MIME_TYPES: [&str; 5] = [
+    "image/png",
+    "image/jpeg",
+    "application/pdf",
+    "text/plain",
+    "application/zip",
+];
+const DEFAULT_MAX_SIZE: i64 = 50 * 1024 * 1024;
+const DEFAULT_CHUNK_SIZE: i64 = 5 * 1024 * 1024;
+const DEFAULT_TEMP_FILE_LIFE: i64 = 24 * 60 * 60;
+const DEFAULT_QUOTA: i64 = 1024 * 1024 * 1024;
+
 impl PgGlobalConfigRepository {
     pub fn new(pool: sqlx::PgPool) -> Self {
         Self { pool }
     }
+
+    /// Inserta la fila por defecto solo si la tabla está vacía; devuelve si se creó
+    async fn insert_default_if_missing(&self) -> Result<bool, ApplicationError> {
+        let query = "
+            INSERT INTO config.global (mime_types, max_size, chunk_size, temp_file_life, default_quota)
+            SELECT $1, $2, $3, $4, $5
+            WHERE NOT EXISTS (SELECT 1 FROM config.global)
+        ";
+        let mime_types: Vec<String> = DEFAULT_MIME_TYPES.iter().map(|s| s.to_string()).collect();
+        let result = sqlx::query(query)
+            .bind(mime_types)
+            .bind(DEFAULT_MAX_SIZE)
+            .bind(DEFAULT_CHUNK_SIZE)
+            .bind(DEFAULT_TEMP_FILE_LIFE)
+            .bind(DEFAULT_QUOTA)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| ApplicationError::DatabaseError(e.to_string()))?;
+
+        let created = result.rows_affected() > 0;
+        if created {
+            info!("Global config row was missing, created defaults");
+        }
+        Ok(created)
+    }
 }
 
 #[async_trait]
 impl GlobalConfigRepository for PgGlobalConfigRepository {
     async fn get_global_config(&self) -> Result<GlobalConfig, ApplicationError> {
         debug!("Fetching global config from database");
-        let query = "SELECT * FROM config.global LIMIT 1";
-        let config_dto: GlobalConfigDTO = query_as::<_, GlobalConfigDTO>(query)
-            .fetch_one(&self.pool)
+        // LIMIT 2 basta para detectar filas de más
+        let query = "SELECT * FROM config.global LIMIT 2";
+        let rows: Vec<GlobalConfigDTO> = query_as::<_, GlobalConfigDTO>(query)
+            .fetch_all(&self.pool)
             .await
             .map_err(|e| ApplicationError::DatabaseError(e.to_string()))?;
+        let config_dto = single_row(rows, "config.global")?;
         let config: GlobalConfig = config_dto.into();
         info!(
             "Global config fetched successfully: max_size={}, default_quota={}",
@@ -43,6 +85,10 @@ impl GlobalConfigRepository for PgGlobalConfigRepository {
     ) -> Result<GlobalConfig, ApplicationError> {
         let mut config = config;
         config.sanitize();
+        config.validate()?;
+
+        // The UPDATE below needs the single row to exist
+        self.insert_default_if_missing().await?;
 
         // If all fields are None, just return the current config
         if config.mime_types.is_none()
@@ -50,6 +96,13 @@ impl GlobalConfigRepository for PgGlobalConfigRepository {
             && config.chunk_size.is_none()
             && config.temp_file_life.is_none()
             && config.default_quota.is_none()
+            && config.mime_size_limits.is_none()
+            && config.trash_retention.is_none()
+            && config.strict_mime_check.is_none()
+            && config.stream_uploads.is_none()
+            && config.require_user_id.is_none()
+            && config.allow_duplicate_names.is_none()
+            && config.tiers.is_none()
         {
             return self.get_global_config().await;
         }
@@ -84,14 +137,50 @@ impl GlobalConfigRepository for PgGlobalConfigRepository {
             separated.push_bind_unseparated(default_quota as i64);
         }
 
+        if let Some(mime_size_limits) = &config.mime_size_limits {
+            separated.push("mime_size_limits = ");
+            separated.push_bind_unseparated(Json(mime_size_limits));
+        }
+
+        if let Some(trash_retention) = config.trash_retention {
+            separated.push("trash_retention = ");
+            separated.push_bind_unseparated(trash_retention as i64);
+        }
+
+        if let Some(strict_mime_check) = config.strict_mime_check {
+            separated.push("strict_mime_check = ");
+            separated.push_bind_unseparated(strict_mime_check);
+        }
+
+        if let Some(stream_uploads) = config.stream_uploads {
+            separated.push("stream_uploads = ");
+            separated.push_bind_unseparated(stream_uploads);
+        }
+
+        if let Some(require_user_id) = config.require_user_id {
+            separated.push("require_user_id = ");
+            separated.push_bind_unseparated(require_user_id);
+        }
+
+        if let Some(allow_duplicate_names) = config.allow_duplicate_names {
+            separated.push("allow_duplicate_names = ");
+            separated.push_bind_unseparated(allow_duplicate_names);
+        }
+
+        if let Some(tiers) = &config.tiers {
+            separated.push("tiers = ");
+            separated.push_bind_unseparated(Json(tiers));
+        }
+
         builder.push(" RETURNING *");
 
+        // Sin WHERE: si hay filas de más se actualizan todas para que no diverjan
         let query = builder.build_query_as::<GlobalConfigDTO>();
-        let updated_config_dto: GlobalConfigDTO = query
-            .fetch_one(&self.pool)
+        let rows: Vec<GlobalConfigDTO> = query
+            .fetch_all(&self.pool)
             .await
             .map_err(|e| ApplicationError::DatabaseError(e.to_string()))?;
 
-        Ok(updated_config_dto.into())
+        Ok(single_row(rows, "config.global")?.into())
     }
 }
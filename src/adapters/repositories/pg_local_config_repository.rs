@@ -47,9 +47,13 @@ impl LocalConfigRepository for PgLocalConfigRepository {
         debug!("Upserting local config for server_id: {}", server_id);
         let mut config = config;
         config.sanitize();
+        config.validate()?;
 
         // If no fields provided, insert with defaults or get existing
-        if config.provider.is_none() && config.server_name.is_none() && config.server_url.is_none()
+        if config.provider.is_none()
+            && config.server_name.is_none()
+            && config.server_url.is_none()
+            && config.default_quota.is_none()
         {
             debug!(
                 "No fields provided, inserting default config or getting existing for server_id: {}",
@@ -94,6 +98,9 @@ impl LocalConfigRepository for PgLocalConfigRepository {
                 let provider_str = match provider {
                     Provider::GDrive => "gdrive",
                     Provider::Supabase => "supabase",
+                    Provider::S3 => "s3",
+                    Provider::Azure => "azure",
+                    Provider::LocalFs => "localfs",
                 };
                 separated.push("provider = ");
                 separated.push_bind_unseparated(provider_str);
@@ -109,6 +116,11 @@ impl LocalConfigRepository for PgLocalConfigRepository {
                 separated.push_bind_unseparated(server_url);
             }
 
+            if let Some(default_quota) = config.default_quota {
+                separated.push("default_quota = ");
+                separated.push_bind_unseparated(default_quota as i64);
+            }
+
             builder.push(" WHERE server_id = ");
             builder.push_bind(server_id);
             builder.push(" RETURNING *");
@@ -123,20 +135,25 @@ impl LocalConfigRepository for PgLocalConfigRepository {
             let provider_str = match config.provider {
                 Some(Provider::GDrive) => "gdrive",
                 Some(Provider::Supabase) => "supabase",
+                Some(Provider::S3) => "s3",
+                Some(Provider::Azure) => "azure",
+                Some(Provider::LocalFs) => "localfs",
                 None => "gdrive", // default
             };
             let server_name = config.server_name.as_deref().unwrap_or("");
             let server_url = config.server_url.as_deref().unwrap_or("");
+            let default_quota = config.default_quota.map(|q| q as i64);
 
             query_as::<_, LocalConfigDTO>(
-                "INSERT INTO config.local (server_id, provider, server_name, server_url)
-                 VALUES ($1, $2, $3, $4)
+                "INSERT INTO config.local (server_id, provider, server_name, server_url, default_quota)
+                 VALUES ($1, $2, $3, $4, $5)
                  RETURNING *"
             )
             .bind(server_id)
             .bind(provider_str)
             .bind(server_name)
             .bind(server_url)
+            .bind(default_quota)
             .fetch_one(&self.pool)
             .await
             .map_err(|e| ApplicationError::DatabaseError(e.to_string()))?
@@ -1,14 +1,28 @@
 use async_trait::async_trait;
-use sqlx::{query_as, QueryBuilder};
+use chrono::{DateTime, Utc};
+use futures_util::{
+    future::BoxFuture,
+    stream::{self, BoxStream},
+    StreamExt,
+};
+use sqlx::{query_as, PgConnection, QueryBuilder};
+use tokio::sync::mpsc;
+use uuid::Uuid;
 
 use crate::{
     application::{
-        dto::metadata_dto::MetadataDTO, error::ApplicationError,
-        repositories::metadata_repository::MetadataRepository,
+        dto::{metadata_dto::MetadataDTO, user_dto::UserDTO},
+        error::ApplicationError,
+        repositories::metadata_repository::{MetadataRepository, MetadataStream},
+    },
+    domain::models::metadata::{
+        ClientStats, FileStats, Metadata, UserFilesFilter, UserFilesSortBy, UserUsageStats,
     },
-    domain::models::metadata::Metadata,
 };
 
+/// Filas en vuelo entre la consulta y el cliente; acota la memoria en streaming
+const STREAM_BUFFER: usize = 64;
+
 pub struct PgMetadataRepository {
     pool: sqlx::PgPool,
 }
@@ -17,6 +31,63 @@ impl PgMetadataRepository {
     pub fn new(pool: sqlx::PgPool) -> Self {
         Self { pool }
     }
+
+    /// La consulta corre en su propia tarea y se detiene cuando el cliente se desconecta
+    fn receiver_stream(rx: mpsc::Receiver<Result<Metadata, ApplicationError>>) -> MetadataStream {
+        Box::pin(stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|item| (item, rx))
+        }))
+    }
+
+    async fn forward_rows<'q>(
+        mut rows: BoxStream<'q, Result<MetadataDTO, sqlx::Error>>,
+        tx: mpsc::Sender<Result<Metadata, ApplicationError>>,
+    ) {
+        while let Some(row) = rows.next().await {
+            let item = row
+                .map(Metadata::from)
+                .map_err(|e| ApplicationError::DatabaseError(e.to_string()));
+            if tx.send(item).await.is_err() {
+                break;
+            }
+        }
+    }
+
+    /// Bloqueo hasta el final de la transacción que serializa el alta y la liberación de
+    /// las filas que comparten un objeto
+    async fn lock_object(conn: &mut PgConnection, object_id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended($1, 0))")
+            .bind(object_id)
+            .execute(conn)
+            .await
+            .map(|_| ())
+    }
+
+    /// Solo los valores van como parámetros; columna y dirección salen de enums cerrados
+    fn push_user_files_where(
+        builder: &mut QueryBuilder<'_, sqlx::Postgres>,
+        user_id: &str,
+        filter: &UserFilesFilter,
+    ) {
+        builder
+            .push(" WHERE trashed_at IS NULL AND user_id = ")
+            .push_bind(user_id.to_string());
+        if let Some(mime_type) = &filter.mime_type {
+            builder
+                .push(" AND mime_type = ")
+                .push_bind(mime_type.clone());
+        }
+    }
+
+    fn user_files_order(filter: &UserFilesFilter) -> String {
+        let column = match filter.sort_by {
+            UserFilesSortBy::UploadedAt => "uploaded_at",
+            UserFilesSortBy::Size => "size",
+            UserFilesSortBy::DownloadCount => "download_count",
+        };
+        let direction = if filter.descending { "DESC" } else { "ASC" };
+        format!(" ORDER BY {} {}, file_id", column, direction)
+    }
 }
 
 #[async_trait]
@@ -25,33 +96,82 @@ impl MetadataRepository for PgMetadataRepository {
         let mut metadata = metadata;
         metadata.sanitize();
 
-        let query = r#"
-            INSERT INTO application.metadata (
-                file_id, mime_type, size, user_id, description,
-                file_name, server_id, uploaded_at, download_count,
-                last_access, delete_at
-            )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
-            RETURNING *
-        "#;
-
         let new_metadata: Metadata = metadata.into();
 
-        let created: MetadataDTO = query_as::<_, MetadataDTO>(query)
-            .bind(&new_metadata.file_id)
-            .bind(&new_metadata.mime_type)
-            .bind(new_metadata.size as i64)
-            .bind(&new_metadata.user_id)
-            .bind(&new_metadata.description)
-            .bind(&new_metadata.file_name)
-            .bind(&new_metadata.server_id)
-            .bind(new_metadata.uploaded_at)
-            .bind(new_metadata.download_count as i64)
-            .bind(new_metadata.last_access)
-            .bind(new_metadata.delete_at)
-            .fetch_one(&self.pool)
+        let mut builder = QueryBuilder::new(
+            "INSERT INTO application.metadata (file_id, mime_type, size, user_id, description, \
+             file_name, server_id, uploaded_at, download_count, last_access, delete_at, client_id",
+        );
+        if new_metadata.trashed_at.is_some() {
+            builder.push(", trashed_at");
+        }
+        if new_metadata.content_hash.is_some() {
+            builder.push(", content_hash");
+        }
+        if new_metadata.object_id.is_some() {
+            builder.push(", object_id");
+        }
+        builder.push(") VALUES (");
+
+        let mut values = builder.separated(", ");
+        values.push_bind(&new_metadata.file_id);
+        values.push_bind(&new_metadata.mime_type);
+        values.push_bind(new_metadata.size as i64);
+        values.push_bind(&new_metadata.user_id);
+        values.push_bind(&new_metadata.description);
+        values.push_bind(&new_metadata.file_name);
+        values.push_bind(&new_metadata.server_id);
+        values.push_bind(new_metadata.uploaded_at);
+        values.push_bind(new_metadata.download_count as i64);
+        values.push_bind(new_metadata.last_access);
+        values.push_bind(new_metadata.delete_at);
+        values.push_bind(&new_metadata.client_id);
+        if let Some(trashed_at) = new_metadata.trashed_at {
+            values.push_bind(trashed_at);
+        }
+        if let Some(content_hash) = &new_metadata.content_hash {
+            values.push_bind(content_hash);
+        }
+        if let Some(object_id) = &new_metadata.object_id {
+            values.push_bind(object_id);
+        }
+        builder.push(") RETURNING *");
+
+        let db_error = |e: sqlx::Error| ApplicationError::DatabaseError(e.to_string());
+        let Some(object_id) = &new_metadata.object_id else {
+            let created: MetadataDTO = builder
+                .build_query_as::<MetadataDTO>()
+                .fetch_one(&self.pool)
+                .await
+                .map_err(db_error)?;
+            return Ok(created.into());
+        };
+
+        // El objeto reutilizado tiene que seguir teniendo filas cuando se inserta esta
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        Self::lock_object(&mut tx, object_id)
             .await
-            .map_err(|e| ApplicationError::DatabaseError(e.to_string()))?;
+            .map_err(db_error)?;
+        let (in_use,): (bool,) = query_as(
+            "SELECT EXISTS (SELECT 1 FROM application.metadata \
+             WHERE COALESCE(object_id, file_id) = $1)",
+        )
+        .bind(object_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(db_error)?;
+        if !in_use {
+            return Err(ApplicationError::Conflict(format!(
+                "Object {} was released before it could be reused",
+                object_id
+            )));
+        }
+        let created: MetadataDTO = builder
+            .build_query_as::<MetadataDTO>()
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        tx.commit().await.map_err(db_error)?;
 
         Ok(created.into())
     }
@@ -63,11 +183,37 @@ impl MetadataRepository for PgMetadataRepository {
             .bind(file_id)
             .fetch_one(&self.pool)
             .await
-            .map_err(|e| ApplicationError::DatabaseError(e.to_string()))?;
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => ApplicationError::NotFound,
+                _ => ApplicationError::DatabaseError(e.to_string()),
+            })?;
 
         Ok(fetched.into())
     }
 
+    async fn get_file_stats(&self, file_id: &str) -> Result<FileStats, ApplicationError> {
+        let query = r#"
+            SELECT download_count, last_access, size
+            FROM application.metadata
+            WHERE file_id = $1 AND trashed_at IS NULL
+        "#;
+
+        let (download_count, last_access, size): (i64, DateTime<Utc>, i64) = query_as(query)
+            .bind(file_id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => ApplicationError::NotFound,
+                _ => ApplicationError::DatabaseError(e.to_string()),
+            })?;
+
+        Ok(FileStats {
+            download_count: download_count as u64,
+            last_access,
+            size: size as u64,
+        })
+    }
+
     async fn update_metadata(&self, metadata: MetadataDTO) -> Result<Metadata, ApplicationError> {
         let mut metadata = metadata;
         metadata.sanitize();
@@ -82,6 +228,7 @@ impl MetadataRepository for PgMetadataRepository {
             && metadata.download_count.is_none()
             && metadata.last_access.is_none()
             && metadata.delete_at.is_none()
+            && metadata.trashed_at.is_none()
         {
             return self.get_metadata(&metadata.file_id).await;
         }
@@ -129,6 +276,10 @@ impl MetadataRepository for PgMetadataRepository {
             separated.push("delete_at = ");
             separated.push_bind_unseparated(metadata.delete_at);
         }
+        if metadata.trashed_at.is_some() {
+            separated.push("trashed_at = ");
+            separated.push_bind_unseparated(metadata.trashed_at);
+        }
 
         builder.push(" WHERE file_id = ");
         builder.push_bind(&metadata.file_id);
@@ -156,31 +307,342 @@ impl MetadataRepository for PgMetadataRepository {
         Ok(deleted.into())
     }
 
-    async fn increment_download_count(&self, file_id: &str) -> Result<Metadata, ApplicationError> {
+    async fn trash_metadata(&self, file_id: &str) -> Result<Metadata, ApplicationError> {
         let query = r#"
             UPDATE application.metadata
-            SET download_count = download_count + 1,
-                last_access = NOW()
+            SET trashed_at = COALESCE(trashed_at, NOW())
+            WHERE file_id = $1
+            RETURNING *
+        "#;
+
+        let trashed: MetadataDTO = query_as::<_, MetadataDTO>(query)
+            .bind(file_id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => ApplicationError::NotFound,
+                _ => ApplicationError::DatabaseError(e.to_string()),
+            })?;
+
+        Ok(trashed.into())
+    }
+
+    async fn restore_metadata(&self, file_id: &str) -> Result<Metadata, ApplicationError> {
+        let query = r#"
+            UPDATE application.metadata
+            SET trashed_at = NULL
             WHERE file_id = $1
             RETURNING *
         "#;
 
-        let updated: MetadataDTO = query_as::<_, MetadataDTO>(query)
+        let restored: MetadataDTO = query_as::<_, MetadataDTO>(query)
             .bind(file_id)
             .fetch_one(&self.pool)
             .await
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => ApplicationError::NotFound,
+                _ => ApplicationError::DatabaseError(e.to_string()),
+            })?;
+
+        Ok(restored.into())
+    }
+
+    async fn transfer_metadata(
+        &self,
+        file_id: &str,
+        to_user_id: Uuid,
+    ) -> Result<Metadata, ApplicationError> {
+        let db_error = |e: sqlx::Error| ApplicationError::DatabaseError(e.to_string());
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+
+        let file: MetadataDTO = query_as::<_, MetadataDTO>(
+            "SELECT * FROM application.metadata WHERE file_id = $1 FOR UPDATE",
+        )
+        .bind(file_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => ApplicationError::NotFound,
+            _ => db_error(e),
+        })?;
+
+        let from_user_id = file
+            .user_id
+            .as_deref()
+            .and_then(|id| Uuid::parse_str(id).ok())
+            .ok_or_else(|| {
+                ApplicationError::BadRequest(format!("File {} has no owner", file_id))
+            })?;
+        if from_user_id == to_user_id {
+            return Err(ApplicationError::BadRequest(format!(
+                "File {} already belongs to user {}",
+                file_id, to_user_id
+            )));
+        }
+        let size = file.size.unwrap_or(0) as i64;
+
+        // Ambos usuarios se bloquean en orden de uid para que dos traspasos cruzados no se
+        // bloqueen mutuamente
+        let users: Vec<UserDTO> = query_as::<_, UserDTO>(
+            "SELECT * FROM application.users WHERE uid = ANY($1) ORDER BY uid FOR UPDATE",
+        )
+        .bind(vec![from_user_id, to_user_id])
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        let target = users
+            .iter()
+            .find(|user| user.uid == to_user_id)
+            .ok_or(ApplicationError::NotFound)?;
+        let used_space = target.used_space.unwrap_or(0);
+        let total_space = target.total_space.unwrap_or(0);
+        if used_space.saturating_add(size as u64) > total_space {
+            return Err(ApplicationError::InsufficientStorage);
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE application.users
+            SET file_count = GREATEST(file_count - 1, 0),
+                used_space = GREATEST(used_space - $2, 0)
+            WHERE uid = $1
+        "#,
+        )
+        .bind(from_user_id)
+        .bind(size)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        sqlx::query(
+            r#"
+            UPDATE application.users
+            SET file_count = file_count + 1,
+                used_space = used_space + $2
+            WHERE uid = $1
+        "#,
+        )
+        .bind(to_user_id)
+        .bind(size)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        let transferred: MetadataDTO = query_as::<_, MetadataDTO>(
+            "UPDATE application.metadata SET user_id = $2 WHERE file_id = $1 RETURNING *",
+        )
+        .bind(file_id)
+        .bind(to_user_id.to_string())
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        tx.commit().await.map_err(db_error)?;
+        Ok(transferred.into())
+    }
+
+    async fn find_by_hash(
+        &self,
+        content_hash: &str,
+        server_id: &str,
+        user_id: Option<&str>,
+    ) -> Result<Option<Metadata>, ApplicationError> {
+        let query = r#"
+            SELECT * FROM application.metadata
+            WHERE content_hash = $1
+              AND server_id = $2
+              AND (($3::text IS NULL AND user_id IS NULL) OR user_id = $3)
+              AND trashed_at IS NULL
+            ORDER BY uploaded_at
+            LIMIT 1
+        "#;
+
+        let row: Option<MetadataDTO> = query_as::<_, MetadataDTO>(query)
+            .bind(content_hash)
+            .bind(server_id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await
             .map_err(|e| ApplicationError::DatabaseError(e.to_string()))?;
 
-        Ok(updated.into())
+        Ok(row.map(|dto| dto.into()))
+    }
+
+    async fn release_object(
+        &self,
+        object_id: &str,
+        file_ids: &[String],
+        delete_object: BoxFuture<'_, Result<(), ApplicationError>>,
+    ) -> Result<u64, ApplicationError> {
+        let db_error = |e: sqlx::Error| ApplicationError::DatabaseError(e.to_string());
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        Self::lock_object(&mut tx, object_id)
+            .await
+            .map_err(db_error)?;
+
+        let (references,): (i64,) = query_as(
+            r#"
+            SELECT COUNT(*) FROM application.metadata
+            WHERE COALESCE(object_id, file_id) = $1 AND file_id <> ALL($2)
+            "#,
+        )
+        .bind(object_id)
+        .bind(file_ids)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(db_error)?;
+        // Si el proveedor falla las filas se conservan y el borrado se puede reintentar
+        if references == 0 {
+            delete_object.await?;
+        }
+
+        sqlx::query("DELETE FROM application.metadata WHERE file_id = ANY($1)")
+            .bind(file_ids)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        tx.commit().await.map_err(db_error)?;
+
+        Ok(references as u64)
+    }
+
+    async fn set_content_hash(
+        &self,
+        file_id: &str,
+        content_hash: &str,
+    ) -> Result<(), ApplicationError> {
+        let query = r#"
+            UPDATE application.metadata
+            SET content_hash = $2
+            WHERE file_id = $1
+        "#;
+
+        let result = sqlx::query(query)
+            .bind(file_id)
+            .bind(content_hash)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| ApplicationError::DatabaseError(e.to_string()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(ApplicationError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn record_download(&self, file_id: &str) -> Result<(), ApplicationError> {
+        let query = r#"
+            UPDATE application.metadata
+            SET download_count = download_count + 1,
+                last_access = NOW()
+            WHERE file_id = $1
+        "#;
+
+        let result = sqlx::query(query)
+            .bind(file_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| ApplicationError::DatabaseError(e.to_string()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(ApplicationError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn export_metadata(
+        &self,
+        cursor: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<Metadata>, ApplicationError> {
+        let query = r#"
+            SELECT * FROM application.metadata
+            WHERE $1::text IS NULL OR file_id > $1
+            ORDER BY file_id
+            LIMIT $2
+        "#;
+
+        let rows: Vec<MetadataDTO> = query_as::<_, MetadataDTO>(query)
+            .bind(cursor)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| ApplicationError::DatabaseError(e.to_string()))?;
+
+        Ok(rows.into_iter().map(|dto| dto.into()).collect())
+    }
+
+    fn stream_export(&self, cursor: Option<String>, limit: Option<i64>) -> MetadataStream {
+        let pool = self.pool.clone();
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+
+        tokio::spawn(async move {
+            let query = r#"
+                SELECT * FROM application.metadata
+                WHERE $1::text IS NULL OR file_id > $1
+                ORDER BY file_id
+                LIMIT $2
+            "#;
+            let rows = query_as::<_, MetadataDTO>(query)
+                .bind(cursor)
+                .bind(limit)
+                .fetch(&pool);
+            Self::forward_rows(rows, tx).await;
+        });
+
+        Self::receiver_stream(rx)
+    }
+
+    fn stream_files_by_user(&self, user_id: String, filter: UserFilesFilter) -> MetadataStream {
+        let pool = self.pool.clone();
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+
+        tokio::spawn(async move {
+            let mut builder = QueryBuilder::new("SELECT * FROM application.metadata");
+            Self::push_user_files_where(&mut builder, &user_id, &filter);
+            builder.push(Self::user_files_order(&filter));
+            let rows = builder.build_query_as::<MetadataDTO>().fetch(&pool);
+            Self::forward_rows(rows, tx).await;
+        });
+
+        Self::receiver_stream(rx)
     }
 
-    async fn get_expired_files(&self) -> Result<Vec<Metadata>, ApplicationError> {
+    async fn get_expired_files(
+        &self,
+        trash_retention: u64,
+    ) -> Result<Vec<Metadata>, ApplicationError> {
         let query = r#"
             SELECT * FROM application.metadata
-            WHERE delete_at IS NOT NULL AND delete_at <= NOW()
+            WHERE (delete_at IS NOT NULL AND delete_at <= NOW())
+               OR (trashed_at IS NOT NULL AND trashed_at <= NOW() - make_interval(secs => $1))
         "#;
 
         let rows: Vec<MetadataDTO> = query_as::<_, MetadataDTO>(query)
+            .bind(trash_retention.min(i64::MAX as u64) as f64)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| ApplicationError::DatabaseError(e.to_string()))?;
+
+        Ok(rows.into_iter().map(|dto| dto.into()).collect())
+    }
+
+    async fn get_trashed_files(
+        &self,
+        trash_retention: u64,
+        limit: i64,
+    ) -> Result<Vec<Metadata>, ApplicationError> {
+        let query = r#"
+            SELECT * FROM application.metadata
+            WHERE trashed_at IS NOT NULL AND trashed_at <= NOW() - make_interval(secs => $1)
+            ORDER BY trashed_at, file_id
+            LIMIT $2
+        "#;
+
+        let rows: Vec<MetadataDTO> = query_as::<_, MetadataDTO>(query)
+            .bind(trash_retention.min(i64::MAX as u64) as f64)
+            .bind(limit)
             .fetch_all(&self.pool)
             .await
             .map_err(|e| ApplicationError::DatabaseError(e.to_string()))?;
@@ -200,4 +662,174 @@ impl MetadataRepository for PgMetadataRepository {
 
         Ok(rows.into_iter().map(|(id,)| id).collect())
     }
+
+    async fn get_files_by_user_paginated(
+        &self,
+        user_id: &str,
+        filter: &UserFilesFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<Metadata>, u64), ApplicationError> {
+        let mut count_builder = QueryBuilder::new("SELECT COUNT(*) FROM application.metadata");
+        Self::push_user_files_where(&mut count_builder, user_id, filter);
+        let (total,): (i64,) = count_builder
+            .build_query_as()
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| ApplicationError::DatabaseError(e.to_string()))?;
+
+        let mut page_builder = QueryBuilder::new("SELECT * FROM application.metadata");
+        Self::push_user_files_where(&mut page_builder, user_id, filter);
+        page_builder
+            .push(Self::user_files_order(filter))
+            .push(" LIMIT ")
+            .push_bind(limit)
+            .push(" OFFSET ")
+            .push_bind(offset);
+        let rows: Vec<MetadataDTO> = page_builder
+            .build_query_as::<MetadataDTO>()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| ApplicationError::DatabaseError(e.to_string()))?;
+
+        Ok((
+            rows.into_iter().map(|dto| dto.into()).collect(),
+            total as u64,
+        ))
+    }
+
+    async fn get_user_usage_stats(
+        &self,
+        user_id: &str,
+    ) -> Result<UserUsageStats, ApplicationError> {
+        let query = r#"
+            SELECT MAX(size)::BIGINT, MIN(uploaded_at)
+            FROM application.metadata
+            WHERE user_id = $1 AND trashed_at IS NULL
+        "#;
+
+        let (largest_file_size, oldest_file_uploaded_at): (Option<i64>, Option<DateTime<Utc>>) =
+            query_as(query)
+                .bind(user_id)
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| ApplicationError::DatabaseError(e.to_string()))?;
+
+        Ok(UserUsageStats {
+            largest_file_size: largest_file_size.map(|size| size as u64),
+            oldest_file_uploaded_at,
+        })
+    }
+
+    async fn get_files_by_user(&self, user_id: &str) -> Result<Vec<Metadata>, ApplicationError> {
+        let query = r#"
+            SELECT * FROM application.metadata
+            WHERE user_id = $1 AND trashed_at IS NULL
+            ORDER BY uploaded_at DESC
+        "#;
+
+        let rows: Vec<MetadataDTO> = query_as::<_, MetadataDTO>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| ApplicationError::DatabaseError(e.to_string()))?;
+
+        Ok(rows.into_iter().map(|dto| dto.into()).collect())
+    }
+
+    async fn schedule_user_files_expiry(
+        &self,
+        user_id: &str,
+        file_ids: Option<&[String]>,
+        older_than: Option<DateTime<Utc>>,
+        delete_at: DateTime<Utc>,
+    ) -> Result<u64, ApplicationError> {
+        let query = r#"
+            UPDATE application.metadata
+            SET delete_at = $2
+            WHERE user_id = $1
+              AND trashed_at IS NULL
+              AND ($3::text[] IS NULL OR file_id = ANY($3))
+              AND ($4::timestamptz IS NULL OR uploaded_at < $4)
+        "#;
+
+        let result = sqlx::query(query)
+            .bind(user_id)
+            .bind(delete_at)
+            .bind(file_ids)
+            .bind(older_than)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| ApplicationError::DatabaseError(e.to_string()))?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn find_user_files_by_name(
+        &self,
+        user_id: &str,
+        file_name: &str,
+    ) -> Result<Vec<Metadata>, ApplicationError> {
+        let query = r#"
+            SELECT * FROM application.metadata
+            WHERE user_id = $1 AND file_name = $2 AND trashed_at IS NULL
+            ORDER BY uploaded_at DESC
+        "#;
+
+        let rows: Vec<MetadataDTO> = query_as::<_, MetadataDTO>(query)
+            .bind(user_id)
+            .bind(file_name)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| ApplicationError::DatabaseError(e.to_string()))?;
+
+        Ok(rows.into_iter().map(|dto| dto.into()).collect())
+    }
+
+    async fn get_client_stats(&self) -> Result<Vec<ClientStats>, ApplicationError> {
+        let query = r#"
+            SELECT client_id,
+                   COUNT(*) AS file_count,
+                   COALESCE(SUM(size), 0)::BIGINT AS total_size,
+                   COALESCE(SUM(download_count), 0)::BIGINT AS download_count
+            FROM application.metadata
+            GROUP BY client_id
+            ORDER BY client_id NULLS LAST
+        "#;
+
+        let rows: Vec<(Option<String>, i64, i64, i64)> = sqlx::query_as(query)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| ApplicationError::DatabaseError(e.to_string()))?;
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(client_id, file_count, total_size, download_count)| ClientStats {
+                    client_id,
+                    file_count: file_count as u64,
+                    total_size: total_size as u64,
+                    download_count: download_count as u64,
+                },
+            )
+            .collect())
+    }
+
+    async fn database_now(&self) -> Result<DateTime<Utc>, ApplicationError> {
+        let (now,): (DateTime<Utc>,) =
+            sqlx::query_as("SELECT NOW()")
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| ApplicationError::DatabaseError(e.to_string()))?;
+
+        Ok(now)
+    }
+
+    async fn ping(&self) -> Result<(), ApplicationError> {
+        sqlx::query("SELECT 1")
+            .execute(&self.pool)
+            .await
+            .map_err(|e| ApplicationError::DatabaseError(e.to_string()))?;
+        Ok(())
+    }
 }
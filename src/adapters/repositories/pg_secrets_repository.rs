@@ -1,6 +1,7 @@
 use async_trait::async_trait;
 use sqlx::{query_as, QueryBuilder};
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
 
 use crate::{
     application::{
@@ -10,6 +11,8 @@ use crate::{
     domain::config::secrets::Secrets,
 };
 
+use super::single_row::single_row;
+
 pub struct PgSecretsRepository {
     pool: sqlx::PgPool,
 }
@@ -18,17 +21,42 @@ impl PgSecretsRepository {
     pub fn new(pool: sqlx::PgPool) -> Self {
         Self { pool }
     }
+
+    /// Inserta la fila por defecto solo si la tabla está vacía; devuelve si se creó
+    ///
+    /// El vk_secret inicial es aleatorio para que nadie pueda autenticarse con un
+    /// secreto vacío hasta que se configure uno real
+    async fn insert_default_if_missing(&self) -> Result<bool, ApplicationError> {
+        let query = "
+            INSERT INTO config.secrets (db_password, db_username, vk_secret)
+            SELECT '', '', $1
+            WHERE NOT EXISTS (SELECT 1 FROM config.secrets)
+        ";
+        let result = sqlx::query(query)
+            .bind(Uuid::new_v4().simple().to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| ApplicationError::DatabaseError(e.to_string()))?;
+
+        let created = result.rows_affected() > 0;
+        if created {
+            warn!("Secrets row was missing, created placeholders; set vk_secret and provider");
+        }
+        Ok(created)
+    }
 }
 
 #[async_trait]
 impl SecretsRepository for PgSecretsRepository {
     async fn get_secrets(&self) -> Result<Secrets, ApplicationError> {
         debug!("Fetching secrets from database");
-        let query = "SELECT * FROM config.secrets LIMIT 1";
-        let secrets_dto: SecretsDTO = query_as::<_, SecretsDTO>(query)
-            .fetch_one(&self.pool)
+        // LIMIT 2 basta para detectar filas de más
+        let query = "SELECT * FROM config.secrets LIMIT 2";
+        let rows: Vec<SecretsDTO> = query_as::<_, SecretsDTO>(query)
+            .fetch_all(&self.pool)
             .await
             .map_err(|e| ApplicationError::DatabaseError(e.to_string()))?;
+        let secrets_dto = single_row(rows, "config.secrets")?;
         let secrets: Secrets = secrets_dto.into();
         info!("Secrets fetched successfully: db_username={}, has_gdrive_secrets={}, has_supabase_secrets={}",
               secrets.db_username,
@@ -41,12 +69,18 @@ impl SecretsRepository for PgSecretsRepository {
         let mut secrets = secrets;
         secrets.sanitize();
 
+        // The UPDATE below needs the single row to exist
+        self.insert_default_if_missing().await?;
+
         // If all fields are None, just return the current secrets
         if secrets.db_password.is_none()
             && secrets.db_username.is_none()
             && secrets.vk_secret.is_none()
             && secrets.gdrive_secrets.is_none()
             && secrets.supabase_secrets.is_none()
+            && secrets.s3_secrets.is_none()
+            && secrets.azure_secrets.is_none()
+            && secrets.local_fs_secrets.is_none()
         {
             return self.get_secrets().await;
         }
@@ -85,14 +119,36 @@ impl SecretsRepository for PgSecretsRepository {
             );
         }
 
+        if let Some(ref s3_secrets) = secrets.s3_secrets {
+            separated.push("s3_secrets = ");
+            separated.push_bind_unseparated(
+                serde_json::to_value(s3_secrets).unwrap_or(serde_json::Value::Null),
+            );
+        }
+
+        if let Some(ref azure_secrets) = secrets.azure_secrets {
+            separated.push("azure_secrets = ");
+            separated.push_bind_unseparated(
+                serde_json::to_value(azure_secrets).unwrap_or(serde_json::Value::Null),
+            );
+        }
+
+        if let Some(ref local_fs_secrets) = secrets.local_fs_secrets {
+            separated.push("localfs_secrets = ");
+            separated.push_bind_unseparated(
+                serde_json::to_value(local_fs_secrets).unwrap_or(serde_json::Value::Null),
+            );
+        }
+
         builder.push(" RETURNING *");
 
+        // Sin WHERE: si hay filas de más se actualizan todas para que no diverjan
         let query = builder.build_query_as::<SecretsDTO>();
-        let updated_secrets_dto: SecretsDTO = query
-            .fetch_one(&self.pool)
+        let rows: Vec<SecretsDTO> = query
+            .fetch_all(&self.pool)
             .await
             .map_err(|e| ApplicationError::DatabaseError(e.to_string()))?;
 
-        Ok(updated_secrets_dto.into())
+        Ok(single_row(rows, "config.secrets")?.into())
     }
 }
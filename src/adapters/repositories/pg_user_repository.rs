@@ -22,35 +22,53 @@ impl PgUserRepository {
 #[async_trait]
 impl UserRepository for PgUserRepository {
     async fn create_user(&self, user: UserDTO, new_space: u64) -> Result<User, ApplicationError> {
-        let query = r#"
-            INSERT INTO application.users (uid, file_count, total_space, used_space) 
-            VALUES ($1, $2, $3, $4) 
+        // La columna tier solo se escribe si hay plan, para no exigirla a instalaciones sin migrar
+        let (tier_column, tier_value) = match user.tier {
+            Some(_) => (", tier", ", $5"),
+            None => ("", ""),
+        };
+        let query = format!(
+            r#"
+            INSERT INTO application.users (uid, file_count, total_space, used_space{}) 
+            VALUES ($1, $2, $3, $4{}) 
             RETURNING *
-        "#;
+        "#,
+            tier_column, tier_value
+        );
         let new_user = User {
             uid: user.uid,
             file_count: 0,
             total_space: new_space,
             used_space: 0,
+            tier: user.tier,
         };
-        let created_user: UserDTO = query_as::<_, UserDTO>(&query)
-            .bind(&new_user.uid)
+        let mut insert = query_as::<_, UserDTO>(&query)
+            .bind(new_user.uid)
             .bind(new_user.file_count as i64)
             .bind(new_user.total_space as i64)
-            .bind(new_user.used_space as i64)
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|e| ApplicationError::DatabaseError(e.to_string()))?;
+            .bind(new_user.used_space as i64);
+        if let Some(tier) = &new_user.tier {
+            insert = insert.bind(tier);
+        }
+        let created_user: UserDTO = insert.fetch_one(&self.pool).await.map_err(|e| match e {
+            sqlx::Error::Database(ref db) if db.is_unique_violation() => {
+                ApplicationError::Conflict(format!("User {} already exists", new_user.uid))
+            }
+            e => ApplicationError::DatabaseError(e.to_string()),
+        })?;
         Ok(created_user.into())
     }
 
     async fn get_user(&self, user: UserDTO) -> Result<User, ApplicationError> {
         let query = "SELECT * FROM application.users WHERE uid = $1";
         let fetched_user: UserDTO = query_as::<_, UserDTO>(query)
-            .bind(&user.uid)
+            .bind(user.uid)
             .fetch_one(&self.pool)
             .await
-            .map_err(|e| ApplicationError::DatabaseError(e.to_string()))?;
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => ApplicationError::NotFound,
+                _ => ApplicationError::DatabaseError(e.to_string()),
+            })?;
         Ok(fetched_user.into())
     }
 
@@ -75,7 +93,7 @@ impl UserRepository for PgUserRepository {
             separated.push_bind_unseparated(used_space as i64);
         }
         builder.push(" WHERE uid = ");
-        builder.push_bind(&user.uid);
+        builder.push_bind(user.uid);
         builder.push(" RETURNING *");
         let query = builder.build_query_as::<UserDTO>();
         let updated_user = query
@@ -88,7 +106,7 @@ impl UserRepository for PgUserRepository {
     async fn delete_user(&self, user: UserDTO) -> Result<User, ApplicationError> {
         let query = "DELETE FROM application.users WHERE uid = $1 RETURNING *";
         let deleted_user: UserDTO = query_as::<_, UserDTO>(query)
-            .bind(&user.uid)
+            .bind(user.uid)
             .fetch_one(&self.pool)
             .await
             .map_err(|e| ApplicationError::DatabaseError(e.to_string()))?;
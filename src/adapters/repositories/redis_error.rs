@@ -0,0 +1,48 @@
+use crate::application::error::ApplicationError;
+
+/// Un Redis caído o inalcanzable es 503 (el gateway puede enrutar a otra instancia);
+/// cualquier otro fallo sigue siendo un error interno
+pub(super) fn redis_error(context: &str, error: redis::RedisError) -> ApplicationError {
+    if error.is_io_error()
+        || error.is_connection_refusal()
+        || error.is_connection_dropped()
+        || error.is_timeout()
+    {
+        ApplicationError::ServiceUnavailable(format!("{}: Redis unreachable: {}", context, error))
+    } else {
+        ApplicationError::InternalError(format!("{}: {}", context, error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::*;
+
+    #[test]
+    fn connection_failures_are_service_unavailable() {
+        for kind in [
+            io::ErrorKind::ConnectionRefused,
+            io::ErrorKind::ConnectionReset,
+            io::ErrorKind::TimedOut,
+        ] {
+            let error = redis_error("Failed to store token", io::Error::from(kind).into());
+            assert!(
+                matches!(&error, ApplicationError::ServiceUnavailable(msg) if msg.starts_with("Failed to store token")),
+                "{:?} mapped to {:?}",
+                kind,
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn other_redis_errors_stay_internal() {
+        let error = redis::RedisError::from((redis::ErrorKind::TypeError, "unexpected reply"));
+        assert!(matches!(
+            redis_error("Failed to verify token", error),
+            ApplicationError::InternalError(_)
+        ));
+    }
+}
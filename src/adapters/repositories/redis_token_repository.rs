@@ -3,6 +3,7 @@ use redis::AsyncCommands;
 use tracing::info;
 use uuid::Uuid;
 
+use super::redis_error::redis_error;
 use crate::application::{
     error::ApplicationError, repositories::token_repository::TokenRepository,
 };
@@ -19,6 +20,10 @@ impl RedisTokenRepository {
     fn get_redis_key(token: &str) -> String {
         format!("upload_token:{}", token)
     }
+
+    fn user_token_key(token: &str) -> String {
+        format!("user_token:{}", token)
+    }
 }
 
 #[async_trait]
@@ -41,9 +46,7 @@ impl TokenRepository for RedisTokenRepository {
 
         conn.set_ex::<_, _, ()>(&key, &value, ttl_seconds)
             .await
-            .map_err(|e| {
-                ApplicationError::InternalError(format!("Failed to store token: {}", e))
-            })?;
+            .map_err(|e| redis_error("Failed to store token", e))?;
 
         info!("Token stored successfully in Redis");
         Ok(token)
@@ -59,9 +62,10 @@ impl TokenRepository for RedisTokenRepository {
         info!("Verifying and consuming token from Redis: key='{}'", key);
 
         // GETDEL es atómico - garantiza un solo uso
-        let value: Option<String> = conn.get_del(&key).await.map_err(|e| {
-            ApplicationError::InternalError(format!("Failed to verify token: {}", e))
-        })?;
+        let value: Option<String> = conn
+            .get_del(&key)
+            .await
+            .map_err(|e| redis_error("Failed to verify token", e))?;
 
         info!("Token value retrieved from Redis: {:?}", value);
 
@@ -80,4 +84,92 @@ impl TokenRepository for RedisTokenRepository {
             }
         }
     }
+
+    async fn generate_user_token(
+        &self,
+        user_id: &str,
+        ttl_seconds: u64,
+    ) -> Result<String, ApplicationError> {
+        let token = Uuid::new_v4().to_string();
+        let mut conn = self.client.clone();
+
+        conn.set_ex::<_, _, ()>(Self::user_token_key(&token), user_id, ttl_seconds)
+            .await
+            .map_err(|e| redis_error("Failed to store user token", e))?;
+
+        info!("User token issued for user_id: {}", user_id);
+        Ok(token)
+    }
+
+    async fn verify_user_token(&self, token: &str) -> Result<String, ApplicationError> {
+        let mut conn = self.client.clone();
+
+        let user_id: Option<String> = conn
+            .get(Self::user_token_key(token))
+            .await
+            .map_err(|e| redis_error("Failed to verify user token", e))?;
+
+        user_id
+            .filter(|user_id| !user_id.is_empty())
+            .ok_or(ApplicationError::InvalidToken)
+    }
+
+    async fn ping(&self) -> Result<(), ApplicationError> {
+        let mut conn = self.client.clone();
+        let _pong: String = redis::cmd("PING")
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| redis_error("Redis ping failed", e))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    /// Redis falso: responde +OK al handshake y cierra la conexión con el primer comando de
+    /// tokens, como un Redis que cae a mitad de servicio
+    async fn dying_redis() -> redis::aio::ConnectionManager {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0; 4096];
+            loop {
+                let read = socket.read(&mut buffer).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buffer[..read]);
+                if read == 0 || request.contains("SETEX") || request.contains("GETDEL") {
+                    return;
+                }
+                // Un +OK por comando del pipeline
+                let commands = request.matches("\r\n*").count() + 1;
+                socket
+                    .write_all("+OK\r\n".repeat(commands).as_bytes())
+                    .await
+                    .unwrap();
+            }
+        });
+
+        let client = redis::Client::open(format!("redis://{}", address)).unwrap();
+        redis::aio::ConnectionManager::new(client).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn dropped_redis_connection_is_service_unavailable() {
+        let repository = RedisTokenRepository::new(dying_redis().await);
+
+        let result = repository.generate_token(None, 60).await;
+
+        assert!(
+            matches!(result, Err(ApplicationError::ServiceUnavailable(_))),
+            "{:?}",
+            result
+        );
+    }
 }
@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use redis::AsyncCommands;
+use tracing::info;
+
+use super::redis_error::redis_error;
+use crate::{
+    application::{
+        error::ApplicationError, repositories::upload_session_repository::UploadSessionRepository,
+    },
+    domain::models::upload_session::UploadSession,
+};
+
+/// Añade un bloque validando tamaño, offset y sesión cerrada en un solo paso
+///
+/// KEYS: hash de la sesión, lista de bloques. ARGV: bloque, offset esperado (-1 = cualquiera).
+/// Códigos negativos: -1 no existe, -2 ya recibió el último bloque, -3 bloque mayor que
+/// chunk_size, -4 supera max_size, -5 offset distinto de los bytes recibidos
+const APPEND_CHUNK_SCRIPT: &str = r#"
+if redis.call('EXISTS', KEYS[1]) == 0 then return -1 end
+if redis.call('HGET', KEYS[1], 'sealed') == '1' then return -2 end
+local chunk_size = tonumber(redis.call('HGET', KEYS[1], 'chunk_size'))
+local max_size = tonumber(redis.call('HGET', KEYS[1], 'max_size'))
+local received = tonumber(redis.call('HGET', KEYS[1], 'received'))
+local length = string.len(ARGV[1])
+if length > chunk_size then return -3 end
+if received + length > max_size then return -4 end
+local offset = tonumber(ARGV[2])
+if offset >= 0 and offset ~= received then return -5 end
+redis.call('RPUSH', KEYS[2], ARGV[1])
+redis.call('PEXPIRE', KEYS[2], redis.call('PTTL', KEYS[1]))
+if length < chunk_size then redis.call('HSET', KEYS[1], 'sealed', '1') end
+return redis.call('HINCRBY', KEYS[1], 'received', length)
+"#;
+
+pub struct RedisUploadSessionRepository {
+    client: redis::aio::ConnectionManager,
+}
+
+impl RedisUploadSessionRepository {
+    pub fn new(client: redis::aio::ConnectionManager) -> Self {
+        Self { client }
+    }
+
+    fn get_redis_key(session_id: &str) -> String {
+        format!("upload_session:{}", session_id)
+    }
+
+    fn get_chunks_key(session_id: &str) -> String {
+        format!("upload_session:{}:chunks", session_id)
+    }
+
+    fn parse_session(fields: HashMap<String, String>) -> Result<UploadSession, ApplicationError> {
+        let mut session: UploadSession = fields
+            .get("session")
+            .map(|json| serde_json::from_str(json))
+            .transpose()
+            .map_err(|e| ApplicationError::InternalError(format!("Corrupt upload session: {}", e)))?
+            .ok_or(ApplicationError::NotFound)?;
+
+        session.received_bytes = fields
+            .get("received")
+            .and_then(|received| received.parse().ok())
+            .unwrap_or(0);
+        Ok(session)
+    }
+}
+
+#[async_trait]
+impl UploadSessionRepository for RedisUploadSessionRepository {
+    async fn create_session(
+        &self,
+        session: &UploadSession,
+        ttl_seconds: u64,
+    ) -> Result<(), ApplicationError> {
+        let key = Self::get_redis_key(&session.session_id);
+        let json = serde_json::to_string(session).map_err(|e| {
+            ApplicationError::InternalError(format!("Failed to serialize upload session: {}", e))
+        })?;
+
+        let mut conn = self.client.clone();
+        let () = redis::pipe()
+            .atomic()
+            .hset_multiple(
+                &key,
+                &[
+                    ("session", json),
+                    ("chunk_size", session.chunk_size.to_string()),
+                    ("max_size", session.max_size.to_string()),
+                    ("received", "0".to_string()),
+                ],
+            )
+            .ignore()
+            .expire(&key, ttl_seconds as i64)
+            .ignore()
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| redis_error("Failed to store upload session", e))?;
+
+        info!(
+            "Upload session {} opened, expires in {}s",
+            session.session_id, ttl_seconds
+        );
+        Ok(())
+    }
+
+    async fn get_session(&self, session_id: &str) -> Result<UploadSession, ApplicationError> {
+        let mut conn = self.client.clone();
+        let key = Self::get_redis_key(session_id);
+        let fields: HashMap<String, String> = conn
+            .hgetall(&key)
+            .await
+            .map_err(|e| redis_error("Failed to read upload session", e))?;
+
+        Self::parse_session(fields)
+    }
+
+    async fn append_chunk(
+        &self,
+        session_id: &str,
+        chunk: &[u8],
+        expected_offset: Option<u64>,
+    ) -> Result<u64, ApplicationError> {
+        let mut conn = self.client.clone();
+        let received: i64 = redis::Script::new(APPEND_CHUNK_SCRIPT)
+            .key(Self::get_redis_key(session_id))
+            .key(Self::get_chunks_key(session_id))
+            .arg(chunk)
+            .arg(expected_offset.map_or(-1, |offset| offset as i64))
+            .invoke_async(&mut conn)
+            .await
+            .map_err(|e| redis_error("Failed to append chunk", e))?;
+
+        match received {
+            -1 => Err(ApplicationError::NotFound),
+            -2 => Err(ApplicationError::BadRequest(
+                "Upload session already received its final chunk".to_string(),
+            )),
+            -3 => Err(ApplicationError::BadRequest(
+                "Chunk exceeds the session chunk size".to_string(),
+            )),
+            -4 => Err(ApplicationError::PayloadTooLarge),
+            -5 => Err(ApplicationError::BadRequest(
+                "Upload-Offset does not match the bytes received".to_string(),
+            )),
+            received => Ok(received as u64),
+        }
+    }
+
+    async fn take_session(
+        &self,
+        session_id: &str,
+    ) -> Result<(UploadSession, Bytes), ApplicationError> {
+        let key = Self::get_redis_key(session_id);
+        let chunks_key = Self::get_chunks_key(session_id);
+
+        // MULTI: solo una petición concurrente recibe los bloques antes del borrado
+        let mut conn = self.client.clone();
+        let (fields, chunks): (HashMap<String, String>, Vec<Vec<u8>>) = redis::pipe()
+            .atomic()
+            .hgetall(&key)
+            .lrange(&chunks_key, 0, -1)
+            .del(&[&key, &chunks_key])
+            .ignore()
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| redis_error("Failed to read upload session", e))?;
+
+        let session = Self::parse_session(fields)?;
+        Ok((session, chunks.concat().into()))
+    }
+
+    async fn delete_session(&self, session_id: &str) -> Result<(), ApplicationError> {
+        let key = Self::get_redis_key(session_id);
+        let chunks_key = Self::get_chunks_key(session_id);
+
+        let mut conn = self.client.clone();
+        let (removed,): (i64,) = redis::pipe()
+            .atomic()
+            .del(&key)
+            .del(&chunks_key)
+            .ignore()
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| redis_error("Failed to delete upload session", e))?;
+
+        if removed == 0 {
+            return Err(ApplicationError::NotFound);
+        }
+
+        info!("Upload session {} canceled", session_id);
+        Ok(())
+    }
+}
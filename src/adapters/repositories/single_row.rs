@@ -0,0 +1,41 @@
+use tracing::warn;
+
+use crate::application::error::ApplicationError;
+
+/// Las tablas config.global y config.secrets deben tener exactamente una fila
+///
+/// Sin filas devuelve NotFound (el llamador siembra los valores por defecto); con
+/// varias avisa y usa la primera, para no tumbar la instancia por una base mal sembrada
+pub(super) fn single_row<T>(rows: Vec<T>, table: &str) -> Result<T, ApplicationError> {
+    if rows.len() > 1 {
+        warn!(
+            "{} has {} rows but must have exactly one; using the first, remove the extra rows",
+            table,
+            rows.len()
+        );
+    }
+    rows.into_iter().next().ok_or(ApplicationError::NotFound)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_single_row_is_returned() {
+        assert_eq!(single_row(vec![7], "config.global").unwrap(), 7);
+    }
+
+    #[test]
+    fn an_empty_table_is_not_found() {
+        assert!(matches!(
+            single_row(Vec::<u8>::new(), "config.secrets"),
+            Err(ApplicationError::NotFound)
+        ));
+    }
+
+    #[test]
+    fn extra_rows_fall_back_to_the_first() {
+        assert_eq!(single_row(vec![1, 2], "config.global").unwrap(), 1);
+    }
+}
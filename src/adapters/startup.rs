@@ -0,0 +1,203 @@
+use std::{fmt::Debug, future::Future, time::Duration};
+
+use sqlx::postgres::PgPoolOptions;
+use tracing::{info, warn};
+
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Presupuesto de reintentos para conectar a dependencias al arrancar
+#[derive(Debug, Clone, Copy)]
+pub struct ConnectRetry {
+    pub max_attempts: u32,
+    pub interval: Duration,
+}
+
+impl ConnectRetry {
+    /// STARTUP_CONNECT_ATTEMPTS (default 5) y STARTUP_CONNECT_INTERVAL_MS (default 1000)
+    pub fn from_env() -> Self {
+        let max_attempts = std::env::var("STARTUP_CONNECT_ATTEMPTS")
+            .ok()
+            .map(|v| {
+                v.parse::<u32>()
+                    .expect("STARTUP_CONNECT_ATTEMPTS must be a valid u32")
+            })
+            .unwrap_or(5);
+        let interval_ms = std::env::var("STARTUP_CONNECT_INTERVAL_MS")
+            .ok()
+            .map(|v| {
+                v.parse::<u64>()
+                    .expect("STARTUP_CONNECT_INTERVAL_MS must be a valid u64")
+            })
+            .unwrap_or(1000);
+
+        Self {
+            max_attempts: max_attempts.max(1),
+            interval: Duration::from_millis(interval_ms),
+        }
+    }
+
+    /// Espera antes del reintento `attempt` (1-based): interval * 2^(attempt-1), máximo 30s
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.interval
+            .saturating_mul(1 << (attempt - 1).min(16))
+            .min(MAX_BACKOFF)
+    }
+
+    /// Reintenta `connect` hasta que tenga éxito o se agote el presupuesto
+    pub async fn run<T, E, F, Fut>(&self, dependency: &str, mut connect: F) -> Result<T, E>
+    where
+        E: Debug,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut attempt = 1;
+        loop {
+            info!(
+                "Connecting to {} (attempt {}/{})",
+                dependency, attempt, self.max_attempts
+            );
+            match connect().await {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt < self.max_attempts => {
+                    let delay = self.backoff(attempt);
+                    warn!(
+                        "Failed to connect to {} (attempt {}/{}): {:?}, retrying in {:?}",
+                        dependency, attempt, self.max_attempts, e, delay
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Tamaño y tiempos del pool de PostgreSQL
+#[derive(Debug, Clone, Copy)]
+pub struct DbPoolConfig {
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub acquire_timeout: Duration,
+    /// None = las conexiones ociosas no se cierran
+    pub idle_timeout: Option<Duration>,
+}
+
+impl DbPoolConfig {
+    /// DB_MAX_CONNECTIONS (default 5), DB_MIN_CONNECTIONS (default 0),
+    /// DB_ACQUIRE_TIMEOUT_SECS (default 30) y DB_IDLE_TIMEOUT_SECS (default 600, 0 = nunca)
+    pub fn from_env() -> Self {
+        let max_connections = std::env::var("DB_MAX_CONNECTIONS")
+            .ok()
+            .map(|v| {
+                v.parse::<u32>()
+                    .expect("DB_MAX_CONNECTIONS must be a valid u32")
+            })
+            .unwrap_or(5);
+        let min_connections = std::env::var("DB_MIN_CONNECTIONS")
+            .ok()
+            .map(|v| {
+                v.parse::<u32>()
+                    .expect("DB_MIN_CONNECTIONS must be a valid u32")
+            })
+            .unwrap_or(0);
+        let acquire_timeout_secs = std::env::var("DB_ACQUIRE_TIMEOUT_SECS")
+            .ok()
+            .map(|v| {
+                v.parse::<u64>()
+                    .expect("DB_ACQUIRE_TIMEOUT_SECS must be a valid u64")
+            })
+            .unwrap_or(30);
+        let idle_timeout_secs = std::env::var("DB_IDLE_TIMEOUT_SECS")
+            .ok()
+            .map(|v| {
+                v.parse::<u64>()
+                    .expect("DB_IDLE_TIMEOUT_SECS must be a valid u64")
+            })
+            .unwrap_or(600);
+
+        if max_connections == 0 {
+            panic!("DB_MAX_CONNECTIONS must be at least 1");
+        }
+        if max_connections < min_connections {
+            panic!(
+                "DB_MAX_CONNECTIONS ({}) must be greater than or equal to DB_MIN_CONNECTIONS ({})",
+                max_connections, min_connections
+            );
+        }
+
+        Self {
+            max_connections,
+            min_connections,
+            acquire_timeout: Duration::from_secs(acquire_timeout_secs),
+            idle_timeout: (idle_timeout_secs > 0).then(|| Duration::from_secs(idle_timeout_secs)),
+        }
+    }
+
+    pub fn pool_options(&self) -> PgPoolOptions {
+        PgPoolOptions::new()
+            .max_connections(self.max_connections)
+            .min_connections(self.min_connections)
+            .acquire_timeout(self.acquire_timeout)
+            .idle_timeout(self.idle_timeout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+
+    fn retry(max_attempts: u32) -> ConnectRetry {
+        ConnectRetry {
+            max_attempts,
+            interval: Duration::from_millis(1),
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let retry = ConnectRetry {
+            max_attempts: 10,
+            interval: Duration::from_secs(1),
+        };
+        assert_eq!(retry.backoff(1), Duration::from_secs(1));
+        assert_eq!(retry.backoff(2), Duration::from_secs(2));
+        assert_eq!(retry.backoff(4), Duration::from_secs(8));
+        assert_eq!(retry.backoff(6), MAX_BACKOFF);
+        assert_eq!(retry.backoff(40), MAX_BACKOFF);
+    }
+
+    #[tokio::test]
+    async fn dependency_that_comes_up_late_connects() {
+        let attempts = AtomicU32::new(0);
+
+        let result: Result<&str, &str> = retry(5)
+            .run("redis", || async {
+                match attempts.fetch_add(1, Ordering::SeqCst) + 1 {
+                    3 => Ok("connected"),
+                    _ => Err("connection refused"),
+                }
+            })
+            .await;
+
+        assert_eq!(result, Ok("connected"));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_the_budget_with_the_last_error() {
+        let attempts = AtomicU32::new(0);
+
+        let result: Result<(), String> = retry(4)
+            .run("postgres", || async {
+                let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+                Err(format!("attempt {} refused", attempt))
+            })
+            .await;
+
+        assert_eq!(result, Err("attempt 4 refused".to_string()));
+        assert_eq!(attempts.load(Ordering::SeqCst), 4);
+    }
+}
@@ -2,14 +2,25 @@ use axum::extract::FromRef;
 use std::sync::{Arc, Mutex};
 
 use crate::{
-    adapters::storage_service_wrapper::StorageServiceWrapper,
-    application::repositories::{
-        global_config_repository::GlobalConfigRepository,
-        local_config_repository::LocalConfigRepository, metadata_repository::MetadataRepository,
-        secrets_repository::SecretsRepository, token_repository::TokenRepository,
-        user_repository::UserRepository,
+    adapters::{
+        client_ip::TrustedProxies, error_metrics::ErrorMetrics,
+        storage_service_wrapper::StorageServiceWrapper, upload_buffer::UploadBufferBudget,
     },
-    domain::config::{global::GlobalConfig, local::LocalConfig, secrets::Secrets},
+    application::{
+        repositories::{
+            global_config_repository::GlobalConfigRepository,
+            local_config_repository::LocalConfigRepository,
+            metadata_repository::MetadataRepository, secrets_repository::SecretsRepository,
+            token_repository::TokenRepository, upload_session_repository::UploadSessionRepository,
+            user_repository::UserRepository,
+        },
+        services::FileScanner,
+    },
+    domain::{
+        config::{global::GlobalConfig, local::LocalConfig, secrets::Secrets},
+        models::file::MimeTypePolicy,
+    },
+    services::EventQueue,
 };
 
 #[derive(Clone, FromRef)]
@@ -25,4 +36,31 @@ pub struct AppState {
     pub local_config_repository: Arc<dyn LocalConfigRepository>,
     pub storage_service: StorageServiceWrapper,
     pub token_repository: Arc<dyn TokenRepository>,
+    pub upload_session_repository: Arc<dyn UploadSessionRepository>,
+    pub file_scanner: Option<Arc<dyn FileScanner>>,
+    pub event_queue: EventQueue,
+    /// Límite de bytes/segundo por descarga; 0 = sin límite
+    #[from_ref(skip)]
+    pub download_bytes_per_second: u64,
+    /// Llamadas simultáneas al proveedor dentro de una misma petición multi-objeto
+    pub provider_request_concurrency: usize,
+    /// Bytes de archivo retenidos en memoria entre todas las subidas multipart en curso
+    pub upload_buffer_budget: UploadBufferBudget,
+    /// Segundos que se rechazan subidas tras un fallo del proveedor (sonda o llamada); 0 = nunca
+    #[from_ref(skip)]
+    pub storage_down_reject_secs: u64,
+    /// Diferencia máxima con NOW() de la base de datos antes de reportar "degraded"
+    #[from_ref(skip)]
+    pub clock_skew_threshold_ms: u64,
+    /// Validez máxima de las URLs firmadas del proveedor, en segundos
+    #[from_ref(skip)]
+    pub signed_url_max_ttl_secs: u64,
+    pub mime_type_policy: MimeTypePolicy,
+    /// Clientes aceptados en X-Client-Id; vacío acepta cualquiera
+    pub allowed_client_ids: Arc<Vec<String>>,
+    pub error_metrics: Arc<ErrorMetrics>,
+    /// Proxies cuyas cabeceras X-Forwarded-For/Forwarded se aceptan; vacío no confía en ninguno
+    pub trusted_proxies: Arc<TrustedProxies>,
+    /// Evita que la limpieza de expirados corra dos veces a la vez (endpoint y tarea periódica)
+    pub cleanup_lock: Arc<tokio::sync::Mutex<()>>,
 }
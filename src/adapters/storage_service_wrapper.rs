@@ -1,25 +1,616 @@
-use std::sync::{Arc, RwLock};
+use std::{
+    future::Future,
+    sync::{Arc, Mutex, PoisonError, RwLock},
+    time::{Duration, Instant},
+};
 
-use crate::application::services::StorageService;
+use async_trait::async_trait;
+use tracing::{info, warn};
+
+use crate::{
+    application::{
+        error::ApplicationError,
+        repositories::secrets_repository::SecretsRepository,
+        services::{ObjectStream, StorageService, UploadStream},
+    },
+    domain::{
+        config::{local::LocalConfig, secrets::Secrets},
+        models::file::{FileData, FileMetadata},
+    },
+    services::{self, InstrumentedStorageService, ProviderLatency, RetryingStorageService},
+};
 
 #[derive(Clone)]
 pub struct StorageServiceWrapper {
     service: Arc<RwLock<Arc<dyn StorageService>>>,
+    latency: Arc<ProviderLatency>,
+    health: Arc<StorageHealth>,
+    max_attempts: u32,
+    credential_reload: Option<Arc<CredentialReload>>,
+}
+
+/// Último fallo del proveedor, visto por las sondas (health/readiness) o por sus llamadas
+#[derive(Default)]
+pub struct StorageHealth {
+    /// Instante del último fallo; la siguiente sonda correcta lo borra
+    failed_at: Mutex<Option<Instant>>,
+}
+
+impl StorageHealth {
+    pub fn record_probe(&self, up: bool) {
+        self.set_failed_at(if up { None } else { Some(Instant::now()) });
+    }
+
+    /// Una llamada al proveedor falló por disponibilidad; solo una sonda correcta lo borra
+    pub fn record_failure(&self) {
+        self.set_failed_at(Some(Instant::now()));
+    }
+
+    fn set_failed_at(&self, value: Option<Instant>) {
+        *self
+            .failed_at
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = value;
+    }
+
+    /// Tiempo que queda de `window` desde el último fallo; None si la última sonda fue bien
+    pub fn degraded_for(&self, window: Duration) -> Option<Duration> {
+        let failed_at = (*self
+            .failed_at
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner))?;
+        window
+            .checked_sub(failed_at.elapsed())
+            .filter(|remaining| !remaining.is_zero())
+    }
+}
+
+/// Dependencias para recargar los secretos del proveedor cuando rechaza las credenciales
+pub struct CredentialReload {
+    secrets_repository: Arc<dyn SecretsRepository>,
+    secrets: Arc<Mutex<Secrets>>,
+    local_config: Arc<Mutex<LocalConfig>>,
+    /// Serializa las recargas para que fallos concurrentes no recarguen varias veces
+    reload_lock: tokio::sync::Mutex<()>,
+}
+
+impl CredentialReload {
+    pub fn new(
+        secrets_repository: Arc<dyn SecretsRepository>,
+        secrets: Arc<Mutex<Secrets>>,
+        local_config: Arc<Mutex<LocalConfig>>,
+    ) -> Self {
+        Self {
+            secrets_repository,
+            secrets,
+            local_config,
+            reload_lock: tokio::sync::Mutex::new(()),
+        }
+    }
 }
 
 impl StorageServiceWrapper {
-    pub fn new(service: Arc<dyn StorageService>) -> Self {
+    pub fn new(service: Arc<dyn StorageService>, max_attempts: u32) -> Self {
+        let latency = Arc::new(ProviderLatency::default());
+        let health = Arc::new(StorageHealth::default());
         Self {
-            service: Arc::new(RwLock::new(service)),
+            service: Arc::new(RwLock::new(Self::instrument(
+                service,
+                &latency,
+                &health,
+                max_attempts,
+            ))),
+            latency,
+            health,
+            max_attempts,
+            credential_reload: None,
         }
     }
 
+    /// Habilita la recarga de secretos y un único reintento ante credenciales rechazadas
+    pub fn with_credential_reload(mut self, credential_reload: CredentialReload) -> Self {
+        self.credential_reload = Some(Arc::new(credential_reload));
+        self
+    }
+
     pub fn get(&self) -> Arc<dyn StorageService> {
-        self.service.read().unwrap().clone()
+        match self.credential_reload {
+            Some(_) => Arc::new(ReloadingStorageService {
+                wrapper: self.clone(),
+            }),
+            None => self.current(),
+        }
     }
 
     pub fn replace(&self, new_service: Arc<dyn StorageService>) {
-        let mut service = self.service.write().unwrap();
-        *service = new_service;
+        let instrumented =
+            Self::instrument(new_service, &self.latency, &self.health, self.max_attempts);
+        // Solo guardamos un Arc; un pánico con el lock tomado no deja el valor a medias
+        let mut service = self.service.write().unwrap_or_else(PoisonError::into_inner);
+        *service = instrumented;
+    }
+
+    pub fn latency(&self) -> &ProviderLatency {
+        &self.latency
+    }
+
+    pub fn health(&self) -> &StorageHealth {
+        &self.health
+    }
+
+    fn current(&self) -> Arc<dyn StorageService> {
+        self.service
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Cada intento se mide por separado; los reintentos envuelven la medición y la salud
+    /// solo ve el resultado final
+    fn instrument(
+        service: Arc<dyn StorageService>,
+        latency: &Arc<ProviderLatency>,
+        health: &Arc<StorageHealth>,
+        max_attempts: u32,
+    ) -> Arc<dyn StorageService> {
+        let instrumented = Arc::new(InstrumentedStorageService::new(service, latency.clone()));
+        let retrying = Arc::new(RetryingStorageService::new(instrumented, max_attempts));
+        Arc::new(HealthRecordingStorageService {
+            inner: retrying,
+            health: health.clone(),
+        })
+    }
+
+    /// Recarga los secretos y recrea el servicio, salvo que otra petición ya lo haya hecho
+    async fn reload_credentials(
+        &self,
+        failed: &Arc<dyn StorageService>,
+    ) -> Result<(), ApplicationError> {
+        let Some(reload) = self.credential_reload.as_ref() else {
+            return Ok(());
+        };
+        let _guard = reload.reload_lock.lock().await;
+
+        if !Arc::ptr_eq(failed, &self.current()) {
+            info!("Storage service already recreated by a concurrent reload");
+            return Ok(());
+        }
+
+        let secrets = reload.secrets_repository.get_secrets().await?;
+        let provider = reload
+            .local_config
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .provider
+            .clone();
+
+        let new_service = services::create_storage_service(&provider, &secrets)
+            .await
+            .map_err(ApplicationError::from)?;
+        self.replace(new_service);
+        *reload
+            .secrets
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = secrets;
+
+        info!(
+            "Reloaded provider secrets and recreated storage service for {:?}",
+            provider
+        );
+        Ok(())
+    }
+
+    async fn call_with_reload<T, F, Fut>(&self, call: F) -> Result<T, ApplicationError>
+    where
+        F: Fn(Arc<dyn StorageService>) -> Fut + Send + Sync,
+        Fut: Future<Output = Result<T, ApplicationError>> + Send,
+        T: Send,
+    {
+        let service = self.current();
+        match call(service.clone()).await {
+            Err(ApplicationError::ProviderUnauthorized(msg)) => {
+                warn!(
+                    "Provider rejected credentials ({}), reloading secrets and retrying once",
+                    msg
+                );
+                self.reload_credentials(&service).await?;
+                call(self.current()).await
+            }
+            result => result,
+        }
+    }
+}
+
+/// Marca el almacenamiento como degradado cuando una llamada falla por disponibilidad, para
+/// que las subidas se rechacen de antemano sin esperar a que alguien consulte /health/ready
+struct HealthRecordingStorageService {
+    inner: Arc<dyn StorageService>,
+    health: Arc<StorageHealth>,
+}
+
+impl HealthRecordingStorageService {
+    fn observe<T>(&self, result: Result<T, ApplicationError>) -> Result<T, ApplicationError> {
+        if let Err(
+            error @ (ApplicationError::StorageUnavailable(_)
+            | ApplicationError::UpstreamTimeout(_)
+            | ApplicationError::RetriesExhausted { .. }),
+        ) = &result
+        {
+            warn!(
+                "Provider {} unavailable, marking storage as degraded: {:?}",
+                self.inner.provider_name(),
+                error
+            );
+            self.health.record_failure();
+        }
+        result
+    }
+}
+
+#[async_trait]
+impl StorageService for HealthRecordingStorageService {
+    fn provider_name(&self) -> &'static str {
+        self.inner.provider_name()
+    }
+
+    fn locations(&self) -> Vec<String> {
+        self.inner.locations()
+    }
+
+    /// Sin registrar: la sonda ya anota su propio resultado
+    async fn ping(&self) -> Result<(), ApplicationError> {
+        self.inner.ping().await
+    }
+
+    async fn upload(&self, file_data: FileData) -> Result<FileMetadata, ApplicationError> {
+        self.observe(self.inner.upload(file_data).await)
+    }
+
+    fn streams_uploads(&self) -> bool {
+        self.inner.streams_uploads()
+    }
+
+    async fn upload_stream(
+        &self,
+        file_data: FileData,
+        content: UploadStream<'_>,
+        max_size: u64,
+    ) -> Result<FileMetadata, ApplicationError> {
+        self.observe(self.inner.upload_stream(file_data, content, max_size).await)
+    }
+
+    async fn download(&self, file_id: &str) -> Result<Vec<u8>, ApplicationError> {
+        self.observe(self.inner.download(file_id).await)
+    }
+
+    async fn download_stream(&self, file_id: &str) -> Result<ObjectStream, ApplicationError> {
+        self.observe(self.inner.download_stream(file_id).await)
+    }
+
+    async fn generate_presigned_url(
+        &self,
+        file_id: &str,
+        expires_in: Duration,
+    ) -> Result<Option<String>, ApplicationError> {
+        self.observe(self.inner.generate_presigned_url(file_id, expires_in).await)
+    }
+
+    async fn delete(&self, file_id: &str) -> Result<(), ApplicationError> {
+        self.observe(self.inner.delete(file_id).await)
+    }
+
+    async fn get_metadata(&self, file_id: &str) -> Result<FileMetadata, ApplicationError> {
+        self.observe(self.inner.get_metadata(file_id).await)
+    }
+
+    async fn list_files(
+        &self,
+        prefix: Option<&str>,
+    ) -> Result<Vec<FileMetadata>, ApplicationError> {
+        self.observe(self.inner.list_files(prefix).await)
+    }
+
+    async fn exists(&self, file_id: &str) -> Result<bool, ApplicationError> {
+        self.observe(self.inner.exists(file_id).await)
+    }
+}
+
+/// Vista del servicio actual que recarga credenciales ante un 401/403 del proveedor
+struct ReloadingStorageService {
+    wrapper: StorageServiceWrapper,
+}
+
+#[async_trait]
+impl StorageService for ReloadingStorageService {
+    fn provider_name(&self) -> &'static str {
+        self.wrapper.current().provider_name()
+    }
+
+    fn locations(&self) -> Vec<String> {
+        self.wrapper.current().locations()
+    }
+
+    async fn ping(&self) -> Result<(), ApplicationError> {
+        self.wrapper
+            .call_with_reload(|service| async move { service.ping().await })
+            .await
+    }
+
+    async fn upload(&self, file_data: FileData) -> Result<FileMetadata, ApplicationError> {
+        self.wrapper
+            .call_with_reload(|service| {
+                // Barato: el contenido es Bytes y solo se comparte
+                let file_data = file_data.clone();
+                async move { service.upload(file_data).await }
+            })
+            .await
+    }
+
+    fn streams_uploads(&self) -> bool {
+        self.wrapper.current().streams_uploads()
+    }
+
+    /// Sin recarga ante credenciales rechazadas: el flujo no se puede repetir
+    async fn upload_stream(
+        &self,
+        file_data: FileData,
+        content: UploadStream<'_>,
+        max_size: u64,
+    ) -> Result<FileMetadata, ApplicationError> {
+        self.wrapper
+            .current()
+            .upload_stream(file_data, content, max_size)
+            .await
+    }
+
+    async fn download(&self, file_id: &str) -> Result<Vec<u8>, ApplicationError> {
+        self.wrapper
+            .call_with_reload(|service| async move { service.download(file_id).await })
+            .await
+    }
+
+    async fn download_stream(&self, file_id: &str) -> Result<ObjectStream, ApplicationError> {
+        self.wrapper
+            .call_with_reload(|service| async move { service.download_stream(file_id).await })
+            .await
+    }
+
+    async fn generate_presigned_url(
+        &self,
+        file_id: &str,
+        expires_in: Duration,
+    ) -> Result<Option<String>, ApplicationError> {
+        self.wrapper
+            .call_with_reload(|service| async move {
+                service.generate_presigned_url(file_id, expires_in).await
+            })
+            .await
+    }
+
+    async fn delete(&self, file_id: &str) -> Result<(), ApplicationError> {
+        self.wrapper
+            .call_with_reload(|service| async move { service.delete(file_id).await })
+            .await
+    }
+
+    async fn get_metadata(&self, file_id: &str) -> Result<FileMetadata, ApplicationError> {
+        self.wrapper
+            .call_with_reload(|service| async move { service.get_metadata(file_id).await })
+            .await
+    }
+
+    async fn list_files(
+        &self,
+        prefix: Option<&str>,
+    ) -> Result<Vec<FileMetadata>, ApplicationError> {
+        self.wrapper
+            .call_with_reload(|service| async move { service.list_files(prefix).await })
+            .await
+    }
+
+    async fn exists(&self, file_id: &str) -> Result<bool, ApplicationError> {
+        self.wrapper
+            .call_with_reload(|service| async move { service.exists(file_id).await })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Proveedor sin operaciones que solo se distingue por su nombre
+    struct Named(&'static str);
+
+    #[async_trait]
+    impl StorageService for Named {
+        fn provider_name(&self) -> &'static str {
+            self.0
+        }
+
+        async fn ping(&self) -> Result<(), ApplicationError> {
+            Ok(())
+        }
+
+        async fn upload(&self, _file_data: FileData) -> Result<FileMetadata, ApplicationError> {
+            unimplemented!()
+        }
+
+        async fn download(&self, _file_id: &str) -> Result<Vec<u8>, ApplicationError> {
+            unimplemented!()
+        }
+
+        async fn delete(&self, _file_id: &str) -> Result<(), ApplicationError> {
+            unimplemented!()
+        }
+
+        async fn get_metadata(&self, _file_id: &str) -> Result<FileMetadata, ApplicationError> {
+            unimplemented!()
+        }
+
+        async fn list_files(
+            &self,
+            _prefix: Option<&str>,
+        ) -> Result<Vec<FileMetadata>, ApplicationError> {
+            unimplemented!()
+        }
+    }
+
+    #[test]
+    fn replace_and_get_survive_a_poisoned_lock() {
+        let wrapper = StorageServiceWrapper::new(Arc::new(Named("old")), 1);
+
+        let lock = wrapper.service.clone();
+        std::thread::spawn(move || {
+            let _guard = lock.write().unwrap();
+            panic!("provider swap panicked");
+        })
+        .join()
+        .unwrap_err();
+        assert!(wrapper.service.is_poisoned());
+
+        assert_eq!(wrapper.get().provider_name(), "old");
+        wrapper.replace(Arc::new(Named("new")));
+        assert_eq!(wrapper.get().provider_name(), "new");
+    }
+
+    #[test]
+    fn failed_probe_degrades_storage_until_the_window_passes_or_a_probe_succeeds() {
+        let health = StorageHealth::default();
+        let window = Duration::from_secs(30);
+        assert_eq!(health.degraded_for(window), None);
+
+        health.record_probe(false);
+        let remaining = health.degraded_for(window).unwrap();
+        assert!(remaining > Duration::from_secs(29) && remaining <= window);
+        assert_eq!(health.degraded_for(Duration::ZERO), None);
+
+        health.record_probe(true);
+        assert_eq!(health.degraded_for(window), None);
+    }
+
+    #[tokio::test]
+    async fn unavailable_provider_calls_degrade_storage_but_missing_objects_do_not() {
+        let storage = Arc::new(crate::services::InMemoryStorageService::new(
+            "memory", false,
+        ));
+        let wrapper = StorageServiceWrapper::new(storage.clone(), 1);
+        let window = Duration::from_secs(30);
+
+        let missing = wrapper.get().download("missing").await;
+        assert!(matches!(missing, Err(ApplicationError::NotFound)));
+        assert_eq!(wrapper.health().degraded_for(window), None);
+
+        storage.set_unavailable(true);
+        let file_data = FileData::new(
+            b"hello".to_vec(),
+            "notes.txt".to_string(),
+            "text/plain".to_string(),
+        );
+        assert!(wrapper.get().upload(file_data).await.is_err());
+        assert!(wrapper.health().degraded_for(window).is_some());
+    }
+
+    mod call_with_reload {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        use super::*;
+        use crate::{
+            adapters::repositories::in_memory_config_repository::InMemoryConfigRepository,
+            application::dto::{
+                global_config_dto::GlobalConfigDTO, local_config_dto::LocalConfigDTO,
+                secrets_dto::SecretsDTO,
+            },
+            domain::config::{local::Provider, secrets::LocalFsSecrets},
+        };
+
+        /// Envoltorio sobre `Named("old")` que al recargar crea un proveedor localfs
+        fn wrapper() -> (StorageServiceWrapper, Arc<Mutex<LocalConfig>>) {
+            let mut secrets: Secrets = SecretsDTO::default().into();
+            let base_path =
+                std::env::temp_dir().join(format!("vk-reload-{}", uuid::Uuid::new_v4()));
+            secrets.local_fs_secrets = Some(LocalFsSecrets {
+                base_path: base_path.display().to_string(),
+            });
+            let mut local_config: LocalConfig = LocalConfigDTO::default().into();
+            local_config.provider = Provider::LocalFs;
+            let local_config = Arc::new(Mutex::new(local_config));
+
+            let repository = Arc::new(InMemoryConfigRepository::new(
+                secrets,
+                GlobalConfigDTO::default().into(),
+                local_config.lock().unwrap().clone(),
+            ));
+            let wrapper = StorageServiceWrapper::new(Arc::new(Named("old")), 1)
+                .with_credential_reload(CredentialReload::new(
+                    repository,
+                    Arc::new(Mutex::new(SecretsDTO::default().into())),
+                    local_config.clone(),
+                ));
+            (wrapper, local_config)
+        }
+
+        #[tokio::test]
+        async fn rejected_credentials_reload_and_retry_exactly_once() {
+            let (wrapper, _) = wrapper();
+            let calls = AtomicUsize::new(0);
+
+            let result = wrapper
+                .call_with_reload(|service| {
+                    let call = calls.fetch_add(1, Ordering::SeqCst);
+                    async move {
+                        if call == 0 {
+                            return Err(ApplicationError::ProviderUnauthorized(
+                                "expired".to_string(),
+                            ));
+                        }
+                        Ok(service.provider_name())
+                    }
+                })
+                .await;
+
+            assert_eq!(result.unwrap(), "localfs");
+            assert_eq!(calls.load(Ordering::SeqCst), 2);
+        }
+
+        #[tokio::test]
+        async fn a_second_rejection_is_returned_without_another_retry() {
+            let (wrapper, _) = wrapper();
+            let calls = AtomicUsize::new(0);
+
+            let result: Result<(), _> = wrapper
+                .call_with_reload(|_| {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    async {
+                        Err(ApplicationError::ProviderUnauthorized(
+                            "revoked".to_string(),
+                        ))
+                    }
+                })
+                .await;
+
+            assert!(matches!(
+                result,
+                Err(ApplicationError::ProviderUnauthorized(_))
+            ));
+            assert_eq!(calls.load(Ordering::SeqCst), 2);
+        }
+
+        #[tokio::test]
+        async fn reload_survives_a_poisoned_local_config() {
+            let (wrapper, local_config) = wrapper();
+            let lock = local_config.clone();
+            std::thread::spawn(move || {
+                let _guard = lock.lock().unwrap();
+                panic!("config update panicked");
+            })
+            .join()
+            .unwrap_err();
+            assert!(local_config.is_poisoned());
+
+            let old = wrapper.current();
+            wrapper.reload_credentials(&old).await.unwrap();
+            assert_eq!(wrapper.current().provider_name(), "localfs");
+        }
     }
 }
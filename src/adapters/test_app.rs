@@ -0,0 +1,164 @@
+//! AppState sobre repositorios y proveedor en memoria para probar los controladores de
+//! punta a punta sin Postgres, Redis ni un proveedor real.
+
+use std::{
+    net::{IpAddr, Ipv4Addr},
+    sync::{Arc, Mutex},
+};
+
+use axum::http::HeaderMap;
+use uuid::Uuid;
+
+use crate::{
+    adapters::{
+        client_ip::{ClientIp, TrustedProxies},
+        error_metrics::ErrorMetrics,
+        repositories::{
+            in_memory_config_repository::InMemoryConfigRepository,
+            in_memory_metadata_repository::{metadata, InMemoryMetadataRepository},
+            in_memory_token_repository::InMemoryTokenRepository,
+            in_memory_upload_session_repository::InMemoryUploadSessionRepository,
+            in_memory_user_repository::InMemoryUserRepository,
+        },
+        state::AppState,
+        storage_service_wrapper::StorageServiceWrapper,
+        upload_buffer::UploadBufferBudget,
+    },
+    application::{
+        dto::{
+            global_config_dto::GlobalConfigDTO, local_config_dto::LocalConfigDTO,
+            secrets_dto::SecretsDTO,
+        },
+        repositories::token_repository::TokenRepository,
+    },
+    domain::{
+        config::{global::GlobalConfig, local::LocalConfig, secrets::Secrets},
+        models::{file::MimeTypePolicy, metadata::Metadata, user::User},
+    },
+    services::{EventQueue, InMemoryStorageService, TracingEventSink},
+};
+
+pub const SERVER_ID: &str = "server";
+pub const VK_SECRET: &str = "s3cr3t";
+
+pub struct TestApp {
+    pub state: AppState,
+    pub metadata: Arc<InMemoryMetadataRepository>,
+    pub users: Arc<InMemoryUserRepository>,
+    pub tokens: Arc<InMemoryTokenRepository>,
+    pub sessions: Arc<InMemoryUploadSessionRepository>,
+    pub storage: Arc<InMemoryStorageService>,
+}
+
+impl TestApp {
+    /// Proveedor "memory" sin URLs firmadas; text/plain e image/png de hasta 1 KiB
+    pub fn new() -> Self {
+        Self::with_storage(InMemoryStorageService::new("memory", false))
+    }
+
+    pub fn with_storage(storage: InMemoryStorageService) -> Self {
+        let mut secrets: Secrets = SecretsDTO::default().into();
+        secrets.vk_secret = VK_SECRET.to_string();
+        let mut global_config: GlobalConfig = GlobalConfigDTO::default().into();
+        global_config.mime_types = vec!["text/plain".to_string(), "image/png".to_string()];
+        global_config.max_size = 1024;
+        global_config.chunk_size = 4;
+        global_config.temp_file_life = 3600;
+        global_config.default_quota = 1024;
+        let mut local_config: LocalConfig = LocalConfigDTO::default().into();
+        local_config.server_id = SERVER_ID.to_string();
+        local_config.server_url = "https://vk.example".to_string();
+
+        let config = Arc::new(InMemoryConfigRepository::new(
+            secrets.clone(),
+            global_config.clone(),
+            local_config.clone(),
+        ));
+        let metadata = Arc::new(InMemoryMetadataRepository::new(Vec::new()));
+        let users = Arc::new(InMemoryUserRepository::default());
+        let tokens = Arc::new(InMemoryTokenRepository::default());
+        let sessions = Arc::new(InMemoryUploadSessionRepository::default());
+        let storage = Arc::new(storage);
+
+        let state = AppState {
+            server_id: SERVER_ID.to_string(),
+            secrets: Arc::new(Mutex::new(secrets)),
+            local_config: Arc::new(Mutex::new(local_config)),
+            global_config: Arc::new(Mutex::new(global_config)),
+            user_repository: users.clone(),
+            metadata_repository: metadata.clone(),
+            secrets_repository: config.clone(),
+            global_config_repository: config.clone(),
+            local_config_repository: config,
+            storage_service: StorageServiceWrapper::new(storage.clone(), 1),
+            token_repository: tokens.clone(),
+            upload_session_repository: sessions.clone(),
+            file_scanner: None,
+            event_queue: EventQueue::start(Arc::new(TracingEventSink), 16, 1),
+            download_bytes_per_second: 0,
+            provider_request_concurrency: 4,
+            upload_buffer_budget: UploadBufferBudget::new(0),
+            storage_down_reject_secs: 0,
+            clock_skew_threshold_ms: 2_000,
+            signed_url_max_ttl_secs: 3600,
+            mime_type_policy: MimeTypePolicy::default(),
+            allowed_client_ids: Arc::new(Vec::new()),
+            error_metrics: Arc::new(ErrorMetrics::default()),
+            trusted_proxies: Arc::new(TrustedProxies::default()),
+            cleanup_lock: Arc::new(tokio::sync::Mutex::new(())),
+        };
+
+        Self {
+            state,
+            metadata,
+            users,
+            tokens,
+            sessions,
+            storage,
+        }
+    }
+
+    /// Usuario nuevo con `total_space` de cuota y nada usado
+    pub fn user(&self, total_space: u64) -> Uuid {
+        let uid = Uuid::new_v4();
+        self.users.insert(User {
+            uid,
+            total_space,
+            ..Default::default()
+        });
+        uid
+    }
+
+    /// Archivo permanente de `owner` cuyo objeto ya está en el proveedor
+    pub fn file(&self, file_id: &str, owner: Uuid, content: &[u8]) -> Metadata {
+        let mut file = metadata(file_id, owner, &format!("{}.txt", file_id));
+        file.size = content.len() as u64;
+        file.server_id = SERVER_ID.to_string();
+        self.storage.insert(file_id, content);
+        self.metadata.insert(file.clone());
+        file
+    }
+
+    pub async fn upload_token(&self, user_id: Option<Uuid>) -> String {
+        self.tokens
+            .generate_token(user_id.map(|uid| uid.to_string()), 300)
+            .await
+            .unwrap()
+    }
+
+    pub fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, value.parse().unwrap());
+        }
+        headers
+    }
+
+    pub fn admin_headers() -> HeaderMap {
+        Self::headers(&[("X-VK-Secret", VK_SECRET)])
+    }
+
+    pub fn client_ip() -> ClientIp {
+        ClientIp(IpAddr::V4(Ipv4Addr::LOCALHOST))
+    }
+}
@@ -0,0 +1,109 @@
+use axum::body::Body;
+use bytes::Bytes;
+use futures_util::{stream, StreamExt};
+use tokio::time::{sleep_until, Duration, Instant};
+use tracing::warn;
+
+use crate::application::{error::ApplicationError, services::DownloadStream};
+
+/// Tamaño máximo de cada bloque emitido
+const MAX_CHUNK_SIZE: usize = 64 * 1024;
+/// Número de bloques por segundo a tasas bajas, para evitar ráfagas largas
+const CHUNKS_PER_SECOND: u64 = 10;
+
+/// Envuelve el contenido en un Body que emite como máximo `bytes_per_second`
+///
+/// 0 = sin límite. El ritmo se calcula sobre el total enviado desde el primer bloque,
+/// así que un cliente lento no acumula crédito para ráfagas posteriores
+pub fn throttled_body(content: DownloadStream, bytes_per_second: u64) -> Body {
+    let content = content.map(|chunk| chunk.map_err(abort_transfer));
+    if bytes_per_second == 0 {
+        return Body::from_stream(content);
+    }
+
+    let chunk_size = ((bytes_per_second / CHUNKS_PER_SECOND).max(1) as usize).min(MAX_CHUNK_SIZE);
+
+    let stream = stream::unfold(
+        (content.boxed(), Bytes::new(), 0u64, None::<Instant>),
+        move |(mut content, mut pending, sent, started)| async move {
+            while pending.is_empty() {
+                match content.next().await? {
+                    Ok(bytes) => pending = bytes,
+                    Err(e) => return Some((Err(e), (content, pending, sent, started))),
+                }
+            }
+
+            let started = started.unwrap_or_else(Instant::now);
+            let due = Duration::from_secs_f64(sent as f64 / bytes_per_second as f64);
+            sleep_until(started + due).await;
+
+            let chunk = pending.split_to(chunk_size.min(pending.len()));
+            let sent = sent + chunk.len() as u64;
+            Some((Ok(chunk), (content, pending, sent, Some(started))))
+        },
+    );
+
+    Body::from_stream(stream)
+}
+
+/// Con las cabeceras ya enviadas, un fallo del proveedor solo puede cortar la conexión
+fn abort_transfer(error: ApplicationError) -> std::io::Error {
+    warn!("Download aborted mid-transfer: {:?}", error);
+    std::io::Error::other(format!("{:?}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn content(bytes: &[u8]) -> DownloadStream {
+        stream::iter(vec![Ok(Bytes::copy_from_slice(bytes))]).boxed()
+    }
+
+    async fn read(body: Body) -> Vec<u8> {
+        axum::body::to_bytes(body, usize::MAX)
+            .await
+            .unwrap()
+            .to_vec()
+    }
+
+    #[tokio::test]
+    async fn throttled_download_takes_at_least_the_paced_time() {
+        let payload = vec![7u8; 1000];
+        let started = Instant::now();
+
+        // 200 bytes por bloque: el quinto sale cuando ya se enviaron 800 bytes, a los 0,4 s
+        let received = read(throttled_body(content(&payload), 2000)).await;
+
+        assert_eq!(received, payload);
+        assert!(
+            started.elapsed() >= Duration::from_millis(400),
+            "took {:?}",
+            started.elapsed()
+        );
+    }
+
+    #[tokio::test]
+    async fn zero_means_unlimited() {
+        let payload = vec![7u8; 1000];
+        let started = Instant::now();
+
+        let received = read(throttled_body(content(&payload), 0)).await;
+
+        assert_eq!(received, payload);
+        assert!(started.elapsed() < Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn provider_errors_abort_the_body() {
+        let failing: DownloadStream = stream::iter(vec![
+            Ok(Bytes::from_static(b"partial")),
+            Err(ApplicationError::StorageUnavailable("reset".to_string())),
+        ])
+        .boxed();
+
+        let result = axum::body::to_bytes(throttled_body(failing, 1_000_000), usize::MAX).await;
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,57 @@
+use std::sync::Arc;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::application::error::ApplicationError;
+
+/// Bytes de archivo que todas las subidas en curso pueden retener en memoria a la vez
+#[derive(Clone)]
+pub struct UploadBufferBudget {
+    /// None = sin límite
+    semaphore: Option<Arc<Semaphore>>,
+}
+
+/// Bytes reservados por una subida; vuelven al presupuesto al soltarse
+pub struct BufferReservation {
+    semaphore: Option<Arc<Semaphore>>,
+    permit: Option<OwnedSemaphorePermit>,
+}
+
+impl UploadBufferBudget {
+    /// `max_bytes` = 0 desactiva el límite
+    pub fn new(max_bytes: usize) -> Self {
+        let semaphore = (max_bytes > 0)
+            .then(|| Arc::new(Semaphore::new(max_bytes.min(Semaphore::MAX_PERMITS))));
+        Self { semaphore }
+    }
+
+    /// Reserva vacía que crece a medida que la subida acumula bytes
+    pub fn reservation(&self) -> BufferReservation {
+        BufferReservation {
+            semaphore: self.semaphore.clone(),
+            permit: None,
+        }
+    }
+}
+
+impl BufferReservation {
+    /// Suma `bytes` a la reserva sin esperar; 503 si el presupuesto está agotado
+    pub fn grow(&mut self, bytes: usize) -> Result<(), ApplicationError> {
+        let Some(semaphore) = &self.semaphore else {
+            return Ok(());
+        };
+        let exhausted =
+            || ApplicationError::ServiceUnavailable("Upload buffer budget exhausted".to_string());
+
+        let permits = u32::try_from(bytes).map_err(|_| exhausted())?;
+        let permit = semaphore
+            .clone()
+            .try_acquire_many_owned(permits)
+            .map_err(|_| exhausted())?;
+        match &mut self.permit {
+            Some(held) => held.merge(permit),
+            None => self.permit = Some(permit),
+        }
+        Ok(())
+    }
+}
@@ -1,8 +1,13 @@
+use std::collections::{HashMap, HashSet};
+
 use serde::{Deserialize, Serialize};
 
-use crate::domain::config::global::GlobalConfig;
+use crate::{
+    application::error::ApplicationError,
+    domain::config::global::{GlobalConfig, UserTier, DEFAULT_TRASH_RETENTION},
+};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Default)]
 pub struct GlobalConfigDTO {
     #[serde(rename = "mimeTypes")]
     pub mime_types: Option<Vec<String>>,
@@ -14,12 +19,31 @@ pub struct GlobalConfigDTO {
     pub temp_file_life: Option<u64>,
     #[serde(rename = "defaultQuota")]
     pub default_quota: Option<u64>,
+    #[serde(rename = "mimeSizeLimits")]
+    pub mime_size_limits: Option<HashMap<String, u64>>,
+    #[serde(rename = "trashRetention")]
+    pub trash_retention: Option<u64>,
+    #[serde(rename = "strictMimeCheck")]
+    pub strict_mime_check: Option<bool>,
+    #[serde(rename = "streamUploads")]
+    pub stream_uploads: Option<bool>,
+    #[serde(rename = "requireUserId")]
+    pub require_user_id: Option<bool>,
+    #[serde(rename = "allowDuplicateNames")]
+    pub allow_duplicate_names: Option<bool>,
+    pub tiers: Option<HashMap<String, UserTier>>,
 }
 
 impl GlobalConfigDTO {
     pub fn sanitize(&mut self) {
         if let Some(ref mut mime_types) = self.mime_types {
-            mime_types.retain(|s| !s.trim().is_empty());
+            // Normaliza (trim + minúsculas) y elimina vacíos y duplicados conservando el orden
+            let mut seen = HashSet::new();
+            *mime_types = mime_types
+                .iter()
+                .map(|s| s.trim().to_ascii_lowercase())
+                .filter(|s| !s.is_empty() && seen.insert(s.clone()))
+                .collect();
         }
         if let Some(max_size) = self.max_size {
             self.max_size = Some(std::cmp::min(max_size, i64::MAX as u64));
@@ -33,6 +57,94 @@ impl GlobalConfigDTO {
         if let Some(default_quota) = self.default_quota {
             self.default_quota = Some(std::cmp::min(default_quota, i64::MAX as u64));
         }
+        if let Some(trash_retention) = self.trash_retention {
+            self.trash_retention = Some(std::cmp::min(trash_retention, i64::MAX as u64));
+        }
+        if let Some(ref mut limits) = self.mime_size_limits {
+            *limits = limits
+                .drain()
+                .map(|(pattern, limit)| {
+                    (
+                        pattern.trim().to_ascii_lowercase(),
+                        std::cmp::min(limit, i64::MAX as u64),
+                    )
+                })
+                .collect();
+        }
+        if let Some(ref mut tiers) = self.tiers {
+            *tiers = tiers
+                .drain()
+                .map(|(name, mut tier)| {
+                    tier.quota = std::cmp::min(tier.quota, i64::MAX as u64);
+                    if let Some(ref mut mime_types) = tier.allowed_mime_types {
+                        for mime_type in mime_types.iter_mut() {
+                            *mime_type = mime_type.trim().to_ascii_lowercase();
+                        }
+                    }
+                    (name.trim().to_ascii_lowercase(), tier)
+                })
+                .collect();
+        }
+    }
+
+    /// Rechaza mime types que no tengan la forma tipo/subtipo; llamar después de sanitize
+    pub fn validate(&self) -> Result<(), ApplicationError> {
+        if let Some(ref mime_types) = self.mime_types {
+            if let Some(invalid) = mime_types.iter().find(|m| !is_valid_mime_type(m)) {
+                return Err(ApplicationError::BadRequest(format!(
+                    "Invalid mime type: {}",
+                    invalid
+                )));
+            }
+        }
+        if let Some(ref limits) = self.mime_size_limits {
+            if let Some(invalid) = limits.keys().find(|p| !is_valid_mime_pattern(p)) {
+                return Err(ApplicationError::BadRequest(format!(
+                    "Invalid mime size limit pattern: {}",
+                    invalid
+                )));
+            }
+        }
+        if let Some(ref tiers) = self.tiers {
+            if tiers.keys().any(|name| name.is_empty()) {
+                return Err(ApplicationError::BadRequest(
+                    "Tier names cannot be empty".to_string(),
+                ));
+            }
+            let invalid = tiers
+                .values()
+                .filter_map(|tier| tier.allowed_mime_types.as_ref())
+                .flatten()
+                .find(|m| !is_valid_mime_type(m));
+            if let Some(invalid) = invalid {
+                return Err(ApplicationError::BadRequest(format!(
+                    "Invalid tier mime type: {}",
+                    invalid
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// tipo/subtipo con caracteres token de RFC 7230, sin parámetros
+fn is_valid_mime_type(mime_type: &str) -> bool {
+    let is_token = |s: &str| {
+        !s.is_empty()
+            && s.chars()
+                .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c))
+    };
+    match mime_type.split_once('/') {
+        Some((kind, subtype)) => is_token(kind) && is_token(subtype),
+        None => false,
+    }
+}
+
+/// Como is_valid_mime_type, pero el comodín solo se admite como subtipo completo
+fn is_valid_mime_pattern(pattern: &str) -> bool {
+    match pattern.split_once('/') {
+        Some((kind, "*")) => kind == "*" || (is_valid_mime_type(pattern) && !kind.contains('*')),
+        _ => is_valid_mime_type(pattern) && !pattern.contains('*'),
     }
 }
 
@@ -44,6 +156,13 @@ impl From<GlobalConfig> for GlobalConfigDTO {
             chunk_size: Some(value.chunk_size),
             temp_file_life: Some(value.temp_file_life),
             default_quota: Some(value.default_quota),
+            mime_size_limits: Some(value.mime_size_limits),
+            trash_retention: Some(value.trash_retention),
+            strict_mime_check: Some(value.strict_mime_check),
+            stream_uploads: Some(value.stream_uploads),
+            require_user_id: Some(value.require_user_id),
+            allow_duplicate_names: Some(value.allow_duplicate_names),
+            tiers: Some(value.tiers),
         }
     }
 }
@@ -56,6 +175,105 @@ impl From<GlobalConfigDTO> for GlobalConfig {
             chunk_size: value.chunk_size.unwrap_or(0),
             temp_file_life: value.temp_file_life.unwrap_or(0),
             default_quota: value.default_quota.unwrap_or(0),
+            mime_size_limits: value.mime_size_limits.unwrap_or_default(),
+            trash_retention: value.trash_retention.unwrap_or(DEFAULT_TRASH_RETENTION),
+            strict_mime_check: value.strict_mime_check.unwrap_or(false),
+            stream_uploads: value.stream_uploads.unwrap_or(false),
+            require_user_id: value.require_user_id.unwrap_or(false),
+            allow_duplicate_names: value.allow_duplicate_names.unwrap_or(false),
+            tiers: value.tiers.unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_mime_types(mime_types: &[&str]) -> GlobalConfigDTO {
+        GlobalConfigDTO {
+            mime_types: Some(mime_types.iter().map(|m| m.to_string()).collect()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn sanitize_trims_and_lowercases_mime_types() {
+        let mut dto = with_mime_types(&[" Image/PNG ", "application/JSON"]);
+        dto.sanitize();
+        assert_eq!(
+            dto.mime_types.unwrap(),
+            vec!["image/png", "application/json"]
+        );
+    }
+
+    #[test]
+    fn sanitize_drops_duplicates_and_blanks_keeping_order() {
+        let mut dto = with_mime_types(&["text/plain", "", "image/png", "TEXT/PLAIN", "  "]);
+        dto.sanitize();
+        assert_eq!(dto.mime_types.unwrap(), vec!["text/plain", "image/png"]);
+    }
+
+    #[test]
+    fn validate_rejects_malformed_mime_types() {
+        for invalid in [
+            "image",
+            "image/",
+            "/png",
+            "image/png; charset=utf-8",
+            "ima ge/png",
+        ] {
+            let mut dto = with_mime_types(&["text/plain", invalid]);
+            dto.sanitize();
+            assert!(
+                matches!(dto.validate(), Err(ApplicationError::BadRequest(_))),
+                "{} should be rejected",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn validate_accepts_well_formed_and_missing_mime_types() {
+        let mut dto = with_mime_types(&["application/vnd.ms-excel", "image/svg+xml"]);
+        dto.sanitize();
+        assert!(dto.validate().is_ok());
+        assert!(GlobalConfigDTO::default().validate().is_ok());
+    }
+
+    fn with_size_limits(limits: &[(&str, u64)]) -> GlobalConfigDTO {
+        GlobalConfigDTO {
+            mime_size_limits: Some(limits.iter().map(|(p, l)| (p.to_string(), *l)).collect()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn sanitize_normalises_size_limit_patterns_and_caps_values() {
+        let mut dto = with_size_limits(&[(" Image/* ", 10), ("video/mp4", u64::MAX)]);
+        dto.sanitize();
+        let limits = dto.mime_size_limits.unwrap();
+        assert_eq!(limits["image/*"], 10);
+        assert_eq!(limits["video/mp4"], i64::MAX as u64);
+    }
+
+    #[test]
+    fn validate_accepts_exact_and_wildcard_size_limits() {
+        let mut dto = with_size_limits(&[("image/png", 1), ("image/*", 2), ("*/*", 3)]);
+        dto.sanitize();
+        assert!(dto.validate().is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_partial_wildcards_in_size_limits() {
+        for invalid in ["image/p*", "*/png", "image", "*"] {
+            let mut dto = with_size_limits(&[(invalid, 1)]);
+            dto.sanitize();
+            assert!(
+                matches!(dto.validate(), Err(ApplicationError::BadRequest(_))),
+                "{} should be rejected",
+                invalid
+            );
         }
     }
 }
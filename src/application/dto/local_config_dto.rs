@@ -1,14 +1,17 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use crate::domain::config::local::{LocalConfig, Provider};
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default, ToSchema)]
 pub struct LocalConfigDTO {
     pub provider: Option<Provider>,
     #[serde(rename = "serverName")]
     pub server_name: Option<String>,
     #[serde(rename = "serverUrl")]
     pub server_url: Option<String>,
+    #[serde(rename = "defaultQuota")]
+    pub default_quota: Option<u64>,
 }
 
 impl From<LocalConfig> for LocalConfigDTO {
@@ -17,6 +20,7 @@ impl From<LocalConfig> for LocalConfigDTO {
             provider: Some(value.provider),
             server_name: Some(value.server_name),
             server_url: Some(value.server_url),
+            default_quota: value.default_quota,
         }
     }
 }
@@ -28,6 +32,7 @@ impl From<LocalConfigDTO> for LocalConfig {
             server_name: value.server_name.unwrap_or_default(),
             server_url: value.server_url.unwrap_or_default(),
             server_id: String::new(),
+            default_quota: value.default_quota,
         }
     }
 }
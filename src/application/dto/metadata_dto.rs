@@ -17,6 +17,10 @@ pub struct MetadataDTO {
     pub download_count: Option<u64>,
     pub last_access: Option<DateTime<Utc>>,
     pub delete_at: Option<DateTime<Utc>>,
+    pub client_id: Option<String>,
+    pub trashed_at: Option<DateTime<Utc>>,
+    pub content_hash: Option<String>,
+    pub object_id: Option<String>,
 }
 
 impl From<Metadata> for MetadataDTO {
@@ -33,6 +37,10 @@ impl From<Metadata> for MetadataDTO {
             download_count: Some(value.download_count),
             last_access: Some(value.last_access),
             delete_at: value.delete_at,
+            client_id: value.client_id,
+            trashed_at: value.trashed_at,
+            content_hash: value.content_hash,
+            object_id: value.object_id,
         }
     }
 }
@@ -51,6 +59,10 @@ impl From<MetadataDTO> for Metadata {
             download_count: value.download_count.unwrap_or(0),
             last_access: value.last_access.unwrap_or_else(Utc::now),
             delete_at: value.delete_at,
+            client_id: value.client_id,
+            trashed_at: value.trashed_at,
+            content_hash: value.content_hash,
+            object_id: value.object_id,
         }
     }
 }
@@ -1,8 +1,10 @@
 use serde::{Deserialize, Serialize};
 
-use crate::domain::config::secrets::{GDriveSecrets, Secrets, SupabaseSecrets};
+use crate::domain::config::secrets::{
+    AzureSecrets, GDriveSecrets, LocalFsSecrets, S3Secrets, Secrets, SupabaseSecrets,
+};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Default)]
 pub struct SecretsDTO {
     #[serde(rename = "dbPassword")]
     pub db_password: Option<String>,
@@ -14,6 +16,12 @@ pub struct SecretsDTO {
     pub gdrive_secrets: Option<GDriveSecrets>,
     #[serde(rename = "supabaseSecrets")]
     pub supabase_secrets: Option<SupabaseSecrets>,
+    #[serde(rename = "s3Secrets")]
+    pub s3_secrets: Option<S3Secrets>,
+    #[serde(rename = "azureSecrets")]
+    pub azure_secrets: Option<AzureSecrets>,
+    #[serde(rename = "localFsSecrets")]
+    pub local_fs_secrets: Option<LocalFsSecrets>,
 }
 
 impl SecretsDTO {
@@ -38,6 +46,9 @@ impl From<Secrets> for SecretsDTO {
             vk_secret: Some(value.vk_secret),
             gdrive_secrets: value.gdrive_secrets,
             supabase_secrets: value.supabase_secrets,
+            s3_secrets: value.s3_secrets,
+            azure_secrets: value.azure_secrets,
+            local_fs_secrets: value.local_fs_secrets,
         }
     }
 }
@@ -50,6 +61,9 @@ impl From<SecretsDTO> for Secrets {
             vk_secret: value.vk_secret.unwrap_or_default(),
             gdrive_secrets: value.gdrive_secrets,
             supabase_secrets: value.supabase_secrets,
+            s3_secrets: value.s3_secrets,
+            azure_secrets: value.azure_secrets,
+            local_fs_secrets: value.local_fs_secrets,
         }
     }
 }
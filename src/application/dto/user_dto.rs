@@ -1,9 +1,10 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 use crate::domain::models::user::User;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UserDTO {
     pub uid: Uuid,
     #[serde(rename = "fileCount")]
@@ -12,6 +13,9 @@ pub struct UserDTO {
     pub total_space: Option<u64>,
     #[serde(rename = "usedSpace")]
     pub used_space: Option<u64>,
+    /// Solo se fija al crear el usuario; una actualización no lo cambia
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub tier: Option<String>,
 }
 
 impl UserDTO {
@@ -22,6 +26,7 @@ impl UserDTO {
             file_count: None,
             total_space: None,
             used_space: None,
+            tier: None,
         }
     }
 
@@ -32,6 +37,7 @@ impl UserDTO {
             file_count: None,
             total_space: None,
             used_space: None,
+            tier: None,
         }
     }
 }
@@ -43,6 +49,7 @@ impl From<User> for UserDTO {
             file_count: Some(value.file_count),
             total_space: Some(value.total_space),
             used_space: Some(value.used_space),
+            tier: value.tier,
         }
     }
 }
@@ -54,6 +61,7 @@ impl From<UserDTO> for User {
             file_count: value.file_count.unwrap_or(0),
             total_space: value.total_space.unwrap_or(0),
             used_space: value.used_space.unwrap_or(0),
+            tier: value.tier,
         }
     }
 }
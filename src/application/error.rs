@@ -1,11 +1,42 @@
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ApplicationError {
     NotFound,
     InternalError(String),
+    /// El proveedor de almacenamiento no responde (red, conexión)
+    StorageUnavailable(String),
+    /// El proveedor de almacenamiento no respondió a tiempo
+    UpstreamTimeout(String),
+    /// Una dependencia interna (Redis) no está disponible
+    ServiceUnavailable(String),
+    ProviderUnauthorized(String),
     DatabaseError(String),
     BadRequest(String),
+    /// Cuerpo JSON ilegible o que no encaja con el tipo esperado; lleva el detalle del parser
+    InvalidJson(String),
+    /// X-Content-SHA256 no coincide con el SHA-256 de los bytes recibidos
+    ChecksumMismatch {
+        computed: String,
+    },
     Unauthorized,
+    Forbidden(String),
+    /// El recurso ya existe o su estado impide la operación
+    Conflict(String),
     PayloadTooLarge,
+    UnsupportedMediaType {
+        expected: &'static str,
+    },
     InsufficientStorage,
+    UnprocessableEntity(String),
+    TooManyRequests {
+        retry_after: Option<u64>,
+    },
+    /// La última sonda del proveedor falló; se rechaza sin intentar la operación
+    StorageDegraded {
+        retry_after: u64,
+    },
+    RetriesExhausted {
+        attempts: u32,
+        error: Box<ApplicationError>,
+    },
     InvalidToken,
 }
@@ -1,17 +1,118 @@
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::{future::BoxFuture, stream::BoxStream};
+use uuid::Uuid;
 
 use crate::{
     application::{dto::metadata_dto::MetadataDTO, error::ApplicationError},
-    domain::models::metadata::Metadata,
+    domain::models::metadata::{ClientStats, FileStats, Metadata, UserFilesFilter, UserUsageStats},
 };
 
+/// Filas entregadas a medida que llegan de la base de datos
+pub type MetadataStream = BoxStream<'static, Result<Metadata, ApplicationError>>;
+
 #[async_trait]
 pub trait MetadataRepository: Send + Sync {
+    /// Con `object_id`, Conflict si el objeto reutilizado ya no tiene filas: se liberó
+    /// mientras tanto y hay que subir el contenido de nuevo
     async fn create_metadata(&self, metadata: MetadataDTO) -> Result<Metadata, ApplicationError>;
     async fn get_metadata(&self, file_id: &str) -> Result<Metadata, ApplicationError>;
+    /// NotFound también si el archivo está en la papelera
+    async fn get_file_stats(&self, file_id: &str) -> Result<FileStats, ApplicationError>;
     async fn update_metadata(&self, metadata: MetadataDTO) -> Result<Metadata, ApplicationError>;
     async fn delete_metadata(&self, file_id: &str) -> Result<Metadata, ApplicationError>;
-    async fn increment_download_count(&self, file_id: &str) -> Result<Metadata, ApplicationError>;
-    async fn get_expired_files(&self) -> Result<Vec<Metadata>, ApplicationError>;
+    /// Marca el archivo como en papelera; conserva la fecha si ya lo estaba
+    async fn trash_metadata(&self, file_id: &str) -> Result<Metadata, ApplicationError>;
+    /// Saca el archivo de la papelera
+    async fn restore_metadata(&self, file_id: &str) -> Result<Metadata, ApplicationError>;
+    /// Cambia el dueño del archivo y mueve su tamaño entre las cuotas de ambos usuarios en
+    /// una sola transacción; InsufficientStorage si no cabe en la cuota del nuevo dueño
+    async fn transfer_metadata(
+        &self,
+        file_id: &str,
+        to_user_id: Uuid,
+    ) -> Result<Metadata, ApplicationError>;
+    /// Archivo de esta instancia con el mismo contenido y fuera de la papelera, entre los de
+    /// `user_id` o, sin él, entre los anónimos
+    async fn find_by_hash(
+        &self,
+        content_hash: &str,
+        server_id: &str,
+        user_id: Option<&str>,
+    ) -> Result<Option<Metadata>, ApplicationError>;
+    /// Borra las filas `file_ids`, que usan el objeto `object_id`, tras esperar a
+    /// `delete_object` si ninguna otra lo usa; devuelve cuántas lo siguen usando
+    ///
+    /// Se serializa con create_metadata sobre el mismo objeto: una subida deduplicada no
+    /// puede reutilizarlo entre el recuento y el borrado.
+    async fn release_object(
+        &self,
+        object_id: &str,
+        file_ids: &[String],
+        delete_object: BoxFuture<'_, Result<(), ApplicationError>>,
+    ) -> Result<u64, ApplicationError>;
+    /// Guarda el hash del contenido calculado fuera de la subida (reindexado)
+    async fn set_content_hash(
+        &self,
+        file_id: &str,
+        content_hash: &str,
+    ) -> Result<(), ApplicationError>;
+    /// Incrementa download_count y actualiza last_access sin devolver la fila
+    async fn record_download(&self, file_id: &str) -> Result<(), ApplicationError>;
+    /// Página de metadata ordenada por file_id, empezando después de `cursor`
+    async fn export_metadata(
+        &self,
+        cursor: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<Metadata>, ApplicationError>;
+    /// Como export_metadata, pero en streaming; sin límite si `limit` es None
+    fn stream_export(&self, cursor: Option<String>, limit: Option<i64>) -> MetadataStream;
+    /// Archivos del usuario fuera de la papelera, filtrados y ordenados como el listado paginado
+    fn stream_files_by_user(&self, user_id: String, filter: UserFilesFilter) -> MetadataStream;
+    /// Archivos con delete_at vencido o en papelera desde hace más de `trash_retention` segundos
+    async fn get_expired_files(
+        &self,
+        trash_retention: u64,
+    ) -> Result<Vec<Metadata>, ApplicationError>;
+    /// Hasta `limit` archivos en papelera desde hace más de `trash_retention` segundos, los
+    /// más antiguos primero
+    async fn get_trashed_files(
+        &self,
+        trash_retention: u64,
+        limit: i64,
+    ) -> Result<Vec<Metadata>, ApplicationError>;
     async fn get_file_ids_by_user(&self, user_id: &str) -> Result<Vec<String>, ApplicationError>;
+    /// Archivos del usuario fuera de la papelera, más recientes primero
+    async fn get_files_by_user(&self, user_id: &str) -> Result<Vec<Metadata>, ApplicationError>;
+    /// Página de archivos del usuario filtrada y ordenada, junto al total filtrado sin paginar
+    async fn get_files_by_user_paginated(
+        &self,
+        user_id: &str,
+        filter: &UserFilesFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<Metadata>, u64), ApplicationError>;
+    /// Archivo más grande y subida más antigua del usuario, sin contar la papelera
+    async fn get_user_usage_stats(&self, user_id: &str)
+        -> Result<UserUsageStats, ApplicationError>;
+    /// Fija delete_at en los archivos permanentes del usuario, fuera de la papelera, que
+    /// cumplan los filtros
+    async fn schedule_user_files_expiry(
+        &self,
+        user_id: &str,
+        file_ids: Option<&[String]>,
+        older_than: Option<DateTime<Utc>>,
+        delete_at: DateTime<Utc>,
+    ) -> Result<u64, ApplicationError>;
+    async fn find_user_files_by_name(
+        &self,
+        user_id: &str,
+        file_name: &str,
+    ) -> Result<Vec<Metadata>, ApplicationError>;
+    /// Archivos, bytes y descargas agrupados por client_id
+    async fn get_client_stats(&self) -> Result<Vec<ClientStats>, ApplicationError>;
+    /// NOW() de la base de datos; las expiraciones se evalúan con este reloj
+    async fn database_now(&self) -> Result<DateTime<Utc>, ApplicationError>;
+    /// SELECT 1 contra el pool
+    async fn ping(&self) -> Result<(), ApplicationError>;
 }
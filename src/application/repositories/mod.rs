@@ -3,4 +3,5 @@ pub mod local_config_repository;
 pub mod metadata_repository;
 pub mod secrets_repository;
 pub mod token_repository;
+pub mod upload_session_repository;
 pub mod user_repository;
@@ -30,4 +30,20 @@ pub trait TokenRepository: Send + Sync {
         &self,
         token: &str,
     ) -> Result<Option<String>, ApplicationError>;
+
+    /// Genera un token de usuario, reutilizable hasta que expira, que acredita al usuario
+    /// como dueño de sus archivos; no sirve como token de subida ni al revés
+    async fn generate_user_token(
+        &self,
+        user_id: &str,
+        ttl_seconds: u64,
+    ) -> Result<String, ApplicationError>;
+
+    /// Usuario asociado a un token de usuario, sin consumirlo
+    ///
+    /// Err(InvalidToken) si el token no existe o expiró
+    async fn verify_user_token(&self, token: &str) -> Result<String, ApplicationError>;
+
+    /// Comprueba que Redis responde (PING)
+    async fn ping(&self) -> Result<(), ApplicationError>;
 }
@@ -0,0 +1,40 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{application::error::ApplicationError, domain::models::upload_session::UploadSession};
+
+#[async_trait]
+pub trait UploadSessionRepository: Send + Sync {
+    /// Registra la sesión; expira junto con sus bloques tras `ttl_seconds`
+    async fn create_session(
+        &self,
+        session: &UploadSession,
+        ttl_seconds: u64,
+    ) -> Result<(), ApplicationError>;
+
+    /// Sesión con los bytes recibidos; NotFound si no existe o expiró
+    async fn get_session(&self, session_id: &str) -> Result<UploadSession, ApplicationError>;
+
+    /// Añade un bloque al final de la sesión (operación atómica)
+    ///
+    /// # Arguments
+    /// * `expected_offset` - Si se indica, debe coincidir con los bytes ya recibidos
+    ///
+    /// # Returns
+    /// Total de bytes recibidos tras añadir el bloque
+    async fn append_chunk(
+        &self,
+        session_id: &str,
+        chunk: &[u8],
+        expected_offset: Option<u64>,
+    ) -> Result<u64, ApplicationError>;
+
+    /// Cierra la sesión y devuelve el contenido ensamblado; un segundo intento da NotFound
+    async fn take_session(
+        &self,
+        session_id: &str,
+    ) -> Result<(UploadSession, Bytes), ApplicationError>;
+
+    /// Descarta la sesión y sus bloques sin almacenar nada; NotFound si no existe o expiró
+    async fn delete_session(&self, session_id: &str) -> Result<(), ApplicationError>;
+}
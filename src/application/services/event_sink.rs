@@ -0,0 +1,11 @@
+use async_trait::async_trait;
+
+use crate::{application::error::ApplicationError, domain::models::audit::AuditEvent};
+
+#[async_trait]
+pub trait EventSink: Send + Sync {
+    /// Entrega un evento de auditoría/webhook fuera del ciclo de la petición
+    ///
+    /// Los errores se registran y el evento se descarta; no se reintenta
+    async fn deliver(&self, event: &AuditEvent) -> Result<(), ApplicationError>;
+}
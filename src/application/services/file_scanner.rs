@@ -0,0 +1,14 @@
+use async_trait::async_trait;
+
+use crate::{application::error::ApplicationError, domain::models::scan::ScanVerdict};
+
+#[async_trait]
+pub trait FileScanner: Send + Sync {
+    /// Escanea el contenido de un archivo antes de enviarlo al proveedor
+    ///
+    /// # Returns
+    /// - Ok(ScanVerdict::Clean) si no se detectó ninguna amenaza
+    /// - Ok(ScanVerdict::Infected(signature)) si el escáner encontró una firma
+    /// - Err(_) si el escáner no está disponible o respondió algo inesperado
+    async fn scan(&self, bytes: &[u8]) -> Result<ScanVerdict, ApplicationError>;
+}
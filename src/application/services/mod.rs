@@ -1,3 +1,7 @@
+mod event_sink;
+mod file_scanner;
 mod storage_service;
 
-pub use storage_service::StorageService;
+pub use event_sink::EventSink;
+pub use file_scanner::FileScanner;
+pub use storage_service::{DownloadStream, ObjectStream, StorageService, UploadStream};
@@ -1,14 +1,105 @@
+use std::time::Duration;
+
 use async_trait::async_trait;
+use bytes::Bytes;
+use futures_util::{
+    stream::{self, BoxStream},
+    StreamExt,
+};
 
 use crate::{
     application::error::ApplicationError,
     domain::models::file::{FileData, FileMetadata},
 };
 
+/// Contenido de un objeto en bloques, tal como llega del proveedor
+pub type DownloadStream = BoxStream<'static, Result<Bytes, ApplicationError>>;
+
+/// Descarga en streaming junto con la longitud que anunció el proveedor (Content-Length)
+pub struct ObjectStream {
+    pub content: DownloadStream,
+    /// None si el proveedor no la indicó
+    pub length: Option<u64>,
+}
+
+/// Contenido de una subida en bloques, tal como llega del cliente
+pub type UploadStream<'a> = BoxStream<'a, Result<Bytes, ApplicationError>>;
+
 #[async_trait]
 pub trait StorageService: Send + Sync {
+    fn provider_name(&self) -> &'static str;
+
+    /// Buckets/carpetas adicionales que se pueden elegir al subir; vacío si solo hay el principal
+    fn locations(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Comprobación ligera de que el proveedor responde y acepta las credenciales
+    async fn ping(&self) -> Result<(), ApplicationError>;
+
     async fn upload(&self, file_data: FileData) -> Result<FileMetadata, ApplicationError>;
+
+    /// Si `upload_stream` envía el contenido según llega en lugar de acumularlo
+    fn streams_uploads(&self) -> bool {
+        false
+    }
+
+    /// Sube `content` sin retenerlo entero; `file_data.content` llega vacío
+    ///
+    /// Falla con PayloadTooLarge en cuanto el flujo supera `max_size`. Por defecto acumula el
+    /// contenido y llama a `upload`.
+    async fn upload_stream(
+        &self,
+        mut file_data: FileData,
+        mut content: UploadStream<'_>,
+        max_size: u64,
+    ) -> Result<FileMetadata, ApplicationError> {
+        let mut buffer = Vec::new();
+        while let Some(chunk) = content.next().await {
+            let chunk = chunk?;
+            if (buffer.len() + chunk.len()) as u64 > max_size {
+                return Err(ApplicationError::PayloadTooLarge);
+            }
+            buffer.extend_from_slice(&chunk);
+        }
+        file_data.content = buffer.into();
+        self.upload(file_data).await
+    }
+
     async fn download(&self, file_id: &str) -> Result<Vec<u8>, ApplicationError>;
+
+    /// Descarga sin cargar el objeto en memoria; por defecto envuelve `download`
+    async fn download_stream(&self, file_id: &str) -> Result<ObjectStream, ApplicationError> {
+        let bytes = self.download(file_id).await?;
+        let length = Some(bytes.len() as u64);
+        Ok(ObjectStream {
+            content: stream::once(async move { Ok(Bytes::from(bytes)) }).boxed(),
+            length,
+        })
+    }
+
+    /// URL firmada del proveedor válida durante `expires_in`; None si el proveedor no las emite
+    async fn generate_presigned_url(
+        &self,
+        _file_id: &str,
+        _expires_in: Duration,
+    ) -> Result<Option<String>, ApplicationError> {
+        Ok(None)
+    }
+
     async fn delete(&self, file_id: &str) -> Result<(), ApplicationError>;
     async fn get_metadata(&self, file_id: &str) -> Result<FileMetadata, ApplicationError>;
+
+    /// Objetos almacenados cuyo file_id empieza por `prefix` (todos si es None)
+    async fn list_files(&self, prefix: Option<&str>)
+        -> Result<Vec<FileMetadata>, ApplicationError>;
+
+    /// Comprueba si el objeto existe sin descargarlo
+    async fn exists(&self, file_id: &str) -> Result<bool, ApplicationError> {
+        match self.get_metadata(file_id).await {
+            Ok(_) => Ok(true),
+            Err(ApplicationError::NotFound) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
 }
@@ -1,6 +1,11 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Retención de la papelera cuando la configuración no la fija: 30 días
+pub const DEFAULT_TRASH_RETENTION: u64 = 30 * 24 * 60 * 60;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct GlobalConfig {
     #[serde(rename = "mimeTypes")]
     pub mime_types: Vec<String>,
@@ -12,4 +17,107 @@ pub struct GlobalConfig {
     pub temp_file_life: u64,
     #[serde(rename = "defaultQuota")]
     pub default_quota: u64,
+    /// Tamaño máximo por mime type (`image/png`, `image/*` o `*/*`); prevalece sobre max_size
+    #[serde(rename = "mimeSizeLimits", default)]
+    pub mime_size_limits: HashMap<String, u64>,
+    /// Segundos que un archivo pasa en la papelera antes de borrarse definitivamente
+    #[serde(rename = "trashRetention", default = "default_trash_retention")]
+    pub trash_retention: u64,
+    /// Rechaza subidas cuyo contenido contradice el mime type declarado; si no, solo avisa
+    #[serde(rename = "strictMimeCheck", default)]
+    pub strict_mime_check: bool,
+    /// Envía el archivo al proveedor según llega si `file` es el último campo del formulario
+    #[serde(rename = "streamUploads", default)]
+    pub stream_uploads: bool,
+    /// Prohíbe tokens y subidas sin user_id, también para archivos temporales
+    #[serde(rename = "requireUserId", default)]
+    pub require_user_id: bool,
+    /// Varios archivos de un usuario pueden compartir nombre; la descarga por nombre es
+    /// ambigua (409) en lugar de servir el más reciente
+    #[serde(rename = "allowDuplicateNames", default)]
+    pub allow_duplicate_names: bool,
+    /// Planes de usuario por nombre (free, pro...), elegibles al crearlo
+    #[serde(default)]
+    pub tiers: HashMap<String, UserTier>,
+}
+
+/// Cuota y límites de un plan de usuario
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct UserTier {
+    /// Sustituye a default_quota como total_space del usuario
+    pub quota: u64,
+    /// Archivos permanentes como máximo; sin límite si es None
+    #[serde(rename = "maxFiles", default, skip_serializing_if = "Option::is_none")]
+    pub max_files: Option<u64>,
+    /// Subconjunto de mimeTypes que puede subir; todos si es None
+    #[serde(
+        rename = "allowedMimeTypes",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub allowed_mime_types: Option<Vec<String>>,
+}
+
+fn default_trash_retention() -> u64 {
+    DEFAULT_TRASH_RETENTION
+}
+
+impl GlobalConfig {
+    /// Mayor tamaño que puede tener una subida, sea cual sea su mime type
+    pub fn max_upload_size(&self) -> u64 {
+        self.mime_size_limits
+            .values()
+            .copied()
+            .fold(self.max_size, u64::max)
+    }
+
+    /// Límite más específico para el mime type: exacto, luego `tipo/*`, luego `*/*`
+    pub fn max_size_for(&self, mime_type: &str) -> u64 {
+        let wildcard = mime_type
+            .split_once('/')
+            .map(|(kind, _)| format!("{}/*", kind));
+
+        self.mime_size_limits
+            .get(mime_type)
+            .or_else(|| wildcard.and_then(|pattern| self.mime_size_limits.get(&pattern)))
+            .or_else(|| self.mime_size_limits.get("*/*"))
+            .copied()
+            .unwrap_or(self.max_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::dto::global_config_dto::GlobalConfigDTO;
+
+    fn config(limits: &[(&str, u64)]) -> GlobalConfig {
+        let mut config: GlobalConfig = GlobalConfigDTO::default().into();
+        config.max_size = 100;
+        config.mime_size_limits = limits.iter().map(|(p, l)| (p.to_string(), *l)).collect();
+        config
+    }
+
+    #[test]
+    fn the_most_specific_limit_wins() {
+        let config = config(&[("image/png", 1), ("image/*", 2), ("*/*", 3)]);
+        assert_eq!(config.max_size_for("image/png"), 1);
+        assert_eq!(config.max_size_for("image/jpeg"), 2);
+        assert_eq!(config.max_size_for("video/mp4"), 3);
+    }
+
+    #[test]
+    fn max_size_applies_without_a_matching_limit() {
+        assert_eq!(config(&[]).max_size_for("image/png"), 100);
+        assert_eq!(config(&[("image/*", 2)]).max_size_for("text/plain"), 100);
+        assert_eq!(config(&[("image/*", 2)]).max_size_for("not-a-mime"), 100);
+    }
+
+    #[test]
+    fn a_limit_may_exceed_max_size() {
+        assert_eq!(
+            config(&[("video/*", 1_000)]).max_size_for("video/mp4"),
+            1_000
+        );
+    }
 }
@@ -1,14 +1,21 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
 pub enum Provider {
     #[serde(rename = "gdrive")]
     GDrive,
     #[serde(rename = "supabase")]
     Supabase,
+    #[serde(rename = "s3")]
+    S3,
+    #[serde(rename = "azure")]
+    Azure,
+    #[serde(rename = "localfs")]
+    LocalFs,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
 pub struct LocalConfig {
     pub provider: Provider,
     #[serde(rename = "serverName")]
@@ -17,4 +24,60 @@ pub struct LocalConfig {
     pub server_url: String,
     #[serde(rename = "serverId")]
     pub server_id: String,
+    /// Sobrescribe global.default_quota para los usuarios creados en esta instancia
+    #[serde(rename = "defaultQuota", skip_serializing_if = "Option::is_none")]
+    pub default_quota: Option<u64>,
+}
+
+/// Longitud máxima de SERVER_ID; aparece en claves, rutas y logs
+pub const MAX_SERVER_ID_LENGTH: usize = 64;
+
+impl LocalConfig {
+    /// 1-64 caracteres de [A-Za-z0-9._-]
+    pub fn is_valid_server_id(server_id: &str) -> bool {
+        !server_id.is_empty()
+            && server_id.len() <= MAX_SERVER_ID_LENGTH
+            && server_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+    }
+
+    /// `allowed_server_ids` separada por comas; vacía admite cualquier server_id
+    pub fn is_allowed_server_id(server_id: &str, allowed_server_ids: &str) -> bool {
+        let mut allowed = allowed_server_ids
+            .split(',')
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .peekable();
+        allowed.peek().is_none() || allowed.any(|id| id == server_id)
+    }
+
+    pub fn effective_default_quota(&self, global_default: u64) -> u64 {
+        self.default_quota.unwrap_or(global_default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn listed_server_ids_are_allowed() {
+        assert!(LocalConfig::is_allowed_server_id(
+            "vk-2",
+            "vk-1, vk-2 ,vk-3"
+        ));
+    }
+
+    #[test]
+    fn unlisted_server_ids_are_refused() {
+        assert!(!LocalConfig::is_allowed_server_id("rogue", "vk-1,vk-2"));
+        assert!(!LocalConfig::is_allowed_server_id("vk", "vk-1,vk-2"));
+    }
+
+    #[test]
+    fn empty_allowlist_admits_any_server_id() {
+        assert!(LocalConfig::is_allowed_server_id("anything", ""));
+        assert!(LocalConfig::is_allowed_server_id("anything", " , "));
+    }
 }
@@ -1,14 +1,22 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct GDriveSecrets {
     #[serde(rename = "folderId")]
     pub folder_id: String,
     #[serde(rename = "googleCredentials")]
     pub google_credentials: String,
+    /// Agrupa las subidas en subcarpetas por mes (YYYY-MM) dentro de folderId
+    #[serde(rename = "dateSubfolders", default)]
+    pub date_subfolders: bool,
+    /// Carpetas adicionales seleccionables en la subida: nombre -> id de carpeta
+    #[serde(rename = "folders", default)]
+    pub folders: BTreeMap<String, String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct SupabaseSecrets {
     #[serde(rename = "endpoint")]
     pub endpoint: String,
@@ -20,9 +28,56 @@ pub struct SupabaseSecrets {
     pub secret_access_key: String,
     #[serde(rename = "bucketName")]
     pub bucket_name: String,
+    /// Sobrescribe el objeto existente en la misma ruta (x-upsert)
+    #[serde(rename = "upsert", default = "default_upsert")]
+    pub upsert: bool,
+    /// Buckets adicionales seleccionables en la subida; bucketName sigue siendo el principal
+    #[serde(rename = "buckets", default)]
+    pub buckets: Vec<String>,
+}
+
+/// Endpoint S3 genérico (MinIO, Ceph, AWS) sin pasar por la API de Supabase
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct S3Secrets {
+    #[serde(rename = "endpoint")]
+    pub endpoint: String,
+    #[serde(rename = "region")]
+    pub region: String,
+    #[serde(rename = "accessKeyId")]
+    pub access_key_id: String,
+    #[serde(rename = "secretAccessKey")]
+    pub secret_access_key: String,
+    #[serde(rename = "bucketName")]
+    pub bucket_name: String,
+}
+
+/// Contenedor de Azure Blob Storage con autenticación Shared Key
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AzureSecrets {
+    #[serde(rename = "accountName")]
+    pub account_name: String,
+    /// Clave de la cuenta en base64, tal como la muestra el portal
+    #[serde(rename = "accountKey")]
+    pub account_key: String,
+    #[serde(rename = "containerName")]
+    pub container_name: String,
+    /// Sobrescribe https://{accountName}.blob.core.windows.net (p. ej. Azurite)
+    #[serde(rename = "endpoint", default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+}
+
+/// Directorio local para desarrollo; no contiene secretos pero sigue el mismo esquema
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LocalFsSecrets {
+    #[serde(rename = "basePath")]
+    pub base_path: String,
+}
+
+fn default_upsert() -> bool {
+    true
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Secrets {
     #[serde(rename = "dbPassword")]
     pub db_password: String,
@@ -34,4 +89,10 @@ pub struct Secrets {
     pub gdrive_secrets: Option<GDriveSecrets>,
     #[serde(rename = "supabaseSecrets")]
     pub supabase_secrets: Option<SupabaseSecrets>,
+    #[serde(rename = "s3Secrets", default)]
+    pub s3_secrets: Option<S3Secrets>,
+    #[serde(rename = "azureSecrets", default)]
+    pub azure_secrets: Option<AzureSecrets>,
+    #[serde(rename = "localFsSecrets", default)]
+    pub local_fs_secrets: Option<LocalFsSecrets>,
 }
@@ -0,0 +1,56 @@
+use std::net::IpAddr;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditAction {
+    Upload,
+    Download,
+    Delete,
+    /// Borrado lógico: el objeto sigue en el proveedor hasta que vence la retención
+    Trash,
+    Restore,
+    /// Cambio de dueño; `user_id` del evento es el nuevo dueño
+    Transfer,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEvent {
+    pub action: AuditAction,
+    #[serde(rename = "fileId")]
+    pub file_id: String,
+    #[serde(rename = "userId")]
+    pub user_id: Option<String>,
+    #[serde(rename = "serverId")]
+    pub server_id: String,
+    #[serde(rename = "occurredAt")]
+    pub occurred_at: DateTime<Utc>,
+    /// IP del cliente resuelta tras los proxies de confianza
+    #[serde(rename = "clientIp")]
+    pub client_ip: Option<IpAddr>,
+}
+
+impl AuditEvent {
+    pub fn new(
+        action: AuditAction,
+        file_id: impl Into<String>,
+        user_id: Option<String>,
+        server_id: impl Into<String>,
+    ) -> Self {
+        Self {
+            action,
+            file_id: file_id.into(),
+            user_id,
+            server_id: server_id.into(),
+            occurred_at: Utc::now(),
+            client_ip: None,
+        }
+    }
+
+    pub fn with_client_ip(mut self, client_ip: IpAddr) -> Self {
+        self.client_ip = Some(client_ip);
+        self
+    }
+}
@@ -1,21 +1,107 @@
+use std::{fmt, str::FromStr};
+
+use bytes::Bytes;
 use serde::{Deserialize, Serialize};
 
+/// Tipo de subida: temporal (expira tras temp_file_life) o permanente (cuenta en la cuota del usuario)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileKind {
+    Temporal,
+    Permanent,
+}
+
+impl FromStr for FileKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "temporal" => Ok(FileKind::Temporal),
+            "permanent" => Ok(FileKind::Permanent),
+            other => Err(format!(
+                "Invalid 'type' field '{}': must be 'temporal' or 'permanent'",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for FileKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileKind::Temporal => write!(f, "temporal"),
+            FileKind::Permanent => write!(f, "permanent"),
+        }
+    }
+}
+
+/// Qué mime type se guarda cuando el proveedor devuelve uno distinto al declarado
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MimeTypePolicy {
+    /// El declarado por el cliente, ya validado contra la lista permitida
+    #[default]
+    TrustClient,
+    /// El que reporta el proveedor tras la subida
+    TrustProvider,
+}
+
+impl MimeTypePolicy {
+    pub fn resolve(self, client: String, provider: String) -> String {
+        match self {
+            MimeTypePolicy::TrustClient => client,
+            MimeTypePolicy::TrustProvider => provider,
+        }
+    }
+}
+
+impl FromStr for MimeTypePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "trust_client" => Ok(MimeTypePolicy::TrustClient),
+            "trust_provider" => Ok(MimeTypePolicy::TrustProvider),
+            other => Err(format!(
+                "Invalid mime type policy '{}': must be 'trust_client' or 'trust_provider'",
+                other
+            )),
+        }
+    }
+}
+
+/// Tipo detectado por los bytes iniciales cuando contradice al declarado
+///
+/// None si coinciden o si `infer` no reconoce el contenido (texto plano, formatos raros).
+pub fn sniffed_mime_mismatch(declared: &str, content: &[u8]) -> Option<&'static str> {
+    let sniffed = infer::get(content)?.mime_type();
+    (!sniffed.eq_ignore_ascii_case(declared)).then_some(sniffed)
+}
+
 #[derive(Debug, Clone)]
 pub struct FileData {
-    pub content: Vec<u8>,
+    /// Bytes: clonar para reintentar una subida no copia el contenido
+    pub content: Bytes,
     pub filename: String,
     pub mime_type: String,
+    /// Bucket o carpeta adicional elegido en la subida; None usa el principal
+    pub location: Option<String>,
 }
 
 impl FileData {
-    pub fn new(content: Vec<u8>, filename: String, mime_type: String) -> Self {
+    pub fn new(content: impl Into<Bytes>, filename: String, mime_type: String) -> Self {
         Self {
-            content,
+            content: content.into(),
             filename,
             mime_type,
+            location: None,
         }
     }
 
+    pub fn with_location(mut self, location: Option<String>) -> Self {
+        self.location = location;
+        self
+    }
+
     pub fn validate_size(&self, max_size: u64) -> bool {
         (self.content.len() as u64) <= max_size
     }
@@ -32,4 +118,67 @@ pub struct FileMetadata {
     pub mime_type: String,
     pub filename: Option<String>,
     pub provider: String,
+    pub web_view_link: Option<String>,
+    pub web_content_link: Option<String>,
+    /// md5Checksum en GDrive, ETag en S3/Supabase/Azure
+    pub checksum: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_kind_parses_and_displays_the_form_values() {
+        for kind in [FileKind::Temporal, FileKind::Permanent] {
+            assert_eq!(kind.to_string().parse::<FileKind>(), Ok(kind));
+        }
+        assert_eq!(" permanent\n".parse::<FileKind>(), Ok(FileKind::Permanent));
+    }
+
+    #[test]
+    fn file_kind_rejects_unknown_values() {
+        for value in ["", "Permanent", "temp", "perm"] {
+            let error = value.parse::<FileKind>().unwrap_err();
+            assert!(error.contains("'temporal' or 'permanent'"), "{error}");
+        }
+    }
+
+    #[test]
+    fn file_kind_serializes_lowercase() {
+        assert_eq!(
+            serde_json::to_string(&FileKind::Temporal).unwrap(),
+            "\"temporal\""
+        );
+        assert_eq!(
+            serde_json::from_str::<FileKind>("\"permanent\"").unwrap(),
+            FileKind::Permanent
+        );
+    }
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    #[test]
+    fn sniffing_accepts_matching_content() {
+        assert_eq!(sniffed_mime_mismatch("image/png", PNG), None);
+        assert_eq!(sniffed_mime_mismatch("IMAGE/PNG", PNG), None);
+    }
+
+    #[test]
+    fn sniffing_reports_the_detected_type_on_mismatch() {
+        assert_eq!(
+            sniffed_mime_mismatch("application/pdf", PNG),
+            Some("image/png")
+        );
+        assert_eq!(
+            sniffed_mime_mismatch("image/png", b"%PDF-1.7\n"),
+            Some("application/pdf")
+        );
+    }
+
+    #[test]
+    fn sniffing_ignores_unrecognised_content() {
+        assert_eq!(sniffed_mime_mismatch("text/csv", b"a,b\n1,2\n"), None);
+        assert_eq!(sniffed_mime_mismatch("application/pdf", b""), None);
+    }
 }
@@ -15,4 +15,78 @@ pub struct Metadata {
     pub last_access: DateTime<Utc>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub delete_at: Option<DateTime<Utc>>,
+    /// Aplicación cliente que subió el archivo (X-Client-Id)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    /// En la papelera desde este momento; el objeto se purga al vencer trash_retention
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trashed_at: Option<DateTime<Utc>>,
+    /// SHA-256 en hexadecimal del contenido subido
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+    /// Objeto del proveedor que comparte por deduplicación; None si el objeto es el suyo
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub object_id: Option<String>,
+}
+
+impl Metadata {
+    pub fn is_trashed(&self) -> bool {
+        self.trashed_at.is_some()
+    }
+
+    /// Clave del objeto en el proveedor: el compartido si está deduplicado, si no file_id
+    pub fn storage_key(&self) -> &str {
+        self.object_id.as_deref().unwrap_or(&self.file_id)
+    }
+}
+
+/// Totales de archivos agrupados por aplicación cliente; None agrupa los no etiquetados
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientStats {
+    pub client_id: Option<String>,
+    pub file_count: u64,
+    pub total_size: u64,
+    pub download_count: u64,
+}
+
+/// Agregados de los archivos de un usuario; None si no tiene archivos
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserUsageStats {
+    pub largest_file_size: Option<u64>,
+    pub oldest_file_uploaded_at: Option<DateTime<Utc>>,
+}
+
+/// Campo por el que se ordena el listado de archivos del usuario
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UserFilesSortBy {
+    #[default]
+    UploadedAt,
+    Size,
+    DownloadCount,
+}
+
+/// Filtro y orden del listado de archivos del usuario; por defecto uploadedAt descendente
+#[derive(Debug, Clone)]
+pub struct UserFilesFilter {
+    pub mime_type: Option<String>,
+    pub sort_by: UserFilesSortBy,
+    pub descending: bool,
+}
+
+impl Default for UserFilesFilter {
+    fn default() -> Self {
+        Self {
+            mime_type: None,
+            sort_by: UserFilesSortBy::default(),
+            descending: true,
+        }
+    }
+}
+
+/// Contadores de un archivo, sin el resto de la metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileStats {
+    pub download_count: u64,
+    pub last_access: DateTime<Utc>,
+    pub size: u64,
 }
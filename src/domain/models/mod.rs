@@ -1,3 +1,6 @@
+pub mod audit;
 pub mod file;
 pub mod metadata;
+pub mod scan;
+pub mod upload_session;
 pub mod user;
@@ -0,0 +1,30 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::models::file::FileKind;
+
+/// Subida fragmentada en curso; los bloques recibidos se guardan aparte
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadSession {
+    pub session_id: String,
+    pub filename: String,
+    pub mime_type: String,
+    pub file_type: FileKind,
+    pub user_id: Option<String>,
+    pub description: Option<String>,
+    pub client_id: Option<String>,
+    /// Bucket/carpeta adicional elegido al abrir la sesión
+    #[serde(default)]
+    pub location: Option<String>,
+    /// chunk_size vigente al abrir la sesión; todos los bloques salvo el último lo miden exacto
+    pub chunk_size: u64,
+    /// Tamaño máximo del archivo ensamblado para su mime type
+    pub max_size: u64,
+    pub expires_at: DateTime<Utc>,
+    /// Credencial para continuar la sesión sin token de usuario; solo se entrega al abrirla
+    #[serde(default)]
+    pub session_secret: Option<String>,
+    /// Bytes recibidos hasta ahora; lo mantiene el repositorio
+    #[serde(skip)]
+    pub received_bytes: u64,
+}
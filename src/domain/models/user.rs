@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
 pub struct User {
     pub uid: Uuid,
     #[serde(rename = "fileCount")]
@@ -10,4 +11,7 @@ pub struct User {
     pub total_space: u64,
     #[serde(rename = "usedSpace")]
     pub used_space: u64,
+    /// Plan con el que se creó; None si usa default_quota
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tier: Option<String>,
 }
@@ -3,41 +3,143 @@ mod application;
 mod domain;
 mod services;
 
-use std::sync::{Arc, Mutex};
+use std::{
+    future::IntoFuture,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use adapters::{
+    client_ip::TrustedProxies,
     controllers::{
-        file_controller::FileController, health_controller::HealthController,
-        instance_controller::InstanceController, user_controller::UserController,
+        admin_controller,
+        docs_controller::DocsController,
+        file_controller::{self, FileController, MAX_SIGNED_URL_EXPIRY_SECS},
+        health_controller, instance_controller, user_controller,
     },
-    middleware::validate_kv_secret,
+    cors::build_cors_layer,
+    error_metrics::ErrorMetrics,
+    middleware::{json_body, record_error_outcomes, request_id, upload_body, validate_kv_secret},
     repositories::{
         PgGlobalConfigRepository, PgLocalConfigRepository, PgMetadataRepository,
-        PgSecretsRepository, PgUserRepository, RedisTokenRepository,
+        PgSecretsRepository, PgUserRepository, RedisTokenRepository, RedisUploadSessionRepository,
     },
+    startup::{ConnectRetry, DbPoolConfig},
     state::AppState,
-    storage_service_wrapper::StorageServiceWrapper,
+    storage_service_wrapper::{CredentialReload, StorageServiceWrapper},
+    upload_buffer::UploadBufferBudget,
 };
 use application::{
-    dto::local_config_dto::LocalConfigDTO,
+    dto::{
+        global_config_dto::GlobalConfigDTO, local_config_dto::LocalConfigDTO,
+        secrets_dto::SecretsDTO,
+    },
+    error::ApplicationError,
     repositories::{
         global_config_repository::GlobalConfigRepository,
         local_config_repository::LocalConfigRepository, metadata_repository::MetadataRepository,
         secrets_repository::SecretsRepository, token_repository::TokenRepository,
-        user_repository::UserRepository,
+        upload_session_repository::UploadSessionRepository, user_repository::UserRepository,
     },
+    services::{FileScanner, StorageService},
 };
 use axum::{
     middleware,
-    routing::{get, post},
+    routing::{get, patch, post},
     Router,
 };
-use tower_http::cors::{Any, CorsLayer};
+use domain::{
+    config::local::{LocalConfig, MAX_SERVER_ID_LENGTH},
+    models::file::MimeTypePolicy,
+};
+use services::{
+    ClamAvScanner, EventQueue, StorageError, TracingEventSink, UnconfiguredStorageService,
+};
+use tower_http::normalize_path::NormalizePath;
 
 async fn hello_world() -> &'static str {
     "Hello, world!"
 }
 
+/// Resolves on SIGINT (Ctrl+C) or, on Unix, SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to install SIGINT handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+/// Runs the expired-file cleanup every `interval`; a failed cycle is logged and retried next tick
+///
+/// Returns once `shutdown` changes; a cycle already running is allowed to finish first.
+async fn run_cleanup_task(
+    app_state: AppState,
+    interval: Duration,
+    mut shutdown: tokio::sync::watch::Receiver<bool>,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    // The first tick fires immediately; wait a full interval after startup instead
+    ticker.tick().await;
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = shutdown.changed() => {
+                tracing::info!("Scheduled cleanup stopped");
+                return;
+            }
+        }
+        match FileController::run_cleanup(&app_state).await {
+            Ok(report) if report.errors.is_empty() => {
+                tracing::info!(
+                    "Scheduled cleanup deleted {} expired files",
+                    report.deleted_count
+                );
+            }
+            Ok(report) => {
+                tracing::warn!(
+                    "Scheduled cleanup deleted {} expired files with {} errors: {:?}",
+                    report.deleted_count,
+                    report.errors.len(),
+                    report.errors
+                );
+            }
+            Err(ApplicationError::Conflict(_)) => {
+                tracing::info!("Scheduled cleanup skipped: another cleanup is running");
+            }
+            Err(e) => {
+                tracing::error!("Scheduled cleanup failed: {:?}", e);
+            }
+        }
+    }
+}
+
+/// Trims trailing slashes before routing and wraps everything, the trim included, in the
+/// request id span, so every log line of a request carries its id
+fn into_app(router: Router) -> Router {
+    Router::new()
+        .fallback_service(NormalizePath::trim_trailing_slash(router))
+        .layer(middleware::from_fn(request_id))
+}
+
 #[tokio::main]
 async fn main() {
     // Initialize tracing to write to stdout with immediate flushing for Cloud Run
@@ -58,12 +160,29 @@ async fn main() {
     let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
     tracing::info!("Rustls crypto provider initialized");
 
-
     tracing::info!("Loading environment variables...");
     let server_id =
         std::env::var("SERVER_ID").expect("ERROR: SERVER_ID environment variable must be set");
+    // Checked before it reaches logs, Redis keys or paths
+    if !LocalConfig::is_valid_server_id(&server_id) {
+        panic!(
+            "ERROR: SERVER_ID must be 1-{} characters of [A-Za-z0-9._-], got {:?}",
+            MAX_SERVER_ID_LENGTH, server_id
+        );
+    }
     tracing::info!("SERVER_ID loaded: {}", server_id);
 
+    // Optional comma-separated allowlist; unset or empty lets any SERVER_ID register
+    if let Ok(allowed_server_ids) = std::env::var("ALLOWED_SERVER_IDS") {
+        if !LocalConfig::is_allowed_server_id(&server_id, &allowed_server_ids) {
+            panic!(
+                "ERROR: SERVER_ID {} is not listed in ALLOWED_SERVER_IDS; refusing to register",
+                server_id
+            );
+        }
+        tracing::info!("SERVER_ID allowed by ALLOWED_SERVER_IDS");
+    }
+
     let database_url = std::env::var("DATABASE_URL")
         .expect("ERROR: DATABASE_URL environment variable must be set");
     tracing::info!("DATABASE_URL loaded");
@@ -74,43 +193,179 @@ async fn main() {
 
     tracing::info!("Starting vk-service with SERVER_ID: {}", server_id);
 
+    // Total attempts per provider call, including the first (1 = no retries)
+    let provider_max_attempts = std::env::var("PROVIDER_MAX_ATTEMPTS")
+        .ok()
+        .map(|v| {
+            v.parse::<u32>()
+                .expect("PROVIDER_MAX_ATTEMPTS must be a valid u32")
+        })
+        .unwrap_or(1);
+
+    // Reload provider secrets from the DB and retry once when the provider rejects credentials
+    let provider_reload_on_auth_failure = std::env::var("PROVIDER_RELOAD_ON_AUTH_FAILURE")
+        .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+        .unwrap_or(false);
+
+    // Bounded audit event queue drained by a fixed worker pool
+    let event_queue_capacity = std::env::var("EVENT_QUEUE_CAPACITY")
+        .ok()
+        .map(|v| {
+            v.parse::<usize>()
+                .expect("EVENT_QUEUE_CAPACITY must be a valid usize")
+        })
+        .unwrap_or(1024);
+    let event_workers = std::env::var("EVENT_WORKERS")
+        .ok()
+        .map(|v| {
+            v.parse::<usize>()
+                .expect("EVENT_WORKERS must be a valid usize")
+        })
+        .unwrap_or(2);
+
+    // Per-download bandwidth cap in bytes/second (0 = unlimited)
+    let download_bytes_per_second = std::env::var("DOWNLOAD_BYTES_PER_SECOND")
+        .ok()
+        .map(|v| {
+            v.parse::<u64>()
+                .expect("DOWNLOAD_BYTES_PER_SECOND must be a valid u64")
+        })
+        .unwrap_or(0);
+
+    // Max simultaneous provider calls within one multi-object request
+    let provider_request_concurrency = std::env::var("PROVIDER_REQUEST_CONCURRENCY")
+        .ok()
+        .map(|v| {
+            v.parse::<usize>()
+                .expect("PROVIDER_REQUEST_CONCURRENCY must be a valid usize")
+        })
+        .unwrap_or(8)
+        .max(1);
+
+    // Total file bytes buffered across in-flight multipart uploads (0 = unlimited)
+    let upload_buffer_budget_bytes = std::env::var("UPLOAD_BUFFER_BUDGET_BYTES")
+        .ok()
+        .map(|v| {
+            v.parse::<usize>()
+                .expect("UPLOAD_BUFFER_BUDGET_BYTES must be a valid usize")
+        })
+        .unwrap_or(0);
+
+    // Health reports "degraded" when app and database clocks differ by more than this
+    let clock_skew_threshold_ms = std::env::var("CLOCK_SKEW_THRESHOLD_MS")
+        .ok()
+        .map(|v| {
+            v.parse::<u64>()
+                .expect("CLOCK_SKEW_THRESHOLD_MS must be a valid u64")
+        })
+        .unwrap_or(2_000);
+
+    // Longest lifetime handed out for provider signed URLs; SigV4 caps it at 7 days
+    let signed_url_max_ttl_secs = std::env::var("SIGNED_URL_MAX_TTL_SECONDS")
+        .ok()
+        .map(|v| {
+            v.parse::<u64>()
+                .expect("SIGNED_URL_MAX_TTL_SECONDS must be a valid u64")
+        })
+        .unwrap_or(MAX_SIGNED_URL_EXPIRY_SECS)
+        .clamp(1, MAX_SIGNED_URL_EXPIRY_SECS);
+
+    // Which mime type to persist when the provider disagrees with the client
+    let mime_type_policy: MimeTypePolicy = std::env::var("MIME_TYPE_POLICY")
+        .ok()
+        .map(|v| {
+            v.parse()
+                .expect("MIME_TYPE_POLICY must be trust_client or trust_provider")
+        })
+        .unwrap_or_default();
+
+    // Optional comma-separated allowlist for X-Client-Id on upload; unset or empty accepts any id
+    let allowed_client_ids: Vec<String> = std::env::var("ALLOWED_CLIENT_IDS")
+        .map(|ids| {
+            ids.split(',')
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // Optional comma-separated IPs/CIDRs of proxies allowed to set X-Forwarded-For/Forwarded
+    let trusted_proxies = std::env::var("TRUSTED_PROXIES")
+        .map(|list| TrustedProxies::parse(&list).expect("TRUSTED_PROXIES must list IPs or CIDRs"))
+        .unwrap_or_default();
+    if !trusted_proxies.is_empty() {
+        tracing::info!("Client IP taken from forwarding headers of trusted proxies");
+    }
+
+    // Periodic expired-file cleanup; unset or 0 leaves it to DELETE /api/v1/files
+    let cleanup_interval = std::env::var("CLEANUP_INTERVAL_SECONDS")
+        .ok()
+        .map(|v| {
+            v.parse::<u64>()
+                .expect("CLEANUP_INTERVAL_SECONDS must be a valid u64")
+        })
+        .filter(|seconds| *seconds > 0)
+        .map(Duration::from_secs);
+
+    // How long in-flight requests may drain after SIGTERM/SIGINT before connections are dropped
+    let shutdown_timeout = std::env::var("SHUTDOWN_TIMEOUT_SECS")
+        .ok()
+        .map(|v| {
+            v.parse::<u64>()
+                .expect("SHUTDOWN_TIMEOUT_SECS must be a valid u64")
+        })
+        .map(Duration::from_secs)
+        .unwrap_or(Duration::from_secs(30));
+
+    // Optional virus scanning of uploads via clamd (host:port)
+    let file_scanner = std::env::var("CLAMAV_ADDR").ok().map(|addr| {
+        tracing::info!("Upload virus scanning enabled via ClamAV at {}", addr);
+        Arc::new(ClamAvScanner::new(addr)) as Arc<dyn FileScanner>
+    });
+
+    // Seconds uploads are rejected after a failed storage probe or provider call (0 = always try)
+    let storage_down_reject_secs = std::env::var("STORAGE_DOWN_REJECT_SECONDS")
+        .ok()
+        .map(|v| {
+            v.parse::<u64>()
+                .expect("STORAGE_DOWN_REJECT_SECONDS must be a valid u64")
+        })
+        .unwrap_or(0);
+
+    // Postgres pool sizing; panics on max < min before anything connects
+    let db_pool_config = DbPoolConfig::from_env();
+    tracing::info!("PostgreSQL pool: {:?}", db_pool_config);
+
     let port = std::env::var("PORT")
         .unwrap_or_else(|_| "8080".to_string())
         .parse::<u16>()
         .expect("PORT must be a valid u16");
 
-    // Configure CORS
-    let cors = if let Ok(allowed_origins) = std::env::var("CORS_ALLOWED_ORIGINS") {
-        // Parse comma-separated origins
-        let origins: Vec<_> = allowed_origins
-            .split(',')
-            .map(|s| s.trim().parse().expect("Invalid CORS origin"))
-            .collect();
-        CorsLayer::new()
-            .allow_origin(origins)
-            .allow_methods(Any)
-            .allow_headers(Any)
-    } else {
-        // Allow all origins if not specified (only for development)
-        CorsLayer::permissive()
-    };
+    // Configure CORS; panics on an invalid combination before anything is served
+    let cors = build_cors_layer();
 
     // Connect to PostgreSQL and Redis in parallel for faster startup
     println!(">>> Connecting to databases...");
     tracing::info!("Connecting to databases...");
+    // Retry both connections so a dependency that comes up seconds later doesn't crash-loop us
+    let connect_retry = ConnectRetry::from_env();
     let (pool, redis_conn_manager) = tokio::join!(
         async {
-            sqlx::postgres::PgPoolOptions::new()
-                .max_connections(5)
-                .acquire_timeout(std::time::Duration::from_secs(30))
-                .connect(&database_url)
+            connect_retry
+                .run("PostgreSQL", || {
+                    db_pool_config.pool_options().connect(&database_url)
+                })
                 .await
                 .expect("ERROR: Failed to connect to PostgreSQL database. Check DATABASE_URL and network connectivity.")
         },
         async {
             let redis_client = redis::Client::open(redis_url.as_str())
                 .expect("ERROR: Failed to create Redis client. Check REDIS_URL format.");
-            redis::aio::ConnectionManager::new(redis_client)
+            connect_retry
+                .run("Redis", || {
+                    redis::aio::ConnectionManager::new(redis_client.clone())
+                })
                 .await
                 .expect(
                     "ERROR: Failed to connect to Redis. Check REDIS_URL and network connectivity.",
@@ -129,7 +384,10 @@ async fn main() {
         Arc::new(PgLocalConfigRepository::new(pool.clone())) as Arc<dyn LocalConfigRepository>;
 
     // Load all configurations in parallel for faster startup
-    tracing::info!("Loading configurations from database for server_id: {}", server_id);
+    tracing::info!(
+        "Loading configurations from database for server_id: {}",
+        server_id
+    );
     let (local_config_result, secrets_result, global_config_result) = tokio::join!(
         local_config_repo.get_local_config(&server_id),
         secrets_repo.get_secrets(),
@@ -156,18 +414,41 @@ async fn main() {
         }
     };
 
-    let secrets = secrets_result.expect("Failed to load secrets");
-    let global_config = global_config_result.expect("Failed to load global config");
+    // Handle secrets and global config: seed defaults if the single row is missing
+    let secrets = match secrets_result {
+        Ok(secrets) => secrets,
+        Err(ApplicationError::NotFound) => {
+            tracing::warn!("Secrets not found, creating placeholder secrets");
+            secrets_repo
+                .upsert_secrets(SecretsDTO::default())
+                .await
+                .expect("Failed to create default secrets")
+        }
+        Err(e) => panic!("Failed to load secrets: {:?}", e),
+    };
+    let global_config = match global_config_result {
+        Ok(config) => config,
+        Err(ApplicationError::NotFound) => {
+            tracing::info!("Global config not found, creating default config");
+            global_config_repo
+                .upsert_global_config(GlobalConfigDTO::default())
+                .await
+                .expect("Failed to create default global config")
+        }
+        Err(e) => panic!("Failed to load global config: {:?}", e),
+    };
 
-    tracing::info!("Creating storage service for provider: {:?}", local_config.provider);
+    tracing::info!(
+        "Creating storage service for provider: {:?}",
+        local_config.provider
+    );
 
     // Create storage service and token repository in parallel
     let (storage_service_result, token_repo) = tokio::join!(
+        async { services::create_storage_service(&local_config.provider, &secrets).await },
         async {
-            services::create_storage_service(&local_config.provider, &secrets).await
-        },
-        async {
-            Arc::new(RedisTokenRepository::new(redis_conn_manager)) as Arc<dyn TokenRepository>
+            Arc::new(RedisTokenRepository::new(redis_conn_manager.clone()))
+                as Arc<dyn TokenRepository>
         }
     );
 
@@ -176,16 +457,38 @@ async fn main() {
             tracing::info!("Storage service created successfully");
             service
         }
+        Err(StorageError::InvalidCredentials(msg)) => {
+            // Fresh database: boot anyway so secrets can be configured and refreshed
+            tracing::warn!(
+                "Storage provider not configured ({}), starting without it",
+                msg
+            );
+            Arc::new(UnconfiguredStorageService::new(
+                local_config.provider.clone(),
+            )) as Arc<dyn StorageService>
+        }
         Err(e) => {
             tracing::error!("Failed to create storage service: {:?}", e);
             panic!("Failed to create storage service: {:?}", e);
         }
     };
 
+    let secrets = Arc::new(Mutex::new(secrets));
+    let local_config = Arc::new(Mutex::new(local_config));
+
+    let mut storage_service_wrapper =
+        StorageServiceWrapper::new(storage_service, provider_max_attempts);
+    if provider_reload_on_auth_failure {
+        tracing::info!("Provider secret reload on credential rejection enabled");
+        storage_service_wrapper = storage_service_wrapper.with_credential_reload(
+            CredentialReload::new(secrets_repo.clone(), secrets.clone(), local_config.clone()),
+        );
+    }
+
     let app_state = AppState {
         server_id,
-        secrets: Arc::new(Mutex::new(secrets)),
-        local_config: Arc::new(Mutex::new(local_config)),
+        secrets,
+        local_config,
         global_config: Arc::new(Mutex::new(global_config)),
         user_repository: Arc::new(PgUserRepository::new(pool.clone())) as Arc<dyn UserRepository>,
         metadata_repository: Arc::new(PgMetadataRepository::new(pool))
@@ -193,21 +496,82 @@ async fn main() {
         secrets_repository: secrets_repo,
         global_config_repository: global_config_repo,
         local_config_repository: local_config_repo,
-        storage_service: StorageServiceWrapper::new(storage_service),
+        storage_service: storage_service_wrapper,
         token_repository: token_repo,
+        upload_session_repository: Arc::new(RedisUploadSessionRepository::new(redis_conn_manager))
+            as Arc<dyn UploadSessionRepository>,
+        file_scanner,
+        event_queue: EventQueue::start(
+            Arc::new(TracingEventSink),
+            event_queue_capacity,
+            event_workers,
+        ),
+        download_bytes_per_second,
+        provider_request_concurrency,
+        upload_buffer_budget: UploadBufferBudget::new(upload_buffer_budget_bytes),
+        storage_down_reject_secs,
+        clock_skew_threshold_ms,
+        signed_url_max_ttl_secs,
+        mime_type_policy,
+        allowed_client_ids: Arc::new(allowed_client_ids),
+        error_metrics: Arc::new(ErrorMetrics::default()),
+        trusted_proxies: Arc::new(trusted_proxies),
+        cleanup_lock: Arc::new(tokio::sync::Mutex::new(())),
     };
 
+    // Flipped to true once a shutdown signal arrives
+    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+
+    let cleanup_task = cleanup_interval.map(|interval| {
+        tracing::info!("Expired file cleanup scheduled every {:?}", interval);
+        tokio::spawn(run_cleanup_task(
+            app_state.clone(),
+            interval,
+            shutdown_rx.clone(),
+        ))
+    });
+
     // Protected routes that require X-KV-SECRET header
     let protected_routes = Router::new()
-        .route("/api/v1/health", get(HealthController::health_check))
+        .route("/api/v1/health", get(health_controller::health_check))
+        .route("/api/v1/health/live", get(health_controller::liveness))
+        .route("/api/v1/health/ready", get(health_controller::readiness))
+        .route("/metrics", get(health_controller::metrics))
         .route(
             "/api/v1/instances",
-            get(InstanceController::get_all_instances),
+            get(instance_controller::get_all_instances),
         )
         .route(
             "/api/v1/instances/{server_id}",
-            get(InstanceController::get_instance).patch(InstanceController::update_instance),
+            json_body(
+                get(instance_controller::get_instance).patch(instance_controller::update_instance),
+            ),
+        )
+        .route(
+            "/api/v1/admin/export",
+            get(admin_controller::export_metadata),
+        )
+        .route(
+            "/api/v1/admin/stats/clients",
+            get(admin_controller::client_stats),
+        )
+        .route(
+            "/api/v1/admin/refresh-config",
+            post(admin_controller::refresh_config),
+        )
+        .route(
+            "/api/v1/admin/reindex-checksums",
+            post(admin_controller::reindex_checksums),
+        )
+        .route("/api/v1/admin/vacuum", post(admin_controller::vacuum))
+        .route(
+            "/api/v1/users/{user_id}/token",
+            post(user_controller::issue_user_token),
         )
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            record_error_outcomes,
+        ))
         .route_layer(middleware::from_fn_with_state(
             app_state.clone(),
             validate_kv_secret,
@@ -216,35 +580,107 @@ async fn main() {
     // Public routes that don't require authentication
     let public_routes = Router::new()
         .route("/", get(hello_world))
-        .route("/api/v1/users", post(UserController::create_user))
+        .route("/api/v1/openapi.json", get(DocsController::openapi_json))
+        .route(
+            "/api/v1/users",
+            json_body(post(user_controller::create_user)),
+        )
         .route(
             "/api/v1/users/{user_id}",
-            get(UserController::get_user)
-                .patch(UserController::update_user)
-                .delete(UserController::delete_user),
+            json_body(
+                get(user_controller::get_user)
+                    .patch(user_controller::update_user)
+                    .delete(user_controller::delete_user),
+            ),
         )
         .route(
             "/api/v1/users/{user_id}/files",
-            get(UserController::get_user_files),
+            get(user_controller::get_user_files),
+        )
+        .route(
+            "/api/v1/users/{user_id}/usage",
+            get(user_controller::get_user_usage),
+        )
+        .route(
+            "/api/v1/users/{user_id}/schedule-expiry",
+            json_body(post(user_controller::schedule_expiry)),
+        )
+        .route(
+            "/api/v1/users/{user_id}/files/by-name/{filename}/content",
+            get(file_controller::download_file_by_name),
         )
         .route(
             "/api/v1/files/token",
-            post(FileController::generate_upload_token),
+            json_body(post(file_controller::generate_upload_token)),
         )
         .route(
             "/api/v1/files",
-            post(FileController::upload_file).delete(FileController::cleanup_expired_files),
+            upload_body(
+                post(file_controller::upload_file),
+                app_state.global_config.clone(),
+            )
+            .delete(file_controller::cleanup_expired_files),
+        )
+        .route(
+            "/api/v1/files/reconcile",
+            get(file_controller::reconcile_files),
+        )
+        .route(
+            "/api/v1/files/batch-delete",
+            json_body(post(file_controller::batch_delete_files)),
+        )
+        .route(
+            "/api/v1/files/signed-urls:batch",
+            json_body(post(file_controller::signed_urls_batch)),
+        )
+        .route(
+            "/api/v1/files/chunked/init",
+            json_body(post(file_controller::init_chunked_upload)),
+        )
+        .route(
+            "/api/v1/files/chunked/{session_id}",
+            patch(file_controller::append_chunk).delete(file_controller::cancel_chunked_upload),
+        )
+        .route(
+            "/api/v1/files/chunked/{session_id}/complete",
+            post(file_controller::complete_chunked_upload),
         )
         .route(
             "/api/v1/files/{file_id}/content",
-            get(FileController::download_file),
+            get(file_controller::download_file).head(file_controller::head_file),
+        )
+        .route(
+            "/api/v1/files/{file_id}/url",
+            get(file_controller::get_signed_url),
+        )
+        .route(
+            "/api/v1/files/{file_id}/links",
+            get(file_controller::get_file_links),
+        )
+        .route(
+            "/api/v1/files/{file_id}/stats",
+            get(file_controller::get_file_stats),
+        )
+        .route(
+            "/api/v1/files/{file_id}/restore",
+            post(file_controller::restore_file),
+        )
+        .route(
+            "/api/v1/files/{file_id}/transfer",
+            json_body(post(file_controller::transfer_file)),
         )
         .route(
             "/api/v1/files/{file_id}",
-            get(FileController::get_file_metadata)
-                .patch(FileController::update_file_metadata)
-                .delete(FileController::delete_file),
-        );
+            json_body(
+                get(file_controller::get_file_metadata)
+                    .patch(file_controller::update_file_metadata)
+                    .delete(file_controller::delete_file),
+            ),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            record_error_outcomes,
+        ));
 
     // Combine routes and add CORS layer
     let router = Router::new()
@@ -259,12 +695,125 @@ async fn main() {
         .await
         .expect("Failed to bind to port");
 
-    println!(">>> ✓ Server successfully bound and listening on 0.0.0.0:{}", port);
-    tracing::info!("✓ Server successfully bound and listening on 0.0.0.0:{}", port);
+    println!(
+        ">>> ✓ Server successfully bound and listening on 0.0.0.0:{}",
+        port
+    );
+    tracing::info!(
+        "✓ Server successfully bound and listening on 0.0.0.0:{}",
+        port
+    );
     println!(">>> Application startup complete - ready to accept requests");
     tracing::info!("Application startup complete - ready to accept requests");
 
-    axum::serve(listener, router)
-        .await
-        .expect("Failed to start server");
+    // Trailing slashes are trimmed before routing, so /content/ reaches /content; "/" is kept
+    let app = into_app(router);
+
+    let server = axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(async move {
+        shutdown_signal().await;
+        tracing::info!(
+            "Shutdown initiated, draining in-flight requests for up to {:?}",
+            shutdown_timeout
+        );
+        let _ = shutdown_tx.send(true);
+    });
+
+    // Once the drain timeout passes, returning from main drops the connections still open
+    let mut drain_started = shutdown_rx;
+    let drain_deadline = async move {
+        let _ = drain_started.changed().await;
+        tokio::time::sleep(shutdown_timeout).await;
+    };
+    tokio::select! {
+        result = server.into_future() => result.expect("Failed to start server"),
+        _ = drain_deadline => {
+            tracing::warn!(
+                "Drain timeout of {:?} elapsed, closing remaining connections",
+                shutdown_timeout
+            );
+        }
+    }
+
+    if let Some(cleanup_task) = cleanup_task {
+        if tokio::time::timeout(shutdown_timeout, cleanup_task)
+            .await
+            .is_err()
+        {
+            tracing::warn!(
+                "Scheduled cleanup did not stop within {:?}",
+                shutdown_timeout
+            );
+        }
+    }
+    tracing::info!("Shutdown complete");
+}
+
+#[cfg(test)]
+mod tests {
+    use adapters::middleware::REQUEST_ID_HEADER;
+    use domain::config::secrets::Secrets;
+
+    use super::*;
+
+    /// Serves a route protected by validate_kv_secret through into_app; returns its base URL
+    async fn serve_protected() -> String {
+        let mut secrets: Secrets = SecretsDTO::default().into();
+        secrets.vk_secret = "secret".to_string();
+        let router = Router::new()
+            .route("/api/v1/health", get(|| async { "ok" }))
+            .route_layer(middleware::from_fn_with_state(
+                Arc::new(Mutex::new(secrets)),
+                validate_kv_secret,
+            ));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, into_app(router)).await.unwrap() });
+        base
+    }
+
+    #[tokio::test]
+    async fn rejected_secrets_still_carry_a_request_id() {
+        let base = serve_protected().await;
+
+        let response = reqwest::get(format!("{}/api/v1/health/", base))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+        let request_id = response.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+        assert!(uuid::Uuid::parse_str(request_id).is_ok(), "{}", request_id);
+    }
+
+    #[tokio::test]
+    async fn the_client_request_id_is_echoed_on_rejections() {
+        let base = serve_protected().await;
+
+        let response = reqwest::Client::new()
+            .get(format!("{}/api/v1/health", base))
+            .header(REQUEST_ID_HEADER, "gateway-42")
+            .header("X-KV-SECRET", "wrong")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "gateway-42");
+    }
+
+    #[tokio::test]
+    async fn trailing_slashes_are_trimmed_inside_the_request_id_layer() {
+        let base = serve_protected().await;
+
+        let response = reqwest::Client::new()
+            .get(format!("{}/api/v1/health/", base))
+            .header("X-KV-SECRET", "secret")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        assert!(response.headers().contains_key(REQUEST_ID_HEADER));
+    }
 }
@@ -0,0 +1,477 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Utc;
+use futures_util::StreamExt;
+use hmac::{Hmac, Mac};
+use reqwest::{header, Client, Method, Response, Url};
+use sha2::Sha256;
+
+use crate::{
+    application::{
+        error::ApplicationError,
+        services::{ObjectStream, StorageService},
+    },
+    domain::{
+        config::secrets::AzureSecrets,
+        models::file::{FileData, FileMetadata},
+    },
+    services::{error::StorageError, file_path::generate_file_path, tls::ProviderTlsConfig},
+};
+
+const PROVIDER_NAME: &str = "azure";
+/// Versión de la API REST de Blob Storage con la que se firman las peticiones
+const API_VERSION: &str = "2021-12-02";
+/// Máximo que admite List Blobs por página
+const LIST_PAGE_SIZE: &str = "5000";
+
+/// Blobs de un contenedor de Azure Storage, firmando cada petición con la clave de la cuenta
+pub struct AzureBlobStorageService {
+    client: Client,
+    account_name: String,
+    /// Clave de la cuenta ya decodificada
+    account_key: Vec<u8>,
+    container_name: String,
+    /// URL base sin barra final
+    endpoint: String,
+    /// Ruta de `endpoint`; Azurite pone ahí la cuenta y entra en la firma
+    base_path: String,
+}
+
+impl AzureBlobStorageService {
+    pub fn new(secrets: AzureSecrets, tls: &ProviderTlsConfig) -> Result<Self, StorageError> {
+        let account_key = STANDARD
+            .decode(secrets.account_key.trim())
+            .map_err(|e| StorageError::InvalidCredentials(format!("Invalid account key: {}", e)))?;
+
+        let endpoint = match secrets.endpoint {
+            Some(endpoint) => endpoint.trim().trim_end_matches('/').to_string(),
+            None => format!("https://{}.blob.core.windows.net", secrets.account_name),
+        };
+        let base_path = Url::parse(&endpoint)
+            .map_err(|e| StorageError::InvalidCredentials(format!("Invalid endpoint: {}", e)))?
+            .path()
+            .trim_end_matches('/')
+            .to_string();
+
+        Ok(Self {
+            client: tls.http_client()?,
+            account_name: secrets.account_name,
+            account_key,
+            container_name: secrets.container_name,
+            endpoint,
+            base_path,
+        })
+    }
+
+    /// Ruta del contenedor, o del blob si se indica, relativa a `endpoint`
+    fn resource_path(&self, blob: Option<&str>) -> String {
+        match blob {
+            Some(blob) => format!("/{}/{}", self.container_name, encode_blob_name(blob)),
+            None => format!("/{}", self.container_name),
+        }
+    }
+
+    fn sign(&self, string_to_sign: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.account_key)
+            .expect("HMAC accepts keys of any length");
+        mac.update(string_to_sign.as_bytes());
+        STANDARD.encode(mac.finalize().into_bytes())
+    }
+
+    /// Petición firmada con Shared Key; `upload` añade el cuerpo y las cabeceras de Put Blob
+    async fn send(
+        &self,
+        method: Method,
+        blob: Option<&str>,
+        query: &[(&str, &str)],
+        upload: Option<&FileData>,
+    ) -> Result<Response, StorageError> {
+        let path = self.resource_path(blob);
+        let date = Utc::now().format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+
+        // Ordenadas por nombre, como exige la forma canónica
+        let mut ms_headers = vec![
+            ("x-ms-date", date),
+            ("x-ms-version", API_VERSION.to_string()),
+        ];
+        if upload.is_some() {
+            ms_headers.push(("x-ms-blob-type", "BlockBlob".to_string()));
+        }
+        ms_headers.sort();
+
+        // Content-Length vacío si es 0, según las versiones de la API desde 2015-02-21
+        let content_length = upload
+            .map(|file_data| file_data.content.len())
+            .filter(|&length| length > 0)
+            .map(|length| length.to_string())
+            .unwrap_or_default();
+        let content_type = upload.map_or("", |file_data| file_data.mime_type.as_str());
+
+        let mut string_to_sign = format!(
+            "{}\n\n\n{}\n\n{}\n\n\n\n\n\n\n",
+            method.as_str(),
+            content_length,
+            content_type
+        );
+        for (name, value) in &ms_headers {
+            string_to_sign.push_str(&format!("{}:{}\n", name, value));
+        }
+        string_to_sign.push_str(&format!("/{}{}{}", self.account_name, self.base_path, path));
+        let mut sorted_query = query.to_vec();
+        sorted_query.sort();
+        for (name, value) in sorted_query {
+            string_to_sign.push_str(&format!("\n{}:{}", name, value));
+        }
+
+        let mut request = self
+            .client
+            .request(method, format!("{}{}", self.endpoint, path))
+            .query(query)
+            .header(
+                header::AUTHORIZATION,
+                format!(
+                    "SharedKey {}:{}",
+                    self.account_name,
+                    self.sign(&string_to_sign)
+                ),
+            );
+        for (name, value) in ms_headers {
+            request = request.header(name, value);
+        }
+        if let Some(file_data) = upload {
+            request = request
+                .header(header::CONTENT_TYPE, &file_data.mime_type)
+                .body(file_data.content.clone());
+        }
+
+        request.send().await.map_err(StorageError::from)
+    }
+
+    /// HEAD del blob; un nombre inexistente se traduce a StorageError::NotFound
+    async fn head(&self, file_id: &str) -> Result<Response, StorageError> {
+        let response = self.send(Method::HEAD, Some(file_id), &[], None).await?;
+        check_status(response, file_id, "head blob").await
+    }
+
+    /// GET del blob ya validado; el cuerpo queda sin leer
+    async fn get_blob(&self, file_id: &str) -> Result<Response, StorageError> {
+        let response = self.send(Method::GET, Some(file_id), &[], None).await?;
+        check_status(response, file_id, "download").await
+    }
+}
+
+/// Rate limit, credenciales rechazadas y 404 (NotFound de `target`); el resto, ProviderError
+async fn check_status(
+    response: Response,
+    target: &str,
+    operation: &str,
+) -> Result<Response, StorageError> {
+    let status = response.status().as_u16();
+    if status == 401 || status == 403 {
+        return Err(StorageError::Unauthorized(format!(
+            "Azure rejected credentials with status: {}",
+            status
+        )));
+    }
+
+    let retry_after = response
+        .headers()
+        .get(header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok());
+    if let Some(error) = StorageError::from_rate_limit(status, retry_after) {
+        return Err(error);
+    }
+
+    if status == 404 {
+        return Err(StorageError::NotFound(target.to_string()));
+    }
+
+    if !response.status().is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(StorageError::ProviderError(format!(
+            "Azure {} failed with status {}: {}",
+            operation, status, error_text
+        )));
+    }
+
+    Ok(response)
+}
+
+/// Codifica cada segmento del nombre del blob conservando las `/`
+fn encode_blob_name(name: &str) -> String {
+    name.split('/')
+        .map(|segment| {
+            segment
+                .bytes()
+                .map(|b| {
+                    if b.is_ascii_alphanumeric() || b"-._~".contains(&b) {
+                        (b as char).to_string()
+                    } else {
+                        format!("%{:02X}", b)
+                    }
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// ETag sin comillas, como lo guardan los demás proveedores
+fn etag(response: &Response) -> Option<String> {
+    response
+        .headers()
+        .get(header::ETAG)
+        .and_then(|v| v.to_str().ok())
+        .map(|tag| tag.trim_matches('"').to_string())
+}
+
+/// Contenido de cada `<tag>...</tag>` de `xml`; List Blobs no anida elementos del mismo nombre
+fn xml_elements<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let mut elements = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        let after = &rest[start + open.len()..];
+        let Some(end) = after.find(&close) else {
+            break;
+        };
+        elements.push(&after[..end]);
+        rest = &after[end + close.len()..];
+    }
+    elements
+}
+
+/// Texto del primer `<tag>` con las entidades XML predefinidas resueltas
+fn xml_text(xml: &str, tag: &str) -> Option<String> {
+    xml_elements(xml, tag).first().map(|text| {
+        text.replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&amp;", "&")
+    })
+}
+
+#[async_trait]
+impl StorageService for AzureBlobStorageService {
+    fn provider_name(&self) -> &'static str {
+        PROVIDER_NAME
+    }
+
+    /// Get Container Properties: comprueba la clave y que el contenedor exista
+    async fn ping(&self) -> Result<(), ApplicationError> {
+        let response = self
+            .send(Method::HEAD, None, &[("restype", "container")], None)
+            .await?;
+        match check_status(response, &self.container_name, "ping").await {
+            Ok(_) => Ok(()),
+            Err(StorageError::NotFound(container)) => Err(StorageError::ProviderError(format!(
+                "Container {} not found",
+                container
+            ))
+            .into()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// SAS de servicio de solo lectura para el blob, firmada con la clave de la cuenta
+    async fn generate_presigned_url(
+        &self,
+        file_id: &str,
+        expires_in: Duration,
+    ) -> Result<Option<String>, ApplicationError> {
+        let expires_in = chrono::Duration::from_std(expires_in)
+            .map_err(|e| StorageError::InternalError(e.to_string()))?;
+        let expiry = (Utc::now() + expires_in)
+            .format("%Y-%m-%dT%H:%M:%SZ")
+            .to_string();
+        let canonical_resource = format!(
+            "/blob/{}/{}/{}",
+            self.account_name, self.container_name, file_id
+        );
+
+        // sp, st, se, recurso, si, sip, spr, sv, sr, snapshot, ses y las cinco rsc*
+        let string_to_sign = [
+            "r",
+            "",
+            &expiry,
+            &canonical_resource,
+            "",
+            "",
+            "",
+            API_VERSION,
+            "b",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+        ]
+        .join("\n");
+
+        let mut url = Url::parse(&format!(
+            "{}{}",
+            self.endpoint,
+            self.resource_path(Some(file_id))
+        ))
+        .map_err(|e| StorageError::InternalError(e.to_string()))?;
+        url.query_pairs_mut()
+            .append_pair("sv", API_VERSION)
+            .append_pair("sr", "b")
+            .append_pair("sp", "r")
+            .append_pair("se", &expiry)
+            .append_pair("sig", &self.sign(&string_to_sign));
+
+        Ok(Some(url.to_string()))
+    }
+
+    async fn upload(&self, file_data: FileData) -> Result<FileMetadata, ApplicationError> {
+        // El nombre del blob es el file_id
+        let key = generate_file_path(&file_data.filename);
+
+        let response = self
+            .send(Method::PUT, Some(&key), &[], Some(&file_data))
+            .await?;
+        let response = check_status(response, &key, "upload").await?;
+
+        Ok(FileMetadata {
+            file_id: key,
+            size: file_data.size(),
+            mime_type: file_data.mime_type.clone(),
+            filename: Some(file_data.filename),
+            provider: PROVIDER_NAME.to_string(),
+            web_view_link: None,
+            web_content_link: None,
+            checksum: etag(&response),
+        })
+    }
+
+    async fn download(&self, file_id: &str) -> Result<Vec<u8>, ApplicationError> {
+        let bytes = self
+            .get_blob(file_id)
+            .await?
+            .bytes()
+            .await
+            .map_err(|e| StorageError::NetworkError(e.to_string()))?;
+
+        Ok(bytes.to_vec())
+    }
+
+    async fn download_stream(&self, file_id: &str) -> Result<ObjectStream, ApplicationError> {
+        let response = self.get_blob(file_id).await?;
+        let length = response.content_length();
+
+        Ok(ObjectStream {
+            content: response
+                .bytes_stream()
+                .map(|chunk| {
+                    chunk.map_err(|e| {
+                        ApplicationError::from(StorageError::NetworkError(e.to_string()))
+                    })
+                })
+                .boxed(),
+            length,
+        })
+    }
+
+    async fn delete(&self, file_id: &str) -> Result<(), ApplicationError> {
+        let response = self.send(Method::DELETE, Some(file_id), &[], None).await?;
+        check_status(response, file_id, "delete").await?;
+        Ok(())
+    }
+
+    async fn get_metadata(&self, file_id: &str) -> Result<FileMetadata, ApplicationError> {
+        let response = self.head(file_id).await?;
+        let header_value = |name: header::HeaderName| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+
+        Ok(FileMetadata {
+            file_id: file_id.to_string(),
+            size: header_value(header::CONTENT_LENGTH)
+                .and_then(|length| length.parse::<u64>().ok())
+                .unwrap_or(0),
+            mime_type: header_value(header::CONTENT_TYPE)
+                .unwrap_or_else(|| "application/octet-stream".to_string()),
+            filename: file_id.rsplit('/').next().map(|s| s.to_string()),
+            provider: PROVIDER_NAME.to_string(),
+            web_view_link: None,
+            web_content_link: None,
+            checksum: etag(&response),
+        })
+    }
+
+    async fn list_files(
+        &self,
+        prefix: Option<&str>,
+    ) -> Result<Vec<FileMetadata>, ApplicationError> {
+        let mut files = Vec::new();
+        let mut marker = String::new();
+
+        loop {
+            let mut query = vec![
+                ("restype", "container"),
+                ("comp", "list"),
+                ("maxresults", LIST_PAGE_SIZE),
+            ];
+            if let Some(prefix) = prefix {
+                query.push(("prefix", prefix));
+            }
+            if !marker.is_empty() {
+                query.push(("marker", marker.as_str()));
+            }
+
+            let response = self.send(Method::GET, None, &query, None).await?;
+            let body = check_status(response, &self.container_name, "list blobs")
+                .await?
+                .text()
+                .await
+                .map_err(|e| StorageError::NetworkError(e.to_string()))?;
+
+            for blob in xml_elements(&body, "Blob") {
+                let Some(name) = xml_text(blob, "Name") else {
+                    continue;
+                };
+                files.push(FileMetadata {
+                    size: xml_text(blob, "Content-Length")
+                        .and_then(|length| length.parse::<u64>().ok())
+                        .unwrap_or(0),
+                    mime_type: xml_text(blob, "Content-Type")
+                        .unwrap_or_else(|| "application/octet-stream".to_string()),
+                    filename: name.rsplit('/').next().map(|s| s.to_string()),
+                    provider: PROVIDER_NAME.to_string(),
+                    web_view_link: None,
+                    web_content_link: None,
+                    checksum: xml_text(blob, "Etag").map(|tag| tag.trim_matches('"').to_string()),
+                    file_id: name,
+                });
+            }
+
+            // NextMarker va después de la lista de blobs; vacío en la última página
+            let trailer = body.rsplit_once("</Blobs>").map_or("", |(_, rest)| rest);
+            match xml_text(trailer, "NextMarker").filter(|next| !next.is_empty()) {
+                Some(next) => marker = next,
+                None => break,
+            }
+        }
+
+        Ok(files)
+    }
+
+    async fn exists(&self, file_id: &str) -> Result<bool, ApplicationError> {
+        match self.head(file_id).await {
+            Ok(_) => Ok(true),
+            Err(StorageError::NotFound(_)) => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+use crate::{
+    application::{error::ApplicationError, services::FileScanner},
+    domain::models::scan::ScanVerdict,
+};
+
+/// clamd rechaza streams mayores a StreamMaxLength; enviamos en bloques pequeños
+const CHUNK_SIZE: usize = 64 * 1024;
+const SCAN_TIMEOUT_SECONDS: u64 = 60;
+
+pub struct ClamAvScanner {
+    address: String,
+}
+
+impl ClamAvScanner {
+    pub fn new(address: String) -> Self {
+        Self { address }
+    }
+
+    async fn instream(&self, bytes: &[u8]) -> Result<String, std::io::Error> {
+        let mut stream = TcpStream::connect(&self.address).await?;
+
+        stream.write_all(b"zINSTREAM\0").await?;
+        for chunk in bytes.chunks(CHUNK_SIZE) {
+            stream
+                .write_all(&(chunk.len() as u32).to_be_bytes())
+                .await?;
+            stream.write_all(chunk).await?;
+        }
+        // Un bloque de longitud cero marca el fin del stream
+        stream.write_all(&0u32.to_be_bytes()).await?;
+        stream.flush().await?;
+
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).await?;
+
+        Ok(String::from_utf8_lossy(&response)
+            .trim_end_matches('\0')
+            .trim()
+            .to_string())
+    }
+
+    fn parse_response(response: &str) -> Result<ScanVerdict, ApplicationError> {
+        // Formato: "stream: OK" o "stream: <firma> FOUND"
+        let result = response
+            .strip_prefix("stream:")
+            .map(str::trim)
+            .unwrap_or(response);
+
+        if result == "OK" {
+            Ok(ScanVerdict::Clean)
+        } else if let Some(signature) = result.strip_suffix("FOUND") {
+            Ok(ScanVerdict::Infected(signature.trim().to_string()))
+        } else {
+            Err(ApplicationError::InternalError(format!(
+                "Unexpected ClamAV response: {}",
+                response
+            )))
+        }
+    }
+}
+
+#[async_trait]
+impl FileScanner for ClamAvScanner {
+    async fn scan(&self, bytes: &[u8]) -> Result<ScanVerdict, ApplicationError> {
+        let response = tokio::time::timeout(
+            Duration::from_secs(SCAN_TIMEOUT_SECONDS),
+            self.instream(bytes),
+        )
+        .await
+        .map_err(|_| ApplicationError::InternalError("ClamAV scan timed out".to_string()))?
+        .map_err(|e| ApplicationError::InternalError(format!("ClamAV scan failed: {}", e)))?;
+
+        Self::parse_response(&response)
+    }
+}
@@ -13,6 +13,9 @@ pub enum StorageError {
     #[error("Network error: {0}")]
     NetworkError(String),
 
+    #[error("Timeout: {0}")]
+    Timeout(String),
+
     #[error("Invalid credentials: {0}")]
     InvalidCredentials(String),
 
@@ -21,17 +24,47 @@ pub enum StorageError {
 
     #[error("Internal error: {0}")]
     InternalError(String),
+
+    #[error("Rate limited by provider (retry after {retry_after:?}s)")]
+    RateLimited { retry_after: Option<u64> },
+}
+
+impl StorageError {
+    /// Detecta respuestas 429/503 del proveedor conservando su cabecera Retry-After
+    pub fn from_rate_limit(status: u16, retry_after: Option<&str>) -> Option<Self> {
+        if status != 429 && status != 503 {
+            return None;
+        }
+        Some(StorageError::RateLimited {
+            retry_after: retry_after.and_then(parse_retry_after),
+        })
+    }
+}
+
+/// Retry-After puede venir en segundos o como fecha HTTP
+fn parse_retry_after(value: &str) -> Option<u64> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(seconds);
+    }
+    chrono::DateTime::parse_from_rfc2822(value)
+        .ok()
+        .map(|date| (date.timestamp() - chrono::Utc::now().timestamp()).max(0) as u64)
 }
 
 impl From<StorageError> for ApplicationError {
     fn from(error: StorageError) -> Self {
         match error {
             StorageError::NotFound(_) => ApplicationError::NotFound,
-            StorageError::Unauthorized(msg)
-            | StorageError::NetworkError(msg)
-            | StorageError::InvalidCredentials(msg)
-            | StorageError::ProviderError(msg)
-            | StorageError::InternalError(msg) => {
+            StorageError::RateLimited { retry_after } => {
+                ApplicationError::TooManyRequests { retry_after }
+            }
+            StorageError::Unauthorized(msg) | StorageError::InvalidCredentials(msg) => {
+                ApplicationError::ProviderUnauthorized(msg)
+            }
+            StorageError::NetworkError(msg) => ApplicationError::StorageUnavailable(msg),
+            StorageError::Timeout(msg) => ApplicationError::UpstreamTimeout(msg),
+            StorageError::ProviderError(msg) | StorageError::InternalError(msg) => {
                 ApplicationError::InternalError(format!("Storage error: {}", msg))
             }
         }
@@ -41,12 +74,13 @@ impl From<StorageError> for ApplicationError {
 impl From<reqwest::Error> for StorageError {
     fn from(error: reqwest::Error) -> Self {
         if error.is_timeout() {
-            StorageError::NetworkError("Request timeout".to_string())
+            StorageError::Timeout(error.to_string())
         } else if error.is_connect() {
             StorageError::NetworkError(format!("Connection failed: {}", error))
         } else if let Some(status) = error.status() {
             match status.as_u16() {
                 404 => StorageError::NotFound(error.to_string()),
+                429 | 503 => StorageError::RateLimited { retry_after: None },
                 401 | 403 => StorageError::Unauthorized(error.to_string()),
                 _ => StorageError::ProviderError(error.to_string()),
             }
@@ -55,3 +89,63 @@ impl From<reqwest::Error> for StorageError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn retry_after(error: Option<StorageError>) -> Option<u64> {
+        match error {
+            Some(StorageError::RateLimited { retry_after }) => retry_after,
+            other => panic!("expected RateLimited, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn from_rate_limit_only_matches_429_and_503() {
+        assert!(StorageError::from_rate_limit(200, Some("5")).is_none());
+        assert!(StorageError::from_rate_limit(500, Some("5")).is_none());
+        assert_eq!(
+            retry_after(StorageError::from_rate_limit(429, Some("5"))),
+            Some(5)
+        );
+        assert_eq!(retry_after(StorageError::from_rate_limit(503, None)), None);
+    }
+
+    #[test]
+    fn parse_retry_after_reads_seconds_and_http_dates() {
+        assert_eq!(parse_retry_after(" 120 "), Some(120));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), Some(0));
+
+        let later = (chrono::Utc::now() + chrono::Duration::seconds(60)).to_rfc2822();
+        let seconds = parse_retry_after(&later).unwrap();
+        assert!(
+            (58..=60).contains(&seconds),
+            "{} should be about 60",
+            seconds
+        );
+    }
+
+    #[test]
+    fn parse_retry_after_ignores_garbage() {
+        assert_eq!(parse_retry_after("soon"), None);
+        assert_eq!(parse_retry_after("-5"), None);
+        assert_eq!(
+            retry_after(StorageError::from_rate_limit(429, Some("soon"))),
+            None
+        );
+    }
+
+    #[test]
+    fn rate_limits_map_to_too_many_requests() {
+        let error = ApplicationError::from(StorageError::RateLimited {
+            retry_after: Some(7),
+        });
+        assert!(matches!(
+            error,
+            ApplicationError::TooManyRequests {
+                retry_after: Some(7)
+            }
+        ));
+    }
+}
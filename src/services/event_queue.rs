@@ -0,0 +1,166 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use tokio::sync::{
+    mpsc::{self, error::TrySendError},
+    Mutex,
+};
+use tracing::{debug, warn};
+
+use crate::{application::services::EventSink, domain::models::audit::AuditEvent};
+
+/// Cola acotada con un pool fijo de workers para entregar eventos sin bloquear las peticiones
+///
+/// Cuando la cola está llena el evento se descarta y se incrementa el contador, en lugar
+/// de crear una tarea por evento sin límite
+#[derive(Clone)]
+pub struct EventQueue {
+    sender: mpsc::Sender<AuditEvent>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl EventQueue {
+    pub fn start(sink: Arc<dyn EventSink>, capacity: usize, workers: usize) -> Self {
+        let (sender, receiver) = mpsc::channel(capacity.max(1));
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        for worker in 0..workers.max(1) {
+            let receiver = receiver.clone();
+            let sink = sink.clone();
+            tokio::spawn(async move {
+                loop {
+                    // El lock solo se mantiene mientras se espera el siguiente evento
+                    let event = { receiver.lock().await.recv().await };
+                    let Some(event) = event else {
+                        debug!("Event worker {} stopping, queue closed", worker);
+                        break;
+                    };
+                    if let Err(e) = sink.deliver(&event).await {
+                        warn!("Failed to deliver {:?} event: {:?}", event.action, e);
+                    }
+                }
+            });
+        }
+
+        Self {
+            sender,
+            dropped: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Encola el evento sin esperar; nunca bloquea al llamador
+    pub fn publish(&self, event: AuditEvent) {
+        match self.sender.try_send(event) {
+            Ok(()) => {}
+            Err(TrySendError::Full(event)) => {
+                let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                warn!(
+                    "Event queue full, dropping {:?} event for {} ({} dropped so far)",
+                    event.action, event.file_id, dropped
+                );
+            }
+            Err(TrySendError::Closed(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                warn!("Event queue closed, dropping event");
+            }
+        }
+    }
+
+    pub fn dropped_events(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    pub fn queued_events(&self) -> usize {
+        self.sender.max_capacity() - self.sender.capacity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use tokio::sync::{Notify, Semaphore};
+
+    use super::*;
+    use crate::{application::error::ApplicationError, domain::models::audit::AuditAction};
+
+    /// Registra los file_id entregados; con `gate`, cada entrega espera un permiso
+    #[derive(Default)]
+    struct RecordingSink {
+        delivered: std::sync::Mutex<Vec<String>>,
+        started: Notify,
+        gate: Option<Semaphore>,
+    }
+
+    #[async_trait]
+    impl EventSink for RecordingSink {
+        async fn deliver(&self, event: &AuditEvent) -> Result<(), ApplicationError> {
+            self.started.notify_one();
+            if let Some(gate) = &self.gate {
+                gate.acquire().await.unwrap().forget();
+            }
+            self.delivered.lock().unwrap().push(event.file_id.clone());
+            Ok(())
+        }
+    }
+
+    fn event(file_id: &str) -> AuditEvent {
+        AuditEvent::new(AuditAction::Upload, file_id, None, "server")
+    }
+
+    async fn wait_for_deliveries(sink: &RecordingSink, count: usize) -> Vec<String> {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let delivered = sink.delivered.lock().unwrap().clone();
+                if delivered.len() >= count {
+                    return delivered;
+                }
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("events were not delivered")
+    }
+
+    #[tokio::test]
+    async fn published_events_are_delivered() {
+        let sink = Arc::new(RecordingSink::default());
+        let queue = EventQueue::start(sink.clone(), 16, 4);
+
+        for id in ["a", "b", "c", "d", "e"] {
+            queue.publish(event(id));
+        }
+
+        let mut delivered = wait_for_deliveries(&sink, 5).await;
+        delivered.sort();
+        assert_eq!(delivered, vec!["a", "b", "c", "d", "e"]);
+        assert_eq!(queue.dropped_events(), 0);
+    }
+
+    #[tokio::test]
+    async fn full_queue_drops_events_instead_of_growing() {
+        let sink = Arc::new(RecordingSink {
+            gate: Some(Semaphore::new(0)),
+            ..Default::default()
+        });
+        let queue = EventQueue::start(sink.clone(), 2, 1);
+
+        // El único worker queda bloqueado entregando el primero
+        queue.publish(event("first"));
+        sink.started.notified().await;
+        for i in 0..10 {
+            queue.publish(event(&format!("burst-{}", i)));
+        }
+
+        assert_eq!(queue.queued_events(), 2);
+        assert_eq!(queue.dropped_events(), 8);
+
+        sink.gate.as_ref().unwrap().add_permits(3);
+        let delivered = wait_for_deliveries(&sink, 3).await;
+        assert_eq!(delivered, vec!["first", "burst-0", "burst-1"]);
+        assert_eq!(queue.queued_events(), 0);
+    }
+}
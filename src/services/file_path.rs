@@ -0,0 +1,101 @@
+use uuid::Uuid;
+
+const MAX_FILENAME_SUFFIX_LEN: usize = 64;
+
+/// Genera un identificador único de objeto para proveedores basados en rutas (S3 y compatibles)
+///
+/// El UUID garantiza unicidad incluso para subidas simultáneas; el nombre saneado
+/// se agrega como sufijo solo por legibilidad. Nunca contiene separadores de directorio.
+pub fn generate_file_path(filename: &str) -> String {
+    let unique = Uuid::new_v4().simple().to_string();
+    let suffix = sanitize_filename(filename);
+
+    if suffix.is_empty() {
+        unique
+    } else {
+        format!("{}-{}", unique, suffix)
+    }
+}
+
+fn sanitize_filename(filename: &str) -> String {
+    filename
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>()
+        .trim_matches(|c| c == '.' || c == '_')
+        .chars()
+        .take(MAX_FILENAME_SUFFIX_LEN)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sufijo que sigue al UUID, o None si el path es solo el UUID
+    fn suffix(path: &str) -> Option<&str> {
+        let (unique, rest) = path.split_at(32);
+        assert!(unique.chars().all(|c| c.is_ascii_hexdigit()), "{path}");
+        rest.strip_prefix('-').or_else(|| {
+            assert!(rest.is_empty(), "{path}");
+            None
+        })
+    }
+
+    #[test]
+    fn keeps_a_safe_filename_as_suffix() {
+        assert_eq!(
+            suffix(&generate_file_path("report-2025_v1.pdf")),
+            Some("report-2025_v1.pdf")
+        );
+    }
+
+    #[test]
+    fn paths_are_unique_for_the_same_name() {
+        assert_ne!(generate_file_path("a.txt"), generate_file_path("a.txt"));
+    }
+
+    #[test]
+    fn traversal_characters_never_reach_the_path() {
+        for filename in [
+            "../../etc/passwd",
+            "a/../b",
+            "..\\windows\\system32",
+            "/abs",
+        ] {
+            let path = generate_file_path(filename);
+            assert!(!path.contains('/') && !path.contains('\\'), "{path}");
+            assert!(!suffix(&path).unwrap().starts_with('.'), "{path}");
+        }
+        assert_eq!(
+            suffix(&generate_file_path("../../etc/passwd")),
+            Some("etc_passwd")
+        );
+    }
+
+    #[test]
+    fn replaces_unicode_and_truncates_long_names() {
+        assert_eq!(
+            suffix(&generate_file_path("año 2025.pdf")),
+            Some("a_o_2025.pdf")
+        );
+        let long = "x".repeat(200);
+        assert_eq!(
+            suffix(&generate_file_path(&long)).unwrap().len(),
+            MAX_FILENAME_SUFFIX_LEN
+        );
+    }
+
+    #[test]
+    fn empty_or_fully_stripped_names_leave_only_the_uuid() {
+        for filename in ["", "..", "/", "___"] {
+            assert_eq!(suffix(&generate_file_path(filename)), None, "{filename:?}");
+        }
+    }
+}
@@ -1,18 +1,37 @@
+use std::{
+    collections::BTreeMap,
+    num::NonZeroUsize,
+    time::{Duration, Instant},
+};
+
 use async_trait::async_trait;
+use futures_util::StreamExt;
+use lru::LruCache;
 use reqwest::{multipart, Client};
 use serde::Deserialize;
 
 use crate::{
-    application::{error::ApplicationError, services::StorageService},
+    application::{
+        error::ApplicationError,
+        services::{ObjectStream, StorageService, UploadStream},
+    },
     domain::{
         config::secrets::GDriveSecrets,
         models::file::{FileData, FileMetadata},
     },
-    services::error::StorageError,
+    services::{error::StorageError, tls::ProviderTlsConfig},
 };
 
 const GOOGLE_DRIVE_API_BASE: &str = "https://www.googleapis.com/drive/v3";
 const GOOGLE_UPLOAD_API_BASE: &str = "https://www.googleapis.com/upload/drive/v3";
+const DRIVE_FOLDER_MIME_TYPE: &str = "application/vnd.google-apps.folder";
+const FOLDER_CACHE_CAPACITY: usize = 128;
+/// Máximo que admite files.list por página
+const LIST_PAGE_SIZE: &str = "1000";
+/// Margen antes de la expiración en el que el token ya se renueva
+const TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+/// Bytes por PUT de una sesión resumable; Drive exige múltiplos de 256 KiB
+const RESUMABLE_CHUNK_SIZE: usize = 32 * 256 * 1024;
 
 #[derive(Debug, Deserialize)]
 struct ServiceAccountCredentials {
@@ -24,6 +43,13 @@ struct ServiceAccountCredentials {
 #[derive(Debug, Deserialize)]
 struct TokenResponse {
     access_token: String,
+    /// Segundos de validez; Google emite tokens de una hora
+    #[serde(default = "default_token_lifetime")]
+    expires_in: u64,
+}
+
+fn default_token_lifetime() -> u64 {
+    3600
 }
 
 #[derive(Debug, Deserialize)]
@@ -33,33 +59,280 @@ struct DriveFileMetadata {
     #[serde(rename = "mimeType")]
     mime_type: String,
     size: Option<String>,
+    #[serde(rename = "webViewLink")]
+    web_view_link: Option<String>,
+    #[serde(rename = "webContentLink")]
+    web_content_link: Option<String>,
+    #[serde(rename = "md5Checksum")]
+    md5_checksum: Option<String>,
+}
+
+impl DriveFileMetadata {
+    fn into_file_metadata(self) -> FileMetadata {
+        FileMetadata {
+            file_id: self.id,
+            size: self.size.and_then(|s| s.parse::<u64>().ok()).unwrap_or(0),
+            mime_type: self.mime_type,
+            filename: self.name,
+            provider: "gdrive".to_string(),
+            web_view_link: self.web_view_link,
+            web_content_link: self.web_content_link,
+            checksum: self.md5_checksum,
+        }
+    }
+}
+
+/// Respuesta a un PUT de sesión resumable
+enum ResumableProgress {
+    /// 308: Drive tiene persistidos estos bytes y espera el resto
+    Incomplete(u64),
+    Complete(DriveFileMetadata),
+}
+
+#[derive(Debug, Deserialize)]
+struct DriveFilePage {
+    #[serde(default)]
+    files: Vec<DriveFileMetadata>,
+    #[serde(rename = "nextPageToken")]
+    next_page_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DriveFileList {
+    files: Vec<DriveFileId>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DriveFileId {
+    id: String,
 }
 
 pub struct GDriveStorageService {
     client: Client,
     folder_id: String,
+    /// Carpetas adicionales por nombre, elegibles en la subida
+    folders: BTreeMap<String, String>,
+    date_subfolders: bool,
     credentials: ServiceAccountCredentials,
-    access_token: tokio::sync::Mutex<Option<String>>,
+    /// Token de acceso y el instante en que expira
+    access_token: tokio::sync::Mutex<Option<(String, Instant)>>,
+    /// (carpeta raíz, prefijo lógico) -> id de subcarpeta, para no resolverla en cada subida
+    folder_cache: std::sync::Mutex<LruCache<(String, String), String>>,
+    /// Raíces de la API de Drive y de subidas; los tests las apuntan a un servidor local
+    api_base: String,
+    upload_api_base: String,
 }
 
 impl GDriveStorageService {
-    pub fn new(secrets: GDriveSecrets) -> Result<Self, StorageError> {
+    pub fn new(secrets: GDriveSecrets, tls: &ProviderTlsConfig) -> Result<Self, StorageError> {
         let credentials: ServiceAccountCredentials =
             serde_json::from_str(&secrets.google_credentials)
                 .map_err(|e| StorageError::InvalidCredentials(e.to_string()))?;
 
         Ok(Self {
-            client: Client::new(),
+            client: tls.http_client()?,
             folder_id: secrets.folder_id,
+            folders: secrets.folders,
+            date_subfolders: secrets.date_subfolders,
             credentials,
             access_token: tokio::sync::Mutex::new(None),
+            folder_cache: std::sync::Mutex::new(LruCache::new(
+                NonZeroUsize::new(FOLDER_CACHE_CAPACITY).unwrap(),
+            )),
+            api_base: GOOGLE_DRIVE_API_BASE.to_string(),
+            upload_api_base: GOOGLE_UPLOAD_API_BASE.to_string(),
         })
     }
 
+    /// Carpeta raíz elegida en la subida, validada contra las configuradas
+    fn upload_root(&self, location: Option<&str>) -> Result<&str, ApplicationError> {
+        match location {
+            None => Ok(&self.folder_id),
+            Some(name) => {
+                self.folders.get(name).map(String::as_str).ok_or_else(|| {
+                    ApplicationError::BadRequest(format!("Unknown folder '{}'", name))
+                })
+            }
+        }
+    }
+
+    /// Carpeta destino de una subida: la raíz, o la subcarpeta del mes actual si está habilitado
+    async fn upload_parent(&self, root: &str, token: &str) -> Result<String, StorageError> {
+        if !self.date_subfolders {
+            return Ok(root.to_string());
+        }
+        let prefix = chrono::Utc::now().format("%Y-%m").to_string();
+        self.resolve_folder(root, &prefix, token).await
+    }
+
+    async fn resolve_folder(
+        &self,
+        root: &str,
+        prefix: &str,
+        token: &str,
+    ) -> Result<String, StorageError> {
+        let cache_key = (root.to_string(), prefix.to_string());
+        if let Some(folder_id) = self.folder_cache.lock().unwrap().get(&cache_key) {
+            return Ok(folder_id.clone());
+        }
+
+        let folder_id = match self.find_folder(root, prefix, token).await? {
+            Some(folder_id) => folder_id,
+            None => self.create_folder(root, prefix, token).await?,
+        };
+
+        self.folder_cache
+            .lock()
+            .unwrap()
+            .put(cache_key, folder_id.clone());
+        Ok(folder_id)
+    }
+
+    fn invalidate_folder(&self, folder_id: &str) {
+        let mut cache = self.folder_cache.lock().unwrap();
+        let stale: Vec<(String, String)> = cache
+            .iter()
+            .filter(|(_, id)| id.as_str() == folder_id)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in stale {
+            cache.pop(&key);
+        }
+    }
+
+    async fn find_folder(
+        &self,
+        parent_id: &str,
+        name: &str,
+        token: &str,
+    ) -> Result<Option<String>, StorageError> {
+        let query = format!(
+            "name = '{}' and mimeType = '{}' and '{}' in parents and trashed = false",
+            name, DRIVE_FOLDER_MIME_TYPE, parent_id
+        );
+
+        let response = self
+            .client
+            .get(format!("{}/files", self.api_base))
+            .query(&[("q", query.as_str()), ("fields", "files(id)")])
+            .bearer_auth(token)
+            .send()
+            .await?;
+
+        check_provider_status(&response)?;
+
+        if !response.status().is_success() {
+            return Err(StorageError::ProviderError(format!(
+                "Folder lookup failed with status: {}",
+                response.status()
+            )));
+        }
+
+        let list: DriveFileList = response
+            .json()
+            .await
+            .map_err(|e| StorageError::InternalError(e.to_string()))?;
+
+        Ok(list.files.into_iter().next().map(|f| f.id))
+    }
+
+    /// Hijos directos de una carpeta, recorriendo todas las páginas de files.list
+    async fn list_children(
+        &self,
+        parent_id: &str,
+        token: &str,
+    ) -> Result<Vec<DriveFileMetadata>, StorageError> {
+        let query = format!("'{}' in parents and trashed = false", parent_id);
+
+        let mut files = Vec::new();
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut request = self
+                .client
+                .get(format!("{}/files", self.api_base))
+                .query(&[
+                    ("q", query.as_str()),
+                    ("pageSize", LIST_PAGE_SIZE),
+                    (
+                        "fields",
+                        "nextPageToken,files(id,name,mimeType,size,md5Checksum)",
+                    ),
+                ])
+                .bearer_auth(token);
+            if let Some(page_token) = page_token.as_deref() {
+                request = request.query(&[("pageToken", page_token)]);
+            }
+
+            let response = request.send().await?;
+
+            check_provider_status(&response)?;
+
+            if !response.status().is_success() {
+                return Err(StorageError::ProviderError(format!(
+                    "List files failed with status: {}",
+                    response.status()
+                )));
+            }
+
+            let page: DriveFilePage = response
+                .json()
+                .await
+                .map_err(|e| StorageError::InternalError(e.to_string()))?;
+            files.extend(page.files);
+
+            match page.next_page_token {
+                Some(next) => page_token = Some(next),
+                None => break,
+            }
+        }
+
+        Ok(files)
+    }
+
+    async fn create_folder(
+        &self,
+        parent_id: &str,
+        name: &str,
+        token: &str,
+    ) -> Result<String, StorageError> {
+        let body = serde_json::json!({
+            "name": name,
+            "mimeType": DRIVE_FOLDER_MIME_TYPE,
+            "parents": [parent_id],
+        });
+
+        let response = self
+            .client
+            .post(format!("{}/files?fields=id", self.api_base))
+            .bearer_auth(token)
+            .json(&body)
+            .send()
+            .await?;
+
+        check_provider_status(&response)?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(StorageError::ProviderError(format!(
+                "Folder creation failed: {}",
+                error_text
+            )));
+        }
+
+        let folder: DriveFileId = response
+            .json()
+            .await
+            .map_err(|e| StorageError::InternalError(e.to_string()))?;
+
+        Ok(folder.id)
+    }
+
     async fn get_access_token(&self) -> Result<String, StorageError> {
         let token = self.access_token.lock().await;
-        if let Some(ref t) = *token {
-            return Ok(t.clone());
+        if let Some((ref t, expires_at)) = *token {
+            if token_is_fresh(expires_at, Instant::now()) {
+                return Ok(t.clone());
+            }
         }
         drop(token);
 
@@ -75,13 +348,16 @@ impl GDriveStorageService {
             .send()
             .await?;
 
+        check_provider_status(&response)?;
+
         let token_response: TokenResponse = response
             .json()
             .await
             .map_err(|e| StorageError::Unauthorized(e.to_string()))?;
 
+        let expires_at = Instant::now() + Duration::from_secs(token_response.expires_in);
         let mut token = self.access_token.lock().await;
-        *token = Some(token_response.access_token.clone());
+        *token = Some((token_response.access_token.clone(), expires_at));
 
         Ok(token_response.access_token)
     }
@@ -119,34 +395,256 @@ impl GDriveStorageService {
         encode(&Header::new(Algorithm::RS256), &claims, &key)
             .map_err(|e| StorageError::InternalError(e.to_string()))
     }
+
+    /// Abre una sesión de subida resumable y devuelve su URI (cabecera Location)
+    async fn start_resumable_session(
+        &self,
+        file_data: &FileData,
+        root: &str,
+        parent_id: &str,
+        token: &str,
+    ) -> Result<String, StorageError> {
+        let file_metadata = serde_json::json!({
+            "name": file_data.filename,
+            "mimeType": file_data.mime_type,
+            "parents": [parent_id],
+        });
+
+        let url = format!(
+            "{}/files?uploadType=resumable&fields=id,name,mimeType,size,md5Checksum",
+            self.upload_api_base
+        );
+
+        let response = self
+            .client
+            .post(&url)
+            .bearer_auth(token)
+            .header("X-Upload-Content-Type", &file_data.mime_type)
+            .json(&file_metadata)
+            .send()
+            .await
+            .map_err(StorageError::from)?;
+
+        check_provider_status(&response)?;
+
+        if response.status().as_u16() == 404 && parent_id != root {
+            self.invalidate_folder(parent_id);
+        }
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(StorageError::ProviderError(format!(
+                "Upload session failed: {}",
+                error_text
+            )));
+        }
+
+        response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from)
+            .ok_or_else(|| {
+                StorageError::ProviderError("Upload session has no Location header".to_string())
+            })
+    }
+
+    /// PUT de `chunk` a partir de `offset`; el total solo se conoce en el último bloque
+    ///
+    /// reqwest no sigue el 308 de Drive porque no trae cabecera Location.
+    async fn put_resumable_chunk(
+        &self,
+        session: &str,
+        chunk: &[u8],
+        offset: u64,
+        total: Option<u64>,
+    ) -> Result<ResumableProgress, StorageError> {
+        let total = total.map_or_else(|| "*".to_string(), |t| t.to_string());
+        let content_range = if chunk.is_empty() {
+            format!("bytes */{}", total)
+        } else {
+            format!(
+                "bytes {}-{}/{}",
+                offset,
+                offset + chunk.len() as u64 - 1,
+                total
+            )
+        };
+
+        // Se pide en cada bloque para renovarlo en subidas que duran más que el token
+        let token = self.get_access_token().await?;
+        let response = self
+            .client
+            .put(session)
+            .bearer_auth(token)
+            .header(reqwest::header::CONTENT_RANGE, content_range)
+            .body(chunk.to_vec())
+            .send()
+            .await
+            .map_err(StorageError::from)?;
+
+        check_provider_status(&response)?;
+
+        if response.status().as_u16() == 308 {
+            let range = response
+                .headers()
+                .get(reqwest::header::RANGE)
+                .and_then(|v| v.to_str().ok());
+            return Ok(ResumableProgress::Incomplete(persisted_bytes(range)));
+        }
+
+        if response.status().as_u16() == 404 {
+            return Err(StorageError::ProviderError(
+                "Upload session expired".to_string(),
+            ));
+        }
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(StorageError::ProviderError(format!(
+                "Upload chunk failed: {}",
+                error_text
+            )));
+        }
+
+        let drive_metadata = response
+            .json()
+            .await
+            .map_err(|e| StorageError::InternalError(e.to_string()))?;
+        Ok(ResumableProgress::Complete(drive_metadata))
+    }
+
+    /// GET con alt=media ya validado; el cuerpo queda sin leer
+    async fn download_response(&self, file_id: &str) -> Result<reqwest::Response, StorageError> {
+        let token = self.get_access_token().await?;
+
+        let url = format!("{}/files/{}?alt=media", self.api_base, file_id);
+
+        let response = self
+            .client
+            .get(&url)
+            .bearer_auth(token)
+            .send()
+            .await
+            .map_err(StorageError::from)?;
+
+        check_provider_status(&response)?;
+
+        if response.status().as_u16() == 404 {
+            return Err(StorageError::NotFound(file_id.to_string()));
+        }
+
+        if !response.status().is_success() {
+            return Err(StorageError::ProviderError(format!(
+                "Download failed with status: {}",
+                response.status()
+            )));
+        }
+
+        Ok(response)
+    }
+}
+
+/// El token sigue siendo utilizable si le queda más que el margen de renovación
+fn token_is_fresh(expires_at: Instant, now: Instant) -> bool {
+    expires_at.saturating_duration_since(now) > TOKEN_REFRESH_MARGIN
+}
+
+/// Bytes persistidos según la cabecera Range de un 308 (`bytes=0-N`); sin ella, ninguno
+fn persisted_bytes(range: Option<&str>) -> u64 {
+    range
+        .and_then(|r| r.trim().strip_prefix("bytes=0-"))
+        .and_then(|end| end.parse::<u64>().ok())
+        .map_or(0, |end| end + 1)
+}
+
+/// Descarta del búfer lo que Drive ya confirmó; `offset` pasa a ser lo persistido
+fn advance_resumable(
+    buffer: &mut Vec<u8>,
+    offset: &mut u64,
+    sent: usize,
+    persisted: u64,
+) -> Result<(), StorageError> {
+    if persisted <= *offset || persisted > *offset + sent as u64 {
+        return Err(StorageError::ProviderError(format!(
+            "Upload session stalled at byte {}",
+            persisted
+        )));
+    }
+    buffer.drain(..(persisted - *offset) as usize);
+    *offset = persisted;
+    Ok(())
+}
+
+/// Errores de estado comunes a todas las llamadas: rate limit y credenciales rechazadas
+fn check_provider_status(response: &reqwest::Response) -> Result<(), StorageError> {
+    let status = response.status().as_u16();
+    if status == 401 || status == 403 {
+        return Err(StorageError::Unauthorized(format!(
+            "Google Drive rejected credentials with status: {}",
+            status
+        )));
+    }
+
+    let retry_after = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok());
+    match StorageError::from_rate_limit(status, retry_after) {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
 }
 
 #[async_trait]
 impl StorageService for GDriveStorageService {
+    fn provider_name(&self) -> &'static str {
+        "gdrive"
+    }
+
+    fn locations(&self) -> Vec<String> {
+        self.folders.keys().cloned().collect()
+    }
+
+    /// Obtiene (o reutiliza) el token y consulta la carpeta raíz
+    async fn ping(&self) -> Result<(), ApplicationError> {
+        if !self.exists(&self.folder_id).await? {
+            return Err(StorageError::ProviderError(format!(
+                "Root folder {} not found",
+                self.folder_id
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
     async fn upload(&self, file_data: FileData) -> Result<FileMetadata, ApplicationError> {
+        let root = self.upload_root(file_data.location.as_deref())?;
         let token = self.get_access_token().await?;
+        let parent_id = self.upload_parent(root, &token).await?;
 
         let file_metadata = serde_json::json!({
             "name": file_data.filename,
             "mimeType": file_data.mime_type,
-            "parents": [self.folder_id],
+            "parents": [parent_id],
         });
 
         let metadata_part = multipart::Part::text(file_metadata.to_string())
             .mime_str("application/json")
             .map_err(|e| StorageError::InternalError(e.to_string()))?;
 
-        let file_part = multipart::Part::bytes(file_data.content.clone())
-            .mime_str(&file_data.mime_type)
-            .map_err(|e| StorageError::InternalError(e.to_string()))?;
+        let file_part =
+            multipart::Part::stream_with_length(file_data.content.clone(), file_data.size())
+                .mime_str(&file_data.mime_type)
+                .map_err(|e| StorageError::InternalError(e.to_string()))?;
 
         let form = multipart::Form::new()
             .part("metadata", metadata_part)
             .part("file", file_part);
 
         let url = format!(
-            "{}/files?uploadType=multipart&fields=id,name,mimeType,size",
-            GOOGLE_UPLOAD_API_BASE
+            "{}/files?uploadType=multipart&fields=id,name,mimeType,size,md5Checksum",
+            self.upload_api_base
         );
 
         let response = self
@@ -158,6 +656,13 @@ impl StorageService for GDriveStorageService {
             .await
             .map_err(StorageError::from)?;
 
+        check_provider_status(&response)?;
+
+        if response.status().as_u16() == 404 && parent_id != root {
+            // La subcarpeta cacheada ya no existe; se resolverá de nuevo en la próxima subida
+            self.invalidate_folder(&parent_id);
+        }
+
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
             return Err(
@@ -176,35 +681,89 @@ impl StorageService for GDriveStorageService {
             mime_type: drive_metadata.mime_type,
             filename: drive_metadata.name,
             provider: "gdrive".to_string(),
+            web_view_link: drive_metadata.web_view_link,
+            web_content_link: drive_metadata.web_content_link,
+            checksum: drive_metadata.md5_checksum,
         })
     }
 
-    async fn download(&self, file_id: &str) -> Result<Vec<u8>, ApplicationError> {
+    fn streams_uploads(&self) -> bool {
+        true
+    }
+
+    /// Subida resumable: envía bloques de `RESUMABLE_CHUNK_SIZE` según llegan
+    ///
+    /// Nunca retiene más de un bloque más lo recibido del cliente. Si la subida se corta,
+    /// Drive descarta la sesión sin crear el archivo.
+    async fn upload_stream(
+        &self,
+        file_data: FileData,
+        mut content: UploadStream<'_>,
+        max_size: u64,
+    ) -> Result<FileMetadata, ApplicationError> {
+        let root = self.upload_root(file_data.location.as_deref())?;
         let token = self.get_access_token().await?;
+        let parent_id = self.upload_parent(root, &token).await?;
+        let session = self
+            .start_resumable_session(&file_data, root, &parent_id, &token)
+            .await?;
 
-        let url = format!("{}/files/{}?alt=media", GOOGLE_DRIVE_API_BASE, file_id);
+        let mut buffer: Vec<u8> = Vec::with_capacity(RESUMABLE_CHUNK_SIZE);
+        // Bytes ya confirmados por Drive; el búfer empieza en esa posición
+        let mut offset: u64 = 0;
+        let mut received: u64 = 0;
 
-        let response = self
-            .client
-            .get(&url)
-            .bearer_auth(token)
-            .send()
-            .await
-            .map_err(StorageError::from)?;
+        while let Some(chunk) = content.next().await {
+            let chunk = chunk?;
+            received += chunk.len() as u64;
+            if received > max_size {
+                return Err(ApplicationError::PayloadTooLarge);
+            }
+            buffer.extend_from_slice(&chunk);
 
-        if response.status().as_u16() == 404 {
-            return Err(StorageError::NotFound(file_id.to_string()).into());
+            while buffer.len() >= RESUMABLE_CHUNK_SIZE {
+                let sent = RESUMABLE_CHUNK_SIZE;
+                match self
+                    .put_resumable_chunk(&session, &buffer[..sent], offset, None)
+                    .await?
+                {
+                    ResumableProgress::Incomplete(persisted) => {
+                        advance_resumable(&mut buffer, &mut offset, sent, persisted)?
+                    }
+                    ResumableProgress::Complete(_) => {
+                        return Err(StorageError::ProviderError(
+                            "Upload session closed before the last chunk".to_string(),
+                        )
+                        .into())
+                    }
+                }
+            }
         }
 
-        if !response.status().is_success() {
-            return Err(StorageError::ProviderError(format!(
-                "Download failed with status: {}",
-                response.status()
-            ))
-            .into());
-        }
+        // Último bloque con el total; se repite con lo que Drive no llegó a persistir
+        let drive_metadata = loop {
+            let sent = buffer.len();
+            match self
+                .put_resumable_chunk(&session, &buffer, offset, Some(received))
+                .await?
+            {
+                ResumableProgress::Complete(metadata) => break metadata,
+                ResumableProgress::Incomplete(persisted) => {
+                    advance_resumable(&mut buffer, &mut offset, sent, persisted)?
+                }
+            }
+        };
+
+        Ok(FileMetadata {
+            size: received,
+            ..drive_metadata.into_file_metadata()
+        })
+    }
 
-        let bytes = response
+    async fn download(&self, file_id: &str) -> Result<Vec<u8>, ApplicationError> {
+        let bytes = self
+            .download_response(file_id)
+            .await?
             .bytes()
             .await
             .map_err(|e| StorageError::NetworkError(e.to_string()))?;
@@ -212,10 +771,27 @@ impl StorageService for GDriveStorageService {
         Ok(bytes.to_vec())
     }
 
+    async fn download_stream(&self, file_id: &str) -> Result<ObjectStream, ApplicationError> {
+        let response = self.download_response(file_id).await?;
+        let length = response.content_length();
+
+        Ok(ObjectStream {
+            content: response
+                .bytes_stream()
+                .map(|chunk| {
+                    chunk.map_err(|e| {
+                        ApplicationError::from(StorageError::NetworkError(e.to_string()))
+                    })
+                })
+                .boxed(),
+            length,
+        })
+    }
+
     async fn delete(&self, file_id: &str) -> Result<(), ApplicationError> {
         let token = self.get_access_token().await?;
 
-        let url = format!("{}/files/{}", GOOGLE_DRIVE_API_BASE, file_id);
+        let url = format!("{}/files/{}", self.api_base, file_id);
 
         let response = self
             .client
@@ -225,6 +801,8 @@ impl StorageService for GDriveStorageService {
             .await
             .map_err(StorageError::from)?;
 
+        check_provider_status(&response)?;
+
         if response.status().as_u16() == 404 {
             return Err(StorageError::NotFound(file_id.to_string()).into());
         }
@@ -244,8 +822,8 @@ impl StorageService for GDriveStorageService {
         let token = self.get_access_token().await?;
 
         let url = format!(
-            "{}/files/{}?fields=id,name,mimeType,size",
-            GOOGLE_DRIVE_API_BASE, file_id
+            "{}/files/{}?fields=id,name,mimeType,size,webViewLink,webContentLink,md5Checksum",
+            self.api_base, file_id
         );
 
         let response = self
@@ -256,6 +834,8 @@ impl StorageService for GDriveStorageService {
             .await
             .map_err(StorageError::from)?;
 
+        check_provider_status(&response)?;
+
         if response.status().as_u16() == 404 {
             return Err(StorageError::NotFound(file_id.to_string()).into());
         }
@@ -273,17 +853,384 @@ impl StorageService for GDriveStorageService {
             .await
             .map_err(|e| StorageError::InternalError(e.to_string()))?;
 
-        let size = drive_metadata
-            .size
-            .and_then(|s| s.parse::<u64>().ok())
-            .unwrap_or(0);
+        Ok(drive_metadata.into_file_metadata())
+    }
 
-        Ok(FileMetadata {
-            file_id: drive_metadata.id,
-            size,
-            mime_type: drive_metadata.mime_type,
-            filename: drive_metadata.name,
-            provider: "gdrive".to_string(),
-        })
+    async fn list_files(
+        &self,
+        prefix: Option<&str>,
+    ) -> Result<Vec<FileMetadata>, ApplicationError> {
+        let token = self.get_access_token().await?;
+
+        // Con date_subfolders los archivos viven en subcarpetas AAAA-MM bajo cada raíz
+        let roots = std::iter::once(&self.folder_id).chain(self.folders.values());
+        let mut files = Vec::new();
+        for root in roots {
+            for item in self.list_children(root, &token).await? {
+                if item.mime_type == DRIVE_FOLDER_MIME_TYPE {
+                    let children = self.list_children(&item.id, &token).await?;
+                    files.extend(
+                        children
+                            .into_iter()
+                            .filter(|child| child.mime_type != DRIVE_FOLDER_MIME_TYPE),
+                    );
+                } else {
+                    files.push(item);
+                }
+            }
+        }
+
+        Ok(files
+            .into_iter()
+            .filter(|file| match prefix {
+                Some(prefix) => file.id.starts_with(prefix),
+                None => true,
+            })
+            .map(DriveFileMetadata::into_file_metadata)
+            .collect())
+    }
+
+    async fn exists(&self, file_id: &str) -> Result<bool, ApplicationError> {
+        let token = self.get_access_token().await?;
+
+        let url = format!("{}/files/{}?fields=id", self.api_base, file_id);
+
+        let response = self
+            .client
+            .get(&url)
+            .bearer_auth(token)
+            .send()
+            .await
+            .map_err(StorageError::from)?;
+
+        check_provider_status(&response)?;
+
+        if response.status().as_u16() == 404 {
+            return Ok(false);
+        }
+
+        if !response.status().is_success() {
+            return Err(StorageError::ProviderError(format!(
+                "Existence check failed with status: {}",
+                response.status()
+            ))
+            .into());
+        }
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use axum::{
+        body::Bytes,
+        extract::{DefaultBodyLimit, Path, Query, State},
+        http::{header, HeaderMap, StatusCode},
+        response::{IntoResponse, Response},
+        routing::{get, post, put},
+        Json, Router,
+    };
+    use futures_util::stream;
+
+    use super::*;
+
+    /// Llamadas recibidas, en orden: "get_file <fields>", "find_folder", "create_folder" o "upload"
+    type Requests = Arc<Mutex<Vec<String>>>;
+
+    async fn get_file(
+        State(requests): State<Requests>,
+        Path(file_id): Path<String>,
+        Query(query): Query<std::collections::HashMap<String, String>>,
+    ) -> Result<Json<serde_json::Value>, StatusCode> {
+        let fields = query.get("fields").cloned().unwrap_or_default();
+        requests
+            .lock()
+            .unwrap()
+            .push(format!("get_file {}", fields));
+        if file_id == "missing" {
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Ok(Json(serde_json::json!({
+            "id": file_id,
+            "name": "report.pdf",
+            "mimeType": "application/pdf",
+            "size": "2048",
+            "webViewLink": format!("https://drive.google.com/file/d/{}/view", file_id),
+            "webContentLink": format!("https://drive.google.com/uc?id={}&export=download", file_id),
+            "md5Checksum": "9e107d9d372bb6826bd81d3542a419d6",
+        })))
+    }
+
+    /// Ninguna carpeta existe todavía
+    async fn find_folder(State(requests): State<Requests>) -> Json<serde_json::Value> {
+        requests.lock().unwrap().push("find_folder".to_string());
+        Json(serde_json::json!({ "files": [] }))
+    }
+
+    async fn create_folder(State(requests): State<Requests>) -> Json<serde_json::Value> {
+        requests.lock().unwrap().push("create_folder".to_string());
+        Json(serde_json::json!({ "id": "month-folder" }))
+    }
+
+    async fn upload(State(requests): State<Requests>) -> Json<serde_json::Value> {
+        let mut requests = requests.lock().unwrap();
+        requests.push("upload".to_string());
+        Json(serde_json::json!({
+            "id": format!("file-{}", requests.len()),
+            "name": "notes.txt",
+            "mimeType": "text/plain",
+        }))
+    }
+
+    /// API de Drive local: metadata, búsqueda y creación de carpetas y subida multipart
+    async fn fake_drive() -> (String, Requests) {
+        let requests = Requests::default();
+        let app = Router::new()
+            .route("/drive/v3/files", get(find_folder).post(create_folder))
+            .route("/drive/v3/files/{file_id}", get(get_file))
+            .route("/upload/drive/v3/files", post(upload))
+            .with_state(requests.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (endpoint, requests)
+    }
+
+    fn count(requests: &Requests, call: &str) -> usize {
+        requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|request| request.as_str() == call)
+            .count()
+    }
+
+    /// Servicio contra `endpoint` con un token ya emitido, sin pasar por el JWT
+    fn service(endpoint: &str) -> GDriveStorageService {
+        let secrets = GDriveSecrets {
+            folder_id: "root".to_string(),
+            google_credentials: serde_json::json!({
+                "client_email": "svc@test.iam.gserviceaccount.com",
+                "private_key": "unused",
+                "token_uri": format!("{}/token", endpoint),
+            })
+            .to_string(),
+            date_subfolders: false,
+            folders: BTreeMap::new(),
+        };
+        let mut service =
+            GDriveStorageService::new(secrets, &ProviderTlsConfig::default()).unwrap();
+        service.api_base = format!("{}/drive/v3", endpoint);
+        service.upload_api_base = format!("{}/upload/drive/v3", endpoint);
+        *service.access_token.get_mut() = Some((
+            "token".to_string(),
+            Instant::now() + Duration::from_secs(3600),
+        ));
+        service
+    }
+
+    #[tokio::test]
+    async fn metadata_includes_native_drive_links() {
+        let (endpoint, requests) = fake_drive().await;
+        let service = service(&endpoint);
+
+        let metadata = service.get_metadata("drive-file").await.unwrap();
+
+        assert_eq!(metadata.provider, "gdrive");
+        assert_eq!(
+            metadata.web_view_link.as_deref(),
+            Some("https://drive.google.com/file/d/drive-file/view")
+        );
+        assert_eq!(
+            metadata.web_content_link.as_deref(),
+            Some("https://drive.google.com/uc?id=drive-file&export=download")
+        );
+        assert_eq!(metadata.size, 2048);
+        let fields = requests.lock().unwrap()[0].clone();
+        assert!(fields.starts_with("get_file "));
+        assert!(fields.contains("webViewLink") && fields.contains("webContentLink"));
+    }
+
+    #[tokio::test]
+    async fn exists_only_asks_for_the_id() {
+        let (endpoint, requests) = fake_drive().await;
+        let service = service(&endpoint);
+
+        assert!(service.exists("drive-file").await.unwrap());
+        assert!(!service.exists("missing").await.unwrap());
+        assert_eq!(
+            requests.lock().unwrap().as_slice(),
+            &["get_file id".to_string(), "get_file id".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn second_upload_to_the_same_month_reuses_the_cached_folder() {
+        let (endpoint, requests) = fake_drive().await;
+        let mut service = service(&endpoint);
+        service.date_subfolders = true;
+        let file_data = || {
+            FileData::new(
+                b"hello".to_vec(),
+                "notes.txt".to_string(),
+                "text/plain".to_string(),
+            )
+        };
+
+        service.upload(file_data()).await.unwrap();
+        service.upload(file_data()).await.unwrap();
+
+        assert_eq!(count(&requests, "find_folder"), 1);
+        assert_eq!(count(&requests, "create_folder"), 1);
+        assert_eq!(count(&requests, "upload"), 2);
+    }
+
+    /// Sesión resumable del Drive falso: persiste como mucho `PERSISTED_PER_PUT` bytes por
+    /// PUT, como Drive cuando responde 308 sin haber guardado el bloque entero
+    #[derive(Default)]
+    struct ResumableSession {
+        content: Vec<u8>,
+        largest_put: usize,
+    }
+
+    const PERSISTED_PER_PUT: usize = 5 * 1024 * 1024;
+
+    type Session = Arc<Mutex<ResumableSession>>;
+
+    async fn start_session(headers: HeaderMap) -> impl IntoResponse {
+        let host = headers[header::HOST].to_str().unwrap();
+        [(header::LOCATION, format!("http://{}/upload/session", host))]
+    }
+
+    async fn put_chunk(
+        State(session): State<Session>,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> Response {
+        let content_range = headers[header::CONTENT_RANGE].to_str().unwrap();
+        let (range, total) = content_range
+            .strip_prefix("bytes ")
+            .and_then(|r| r.split_once('/'))
+            .unwrap();
+        let mut session = session.lock().unwrap();
+        session.largest_put = session.largest_put.max(body.len());
+        if range != "*" {
+            let start: usize = range.split_once('-').unwrap().0.parse().unwrap();
+            if start != session.content.len() {
+                return StatusCode::BAD_REQUEST.into_response();
+            }
+            let persisted = body.len().min(PERSISTED_PER_PUT);
+            session.content.extend_from_slice(&body[..persisted]);
+        }
+
+        if total.parse() == Ok(session.content.len()) {
+            return Json(serde_json::json!({
+                "id": "streamed",
+                "name": "large.bin",
+                "mimeType": "application/octet-stream",
+                "size": total,
+            }))
+            .into_response();
+        }
+        let range = format!("bytes=0-{}", session.content.len() - 1);
+        (StatusCode::PERMANENT_REDIRECT, [(header::RANGE, range)]).into_response()
+    }
+
+    async fn fake_resumable_drive() -> (String, Session) {
+        let session = Session::default();
+        let app = Router::new()
+            .route("/upload/drive/v3/files", post(start_session))
+            .route("/upload/session", put(put_chunk))
+            .layer(DefaultBodyLimit::disable())
+            .with_state(session.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (endpoint, session)
+    }
+
+    /// `pieces` trozos de 64 KiB generados según se piden, con un patrón que delata desórdenes
+    fn synthetic_stream(pieces: usize) -> UploadStream<'static> {
+        stream::iter(0..pieces)
+            .map(|piece| {
+                let bytes: Vec<u8> = (0..64 * 1024)
+                    .map(|i| ((piece * 31 + i) % 251) as u8)
+                    .collect();
+                Ok(bytes.into())
+            })
+            .boxed()
+    }
+
+    fn large_file() -> FileData {
+        FileData::new(
+            Vec::new(),
+            "large.bin".to_string(),
+            "application/octet-stream".to_string(),
+        )
+    }
+
+    #[tokio::test]
+    async fn streamed_upload_is_sent_in_bounded_chunks_and_reassembled() {
+        let (endpoint, session) = fake_resumable_drive().await;
+        let service = service(&endpoint);
+        // 20 MiB + 64 KiB: dos bloques completos, uno parcial y los reenvíos de cada 308
+        let pieces = 321;
+
+        let metadata = service
+            .upload_stream(large_file(), synthetic_stream(pieces), u64::MAX)
+            .await
+            .unwrap();
+
+        let expected: Vec<u8> = synthetic_stream(pieces)
+            .map(|piece| piece.unwrap().to_vec())
+            .concat()
+            .await;
+        assert_eq!(metadata.size, expected.len() as u64);
+        let session = session.lock().unwrap();
+        assert!(session.content == expected, "reassembled content differs");
+        assert!(session.largest_put <= RESUMABLE_CHUNK_SIZE);
+    }
+
+    #[tokio::test]
+    async fn streamed_upload_over_max_size_is_rejected() {
+        let (endpoint, session) = fake_resumable_drive().await;
+        let service = service(&endpoint);
+
+        let result = service
+            .upload_stream(large_file(), synthetic_stream(200), 10 * 1024 * 1024)
+            .await;
+
+        assert!(matches!(result, Err(ApplicationError::PayloadTooLarge)));
+        assert!(session.lock().unwrap().content.len() <= RESUMABLE_CHUNK_SIZE);
+    }
+
+    #[test]
+    fn token_is_renewed_within_the_refresh_margin() {
+        let now = Instant::now();
+        assert!(token_is_fresh(now + Duration::from_secs(3600), now));
+        assert!(token_is_fresh(
+            now + TOKEN_REFRESH_MARGIN + Duration::from_secs(1),
+            now
+        ));
+        assert!(!token_is_fresh(now + TOKEN_REFRESH_MARGIN, now));
+        assert!(!token_is_fresh(now + Duration::from_secs(10), now));
+    }
+
+    #[test]
+    fn expired_tokens_are_not_fresh() {
+        let now = Instant::now();
+        assert!(!token_is_fresh(now, now + Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn token_lifetime_defaults_to_one_hour() {
+        let token: TokenResponse =
+            serde_json::from_str(r#"{"access_token": "t", "expires_in": 120}"#).unwrap();
+        assert_eq!(token.expires_in, 120);
+        let token: TokenResponse = serde_json::from_str(r#"{"access_token": "t"}"#).unwrap();
+        assert_eq!(token.expires_in, 3600);
     }
 }
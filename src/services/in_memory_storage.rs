@@ -0,0 +1,199 @@
+//! Proveedor en memoria para los tests de los controladores; cuenta las llamadas que hacen
+//! falta para comprobar reintentos, deduplicación y borrados.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{
+    application::{error::ApplicationError, services::StorageService},
+    domain::models::file::{FileData, FileMetadata},
+};
+
+pub struct InMemoryStorageService {
+    provider: &'static str,
+    /// Emite URLs firmadas como S3; si no, como Google Drive
+    signs_urls: bool,
+    /// Tipo que informa tras subir, como Drive cuando no reconoce el contenido
+    reported_mime_type: Option<&'static str>,
+    objects: Mutex<HashMap<String, FileData>>,
+    uploads: AtomicUsize,
+    deletes: AtomicUsize,
+    /// Espera de cada URL firmada, para que las llamadas de un lote se solapen
+    latency: Duration,
+    in_flight: AtomicUsize,
+    peak_in_flight: AtomicUsize,
+    /// Las subidas fallan como un proveedor caído
+    unavailable: AtomicBool,
+}
+
+impl InMemoryStorageService {
+    pub fn new(provider: &'static str, signs_urls: bool) -> Self {
+        Self {
+            provider,
+            signs_urls,
+            reported_mime_type: None,
+            objects: Mutex::new(HashMap::new()),
+            uploads: AtomicUsize::new(0),
+            deletes: AtomicUsize::new(0),
+            latency: Duration::ZERO,
+            in_flight: AtomicUsize::new(0),
+            peak_in_flight: AtomicUsize::new(0),
+            unavailable: AtomicBool::new(false),
+        }
+    }
+
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Máximo de URLs firmadas que llegaron a pedirse a la vez
+    pub fn peak_in_flight(&self) -> usize {
+        self.peak_in_flight.load(Ordering::SeqCst)
+    }
+
+    pub fn reporting_mime_type(mut self, mime_type: &'static str) -> Self {
+        self.reported_mime_type = Some(mime_type);
+        self
+    }
+
+    pub fn set_unavailable(&self, unavailable: bool) {
+        self.unavailable.store(unavailable, Ordering::SeqCst);
+    }
+
+    /// Guarda un objeto con un id elegido por el test
+    pub fn insert(&self, file_id: &str, content: &[u8]) {
+        self.objects.lock().unwrap().insert(
+            file_id.to_string(),
+            FileData::new(
+                content.to_vec(),
+                file_id.to_string(),
+                "text/plain".to_string(),
+            ),
+        );
+    }
+
+    pub fn contains(&self, file_id: &str) -> bool {
+        self.objects.lock().unwrap().contains_key(file_id)
+    }
+
+    pub fn object_count(&self) -> usize {
+        self.objects.lock().unwrap().len()
+    }
+
+    /// Como Google Drive, el proveedor "gdrive" devuelve enlaces nativos; el checksum es el
+    /// SHA-256 del contenido
+    fn metadata(&self, file_id: &str, file_data: &FileData) -> FileMetadata {
+        let drive_link = |kind: &str| {
+            (self.provider == "gdrive")
+                .then(|| format!("https://drive.example/{}/{}", file_id, kind))
+        };
+        FileMetadata {
+            file_id: file_id.to_string(),
+            size: file_data.size(),
+            mime_type: self
+                .reported_mime_type
+                .map_or_else(|| file_data.mime_type.clone(), str::to_string),
+            filename: Some(file_data.filename.clone()),
+            provider: self.provider.to_string(),
+            web_view_link: drive_link("view"),
+            web_content_link: drive_link("download"),
+            checksum: Some(format!("{:x}", Sha256::digest(&file_data.content))),
+        }
+    }
+}
+
+#[async_trait]
+impl StorageService for InMemoryStorageService {
+    fn provider_name(&self) -> &'static str {
+        self.provider
+    }
+
+    async fn ping(&self) -> Result<(), ApplicationError> {
+        Ok(())
+    }
+
+    async fn upload(&self, file_data: FileData) -> Result<FileMetadata, ApplicationError> {
+        self.uploads.fetch_add(1, Ordering::SeqCst);
+        if self.unavailable.load(Ordering::SeqCst) {
+            return Err(ApplicationError::StorageUnavailable(
+                "connection refused".to_string(),
+            ));
+        }
+        let file_id = Uuid::new_v4().to_string();
+        let metadata = self.metadata(&file_id, &file_data);
+        self.objects.lock().unwrap().insert(file_id, file_data);
+        Ok(metadata)
+    }
+
+    async fn download(&self, file_id: &str) -> Result<Vec<u8>, ApplicationError> {
+        self.objects
+            .lock()
+            .unwrap()
+            .get(file_id)
+            .map(|file_data| file_data.content.to_vec())
+            .ok_or(ApplicationError::NotFound)
+    }
+
+    async fn generate_presigned_url(
+        &self,
+        file_id: &str,
+        expires_in: Duration,
+    ) -> Result<Option<String>, ApplicationError> {
+        if !self.signs_urls {
+            return Ok(None);
+        }
+        let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+        tokio::time::sleep(self.latency).await;
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        Ok(Some(format!(
+            "https://signed.example/{}?expires={}",
+            file_id,
+            expires_in.as_secs()
+        )))
+    }
+
+    async fn delete(&self, file_id: &str) -> Result<(), ApplicationError> {
+        self.deletes.fetch_add(1, Ordering::SeqCst);
+        self.objects
+            .lock()
+            .unwrap()
+            .remove(file_id)
+            .map(|_| ())
+            .ok_or(ApplicationError::NotFound)
+    }
+
+    async fn get_metadata(&self, file_id: &str) -> Result<FileMetadata, ApplicationError> {
+        self.objects
+            .lock()
+            .unwrap()
+            .get(file_id)
+            .map(|file_data| self.metadata(file_id, file_data))
+            .ok_or(ApplicationError::NotFound)
+    }
+
+    async fn list_files(
+        &self,
+        prefix: Option<&str>,
+    ) -> Result<Vec<FileMetadata>, ApplicationError> {
+        Ok(self
+            .objects
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(file_id, _)| prefix.is_none_or(|prefix| file_id.starts_with(prefix)))
+            .map(|(file_id, file_data)| self.metadata(file_id, file_data))
+            .collect())
+    }
+}
@@ -0,0 +1,271 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::{
+    application::{
+        error::ApplicationError,
+        services::{ObjectStream, StorageService, UploadStream},
+    },
+    domain::models::file::{FileData, FileMetadata},
+};
+
+/// Muestras conservadas por (proveedor, operación) para calcular percentiles
+const MAX_SAMPLES: usize = 1024;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProviderLatencySummary {
+    pub provider: String,
+    pub operation: String,
+    pub count: u64,
+    #[serde(rename = "p50Ms")]
+    pub p50_ms: u64,
+    #[serde(rename = "p95Ms")]
+    pub p95_ms: u64,
+}
+
+#[derive(Default)]
+struct LatencySeries {
+    count: u64,
+    samples: VecDeque<u64>,
+}
+
+/// Duración de las llamadas a los proveedores, aislada de la latencia del endpoint
+#[derive(Default)]
+pub struct ProviderLatency {
+    series: Mutex<HashMap<(String, &'static str), LatencySeries>>,
+}
+
+impl ProviderLatency {
+    pub fn record(&self, provider: &str, operation: &'static str, elapsed: Duration) {
+        let mut series = self.series.lock().unwrap();
+        let entry = series.entry((provider.to_string(), operation)).or_default();
+        entry.count += 1;
+        if entry.samples.len() == MAX_SAMPLES {
+            entry.samples.pop_front();
+        }
+        entry.samples.push_back(elapsed.as_millis() as u64);
+    }
+
+    pub fn summary(&self) -> Vec<ProviderLatencySummary> {
+        let series = self.series.lock().unwrap();
+        let mut summary: Vec<ProviderLatencySummary> = series
+            .iter()
+            .map(|((provider, operation), entry)| {
+                let mut sorted: Vec<u64> = entry.samples.iter().copied().collect();
+                sorted.sort_unstable();
+                ProviderLatencySummary {
+                    provider: provider.clone(),
+                    operation: operation.to_string(),
+                    count: entry.count,
+                    p50_ms: percentile(&sorted, 50),
+                    p95_ms: percentile(&sorted, 95),
+                }
+            })
+            .collect();
+        summary.sort_by(|a, b| (&a.provider, &a.operation).cmp(&(&b.provider, &b.operation)));
+        summary
+    }
+}
+
+fn percentile(sorted: &[u64], pct: usize) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let index = (sorted.len() * pct).div_ceil(100).saturating_sub(1);
+    sorted[index.min(sorted.len() - 1)]
+}
+
+/// Decorador que mide cada operación del StorageService envuelto
+pub struct InstrumentedStorageService {
+    inner: Arc<dyn StorageService>,
+    latency: Arc<ProviderLatency>,
+}
+
+impl InstrumentedStorageService {
+    pub fn new(inner: Arc<dyn StorageService>, latency: Arc<ProviderLatency>) -> Self {
+        Self { inner, latency }
+    }
+
+    fn record(&self, operation: &'static str, started: Instant) {
+        self.latency
+            .record(self.inner.provider_name(), operation, started.elapsed());
+    }
+}
+
+#[async_trait]
+impl StorageService for InstrumentedStorageService {
+    fn provider_name(&self) -> &'static str {
+        self.inner.provider_name()
+    }
+
+    fn locations(&self) -> Vec<String> {
+        self.inner.locations()
+    }
+
+    async fn ping(&self) -> Result<(), ApplicationError> {
+        let started = Instant::now();
+        let result = self.inner.ping().await;
+        self.record("ping", started);
+        result
+    }
+
+    async fn upload(&self, file_data: FileData) -> Result<FileMetadata, ApplicationError> {
+        let started = Instant::now();
+        let result = self.inner.upload(file_data).await;
+        self.record("upload", started);
+        result
+    }
+
+    fn streams_uploads(&self) -> bool {
+        self.inner.streams_uploads()
+    }
+
+    async fn upload_stream(
+        &self,
+        file_data: FileData,
+        content: UploadStream<'_>,
+        max_size: u64,
+    ) -> Result<FileMetadata, ApplicationError> {
+        let started = Instant::now();
+        let result = self.inner.upload_stream(file_data, content, max_size).await;
+        self.record("upload_stream", started);
+        result
+    }
+
+    async fn download(&self, file_id: &str) -> Result<Vec<u8>, ApplicationError> {
+        let started = Instant::now();
+        let result = self.inner.download(file_id).await;
+        self.record("download", started);
+        result
+    }
+
+    /// Mide hasta que el proveedor empieza a responder, no la transferencia completa
+    async fn download_stream(&self, file_id: &str) -> Result<ObjectStream, ApplicationError> {
+        let started = Instant::now();
+        let result = self.inner.download_stream(file_id).await;
+        self.record("download_stream", started);
+        result
+    }
+
+    async fn generate_presigned_url(
+        &self,
+        file_id: &str,
+        expires_in: Duration,
+    ) -> Result<Option<String>, ApplicationError> {
+        let started = Instant::now();
+        let result = self.inner.generate_presigned_url(file_id, expires_in).await;
+        self.record("generate_presigned_url", started);
+        result
+    }
+
+    async fn delete(&self, file_id: &str) -> Result<(), ApplicationError> {
+        let started = Instant::now();
+        let result = self.inner.delete(file_id).await;
+        self.record("delete", started);
+        result
+    }
+
+    async fn get_metadata(&self, file_id: &str) -> Result<FileMetadata, ApplicationError> {
+        let started = Instant::now();
+        let result = self.inner.get_metadata(file_id).await;
+        self.record("get_metadata", started);
+        result
+    }
+
+    async fn list_files(
+        &self,
+        prefix: Option<&str>,
+    ) -> Result<Vec<FileMetadata>, ApplicationError> {
+        let started = Instant::now();
+        let result = self.inner.list_files(prefix).await;
+        self.record("list_files", started);
+        result
+    }
+
+    async fn exists(&self, file_id: &str) -> Result<bool, ApplicationError> {
+        let started = Instant::now();
+        let result = self.inner.exists(file_id).await;
+        self.record("exists", started);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::InMemoryStorageService;
+
+    fn counts(latency: &ProviderLatency) -> Vec<(String, String, u64)> {
+        latency
+            .summary()
+            .into_iter()
+            .map(|entry| (entry.provider, entry.operation, entry.count))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn every_call_records_a_timing_even_when_it_fails() {
+        let latency = Arc::new(ProviderLatency::default());
+        let service = InstrumentedStorageService::new(
+            Arc::new(InMemoryStorageService::new("s3", false)),
+            latency.clone(),
+        );
+
+        let uploaded = service
+            .upload(FileData::new(
+                b"hello".to_vec(),
+                "notes.txt".to_string(),
+                "text/plain".to_string(),
+            ))
+            .await
+            .unwrap();
+        service.download(&uploaded.file_id).await.unwrap();
+        service.get_metadata(&uploaded.file_id).await.unwrap();
+        service.delete(&uploaded.file_id).await.unwrap();
+        assert!(service.download(&uploaded.file_id).await.is_err());
+
+        assert_eq!(
+            counts(&latency),
+            vec![
+                ("s3".to_string(), "delete".to_string(), 1),
+                ("s3".to_string(), "download".to_string(), 2),
+                ("s3".to_string(), "get_metadata".to_string(), 1),
+                ("s3".to_string(), "upload".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn summary_reports_p50_and_p95() {
+        let latency = ProviderLatency::default();
+        for ms in 1..=100 {
+            latency.record("gdrive", "upload", Duration::from_millis(ms));
+        }
+
+        let [upload] = latency.summary().try_into().unwrap();
+        assert_eq!(upload.count, 100);
+        assert_eq!((upload.p50_ms, upload.p95_ms), (50, 95));
+    }
+
+    #[test]
+    fn only_the_latest_samples_are_kept() {
+        let latency = ProviderLatency::default();
+        for _ in 0..MAX_SAMPLES {
+            latency.record("s3", "download", Duration::from_millis(900));
+        }
+        for _ in 0..MAX_SAMPLES {
+            latency.record("s3", "download", Duration::from_millis(10));
+        }
+
+        let [download] = latency.summary().try_into().unwrap();
+        assert_eq!(download.count, 2 * MAX_SAMPLES as u64);
+        assert_eq!(download.p95_ms, 10);
+    }
+}
@@ -0,0 +1,315 @@
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures_util::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::{fs, io::AsyncReadExt};
+use uuid::Uuid;
+
+use crate::{
+    application::{
+        error::ApplicationError,
+        services::{ObjectStream, StorageService},
+    },
+    domain::{
+        config::secrets::LocalFsSecrets,
+        models::file::{FileData, FileMetadata},
+    },
+    services::error::StorageError,
+};
+
+const PROVIDER_NAME: &str = "localfs";
+const SIDECAR_SUFFIX: &str = ".meta.json";
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Nombre original y mime type guardados junto a cada archivo
+#[derive(Serialize, Deserialize)]
+struct Sidecar {
+    filename: String,
+    #[serde(rename = "mimeType")]
+    mime_type: String,
+    checksum: Option<String>,
+}
+
+/// Archivos en un directorio local, pensado para desarrollo sin credenciales de un proveedor
+pub struct LocalFsStorageService {
+    base_path: PathBuf,
+}
+
+impl LocalFsStorageService {
+    pub async fn new(secrets: LocalFsSecrets) -> Result<Self, StorageError> {
+        let base_path = PathBuf::from(secrets.base_path);
+        fs::create_dir_all(&base_path).await.map_err(|e| {
+            StorageError::InvalidCredentials(format!(
+                "Cannot create {}: {}",
+                base_path.display(),
+                e
+            ))
+        })?;
+
+        Ok(Self { base_path })
+    }
+
+    /// Solo un UUID en forma canónica puede llegar a unirse a la ruta base
+    fn is_file_id(file_id: &str) -> bool {
+        Uuid::parse_str(file_id).is_ok_and(|uuid| uuid.hyphenated().to_string() == file_id)
+    }
+
+    /// Rutas del archivo y de su sidecar; un id que no es UUID es NotFound
+    fn paths(&self, file_id: &str) -> Result<(PathBuf, PathBuf), StorageError> {
+        if !Self::is_file_id(file_id) {
+            return Err(StorageError::NotFound(file_id.to_string()));
+        }
+        Ok((
+            self.base_path.join(file_id),
+            self.base_path
+                .join(format!("{}{}", file_id, SIDECAR_SUFFIX)),
+        ))
+    }
+
+    async fn read_sidecar(path: &Path, file_id: &str) -> Result<Sidecar, StorageError> {
+        let json = fs::read(path).await.map_err(|e| io_error(e, file_id))?;
+        serde_json::from_slice(&json).map_err(|e| {
+            StorageError::InternalError(format!("Corrupt metadata for {}: {}", file_id, e))
+        })
+    }
+}
+
+/// Un archivo inexistente es NotFound; cualquier otro fallo de E/S, ProviderError
+fn io_error(error: std::io::Error, file_id: &str) -> StorageError {
+    if error.kind() == ErrorKind::NotFound {
+        StorageError::NotFound(file_id.to_string())
+    } else {
+        StorageError::ProviderError(format!("I/O error on {}: {}", file_id, error))
+    }
+}
+
+#[async_trait]
+impl StorageService for LocalFsStorageService {
+    fn provider_name(&self) -> &'static str {
+        PROVIDER_NAME
+    }
+
+    async fn ping(&self) -> Result<(), ApplicationError> {
+        let metadata = fs::metadata(&self.base_path).await.map_err(|e| {
+            StorageError::ProviderError(format!("{}: {}", self.base_path.display(), e))
+        })?;
+        if !metadata.is_dir() {
+            return Err(StorageError::ProviderError(format!(
+                "{} is not a directory",
+                self.base_path.display()
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    async fn upload(&self, file_data: FileData) -> Result<FileMetadata, ApplicationError> {
+        let file_id = Uuid::new_v4().to_string();
+        let (path, sidecar_path) = self.paths(&file_id)?;
+
+        let sidecar = Sidecar {
+            filename: file_data.filename.clone(),
+            mime_type: file_data.mime_type.clone(),
+            checksum: Some(format!("{:x}", Sha256::digest(&file_data.content))),
+        };
+        let json =
+            serde_json::to_vec(&sidecar).map_err(|e| StorageError::InternalError(e.to_string()))?;
+
+        fs::write(&path, &file_data.content)
+            .await
+            .map_err(|e| io_error(e, &file_id))?;
+        if let Err(e) = fs::write(&sidecar_path, json).await {
+            let _ = fs::remove_file(&path).await;
+            return Err(io_error(e, &file_id).into());
+        }
+
+        Ok(FileMetadata {
+            file_id,
+            size: file_data.size(),
+            mime_type: sidecar.mime_type,
+            filename: Some(sidecar.filename),
+            provider: PROVIDER_NAME.to_string(),
+            web_view_link: None,
+            web_content_link: None,
+            checksum: sidecar.checksum,
+        })
+    }
+
+    async fn download(&self, file_id: &str) -> Result<Vec<u8>, ApplicationError> {
+        let (path, _) = self.paths(file_id)?;
+        Ok(fs::read(&path).await.map_err(|e| io_error(e, file_id))?)
+    }
+
+    async fn download_stream(&self, file_id: &str) -> Result<ObjectStream, ApplicationError> {
+        let (path, _) = self.paths(file_id)?;
+        let file = fs::File::open(&path)
+            .await
+            .map_err(|e| io_error(e, file_id))?;
+        let length = file.metadata().await.ok().map(|metadata| metadata.len());
+        let file_id = file_id.to_string();
+
+        // El estado pasa a None tras un error para no repetirlo indefinidamente
+        let content = stream::unfold(Some(file), move |file| {
+            let file_id = file_id.clone();
+            async move {
+                let mut file = file?;
+                let mut buffer = vec![0u8; READ_CHUNK_SIZE];
+                match file.read(&mut buffer).await {
+                    Ok(0) => None,
+                    Ok(read) => {
+                        buffer.truncate(read);
+                        Some((Ok(Bytes::from(buffer)), Some(file)))
+                    }
+                    Err(e) => Some((Err(ApplicationError::from(io_error(e, &file_id))), None)),
+                }
+            }
+        })
+        .boxed();
+        Ok(ObjectStream { content, length })
+    }
+
+    async fn delete(&self, file_id: &str) -> Result<(), ApplicationError> {
+        let (path, sidecar_path) = self.paths(file_id)?;
+        fs::remove_file(&path)
+            .await
+            .map_err(|e| io_error(e, file_id))?;
+
+        if let Err(e) = fs::remove_file(&sidecar_path).await {
+            if e.kind() != ErrorKind::NotFound {
+                return Err(io_error(e, file_id).into());
+            }
+        }
+        Ok(())
+    }
+
+    async fn get_metadata(&self, file_id: &str) -> Result<FileMetadata, ApplicationError> {
+        let (path, sidecar_path) = self.paths(file_id)?;
+        let size = fs::metadata(&path)
+            .await
+            .map_err(|e| io_error(e, file_id))?
+            .len();
+        let sidecar = Self::read_sidecar(&sidecar_path, file_id).await?;
+
+        Ok(FileMetadata {
+            file_id: file_id.to_string(),
+            size,
+            mime_type: sidecar.mime_type,
+            filename: Some(sidecar.filename),
+            provider: PROVIDER_NAME.to_string(),
+            web_view_link: None,
+            web_content_link: None,
+            checksum: sidecar.checksum,
+        })
+    }
+
+    async fn list_files(
+        &self,
+        prefix: Option<&str>,
+    ) -> Result<Vec<FileMetadata>, ApplicationError> {
+        let mut entries = fs::read_dir(&self.base_path).await.map_err(|e| {
+            StorageError::ProviderError(format!("{}: {}", self.base_path.display(), e))
+        })?;
+
+        let mut files = Vec::new();
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| StorageError::ProviderError(e.to_string()))?
+        {
+            // Los sidecars y cualquier otro archivo del directorio no son objetos
+            let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+                continue;
+            };
+            if !Self::is_file_id(&name) || prefix.is_some_and(|p| !name.starts_with(p)) {
+                continue;
+            }
+
+            match self.get_metadata(&name).await {
+                Ok(metadata) => files.push(metadata),
+                // Borrado mientras se listaba
+                Err(ApplicationError::NotFound) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(files)
+    }
+
+    async fn exists(&self, file_id: &str) -> Result<bool, ApplicationError> {
+        let Ok((path, _)) = self.paths(file_id) else {
+            return Ok(false);
+        };
+        fs::try_exists(&path)
+            .await
+            .map_err(|e| io_error(e, file_id).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_file_id_only_accepts_canonical_uuids() {
+        let file_id = Uuid::new_v4().to_string();
+        assert!(LocalFsStorageService::is_file_id(&file_id));
+
+        for candidate in [
+            file_id.to_ascii_uppercase(),
+            file_id.replace('-', ""),
+            format!("{{{}}}", file_id),
+            format!("../{}", file_id),
+            format!("{}/../../etc/passwd", file_id),
+            "..".to_string(),
+            String::new(),
+        ] {
+            assert!(
+                !LocalFsStorageService::is_file_id(&candidate),
+                "{:?} should be rejected",
+                candidate
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn round_trips_files_and_rejects_foreign_ids() {
+        let base_path = std::env::temp_dir().join(format!("vk-local-fs-{}", Uuid::new_v4()));
+        let service = LocalFsStorageService::new(LocalFsSecrets {
+            base_path: base_path.display().to_string(),
+        })
+        .await
+        .unwrap();
+
+        let uploaded = service
+            .upload(FileData::new(
+                b"hello".to_vec(),
+                "hello.txt".to_string(),
+                "text/plain".to_string(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(service.download(&uploaded.file_id).await.unwrap(), b"hello");
+        let metadata = service.get_metadata(&uploaded.file_id).await.unwrap();
+        assert_eq!(metadata.size, 5);
+        assert_eq!(metadata.filename.as_deref(), Some("hello.txt"));
+
+        assert!(matches!(
+            service.download("../secrets.json").await,
+            Err(ApplicationError::NotFound)
+        ));
+
+        service.delete(&uploaded.file_id).await.unwrap();
+        assert!(matches!(
+            service.download(&uploaded.file_id).await,
+            Err(ApplicationError::NotFound)
+        ));
+
+        let _ = fs::remove_dir_all(&base_path).await;
+    }
+}
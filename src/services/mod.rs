@@ -1,10 +1,38 @@
+mod azure_blob_storage;
+mod clamav_scanner;
 mod error;
+mod event_queue;
+mod file_path;
 mod google_drive_storage;
+#[cfg(test)]
+mod in_memory_storage;
+mod instrumented_storage;
+mod local_fs_storage;
+mod retrying_storage;
+mod s3_storage;
 mod supabase_storage;
+mod tls;
+mod tracing_event_sink;
+mod unconfigured_storage;
 
+pub use azure_blob_storage::AzureBlobStorageService;
+pub use clamav_scanner::ClamAvScanner;
 pub use error::StorageError;
+pub use event_queue::EventQueue;
+pub use file_path::generate_file_path;
 pub use google_drive_storage::GDriveStorageService;
+#[cfg(test)]
+pub use in_memory_storage::InMemoryStorageService;
+pub use instrumented_storage::{
+    InstrumentedStorageService, ProviderLatency, ProviderLatencySummary,
+};
+pub use local_fs_storage::LocalFsStorageService;
+pub use retrying_storage::RetryingStorageService;
+pub use s3_storage::S3StorageService;
 pub use supabase_storage::SupabaseStorageService;
+pub use tls::ProviderTlsConfig;
+pub use tracing_event_sink::TracingEventSink;
+pub use unconfigured_storage::UnconfiguredStorageService;
 
 use std::sync::Arc;
 
@@ -17,13 +45,15 @@ pub async fn create_storage_service(
     provider: &Provider,
     secrets: &Secrets,
 ) -> Result<Arc<dyn StorageService>, StorageError> {
+    let tls = ProviderTlsConfig::from_env()?;
+
     match provider {
         Provider::GDrive => {
             let gdrive_secrets = secrets.gdrive_secrets.as_ref().ok_or_else(|| {
                 StorageError::InvalidCredentials("GDrive secrets not found".to_string())
             })?;
 
-            let service = GDriveStorageService::new(gdrive_secrets.clone())?;
+            let service = GDriveStorageService::new(gdrive_secrets.clone(), &tls)?;
             Ok(Arc::new(service))
         }
         Provider::Supabase => {
@@ -31,7 +61,31 @@ pub async fn create_storage_service(
                 StorageError::InvalidCredentials("Supabase secrets not found".to_string())
             })?;
 
-            let service = SupabaseStorageService::new(supabase_secrets.clone()).await?;
+            let service = SupabaseStorageService::new(supabase_secrets.clone(), &tls).await?;
+            Ok(Arc::new(service))
+        }
+        Provider::S3 => {
+            let s3_secrets = secrets.s3_secrets.as_ref().ok_or_else(|| {
+                StorageError::InvalidCredentials("S3 secrets not found".to_string())
+            })?;
+
+            let service = S3StorageService::new(s3_secrets.clone(), &tls)?;
+            Ok(Arc::new(service))
+        }
+        Provider::Azure => {
+            let azure_secrets = secrets.azure_secrets.as_ref().ok_or_else(|| {
+                StorageError::InvalidCredentials("Azure secrets not found".to_string())
+            })?;
+
+            let service = AzureBlobStorageService::new(azure_secrets.clone(), &tls)?;
+            Ok(Arc::new(service))
+        }
+        Provider::LocalFs => {
+            let local_fs_secrets = secrets.local_fs_secrets.as_ref().ok_or_else(|| {
+                StorageError::InvalidCredentials("Local filesystem secrets not found".to_string())
+            })?;
+
+            let service = LocalFsStorageService::new(local_fs_secrets.clone()).await?;
             Ok(Arc::new(service))
         }
     }
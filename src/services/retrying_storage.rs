@@ -0,0 +1,286 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use tracing::warn;
+
+use crate::{
+    application::{
+        error::ApplicationError,
+        services::{ObjectStream, StorageService, UploadStream},
+    },
+    domain::models::file::{FileData, FileMetadata},
+};
+
+const BASE_BACKOFF_MS: u64 = 200;
+const MAX_BACKOFF_MS: u64 = 5_000;
+
+/// Decorador que reintenta errores transitorios del proveedor hasta agotar el presupuesto
+pub struct RetryingStorageService {
+    inner: Arc<dyn StorageService>,
+    max_attempts: u32,
+}
+
+impl RetryingStorageService {
+    pub fn new(inner: Arc<dyn StorageService>, max_attempts: u32) -> Self {
+        Self {
+            inner,
+            max_attempts: max_attempts.max(1),
+        }
+    }
+
+    fn is_transient(error: &ApplicationError) -> bool {
+        matches!(
+            error,
+            ApplicationError::InternalError(_)
+                | ApplicationError::StorageUnavailable(_)
+                | ApplicationError::UpstreamTimeout(_)
+                | ApplicationError::TooManyRequests { .. }
+        )
+    }
+
+    async fn backoff(error: &ApplicationError, attempt: u32) {
+        let delay_ms = match error {
+            ApplicationError::TooManyRequests {
+                retry_after: Some(seconds),
+            } => seconds.saturating_mul(1000),
+            _ => BASE_BACKOFF_MS.saturating_mul(1 << (attempt - 1).min(16)),
+        };
+        tokio::time::sleep(Duration::from_millis(delay_ms.min(MAX_BACKOFF_MS))).await;
+    }
+
+    async fn run<T, F, Fut>(&self, operation: &str, mut call: F) -> Result<T, ApplicationError>
+    where
+        F: FnMut() -> Fut + Send,
+        Fut: std::future::Future<Output = Result<T, ApplicationError>> + Send,
+        T: Send,
+    {
+        let mut attempt = 1;
+        loop {
+            match call().await {
+                Ok(value) => return Ok(value),
+                Err(error) if attempt < self.max_attempts && Self::is_transient(&error) => {
+                    warn!(
+                        "Provider {} {} failed (attempt {}/{}): {:?}",
+                        self.inner.provider_name(),
+                        operation,
+                        attempt,
+                        self.max_attempts,
+                        error
+                    );
+                    Self::backoff(&error, attempt).await;
+                    attempt += 1;
+                }
+                Err(error) if attempt > 1 => {
+                    return Err(ApplicationError::RetriesExhausted {
+                        attempts: attempt,
+                        error: Box::new(error),
+                    })
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl StorageService for RetryingStorageService {
+    fn provider_name(&self) -> &'static str {
+        self.inner.provider_name()
+    }
+
+    fn locations(&self) -> Vec<String> {
+        self.inner.locations()
+    }
+
+    /// Sin reintentos: el health check quiere el estado actual, no esperar backoffs
+    async fn ping(&self) -> Result<(), ApplicationError> {
+        self.inner.ping().await
+    }
+
+    async fn upload(&self, file_data: FileData) -> Result<FileMetadata, ApplicationError> {
+        if self.max_attempts == 1 {
+            return self.inner.upload(file_data).await;
+        }
+        self.run("upload", || self.inner.upload(file_data.clone()))
+            .await
+    }
+
+    fn streams_uploads(&self) -> bool {
+        self.inner.streams_uploads()
+    }
+
+    /// Sin reintentos: el flujo del cliente se consume en el primer intento
+    async fn upload_stream(
+        &self,
+        file_data: FileData,
+        content: UploadStream<'_>,
+        max_size: u64,
+    ) -> Result<FileMetadata, ApplicationError> {
+        self.inner.upload_stream(file_data, content, max_size).await
+    }
+
+    async fn download(&self, file_id: &str) -> Result<Vec<u8>, ApplicationError> {
+        self.run("download", || self.inner.download(file_id)).await
+    }
+
+    /// Solo se reintenta abrir el stream; un fallo a mitad de la transferencia se propaga
+    async fn download_stream(&self, file_id: &str) -> Result<ObjectStream, ApplicationError> {
+        self.run("download_stream", || self.inner.download_stream(file_id))
+            .await
+    }
+
+    async fn generate_presigned_url(
+        &self,
+        file_id: &str,
+        expires_in: Duration,
+    ) -> Result<Option<String>, ApplicationError> {
+        self.run("generate_presigned_url", || {
+            self.inner.generate_presigned_url(file_id, expires_in)
+        })
+        .await
+    }
+
+    async fn delete(&self, file_id: &str) -> Result<(), ApplicationError> {
+        self.run("delete", || self.inner.delete(file_id)).await
+    }
+
+    async fn get_metadata(&self, file_id: &str) -> Result<FileMetadata, ApplicationError> {
+        self.run("get_metadata", || self.inner.get_metadata(file_id))
+            .await
+    }
+
+    async fn list_files(
+        &self,
+        prefix: Option<&str>,
+    ) -> Result<Vec<FileMetadata>, ApplicationError> {
+        self.run("list_files", || self.inner.list_files(prefix))
+            .await
+    }
+
+    async fn exists(&self, file_id: &str) -> Result<bool, ApplicationError> {
+        self.run("exists", || self.inner.exists(file_id)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Mutex,
+    };
+
+    use super::*;
+
+    /// Proveedor que devuelve los errores en cola y después responde con éxito
+    struct Flaky {
+        errors: Mutex<Vec<ApplicationError>>,
+        calls: AtomicU32,
+    }
+
+    impl Flaky {
+        fn failing_with(mut errors: Vec<ApplicationError>) -> Arc<Self> {
+            errors.reverse();
+            Arc::new(Self {
+                errors: Mutex::new(errors),
+                calls: AtomicU32::new(0),
+            })
+        }
+
+        fn calls(&self) -> u32 {
+            self.calls.load(Ordering::SeqCst)
+        }
+    }
+
+    #[async_trait]
+    impl StorageService for Flaky {
+        fn provider_name(&self) -> &'static str {
+            "flaky"
+        }
+
+        async fn ping(&self) -> Result<(), ApplicationError> {
+            Ok(())
+        }
+
+        async fn upload(&self, _file_data: FileData) -> Result<FileMetadata, ApplicationError> {
+            unimplemented!()
+        }
+
+        async fn download(&self, _file_id: &str) -> Result<Vec<u8>, ApplicationError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            match self.errors.lock().unwrap().pop() {
+                Some(error) => Err(error),
+                None => Ok(b"content".to_vec()),
+            }
+        }
+
+        async fn delete(&self, _file_id: &str) -> Result<(), ApplicationError> {
+            unimplemented!()
+        }
+
+        async fn get_metadata(&self, _file_id: &str) -> Result<FileMetadata, ApplicationError> {
+            unimplemented!()
+        }
+
+        async fn list_files(
+            &self,
+            _prefix: Option<&str>,
+        ) -> Result<Vec<FileMetadata>, ApplicationError> {
+            unimplemented!()
+        }
+    }
+
+    /// Retry-After de 0 segundos: reintenta sin esperar el backoff
+    fn rate_limited() -> ApplicationError {
+        ApplicationError::TooManyRequests {
+            retry_after: Some(0),
+        }
+    }
+
+    #[tokio::test]
+    async fn transient_errors_are_retried_until_success() {
+        let inner = Flaky::failing_with(vec![rate_limited(), rate_limited()]);
+        let service = RetryingStorageService::new(inner.clone(), 3);
+
+        assert_eq!(service.download("file").await.unwrap(), b"content");
+        assert_eq!(inner.calls(), 3);
+    }
+
+    #[tokio::test]
+    async fn exhausted_budget_reports_the_attempts() {
+        let inner = Flaky::failing_with(vec![rate_limited(), rate_limited(), rate_limited()]);
+        let service = RetryingStorageService::new(inner.clone(), 3);
+
+        match service.download("file").await {
+            Err(ApplicationError::RetriesExhausted { attempts, error }) => {
+                assert_eq!(attempts, 3);
+                assert!(matches!(*error, ApplicationError::TooManyRequests { .. }));
+            }
+            other => panic!("expected RetriesExhausted, got {:?}", other),
+        }
+        assert_eq!(inner.calls(), 3);
+    }
+
+    #[tokio::test]
+    async fn permanent_errors_are_not_retried() {
+        let inner = Flaky::failing_with(vec![ApplicationError::NotFound]);
+        let service = RetryingStorageService::new(inner.clone(), 3);
+
+        assert!(matches!(
+            service.download("file").await,
+            Err(ApplicationError::NotFound)
+        ));
+        assert_eq!(inner.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn a_budget_of_one_attempt_returns_the_original_error() {
+        let inner = Flaky::failing_with(vec![rate_limited()]);
+        let service = RetryingStorageService::new(inner.clone(), 0);
+
+        assert!(matches!(
+            service.download("file").await,
+            Err(ApplicationError::TooManyRequests { .. })
+        ));
+        assert_eq!(inner.calls(), 1);
+    }
+}
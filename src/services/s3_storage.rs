@@ -0,0 +1,238 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use aws_sdk_s3::{
+    config::{Credentials, Region},
+    operation::{get_object::GetObjectOutput, head_object::HeadObjectOutput},
+    primitives::ByteStream,
+    Client,
+};
+
+use crate::{
+    application::{
+        error::ApplicationError,
+        services::{ObjectStream, StorageService},
+    },
+    domain::{
+        config::secrets::S3Secrets,
+        models::file::{FileData, FileMetadata},
+    },
+    services::{
+        error::StorageError,
+        file_path::generate_file_path,
+        supabase_storage::{head_bucket, list_objects, object_stream, presign_get, status_error},
+        tls::ProviderTlsConfig,
+    },
+};
+
+const PROVIDER_NAME: &str = "s3";
+
+/// Almacenamiento directo contra un endpoint S3 (MinIO, Ceph, AWS)
+pub struct S3StorageService {
+    client: Client,
+    bucket_name: String,
+}
+
+impl S3StorageService {
+    pub fn new(secrets: S3Secrets, tls: &ProviderTlsConfig) -> Result<Self, StorageError> {
+        let credentials = Credentials::new(
+            &secrets.access_key_id,
+            &secrets.secret_access_key,
+            None,
+            None,
+            "s3-storage",
+        );
+
+        // Sin cargar la configuración del entorno: evita consultar la metadata de AWS
+        let mut config_builder = aws_sdk_s3::config::Builder::new()
+            .credentials_provider(credentials)
+            .region(Region::new(secrets.region))
+            .endpoint_url(&secrets.endpoint)
+            .force_path_style(true) // MinIO no resuelve buckets como subdominio por defecto
+            .behavior_version_latest();
+
+        if let Some(http_client) = tls.s3_http_client()? {
+            config_builder = config_builder.http_client(http_client);
+        }
+
+        Ok(Self {
+            client: Client::from_conf(config_builder.build()),
+            bucket_name: secrets.bucket_name,
+        })
+    }
+
+    /// GetObject; el cuerpo queda sin leer para poder consumirlo en streaming
+    async fn get_object(&self, file_id: &str) -> Result<GetObjectOutput, StorageError> {
+        let response = self
+            .client
+            .get_object()
+            .bucket(&self.bucket_name)
+            .key(file_id)
+            .send()
+            .await
+            .map_err(|e| {
+                if let Some(error) = status_error(&e) {
+                    return error;
+                }
+                let not_found = e
+                    .as_service_error()
+                    .is_some_and(|service_error| service_error.is_no_such_key())
+                    || e.raw_response().is_some_and(|r| r.status().as_u16() == 404);
+                if not_found {
+                    StorageError::NotFound(file_id.to_string())
+                } else {
+                    StorageError::ProviderError(format!("S3 download failed: {}", e))
+                }
+            })?;
+
+        Ok(response)
+    }
+
+    /// HEAD del objeto; una clave inexistente se traduce a StorageError::NotFound
+    async fn head(&self, file_id: &str) -> Result<HeadObjectOutput, StorageError> {
+        self.client
+            .head_object()
+            .bucket(&self.bucket_name)
+            .key(file_id)
+            .send()
+            .await
+            .map_err(|e| {
+                if let Some(error) = status_error(&e) {
+                    return error;
+                }
+                let not_found = e
+                    .as_service_error()
+                    .is_some_and(|service_error| service_error.is_not_found())
+                    || e.raw_response().is_some_and(|r| r.status().as_u16() == 404);
+                if not_found {
+                    StorageError::NotFound(file_id.to_string())
+                } else {
+                    StorageError::ProviderError(format!("S3 head object failed: {}", e))
+                }
+            })
+    }
+}
+
+#[async_trait]
+impl StorageService for S3StorageService {
+    fn provider_name(&self) -> &'static str {
+        PROVIDER_NAME
+    }
+
+    async fn ping(&self) -> Result<(), ApplicationError> {
+        Ok(head_bucket(&self.client, &self.bucket_name).await?)
+    }
+
+    async fn generate_presigned_url(
+        &self,
+        file_id: &str,
+        expires_in: Duration,
+    ) -> Result<Option<String>, ApplicationError> {
+        Ok(Some(
+            presign_get(&self.client, &self.bucket_name, file_id, expires_in).await?,
+        ))
+    }
+
+    async fn upload(&self, file_data: FileData) -> Result<FileMetadata, ApplicationError> {
+        // La clave del objeto es el file_id
+        let key = generate_file_path(&file_data.filename);
+
+        let output = self
+            .client
+            .put_object()
+            .bucket(&self.bucket_name)
+            .key(&key)
+            .body(ByteStream::from(file_data.content.clone()))
+            .content_type(&file_data.mime_type)
+            .send()
+            .await
+            .map_err(|e| {
+                status_error(&e).unwrap_or_else(|| {
+                    tracing::error!("S3 upload failed - Error details: {:?}", e);
+                    StorageError::ProviderError(format!("S3 upload failed: {:?}", e))
+                })
+            })?;
+
+        Ok(FileMetadata {
+            file_id: key,
+            size: file_data.size(),
+            mime_type: file_data.mime_type.clone(),
+            filename: Some(file_data.filename),
+            provider: PROVIDER_NAME.to_string(),
+            web_view_link: None,
+            web_content_link: None,
+            checksum: output.e_tag().map(|tag| tag.trim_matches('"').to_string()),
+        })
+    }
+
+    async fn download(&self, file_id: &str) -> Result<Vec<u8>, ApplicationError> {
+        let bytes = self
+            .get_object(file_id)
+            .await?
+            .body
+            .collect()
+            .await
+            .map_err(|e| StorageError::NetworkError(e.to_string()))?
+            .into_bytes();
+
+        Ok(bytes.to_vec())
+    }
+
+    async fn download_stream(&self, file_id: &str) -> Result<ObjectStream, ApplicationError> {
+        Ok(object_stream(self.get_object(file_id).await?))
+    }
+
+    async fn list_files(
+        &self,
+        prefix: Option<&str>,
+    ) -> Result<Vec<FileMetadata>, ApplicationError> {
+        Ok(list_objects(&self.client, &self.bucket_name, prefix, PROVIDER_NAME).await?)
+    }
+
+    async fn delete(&self, file_id: &str) -> Result<(), ApplicationError> {
+        // DeleteObject responde 204 aunque la clave no exista; el HEAD previo detecta el 404
+        self.head(file_id).await?;
+
+        self.client
+            .delete_object()
+            .bucket(&self.bucket_name)
+            .key(file_id)
+            .send()
+            .await
+            .map_err(|e| {
+                status_error(&e).unwrap_or_else(|| {
+                    StorageError::ProviderError(format!("S3 delete failed: {}", e))
+                })
+            })?;
+
+        Ok(())
+    }
+
+    async fn get_metadata(&self, file_id: &str) -> Result<FileMetadata, ApplicationError> {
+        let response = self.head(file_id).await?;
+
+        Ok(FileMetadata {
+            file_id: file_id.to_string(),
+            size: response.content_length().unwrap_or(0) as u64,
+            mime_type: response
+                .content_type()
+                .unwrap_or("application/octet-stream")
+                .to_string(),
+            filename: file_id.rsplit('/').next().map(|s| s.to_string()),
+            provider: PROVIDER_NAME.to_string(),
+            web_view_link: None,
+            web_content_link: None,
+            checksum: response
+                .e_tag()
+                .map(|tag| tag.trim_matches('"').to_string()),
+        })
+    }
+
+    async fn exists(&self, file_id: &str) -> Result<bool, ApplicationError> {
+        match self.head(file_id).await {
+            Ok(_) => Ok(true),
+            Err(StorageError::NotFound(_)) => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
@@ -1,26 +1,44 @@
+use std::time::Duration;
+
 use async_trait::async_trait;
 use aws_sdk_s3::{
-    config::{Credentials, Region},
+    config::{http::HttpResponse, Credentials, Region},
+    error::SdkError,
+    operation::get_object::GetObjectOutput,
+    presigning::PresigningConfig,
     primitives::ByteStream,
     Client,
 };
+use futures_util::{stream, StreamExt};
 
 use crate::{
-    application::{error::ApplicationError, services::StorageService},
+    application::{
+        error::ApplicationError,
+        services::{DownloadStream, ObjectStream, StorageService},
+    },
     domain::{
         config::secrets::SupabaseSecrets,
         models::file::{FileData, FileMetadata},
     },
-    services::error::StorageError,
+    services::{error::StorageError, file_path::generate_file_path, tls::ProviderTlsConfig},
 };
 
+/// Separa el bucket adicional de la clave en el file_id (`bucket:clave`); las claves
+/// generadas nunca contienen `:`, así que los ids del bucket principal no se confunden
+const BUCKET_SEPARATOR: char = ':';
+
 pub struct SupabaseStorageService {
     client: Client,
     bucket_name: String,
+    buckets: Vec<String>,
+    upsert: bool,
 }
 
 impl SupabaseStorageService {
-    pub async fn new(secrets: SupabaseSecrets) -> Result<Self, StorageError> {
+    pub async fn new(
+        secrets: SupabaseSecrets,
+        tls: &ProviderTlsConfig,
+    ) -> Result<Self, StorageError> {
         let credentials = Credentials::new(
             &secrets.access_key_id,
             &secrets.secret_access_key,
@@ -31,85 +49,286 @@ impl SupabaseStorageService {
 
         // Build S3 config directly without loading from environment
         // This avoids network calls to AWS metadata service
-        let config = aws_sdk_s3::config::Builder::new()
+        let mut config_builder = aws_sdk_s3::config::Builder::new()
             .credentials_provider(credentials)
             .region(Region::new(secrets.region))
             .endpoint_url(&secrets.endpoint)
             .force_path_style(true) // Required for S3-compatible services like Supabase
-            .behavior_version_latest()
-            .build();
+            .behavior_version_latest();
+
+        // Custom CA bundle for self-hosted endpoints behind a private CA
+        if let Some(http_client) = tls.s3_http_client()? {
+            config_builder = config_builder.http_client(http_client);
+        }
+
+        let config = config_builder.build();
 
         let client = Client::from_conf(config);
 
         Ok(Self {
             client,
             bucket_name: secrets.bucket_name,
+            buckets: secrets.buckets,
+            upsert: secrets.upsert,
         })
     }
 
-    fn generate_file_path(&self, _filename: &str) -> String {
-        use std::time::{SystemTime, UNIX_EPOCH};
+    /// Bucket y clave de un file_id; sin prefijo de un bucket adicional es el principal
+    fn locate<'a>(&'a self, file_id: &'a str) -> (&'a str, &'a str) {
+        match file_id.split_once(BUCKET_SEPARATOR) {
+            Some((bucket, key)) if self.buckets.iter().any(|b| b == bucket) => (bucket, key),
+            _ => (&self.bucket_name, file_id),
+        }
+    }
 
-        // Generate a unique hash-based ID similar to GDrive implementation
-        // Just the timestamp in hex format without extension
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_nanos();
+    /// Bucket elegido en la subida, validado contra los configurados
+    fn upload_bucket(&self, location: Option<&str>) -> Result<Option<&str>, ApplicationError> {
+        match location {
+            None => Ok(None),
+            Some(name) => self
+                .buckets
+                .iter()
+                .find(|bucket| *bucket == name)
+                .map(|bucket| Some(bucket.as_str()))
+                .ok_or_else(|| ApplicationError::BadRequest(format!("Unknown bucket '{}'", name))),
+        }
+    }
+
+    /// GetObject; el cuerpo queda sin leer para poder consumirlo en streaming
+    async fn get_object(&self, file_id: &str) -> Result<GetObjectOutput, StorageError> {
+        let (bucket, key) = self.locate(file_id);
+        let response = self
+            .client
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| {
+                if let Some(error) = status_error(&e) {
+                    return error;
+                }
+                let error_str = e.to_string();
+                if error_str.contains("NoSuchKey") || error_str.contains("404") {
+                    StorageError::NotFound(file_id.to_string())
+                } else {
+                    StorageError::ProviderError(format!("S3 download failed: {}", e))
+                }
+            })?;
+
+        Ok(response)
+    }
+}
+
+/// ListObjectsV2 página a página; el listado no incluye el Content-Type de cada objeto
+pub(super) async fn list_objects(
+    client: &Client,
+    bucket_name: &str,
+    prefix: Option<&str>,
+    provider: &str,
+) -> Result<Vec<FileMetadata>, StorageError> {
+    let mut pages = client
+        .list_objects_v2()
+        .bucket(bucket_name)
+        .set_prefix(prefix.map(str::to_string))
+        .into_paginator()
+        .send();
+
+    let mut files = Vec::new();
+    while let Some(page) = pages.next().await {
+        let page = page.map_err(|e| {
+            status_error(&e).unwrap_or_else(|| {
+                StorageError::ProviderError(format!("S3 list objects failed: {}", e))
+            })
+        })?;
+
+        files.extend(page.contents().iter().filter_map(|object| {
+            let key = object.key()?;
+            Some(FileMetadata {
+                file_id: key.to_string(),
+                size: object.size().unwrap_or(0) as u64,
+                mime_type: "application/octet-stream".to_string(),
+                filename: key.rsplit('/').next().map(|s| s.to_string()),
+                provider: provider.to_string(),
+                web_view_link: None,
+                web_content_link: None,
+                checksum: object.e_tag().map(|tag| tag.trim_matches('"').to_string()),
+            })
+        }));
+    }
+
+    Ok(files)
+}
+
+/// HeadBucket: verifica alcance y credenciales sin listar objetos
+pub(super) async fn head_bucket(client: &Client, bucket_name: &str) -> Result<(), StorageError> {
+    client
+        .head_bucket()
+        .bucket(bucket_name)
+        .send()
+        .await
+        .map_err(|e| {
+            status_error(&e).unwrap_or_else(|| {
+                StorageError::ProviderError(format!("S3 head bucket failed: {}", e))
+            })
+        })?;
+    Ok(())
+}
+
+/// GetObject prefirmado; se calcula localmente, sin llamar al endpoint
+pub(super) async fn presign_get(
+    client: &Client,
+    bucket_name: &str,
+    key: &str,
+    expires_in: Duration,
+) -> Result<String, StorageError> {
+    let config = PresigningConfig::expires_in(expires_in)
+        .map_err(|e| StorageError::InternalError(format!("Invalid presign expiry: {}", e)))?;
+    let request = client
+        .get_object()
+        .bucket(bucket_name)
+        .key(key)
+        .presigned(config)
+        .await
+        .map_err(|e| StorageError::ProviderError(format!("S3 presign failed: {}", e)))?;
+    Ok(request.uri().to_string())
+}
+
+/// Expone una respuesta GetObject como ObjectStream con su Content-Length
+pub(super) fn object_stream(output: GetObjectOutput) -> ObjectStream {
+    let length = output
+        .content_length()
+        .and_then(|length| u64::try_from(length).ok());
+    ObjectStream {
+        content: body_stream(output.body),
+        length,
+    }
+}
+
+/// Expone el cuerpo de una respuesta S3 como DownloadStream, bloque a bloque
+fn body_stream(body: ByteStream) -> DownloadStream {
+    stream::unfold(body, |mut body| async move {
+        let chunk = body
+            .next()
+            .await?
+            .map_err(|e| ApplicationError::from(StorageError::NetworkError(e.to_string())));
+        Some((chunk, body))
+    })
+    .boxed()
+}
+
+/// Traduce respuestas 429/503 (conservando Retry-After) y 401/403 del endpoint S3, y los
+/// fallos sin respuesta (timeout, conexión) a Timeout/NetworkError
+pub(super) fn status_error<E>(error: &SdkError<E, HttpResponse>) -> Option<StorageError> {
+    match error {
+        SdkError::TimeoutError(_) => {
+            return Some(StorageError::Timeout("S3 request timed out".to_string()));
+        }
+        SdkError::DispatchFailure(failure) if failure.is_timeout() => {
+            return Some(StorageError::Timeout(format!("{:?}", failure)));
+        }
+        SdkError::DispatchFailure(failure) => {
+            return Some(StorageError::NetworkError(format!("{:?}", failure)));
+        }
+        _ => {}
+    }
 
-        // Create a hash-like ID using hex timestamp
-        // This ensures uniqueness without using directory separators or extensions
-        format!("{:x}", timestamp)
+    let response = error.raw_response()?;
+    let status = response.status().as_u16();
+    if status == 401 || status == 403 {
+        return Some(StorageError::Unauthorized(format!(
+            "S3 endpoint rejected credentials with status: {}",
+            status
+        )));
     }
+    StorageError::from_rate_limit(status, response.headers().get("retry-after"))
 }
 
 #[async_trait]
 impl StorageService for SupabaseStorageService {
+    fn provider_name(&self) -> &'static str {
+        "supabase"
+    }
+
+    fn locations(&self) -> Vec<String> {
+        self.buckets.clone()
+    }
+
+    async fn ping(&self) -> Result<(), ApplicationError> {
+        Ok(head_bucket(&self.client, &self.bucket_name).await?)
+    }
+
+    async fn generate_presigned_url(
+        &self,
+        file_id: &str,
+        expires_in: Duration,
+    ) -> Result<Option<String>, ApplicationError> {
+        let (bucket, key) = self.locate(file_id);
+        Ok(Some(
+            presign_get(&self.client, bucket, key, expires_in).await?,
+        ))
+    }
+
     async fn upload(&self, file_data: FileData) -> Result<FileMetadata, ApplicationError> {
-        let file_path = self.generate_file_path(&file_data.filename);
+        let extra_bucket = self.upload_bucket(file_data.location.as_deref())?;
+        let file_path = generate_file_path(&file_data.filename);
 
         let byte_stream = ByteStream::from(file_data.content.clone());
 
-        self.client
+        let mut request = self
+            .client
             .put_object()
-            .bucket(&self.bucket_name)
+            .bucket(extra_bucket.unwrap_or(&self.bucket_name))
             .key(&file_path)
             .body(byte_stream)
-            .content_type(&file_data.mime_type)
-            .send()
-            .await
-            .map_err(|e| {
-                tracing::error!("S3 upload failed - Error details: {:?}", e);
-                StorageError::ProviderError(format!("S3 upload failed: {:?}", e))
-            })?;
+            .content_type(&file_data.mime_type);
+
+        // Without upsert, only write when no object exists at the path
+        if !self.upsert {
+            request = request.if_none_match("*");
+        }
+
+        let output = request.send().await.map_err(|e| {
+            if let Some(error) = status_error(&e) {
+                return error;
+            }
+            let status = e.raw_response().map(|r| r.status().as_u16());
+            match status {
+                Some(409) | Some(412) => {
+                    tracing::warn!("S3 upload conflict: object already exists at {}", file_path);
+                    StorageError::ProviderError(format!(
+                        "S3 upload conflict: object already exists at '{}'",
+                        file_path
+                    ))
+                }
+                _ => {
+                    tracing::error!("S3 upload failed - Error details: {:?}", e);
+                    StorageError::ProviderError(format!("S3 upload failed: {:?}", e))
+                }
+            }
+        })?;
+
+        let file_id = match extra_bucket {
+            Some(bucket) => format!("{}{}{}", bucket, BUCKET_SEPARATOR, file_path),
+            None => file_path,
+        };
 
         Ok(FileMetadata {
-            file_id: file_path,
+            file_id,
             size: file_data.size(),
             mime_type: file_data.mime_type.clone(),
             filename: Some(file_data.filename),
             provider: "supabase".to_string(),
+            web_view_link: None,
+            web_content_link: None,
+            checksum: output.e_tag().map(|tag| tag.trim_matches('"').to_string()),
         })
     }
 
     async fn download(&self, file_id: &str) -> Result<Vec<u8>, ApplicationError> {
-        let response = self
-            .client
-            .get_object()
-            .bucket(&self.bucket_name)
-            .key(file_id)
-            .send()
-            .await
-            .map_err(|e| {
-                let error_str = e.to_string();
-                if error_str.contains("NoSuchKey") || error_str.contains("404") {
-                    StorageError::NotFound(file_id.to_string())
-                } else {
-                    StorageError::ProviderError(format!("S3 download failed: {}", e))
-                }
-            })?;
-
-        let bytes = response
+        let bytes = self
+            .get_object(file_id)
+            .await?
             .body
             .collect()
             .await
@@ -119,16 +338,47 @@ impl StorageService for SupabaseStorageService {
         Ok(bytes.to_vec())
     }
 
+    async fn download_stream(&self, file_id: &str) -> Result<ObjectStream, ApplicationError> {
+        Ok(object_stream(self.get_object(file_id).await?))
+    }
+
+    async fn list_files(
+        &self,
+        prefix: Option<&str>,
+    ) -> Result<Vec<FileMetadata>, ApplicationError> {
+        let mut files = list_objects(&self.client, &self.bucket_name, prefix, "supabase").await?;
+
+        // Los ids de los buckets adicionales llevan el prefijo `bucket:`
+        for bucket in &self.buckets {
+            let objects = list_objects(&self.client, bucket, None, "supabase").await?;
+            files.extend(
+                objects
+                    .into_iter()
+                    .map(|mut file| {
+                        file.file_id = format!("{}{}{}", bucket, BUCKET_SEPARATOR, file.file_id);
+                        file
+                    })
+                    .filter(|file| prefix.is_none_or(|prefix| file.file_id.starts_with(prefix))),
+            );
+        }
+
+        Ok(files)
+    }
+
     async fn delete(&self, file_id: &str) -> Result<(), ApplicationError> {
+        let (bucket, key) = self.locate(file_id);
         // First check if the object exists
         let _head = self
             .client
             .head_object()
-            .bucket(&self.bucket_name)
-            .key(file_id)
+            .bucket(bucket)
+            .key(key)
             .send()
             .await
             .map_err(|e| {
+                if let Some(error) = status_error(&e) {
+                    return error;
+                }
                 let error_str = e.to_string();
                 if error_str.contains("NotFound") || error_str.contains("404") {
                     StorageError::NotFound(file_id.to_string())
@@ -140,26 +390,32 @@ impl StorageService for SupabaseStorageService {
         // If exists, delete it
         self.client
             .delete_object()
-            .bucket(&self.bucket_name)
-            .key(file_id)
+            .bucket(bucket)
+            .key(key)
             .send()
             .await
             .map_err(|e| {
-                StorageError::ProviderError(format!("S3 delete failed: {}", e))
+                status_error(&e).unwrap_or_else(|| {
+                    StorageError::ProviderError(format!("S3 delete failed: {}", e))
+                })
             })?;
 
         Ok(())
     }
 
     async fn get_metadata(&self, file_id: &str) -> Result<FileMetadata, ApplicationError> {
+        let (bucket, key) = self.locate(file_id);
         let response = self
             .client
             .head_object()
-            .bucket(&self.bucket_name)
-            .key(file_id)
+            .bucket(bucket)
+            .key(key)
             .send()
             .await
             .map_err(|e| {
+                if let Some(error) = status_error(&e) {
+                    return error;
+                }
                 let error_str = e.to_string();
                 if error_str.contains("NotFound") || error_str.contains("404") {
                     StorageError::NotFound(file_id.to_string())
@@ -173,7 +429,7 @@ impl StorageService for SupabaseStorageService {
             .content_type()
             .unwrap_or("application/octet-stream")
             .to_string();
-        let filename = file_id.split('/').last().map(|s| s.to_string());
+        let filename = key.rsplit('/').next().map(|s| s.to_string());
 
         Ok(FileMetadata {
             file_id: file_id.to_string(),
@@ -181,6 +437,270 @@ impl StorageService for SupabaseStorageService {
             mime_type,
             filename,
             provider: "supabase".to_string(),
+            web_view_link: None,
+            web_content_link: None,
+            checksum: response
+                .e_tag()
+                .map(|tag| tag.trim_matches('"').to_string()),
         })
     }
+
+    async fn exists(&self, file_id: &str) -> Result<bool, ApplicationError> {
+        let (bucket, key) = self.locate(file_id);
+        match self
+            .client
+            .head_object()
+            .bucket(bucket)
+            .key(key)
+            .send()
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(e) => {
+                if let Some(error) = status_error(&e) {
+                    return Err(error.into());
+                }
+                let not_found = e
+                    .as_service_error()
+                    .is_some_and(|service_error| service_error.is_not_found())
+                    || e.raw_response().is_some_and(|r| r.status().as_u16() == 404);
+                if not_found {
+                    Ok(false)
+                } else {
+                    Err(StorageError::ProviderError(format!("S3 head object failed: {}", e)).into())
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+
+    use axum::{
+        body::Bytes,
+        extract::{Path, State},
+        http::StatusCode,
+        routing::get,
+        Router,
+    };
+
+    use super::*;
+
+    type Objects = Arc<Mutex<HashMap<(String, String), Vec<u8>>>>;
+
+    async fn put_object(
+        State(objects): State<Objects>,
+        Path((bucket, key)): Path<(String, String)>,
+        body: Bytes,
+    ) -> StatusCode {
+        objects.lock().unwrap().insert((bucket, key), body.to_vec());
+        StatusCode::OK
+    }
+
+    async fn get_object(
+        State(objects): State<Objects>,
+        Path((bucket, key)): Path<(String, String)>,
+    ) -> Result<Vec<u8>, StatusCode> {
+        objects
+            .lock()
+            .unwrap()
+            .get(&(bucket, key))
+            .cloned()
+            .ok_or(StatusCode::NOT_FOUND)
+    }
+
+    /// Endpoint S3 path-style en memoria: PUT y GET de `/{bucket}/{key}`
+    async fn fake_s3() -> (String, Objects) {
+        let objects = Objects::default();
+        let app = Router::new()
+            .route("/{bucket}/{*key}", get(get_object).put(put_object))
+            .with_state(objects.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (endpoint, objects)
+    }
+
+    async fn service(endpoint: String) -> SupabaseStorageService {
+        let secrets = SupabaseSecrets {
+            endpoint,
+            region: "us-east-1".to_string(),
+            access_key_id: "test".to_string(),
+            secret_access_key: "test".to_string(),
+            bucket_name: "primary".to_string(),
+            upsert: true,
+            buckets: vec!["media".to_string()],
+        };
+        SupabaseStorageService::new(secrets, &ProviderTlsConfig::default())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn upload_to_selected_bucket_round_trips_download() {
+        let (endpoint, objects) = fake_s3().await;
+        let service = service(endpoint).await;
+
+        let file_data = FileData::new(
+            b"clip bytes".to_vec(),
+            "clip.mp4".to_string(),
+            "video/mp4".to_string(),
+        )
+        .with_location(Some("media".to_string()));
+        let metadata = service.upload(file_data).await.unwrap();
+
+        let key = metadata.file_id.strip_prefix("media:").unwrap().to_string();
+        assert!(objects
+            .lock()
+            .unwrap()
+            .contains_key(&("media".to_string(), key)));
+        assert!(objects
+            .lock()
+            .unwrap()
+            .keys()
+            .all(|(bucket, _)| bucket == "media"));
+
+        let downloaded = service.download(&metadata.file_id).await.unwrap();
+        assert_eq!(downloaded, b"clip bytes");
+    }
+
+    /// Endpoint que rechaza todo PUT con 409 y registra si llegó `If-None-Match`
+    async fn conflicting_s3() -> (String, Arc<Mutex<Vec<Option<String>>>>) {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorded = seen.clone();
+        let app = Router::new().route(
+            "/{bucket}/{*key}",
+            axum::routing::put(move |headers: axum::http::HeaderMap| async move {
+                let if_none_match = headers
+                    .get("if-none-match")
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string);
+                recorded.lock().unwrap().push(if_none_match);
+                StatusCode::CONFLICT
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (endpoint, seen)
+    }
+
+    #[tokio::test]
+    async fn upload_conflict_is_reported_as_provider_error() {
+        let (endpoint, seen) = conflicting_s3().await;
+        let mut service = service(endpoint).await;
+        service.upsert = false;
+
+        let file_data = FileData::new(
+            b"report".to_vec(),
+            "report.pdf".to_string(),
+            "application/pdf".to_string(),
+        );
+        let err = service.upload(file_data).await.unwrap_err();
+
+        match err {
+            ApplicationError::InternalError(msg) => {
+                assert!(
+                    msg.contains("S3 upload conflict: object already exists"),
+                    "{}",
+                    msg
+                )
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+        assert_eq!(seen.lock().unwrap().as_slice(), &[Some("*".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn exists_checks_present_and_absent_objects() {
+        let (endpoint, _objects) = fake_s3().await;
+        let service = service(endpoint).await;
+
+        let metadata = service
+            .upload(FileData::new(
+                b"report".to_vec(),
+                "report.pdf".to_string(),
+                "application/pdf".to_string(),
+            ))
+            .await
+            .unwrap();
+
+        assert!(service.exists(&metadata.file_id).await.unwrap());
+        assert!(!service.exists("missing.pdf").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn upload_without_location_uses_primary_bucket() {
+        let (endpoint, objects) = fake_s3().await;
+        let service = service(endpoint).await;
+
+        let file_data = FileData::new(
+            b"report".to_vec(),
+            "report.pdf".to_string(),
+            "application/pdf".to_string(),
+        );
+        let metadata = service.upload(file_data).await.unwrap();
+
+        assert!(!metadata.file_id.contains(BUCKET_SEPARATOR));
+        assert!(objects
+            .lock()
+            .unwrap()
+            .contains_key(&("primary".to_string(), metadata.file_id.clone())));
+        assert_eq!(
+            service.download(&metadata.file_id).await.unwrap(),
+            b"report"
+        );
+    }
+
+    #[tokio::test]
+    async fn rapid_uploads_of_the_same_name_get_distinct_objects() {
+        let (endpoint, objects) = fake_s3().await;
+        let service = service(endpoint).await;
+
+        let upload = |content: &[u8]| {
+            service.upload(FileData::new(
+                content.to_vec(),
+                "photo.jpg".to_string(),
+                "image/jpeg".to_string(),
+            ))
+        };
+        let (first, second) = tokio::join!(upload(b"first"), upload(b"second"));
+        let (first, second) = (first.unwrap(), second.unwrap());
+
+        assert_ne!(first.file_id, second.file_id);
+        assert!(first.file_id.ends_with("-photo.jpg"));
+        assert_eq!(objects.lock().unwrap().len(), 2);
+        assert_eq!(service.download(&first.file_id).await.unwrap(), b"first");
+        assert_eq!(service.download(&second.file_id).await.unwrap(), b"second");
+    }
+
+    #[tokio::test]
+    async fn unknown_bucket_is_rejected() {
+        let service = service("http://127.0.0.1:1".to_string()).await;
+
+        let file_data = FileData::new(b"x".to_vec(), "x.txt".to_string(), "text/plain".to_string())
+            .with_location(Some("archive".to_string()));
+
+        assert!(matches!(
+            service.upload(file_data).await,
+            Err(ApplicationError::BadRequest(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn locate_only_strips_configured_buckets() {
+        let service = service("http://127.0.0.1:1".to_string()).await;
+
+        assert_eq!(service.locate("media:a-b.mp4"), ("media", "a-b.mp4"));
+        assert_eq!(service.locate("a-b.pdf"), ("primary", "a-b.pdf"));
+        assert_eq!(
+            service.locate("other:a-b.pdf"),
+            ("primary", "other:a-b.pdf")
+        );
+    }
 }
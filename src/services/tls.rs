@@ -0,0 +1,202 @@
+use aws_sdk_s3::config::SharedHttpClient;
+use aws_smithy_http_client::{
+    tls::{self, rustls_provider::CryptoMode, TlsContext, TrustStore},
+    Builder,
+};
+use reqwest::{tls::Version, Certificate, Client};
+
+use crate::services::error::StorageError;
+
+/// Configuración TLS compartida por los clientes HTTP de los proveedores
+///
+/// Se lee de `PROVIDER_CA_BUNDLE` (ruta a un bundle PEM con CAs adicionales) y
+/// `PROVIDER_MIN_TLS_VERSION` (`1.2` o `1.3`).
+#[derive(Debug, Default, Clone)]
+pub struct ProviderTlsConfig {
+    ca_bundle: Option<Vec<u8>>,
+    min_tls_version: Option<Version>,
+}
+
+impl ProviderTlsConfig {
+    pub fn from_env() -> Result<Self, StorageError> {
+        let ca_bundle = match std::env::var("PROVIDER_CA_BUNDLE") {
+            Ok(path) => Some(std::fs::read(&path).map_err(|e| {
+                StorageError::InvalidCredentials(format!("Cannot read CA bundle '{}': {}", path, e))
+            })?),
+            Err(_) => None,
+        };
+
+        let min_tls_version = match std::env::var("PROVIDER_MIN_TLS_VERSION").as_deref() {
+            Ok("1.2") => Some(Version::TLS_1_2),
+            Ok("1.3") => Some(Version::TLS_1_3),
+            Ok(other) => {
+                return Err(StorageError::InvalidCredentials(format!(
+                    "Unsupported PROVIDER_MIN_TLS_VERSION '{}': expected 1.2 or 1.3",
+                    other
+                )))
+            }
+            Err(_) => None,
+        };
+
+        Ok(Self {
+            ca_bundle,
+            min_tls_version,
+        })
+    }
+
+    /// Cliente reqwest con las CAs adicionales y la versión mínima de TLS
+    pub fn http_client(&self) -> Result<Client, StorageError> {
+        let mut builder = Client::builder();
+
+        if let Some(ref pem) = self.ca_bundle {
+            let certificates = Certificate::from_pem_bundle(pem).map_err(|e| {
+                StorageError::InvalidCredentials(format!("Invalid CA bundle: {}", e))
+            })?;
+            if certificates.is_empty() {
+                return Err(StorageError::InvalidCredentials(
+                    "CA bundle contains no certificates".to_string(),
+                ));
+            }
+            for certificate in certificates {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        if let Some(version) = self.min_tls_version {
+            builder = builder.min_tls_version(version);
+        }
+
+        builder
+            .build()
+            .map_err(|e| StorageError::InternalError(format!("Cannot build HTTP client: {}", e)))
+    }
+
+    /// Cliente HTTP para el SDK de S3 cuando se configuró un bundle de CAs
+    ///
+    /// rustls solo negocia TLS 1.2 o superior, por lo que la versión mínima
+    /// `1.2` se cumple siempre en este cliente.
+    pub fn s3_http_client(&self) -> Result<Option<SharedHttpClient>, StorageError> {
+        if self.min_tls_version == Some(Version::TLS_1_3) {
+            tracing::warn!("PROVIDER_MIN_TLS_VERSION=1.3 is not enforced for S3 clients");
+        }
+
+        let Some(ref pem) = self.ca_bundle else {
+            return Ok(None);
+        };
+
+        let trust_store = TrustStore::empty()
+            .with_native_roots(true)
+            .with_pem_certificate(pem.clone());
+        let tls_context = TlsContext::builder()
+            .with_trust_store(trust_store)
+            .build()
+            .map_err(|e| StorageError::InvalidCredentials(format!("Invalid CA bundle: {}", e)))?;
+
+        Ok(Some(
+            Builder::new()
+                .tls_provider(tls::Provider::Rustls(CryptoMode::AwsLc))
+                .tls_context(tls_context)
+                .build_https(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+    use tokio_rustls::{
+        rustls::{
+            pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer},
+            ServerConfig,
+        },
+        TlsAcceptor,
+    };
+
+    use super::*;
+
+    /// PEM de una CA privada y el certificado de `localhost` firmado por ella
+    fn private_ca() -> (String, CertificateDer<'static>, PrivateKeyDer<'static>) {
+        let ca_key = KeyPair::generate().unwrap();
+        let mut ca_params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let ca = ca_params.self_signed(&ca_key).unwrap();
+
+        let server_key = KeyPair::generate().unwrap();
+        let server = CertificateParams::new(vec!["localhost".to_string()])
+            .unwrap()
+            .signed_by(&server_key, &ca, &ca_key)
+            .unwrap();
+        let key = PrivatePkcs8KeyDer::from(server_key.serialize_der()).into();
+        (ca.pem(), server.der().clone(), key)
+    }
+
+    /// Servidor HTTPS en localhost que responde "ok" a cualquier petición
+    async fn tls_server(cert: CertificateDer<'static>, key: PrivateKeyDer<'static>) -> u16 {
+        let provider = Arc::new(rustls::crypto::aws_lc_rs::default_provider());
+        let config = ServerConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_no_client_auth()
+            .with_single_cert(vec![cert], key)
+            .unwrap();
+        let acceptor = TlsAcceptor::from(Arc::new(config));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let acceptor = acceptor.clone();
+                tokio::spawn(async move {
+                    let Ok(mut stream) = acceptor.accept(stream).await else {
+                        return;
+                    };
+                    let mut request = [0u8; 1024];
+                    let _ = stream.read(&mut request).await;
+                    let _ = stream
+                        .write_all(
+                            b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok",
+                        )
+                        .await;
+                    let _ = stream.shutdown().await;
+                });
+            }
+        });
+        port
+    }
+
+    #[tokio::test]
+    async fn ca_bundle_trusts_a_private_endpoint() {
+        let (ca_pem, cert, key) = private_ca();
+        let port = tls_server(cert, key).await;
+        let url = format!("https://localhost:{}/", port);
+
+        let default_client = ProviderTlsConfig::default().http_client().unwrap();
+        assert!(default_client.get(&url).send().await.is_err());
+
+        let tls = ProviderTlsConfig {
+            ca_bundle: Some(ca_pem.into_bytes()),
+            min_tls_version: Some(Version::TLS_1_2),
+        };
+        let response = tls.http_client().unwrap().get(&url).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        assert_eq!(response.text().await.unwrap(), "ok");
+    }
+
+    #[test]
+    fn bundle_without_certificates_is_rejected() {
+        let tls = ProviderTlsConfig {
+            ca_bundle: Some(b"not a certificate".to_vec()),
+            min_tls_version: None,
+        };
+        assert!(matches!(
+            tls.http_client(),
+            Err(StorageError::InvalidCredentials(_))
+        ));
+    }
+}
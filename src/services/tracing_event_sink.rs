@@ -0,0 +1,27 @@
+use async_trait::async_trait;
+use tracing::info;
+
+use crate::{
+    application::{error::ApplicationError, services::EventSink},
+    domain::models::audit::AuditEvent,
+};
+
+/// Registra los eventos de auditoría en el log bajo el target "audit"
+pub struct TracingEventSink;
+
+#[async_trait]
+impl EventSink for TracingEventSink {
+    async fn deliver(&self, event: &AuditEvent) -> Result<(), ApplicationError> {
+        info!(
+            target: "audit",
+            action = ?event.action,
+            file_id = %event.file_id,
+            user_id = ?event.user_id,
+            server_id = %event.server_id,
+            client_ip = ?event.client_ip,
+            occurred_at = %event.occurred_at,
+            "audit event"
+        );
+        Ok(())
+    }
+}
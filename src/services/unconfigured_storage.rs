@@ -0,0 +1,135 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::{
+    application::{
+        error::ApplicationError,
+        services::{StorageService, UploadStream},
+    },
+    domain::{
+        config::local::Provider,
+        models::file::{FileData, FileMetadata},
+    },
+};
+
+/// Servicio de reemplazo cuando el proveedor aún no tiene secretos (base de datos nueva)
+///
+/// Permite arrancar la instancia y configurar los secretos después; toda operación
+/// falla hasta que /admin/refresh-config recree el servicio real
+pub struct UnconfiguredStorageService {
+    provider: Provider,
+}
+
+impl UnconfiguredStorageService {
+    pub fn new(provider: Provider) -> Self {
+        Self { provider }
+    }
+
+    fn not_configured(&self) -> ApplicationError {
+        ApplicationError::ProviderUnauthorized(format!(
+            "No secrets configured for provider {:?}",
+            self.provider
+        ))
+    }
+}
+
+#[async_trait]
+impl StorageService for UnconfiguredStorageService {
+    fn provider_name(&self) -> &'static str {
+        match self.provider {
+            Provider::GDrive => "gdrive",
+            Provider::Supabase => "supabase",
+            Provider::S3 => "s3",
+            Provider::Azure => "azure",
+            Provider::LocalFs => "localfs",
+        }
+    }
+
+    async fn ping(&self) -> Result<(), ApplicationError> {
+        Err(self.not_configured())
+    }
+
+    async fn generate_presigned_url(
+        &self,
+        _file_id: &str,
+        _expires_in: Duration,
+    ) -> Result<Option<String>, ApplicationError> {
+        Err(self.not_configured())
+    }
+
+    async fn upload(&self, _file_data: FileData) -> Result<FileMetadata, ApplicationError> {
+        Err(self.not_configured())
+    }
+
+    async fn upload_stream(
+        &self,
+        _file_data: FileData,
+        _content: UploadStream<'_>,
+        _max_size: u64,
+    ) -> Result<FileMetadata, ApplicationError> {
+        Err(self.not_configured())
+    }
+
+    async fn download(&self, _file_id: &str) -> Result<Vec<u8>, ApplicationError> {
+        Err(self.not_configured())
+    }
+
+    async fn delete(&self, _file_id: &str) -> Result<(), ApplicationError> {
+        Err(self.not_configured())
+    }
+
+    async fn get_metadata(&self, _file_id: &str) -> Result<FileMetadata, ApplicationError> {
+        Err(self.not_configured())
+    }
+
+    async fn list_files(
+        &self,
+        _prefix: Option<&str>,
+    ) -> Result<Vec<FileMetadata>, ApplicationError> {
+        Err(self.not_configured())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_configured_provider_name() {
+        assert_eq!(
+            UnconfiguredStorageService::new(Provider::Supabase).provider_name(),
+            "supabase"
+        );
+        assert_eq!(
+            UnconfiguredStorageService::new(Provider::LocalFs).provider_name(),
+            "localfs"
+        );
+    }
+
+    #[tokio::test]
+    async fn every_operation_fails_until_secrets_are_configured() {
+        let service = UnconfiguredStorageService::new(Provider::GDrive);
+        let file = || FileData::new(b"x".to_vec(), "x.txt".to_string(), "text/plain".to_string());
+
+        let errors = [
+            service.ping().await.unwrap_err(),
+            service.upload(file()).await.unwrap_err(),
+            service.download("id").await.unwrap_err(),
+            service.delete("id").await.unwrap_err(),
+            service.get_metadata("id").await.unwrap_err(),
+            service.list_files(None).await.unwrap_err(),
+            service
+                .generate_presigned_url("id", Duration::from_secs(60))
+                .await
+                .unwrap_err(),
+        ];
+        for error in errors {
+            assert!(
+                matches!(&error, ApplicationError::ProviderUnauthorized(message) if message.contains("GDrive")),
+                "{:?}",
+                error
+            );
+        }
+    }
+}